@@ -0,0 +1,615 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::auth::extract_host;
+use crate::auth::providers::{ProviderKind, RateLimit, TokenDetails};
+
+pub const DEFAULT_ACCOUNT: &str = "default";
+pub const EXPIRY_WARNING_DAYS: i64 = 14;
+
+// The token lives in `TokenStorage` under `key`; this record only holds
+// metadata.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Account {
+    pub host: String,
+    pub name: String,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub scopes: Vec<String>,
+    #[serde(default)]
+    pub provider: Option<ProviderKind>,
+    // `YYYY-MM-DD`.
+    #[serde(default)]
+    pub expires_at: Option<String>,
+    #[serde(default)]
+    pub rate_limit: Option<RateLimit>,
+    // Unix time.
+    #[serde(default)]
+    pub checked_at: Option<i64>,
+    // Unix time git last reported the token as rejected.
+    #[serde(default)]
+    pub rejected_at: Option<i64>,
+    key: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenWarning {
+    Rejected,
+    Unchecked,
+    Expired(String),
+    ExpiresSoon { date: String, days: i64 },
+    MissingScopes(Vec<String>),
+    RateLimitLow { remaining: u64, limit: u64 },
+}
+
+impl TokenWarning {
+    pub fn message(&self) -> String {
+        match self {
+            TokenWarning::Rejected => {
+                "Git reported this token as rejected; re-check or replace it.".to_string()
+            }
+            TokenWarning::Unchecked => {
+                "Not validated yet; scopes and expiry are unknown.".to_string()
+            }
+            TokenWarning::Expired(date) => format!("Expired on {date}."),
+            TokenWarning::ExpiresSoon { date, days: 0 } => format!("Expires today ({date})."),
+            TokenWarning::ExpiresSoon { date, days } => {
+                format!("Expires in {days} day(s), on {date}.")
+            }
+            TokenWarning::MissingScopes(scopes) => {
+                format!("Missing scopes: {}.", scopes.join(", "))
+            }
+            TokenWarning::RateLimitLow { remaining, limit } => {
+                format!("Only {remaining} of {limit} API requests left in this window.")
+            }
+        }
+    }
+
+    pub fn is_critical(&self) -> bool {
+        matches!(
+            self,
+            TokenWarning::Rejected | TokenWarning::Expired(_) | TokenWarning::MissingScopes(_)
+        )
+    }
+}
+
+impl Account {
+    pub(crate) fn new(host: &str, name: &str) -> Self {
+        Self {
+            host: host.to_string(),
+            name: name.to_string(),
+            username: None,
+            scopes: Vec::new(),
+            provider: None,
+            expires_at: None,
+            rate_limit: None,
+            checked_at: None,
+            rejected_at: None,
+            key: Account::storage_key_for(host, name),
+        }
+    }
+
+    // Tokens saved before accounts existed are keyed by the bare host, so
+    // they become that host's `default` account without being moved.
+    fn storage_key_for(host: &str, name: &str) -> String {
+        if name == DEFAULT_ACCOUNT {
+            host.to_string()
+        } else {
+            format!("{host}#{name}")
+        }
+    }
+
+    pub fn storage_key(&self) -> &str {
+        &self.key
+    }
+
+    pub fn label(&self) -> String {
+        match &self.username {
+            Some(username) if username != &self.name => {
+                format!("{} ({username}) on {}", self.name, self.host)
+            }
+            _ => format!("{} on {}", self.name, self.host),
+        }
+    }
+
+    pub fn record_details(&mut self, kind: ProviderKind, details: TokenDetails, checked_at: i64) {
+        if !details.profile.username.is_empty() {
+            self.username = Some(details.profile.username);
+        }
+        self.provider = Some(kind);
+        self.scopes = details.scopes;
+        self.expires_at = details.expires_at;
+        self.rate_limit = details.rate_limit;
+        self.checked_at = Some(checked_at);
+        self.rejected_at = None;
+    }
+
+    // Keeps this account's own host, name and storage key.
+    pub fn copy_details_from(&mut self, other: &Account) {
+        self.username = other.username.clone();
+        self.scopes = other.scopes.clone();
+        self.provider = other.provider;
+        self.expires_at = other.expires_at.clone();
+        self.rate_limit = other.rate_limit.clone();
+        self.checked_at = other.checked_at;
+        self.rejected_at = other.rejected_at;
+    }
+
+    pub fn health(&self, today: NaiveDate) -> Vec<TokenWarning> {
+        let mut warnings = Vec::new();
+        if self.rejected_at.is_some() {
+            warnings.push(TokenWarning::Rejected);
+        }
+        if self.checked_at.is_none() {
+            warnings.push(TokenWarning::Unchecked);
+            return warnings;
+        }
+        if let Some(date) = &self.expires_at
+            && let Ok(expiry) = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        {
+            let days = (expiry - today).num_days();
+            if days < 0 {
+                warnings.push(TokenWarning::Expired(date.clone()));
+            } else if days <= EXPIRY_WARNING_DAYS {
+                warnings.push(TokenWarning::ExpiresSoon {
+                    date: date.clone(),
+                    days,
+                });
+            }
+        }
+        // An empty list means the provider did not report scopes.
+        if let Some(kind) = self.provider
+            && !self.scopes.is_empty()
+        {
+            let missing: Vec<String> = kind
+                .required_scopes()
+                .iter()
+                .filter(|options| {
+                    !options
+                        .iter()
+                        .any(|scope| self.scopes.iter().any(|own| own == scope))
+                })
+                .map(|options| options.join(" or "))
+                .collect();
+            if !missing.is_empty() {
+                warnings.push(TokenWarning::MissingScopes(missing));
+            }
+        }
+        if let Some(limit) = &self.rate_limit
+            && limit.limit > 0
+            && limit.remaining * 10 < limit.limit
+        {
+            warnings.push(TokenWarning::RateLimitLow {
+                remaining: limit.remaining,
+                limit: limit.limit,
+            });
+        }
+        warnings
+    }
+
+    fn matches_host(&self, host: &str) -> bool {
+        extract_host(&self.host).is_some_and(|own| canonical_host(&own) == host)
+    }
+}
+
+// Patterns use `*` and `?` wildcards and are matched against the repository
+// path and against the remote as `host/owner/repo`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountRule {
+    pub pattern: String,
+    pub host: String,
+    pub account: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AccountIndex {
+    #[serde(default)]
+    pub accounts: Vec<Account>,
+    #[serde(default)]
+    pub defaults: BTreeMap<String, String>,
+    #[serde(default)]
+    pub rules: Vec<AccountRule>,
+}
+
+impl AccountIndex {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let data =
+            fs::read_to_string(path).map_err(|err| format!("Failed to read accounts: {err}"))?;
+        serde_json::from_str(&data).map_err(|err| format!("Failed to parse accounts: {err}"))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|err| format!("Failed to prepare account directory: {err}"))?;
+        }
+        let serialized = serde_json::to_string_pretty(self)
+            .map_err(|err| format!("Failed to serialize accounts: {err}"))?;
+        fs::write(path, serialized).map_err(|err| format!("Failed to write accounts: {err}"))
+    }
+
+    pub fn adopt_legacy_hosts(&mut self, keys: impl IntoIterator<Item = String>) {
+        for key in keys {
+            if key.contains('#') || self.accounts.iter().any(|account| account.key == key) {
+                continue;
+            }
+            self.defaults
+                .entry(key.clone())
+                .or_insert_with(|| DEFAULT_ACCOUNT.to_string());
+            self.accounts.push(Account::new(&key, DEFAULT_ACCOUNT));
+        }
+    }
+
+    pub fn find(&self, host: &str, name: &str) -> Option<&Account> {
+        self.accounts
+            .iter()
+            .find(|account| account.host == host && account.name == name)
+    }
+
+    // The first account for a host becomes its default.
+    pub fn upsert(&mut self, host: &str, name: &str) -> &mut Account {
+        self.defaults
+            .entry(host.to_string())
+            .or_insert_with(|| name.to_string());
+        let position = self
+            .accounts
+            .iter()
+            .position(|account| account.host == host && account.name == name);
+        let index = match position {
+            Some(index) => index,
+            None => {
+                self.accounts.push(Account::new(host, name));
+                self.accounts.len() - 1
+            }
+        };
+        &mut self.accounts[index]
+    }
+
+    pub fn unused_name(&self, host: &str, name: &str) -> String {
+        if self.find(host, name).is_none() {
+            return name.to_string();
+        }
+        let base = format!("{name}-imported");
+        let mut candidate = base.clone();
+        let mut suffix = 2;
+        while self.find(host, &candidate).is_some() {
+            candidate = format!("{base}-{suffix}");
+            suffix += 1;
+        }
+        candidate
+    }
+
+    // Another account on the host takes over as default.
+    pub fn remove(&mut self, host: &str, name: &str) -> Option<Account> {
+        let index = self
+            .accounts
+            .iter()
+            .position(|account| account.host == host && account.name == name)?;
+        let removed = self.accounts.remove(index);
+        self.rules
+            .retain(|rule| !(rule.host == host && rule.account == name));
+        if self.defaults.get(host).map(String::as_str) == Some(name) {
+            match self.accounts.iter().find(|account| account.host == host) {
+                Some(next) => {
+                    self.defaults.insert(host.to_string(), next.name.clone());
+                }
+                None => {
+                    self.defaults.remove(host);
+                }
+            }
+        }
+        Some(removed)
+    }
+
+    pub fn is_default(&self, account: &Account) -> bool {
+        match self.defaults.get(&account.host) {
+            Some(name) => name == &account.name,
+            None => self
+                .accounts
+                .iter()
+                .find(|other| other.host == account.host)
+                .is_some_and(|first| first.name == account.name),
+        }
+    }
+
+    // `host` may be a bare host, a URL, or a host with a path such as
+    // `dev.azure.com/contoso`. An account saved for exactly that string wins
+    // over the host-wide default.
+    pub fn default_for(&self, host: &str) -> Option<&Account> {
+        let trimmed = host.trim().trim_end_matches('/');
+        if let Some(exact) = self
+            .accounts
+            .iter()
+            .filter(|account| account.host == trimmed)
+            .find(|account| self.is_default(account))
+        {
+            return Some(exact);
+        }
+        let canonical = canonical_host(&extract_host(trimmed)?);
+        let candidates: Vec<&Account> = self
+            .accounts
+            .iter()
+            .filter(|account| account.matches_host(&canonical))
+            .collect();
+        candidates
+            .iter()
+            .find(|account| self.is_default(account))
+            .or(candidates.first())
+            .copied()
+    }
+
+    pub fn select(&self, repo_path: Option<&str>, url: &str) -> Option<&Account> {
+        let host = canonical_host(&extract_host(url)?);
+        let remote = remote_key(url);
+        let repo_path = repo_path.map(|path| path.replace('\\', "/"));
+        let ruled = self.rules.iter().find_map(|rule| {
+            let host_matches = extract_host(&rule.host)
+                .is_some_and(|rule_host| canonical_host(&rule_host) == host);
+            let target_matches = glob_match(&rule.pattern, &remote)
+                || repo_path
+                    .as_deref()
+                    .is_some_and(|path| glob_match(&rule.pattern, path));
+            if host_matches && target_matches {
+                self.find(&rule.host, &rule.account)
+            } else {
+                None
+            }
+        });
+        ruled.or_else(|| self.default_for(url))
+    }
+}
+
+pub fn accounts_path() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("gitspace")
+        .join("accounts.json")
+}
+
+// `api.github.com` tokens serve `github.com` remotes and vice versa.
+fn canonical_host(host: &str) -> String {
+    let host = host.to_ascii_lowercase();
+    match host.as_str() {
+        "api.github.com" => "github.com".to_string(),
+        _ => host,
+    }
+}
+
+// `https://user@host/owner/repo.git` and `git@host:owner/repo.git` both
+// become `host/owner/repo`.
+pub fn remote_key(url: &str) -> String {
+    let trimmed = url.trim().trim_end_matches('/');
+    let without_scheme = trimmed.split_once("://").map_or(trimmed, |(_, rest)| rest);
+    let without_user = without_scheme
+        .split_once('@')
+        .filter(|(user, _)| !user.contains('/'))
+        .map_or(without_scheme, |(_, rest)| rest);
+    let normalized = if trimmed.contains("://") {
+        without_user.to_string()
+    } else {
+        without_user.replacen(':', "/", 1)
+    };
+    let normalized = normalized.trim_end_matches(".git");
+    match normalized.split_once('/') {
+        Some((host, path)) => format!("{}/{path}", canonical_host(host)),
+        None => canonical_host(normalized),
+    }
+}
+
+// `*` spans any characters, including `/`, and `?` matches one.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.trim().to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|ch| *ch == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index() -> AccountIndex {
+        let mut index = AccountIndex::default();
+        index.adopt_legacy_hosts(["api.github.com".to_string(), "gitlab.com".to_string()]);
+        index.upsert("github.com", "work").username = Some("ada-corp".to_string());
+        index.upsert("gitlab.com", "oss");
+        index
+    }
+
+    fn details(scopes: &[&str], expires_at: Option<&str>, remaining: u64) -> TokenDetails {
+        TokenDetails {
+            profile: crate::auth::providers::UserProfile {
+                username: "ada".to_string(),
+                display_name: None,
+            },
+            scopes: scopes.iter().map(|scope| scope.to_string()).collect(),
+            expires_at: expires_at.map(str::to_string),
+            rate_limit: Some(RateLimit {
+                limit: 5000,
+                remaining,
+                reset_at: None,
+            }),
+        }
+    }
+
+    #[test]
+    fn health_flags_expiry_scopes_and_rate_limits() {
+        let today = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+        let mut account = Account::new("gitlab.com", "work");
+        assert_eq!(account.health(today), vec![TokenWarning::Unchecked]);
+        account.rejected_at = Some(0);
+        assert_eq!(
+            account.health(today),
+            vec![TokenWarning::Rejected, TokenWarning::Unchecked]
+        );
+
+        account.record_details(
+            ProviderKind::GitLab,
+            details(&["read_api", "write_repository"], Some("2027-01-01"), 4000),
+            0,
+        );
+        assert_eq!(account.username.as_deref(), Some("ada"));
+        assert!(account.health(today).is_empty());
+
+        account.record_details(
+            ProviderKind::GitLab,
+            details(&["read_api"], Some("2026-10-25"), 100),
+            0,
+        );
+        assert_eq!(
+            account.health(today),
+            vec![
+                TokenWarning::ExpiresSoon {
+                    date: "2026-10-25".to_string(),
+                    days: 7
+                },
+                TokenWarning::MissingScopes(vec!["api or write_repository".to_string()]),
+                TokenWarning::RateLimitLow {
+                    remaining: 100,
+                    limit: 5000
+                },
+            ]
+        );
+
+        // Fine-grained GitHub tokens report neither scopes nor expiry.
+        account.record_details(
+            ProviderKind::GitHub,
+            details(&[], Some("2026-10-01"), 5000),
+            0,
+        );
+        let warnings = account.health(today);
+        assert_eq!(
+            warnings,
+            vec![TokenWarning::Expired("2026-10-01".to_string())]
+        );
+        assert!(warnings[0].is_critical());
+    }
+
+    #[test]
+    fn remote_keys_ignore_scheme_user_and_suffix() {
+        assert_eq!(
+            remote_key("https://ada@github.com/Acme/tool.git"),
+            "github.com/Acme/tool"
+        );
+        assert_eq!(
+            remote_key("git@github.com:acme/tool.git"),
+            "github.com/acme/tool"
+        );
+        assert_eq!(
+            remote_key("ssh://git@gitlab.com/group/sub/proj"),
+            "gitlab.com/group/sub/proj"
+        );
+        assert!(glob_match("github.com/acme/*", "github.com/ACME/tool"));
+        assert!(glob_match("/home/*/work/*", "/home/ada/work/api"));
+        assert!(!glob_match(
+            "github.com/acme/*",
+            "github.com/acme-labs/tool"
+        ));
+        assert!(glob_match(
+            "gitlab.com/?roup/*",
+            "gitlab.com/group/sub/proj"
+        ));
+    }
+
+    #[test]
+    fn legacy_tokens_become_defaults_and_keep_their_keys() {
+        let index = index();
+        let legacy = index.find("api.github.com", DEFAULT_ACCOUNT).unwrap();
+        assert_eq!(legacy.storage_key(), "api.github.com");
+        assert_eq!(
+            index.find("github.com", "work").unwrap().storage_key(),
+            "github.com#work"
+        );
+
+        let selected = index
+            .select(None, "https://github.com/ada/dotfiles.git")
+            .unwrap();
+        assert_eq!(selected.host, "api.github.com");
+        assert_eq!(
+            index
+                .default_for("gitlab.com")
+                .map(|account| account.name.as_str()),
+            Some(DEFAULT_ACCOUNT)
+        );
+    }
+
+    #[test]
+    fn rules_pick_accounts_by_owner_and_repository_path() {
+        let mut index = index();
+        index.rules.push(AccountRule {
+            pattern: "github.com/acme/*".to_string(),
+            host: "github.com".to_string(),
+            account: "work".to_string(),
+        });
+        index.rules.push(AccountRule {
+            pattern: "/srv/clients/*".to_string(),
+            host: "github.com".to_string(),
+            account: "work".to_string(),
+        });
+
+        let pick = |repo: Option<&str>, url: &str| {
+            index.select(repo, url).map(|account| account.name.clone())
+        };
+        assert_eq!(
+            pick(None, "git@github.com:acme/api.git").as_deref(),
+            Some("work")
+        );
+        assert_eq!(
+            pick(Some("/srv/clients/tool"), "https://github.com/ada/tool.git").as_deref(),
+            Some("work")
+        );
+        assert_eq!(
+            pick(None, "https://github.com/ada/tool.git").as_deref(),
+            Some(DEFAULT_ACCOUNT)
+        );
+        // Rules only apply to their own host.
+        assert_eq!(
+            pick(Some("/srv/clients/tool"), "https://gitlab.com/ada/tool.git").as_deref(),
+            Some(DEFAULT_ACCOUNT)
+        );
+    }
+
+    #[test]
+    fn removing_the_default_promotes_another_account() {
+        let mut index = index();
+        index
+            .defaults
+            .insert("gitlab.com".to_string(), "oss".to_string());
+        index.rules.push(AccountRule {
+            pattern: "gitlab.com/oss/*".to_string(),
+            host: "gitlab.com".to_string(),
+            account: "oss".to_string(),
+        });
+
+        index.remove("gitlab.com", "oss").unwrap();
+        assert!(index.rules.is_empty());
+        assert_eq!(
+            index.defaults.get("gitlab.com").map(String::as_str),
+            Some(DEFAULT_ACCOUNT)
+        );
+    }
+}
@@ -0,0 +1,102 @@
+use serde::{Deserialize, Serialize};
+
+use crate::auth::accounts::Account;
+use crate::auth::{KdfParams, open, seal};
+
+const BUNDLE_FORMAT: &str = "gitspace-credentials";
+const BUNDLE_VERSION: u32 = 1;
+const WRONG_PASSPHRASE: &str = "Incorrect passphrase or damaged bundle";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundleEntry {
+    pub account: Account,
+    pub token: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportChoice {
+    Import,
+    KeepBoth,
+    Skip,
+}
+
+// Only the header is readable without the passphrase; accounts, usernames
+// and tokens are all inside `ciphertext`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BundleFile {
+    format: String,
+    version: u32,
+    kdf: KdfParams,
+    nonce: String,
+    ciphertext: String,
+}
+
+pub fn seal_bundle(entries: &[BundleEntry], passphrase: &str) -> Result<String, String> {
+    if passphrase.is_empty() {
+        return Err("Passphrase cannot be empty".to_string());
+    }
+    let kdf = KdfParams::generate();
+    let key = kdf.derive(passphrase)?;
+    let serialized = serde_json::to_vec(entries)
+        .map_err(|err| format!("Failed to serialize credentials: {err}"))?;
+    let (nonce, ciphertext) = seal(&serialized, &key)?;
+    let file = BundleFile {
+        format: BUNDLE_FORMAT.to_string(),
+        version: BUNDLE_VERSION,
+        kdf,
+        nonce,
+        ciphertext,
+    };
+    serde_json::to_string_pretty(&file).map_err(|err| format!("Failed to serialize bundle: {err}"))
+}
+
+pub fn open_bundle(data: &str, passphrase: &str) -> Result<Vec<BundleEntry>, String> {
+    let file: BundleFile =
+        serde_json::from_str(data).map_err(|_| "Not a GitSpace credential bundle".to_string())?;
+    if file.format != BUNDLE_FORMAT {
+        return Err("Not a GitSpace credential bundle".to_string());
+    }
+    if file.version > BUNDLE_VERSION {
+        return Err(format!(
+            "Bundle version {} is newer than this GitSpace supports",
+            file.version
+        ));
+    }
+    let key = file.kdf.derive(passphrase)?;
+    let plaintext =
+        open(&file.nonce, &file.ciphertext, &key).map_err(|_| WRONG_PASSPHRASE.to_string())?;
+    serde_json::from_slice(&plaintext)
+        .map_err(|err| format!("Failed to parse bundle contents: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(host: &str, name: &str, token: &str) -> BundleEntry {
+        let mut account = Account::new(host, name);
+        account.username = Some("octocat".to_string());
+        account.scopes = vec!["repo".to_string()];
+        BundleEntry {
+            account,
+            token: token.to_string(),
+        }
+    }
+
+    #[test]
+    fn bundle_round_trips_and_rejects_wrong_passphrase() {
+        let entries = vec![
+            entry("github.com", "default", "ghp_one"),
+            entry("gitlab.com", "work", "glpat_two"),
+        ];
+        let sealed = seal_bundle(&entries, "correct horse").unwrap();
+        assert!(!sealed.contains("ghp_one"));
+        assert!(!sealed.contains("octocat"));
+        assert!(sealed.contains(BUNDLE_FORMAT));
+
+        assert_eq!(open_bundle(&sealed, "correct horse").unwrap(), entries);
+        assert_eq!(open_bundle(&sealed, "wrong").unwrap_err(), WRONG_PASSPHRASE);
+        assert!(open_bundle("{}", "correct horse").is_err());
+        assert!(seal_bundle(&entries, "").is_err());
+    }
+}
@@ -0,0 +1,370 @@
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use git2::{Config, Repository};
+
+use crate::auth::accounts::Account;
+use crate::auth::{AuthManager, extract_host};
+
+pub const HELPER_ARG: &str = "credential-helper";
+const HELPER_KEY: &str = "credential.helper";
+// Matches entries written by `helper_command`, wherever the executable lives.
+const HELPER_PATTERN: &str = "' credential-helper$";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CredentialRequest {
+    pub protocol: Option<String>,
+    pub host: Option<String>,
+    pub path: Option<String>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
+
+impl CredentialRequest {
+    pub fn parse(input: impl BufRead) -> io::Result<Self> {
+        let mut fields = BTreeMap::new();
+        for line in input.lines() {
+            let line = line?;
+            if line.is_empty() {
+                break;
+            }
+            if let Some((key, value)) = line.split_once('=') {
+                fields.insert(key.to_string(), value.to_string());
+            }
+        }
+        Ok(Self {
+            protocol: fields.remove("protocol"),
+            host: fields.remove("host"),
+            path: fields.remove("path"),
+            username: fields.remove("username"),
+            password: fields.remove("password"),
+        })
+    }
+
+    pub fn write(&self, mut output: impl Write) -> io::Result<()> {
+        for (key, value) in [
+            ("protocol", &self.protocol),
+            ("host", &self.host),
+            ("path", &self.path),
+            ("username", &self.username),
+            ("password", &self.password),
+        ] {
+            if let Some(value) = value {
+                writeln!(output, "{key}={value}")?;
+            }
+        }
+        output.flush()
+    }
+
+    // Tokens are stored by host without a port.
+    fn token_host(&self) -> Option<String> {
+        let host = self.host.as_deref()?;
+        let url = format!("{}://{host}", self.protocol.as_deref().unwrap_or("https"));
+        extract_host(&url)
+    }
+}
+
+pub fn run_credential_helper(
+    auth: &AuthManager,
+    action: &str,
+    repo_path: Option<&Path>,
+    input: impl BufRead,
+    output: impl Write,
+) -> io::Result<()> {
+    let request = CredentialRequest::parse(input)?;
+    let Some(host) = request.token_host() else {
+        return Ok(());
+    };
+
+    match action {
+        "get" => {
+            let Some(account) = requested_account(auth, &request, &host, repo_path) else {
+                return Ok(());
+            };
+            let Some(token) = auth.account_token(&account) else {
+                return Ok(());
+            };
+            CredentialRequest {
+                username: request
+                    .username
+                    .clone()
+                    .or_else(|| Some(token_username(&host).to_string())),
+                password: Some(token),
+                ..request
+            }
+            .write(output)
+        }
+        // Git sends `erase` after any rejection, which can be as minor as one
+        // organization's SSO not being authorized, so the token is kept and
+        // the account `get` handed out is flagged in the Auth panel instead.
+        "erase" => {
+            if let Some(password) = &request.password
+                && let Some(account) = requested_account(auth, &request, &host, repo_path)
+                && auth.account_token(&account).as_ref() == Some(password)
+                && let Err(err) = auth.record_rejection(&account)
+            {
+                return Err(io::Error::other(err));
+            }
+            Ok(())
+        }
+        // Git sends `store` for whatever credential worked, including ones
+        // from other helpers or a prompt, so it never replaces a saved token.
+        // Accounts only change from within GitSpace.
+        _ => Ok(()),
+    }
+}
+
+fn requested_account(
+    auth: &AuthManager,
+    request: &CredentialRequest,
+    host: &str,
+    repo_path: Option<&Path>,
+) -> Option<Account> {
+    let url = request_url(request, host, repo_path);
+    let repo_path = repo_path.map(|path| path.to_string_lossy());
+    auth.account_for(repo_path.as_deref(), &url)
+}
+
+// Git only sends `path` when `credential.useHttpPath` is set, so otherwise
+// the matching remote of the repository git is running in stands in for it.
+fn request_url(request: &CredentialRequest, host: &str, repo_path: Option<&Path>) -> String {
+    let protocol = request.protocol.as_deref().unwrap_or("https");
+    if let Some(path) = &request.path {
+        return format!("{protocol}://{host}/{path}");
+    }
+    let remote = repo_path
+        .and_then(|path| Repository::discover(path).ok())
+        .and_then(|repo| {
+            let names = repo.remotes().ok()?;
+            names.iter().flatten().find_map(|name| {
+                let url = repo.find_remote(name).ok()?.url()?.to_string();
+                (extract_host(&url).as_deref() == Some(host)).then_some(url)
+            })
+        });
+    remote.unwrap_or_else(|| format!("{protocol}://{host}"))
+}
+
+// Providers ignore the username or expect a fixed placeholder.
+fn token_username(host: &str) -> &'static str {
+    if host.contains("gitlab") {
+        "oauth2"
+    } else if host.contains("bitbucket") {
+        "x-token-auth"
+    } else {
+        "x-access-token"
+    }
+}
+
+pub fn helper_command() -> Option<String> {
+    let exe = std::env::current_exe().ok()?;
+    Some(helper_command_for(&exe))
+}
+
+fn helper_command_for(exe: &Path) -> String {
+    // Git runs `!` helpers through sh, including Git for Windows.
+    let path = exe
+        .to_string_lossy()
+        .replace('\\', "/")
+        .replace('\'', r"'\''");
+    format!("!'{path}' {HELPER_ARG}")
+}
+
+pub fn is_helper_registered(config: &Config) -> bool {
+    config
+        .multivar(HELPER_KEY, Some(HELPER_PATTERN))
+        .is_ok_and(|mut entries| entries.next().is_some())
+}
+
+pub fn register_helper(config: &mut Config, command: &str) -> Result<(), git2::Error> {
+    config.set_multivar(HELPER_KEY, HELPER_PATTERN, command)
+}
+
+pub fn unregister_helper(config: &mut Config) -> Result<(), git2::Error> {
+    if !is_helper_registered(config) {
+        return Ok(());
+    }
+    config.remove_multivar(HELPER_KEY, HELPER_PATTERN)
+}
+
+pub fn global_git_config() -> Result<Config, git2::Error> {
+    let path = match Config::find_global() {
+        Ok(path) => path,
+        Err(_) => dirs::home_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join(".gitconfig"),
+    };
+    Config::open(&path)
+}
+
+pub fn register_global_helper() -> Result<(), String> {
+    let command =
+        helper_command().ok_or_else(|| "Could not locate the GitSpace executable".to_string())?;
+    let mut config = global_git_config().map_err(|err| err.message().to_string())?;
+    register_helper(&mut config, &command).map_err(|err| err.message().to_string())
+}
+
+pub fn unregister_global_helper() -> Result<(), String> {
+    let mut config = global_git_config().map_err(|err| err.message().to_string())?;
+    unregister_helper(&mut config).map_err(|err| err.message().to_string())
+}
+
+pub fn is_global_helper_registered() -> bool {
+    global_git_config().is_ok_and(|config| is_helper_registered(&config))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::TokenStorage;
+
+    #[test]
+    fn parses_and_writes_protocol_fields() {
+        let input =
+            b"protocol=https\nhost=example.com:8443\npath=team/repo.git\nunknown=1\n\nignored=1\n";
+        let request = CredentialRequest::parse(&input[..]).unwrap();
+        assert_eq!(request.protocol.as_deref(), Some("https"));
+        assert_eq!(request.host.as_deref(), Some("example.com:8443"));
+        assert_eq!(request.path.as_deref(), Some("team/repo.git"));
+        assert_eq!(request.token_host().as_deref(), Some("example.com"));
+
+        let response = CredentialRequest {
+            username: Some("oauth2".to_string()),
+            password: Some("secret".to_string()),
+            ..request
+        };
+        let mut output = Vec::new();
+        response.write(&mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "protocol=https\nhost=example.com:8443\npath=team/repo.git\nusername=oauth2\npassword=secret\n"
+        );
+    }
+
+    #[test]
+    fn helper_command_quotes_the_executable() {
+        let command = helper_command_for(Path::new("/opt/Git Space/it's/GitSpace"));
+        assert_eq!(
+            command,
+            r"!'/opt/Git Space/it'\''s/GitSpace' credential-helper"
+        );
+        assert_eq!(token_username("gitlab.example.com"), "oauth2");
+        assert_eq!(token_username("github.com"), "x-access-token");
+    }
+
+    #[test]
+    fn request_url_falls_back_to_the_matching_remote() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        repo.remote("mirror", "https://gitlab.com/acme/tool.git")
+            .unwrap();
+        repo.remote("origin", "https://github.com/acme/tool.git")
+            .unwrap();
+        let request = CredentialRequest {
+            protocol: Some("https".to_string()),
+            host: Some("github.com".to_string()),
+            ..CredentialRequest::default()
+        };
+
+        assert_eq!(
+            request_url(&request, "github.com", Some(dir.path())),
+            "https://github.com/acme/tool.git"
+        );
+        assert_eq!(
+            request_url(&request, "github.com", None),
+            "https://github.com"
+        );
+        let with_path = CredentialRequest {
+            path: Some("ada/dotfiles.git".to_string()),
+            ..request
+        };
+        assert_eq!(
+            request_url(&with_path, "github.com", Some(dir.path())),
+            "https://github.com/ada/dotfiles.git"
+        );
+    }
+
+    #[test]
+    fn store_and_erase_keep_the_saved_token() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = TokenStorage::with_paths(
+            [7u8; 32],
+            dir.path().join("tokens.enc"),
+            dir.path().join("token-hosts.json"),
+            true,
+        );
+        storage.enable_master_password("hunter2").unwrap();
+        let auth = AuthManager {
+            storage,
+            accounts_path: dir.path().join("accounts.json"),
+        };
+        auth.save_account("github.com", "work", "ghp_gitspace", None)
+            .unwrap();
+
+        let input = b"protocol=https\nhost=github.com\nusername=ada\npassword=ghp_other\n\n";
+        run_credential_helper(&auth, "store", None, &input[..], io::sink()).unwrap();
+        assert_eq!(
+            auth.resolve_for_host("github.com").as_deref(),
+            Some("ghp_gitspace")
+        );
+
+        let mut output = Vec::new();
+        run_credential_helper(
+            &auth,
+            "get",
+            None,
+            &b"protocol=https\nhost=github.com\n\n"[..],
+            &mut output,
+        )
+        .unwrap();
+        assert!(
+            String::from_utf8(output)
+                .unwrap()
+                .contains("password=ghp_gitspace\n")
+        );
+
+        let erase = |password: &str| {
+            let input = format!("protocol=https\nhost=github.com\npassword={password}\n\n");
+            run_credential_helper(&auth, "erase", None, input.as_bytes(), io::sink()).unwrap();
+            auth.accounts().find("github.com", "work").cloned().unwrap()
+        };
+        assert!(erase("ghp_other").rejected_at.is_none());
+        let account = erase("ghp_gitspace");
+        assert!(account.rejected_at.is_some());
+        assert_eq!(
+            auth.account_token(&account).as_deref(),
+            Some("ghp_gitspace")
+        );
+    }
+
+    #[test]
+    fn registration_keeps_other_helpers() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = Config::open(&dir.path().join("gitconfig")).unwrap();
+        config.set_multivar(HELPER_KEY, "^$", "cache").unwrap();
+        assert!(!is_helper_registered(&config));
+
+        register_helper(&mut config, &helper_command_for(Path::new("/old/GitSpace"))).unwrap();
+        register_helper(&mut config, &helper_command_for(Path::new("/new/GitSpace"))).unwrap();
+        let helpers = |config: &Config| -> Vec<String> {
+            let mut values = Vec::new();
+            let mut entries = config.multivar(HELPER_KEY, None).unwrap();
+            while let Some(entry) = entries.next() {
+                values.push(entry.unwrap().value().unwrap().to_string());
+            }
+            values
+        };
+        assert_eq!(
+            helpers(&config),
+            vec![
+                "cache".to_string(),
+                "!'/new/GitSpace' credential-helper".to_string()
+            ]
+        );
+        assert!(is_helper_registered(&config));
+
+        unregister_helper(&mut config).unwrap();
+        unregister_helper(&mut config).unwrap();
+        assert_eq!(helpers(&config), vec!["cache".to_string()]);
+    }
+}
@@ -1,6 +1,8 @@
 use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
 
 use argon2::{Algorithm, Argon2, Params, Version};
 use base64::{Engine as _, engine::general_purpose};
@@ -10,13 +12,21 @@ use hostname::get as get_hostname;
 use keyring::Entry;
 use rand::RngCore;
 use rand::rngs::OsRng;
-use reqwest::blocking::Client;
-use reqwest::header::{AUTHORIZATION, HeaderMap, HeaderName, HeaderValue, USER_AGENT};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use tracing::{error, warn};
 use url::Url;
 
+use crate::config::NetworkOptions;
+use accounts::{Account, AccountIndex, AccountRule, accounts_path};
+use bundle::{BundleEntry, ImportChoice};
+use providers::{ProviderKind, TokenDetails, detect_provider};
+
+pub mod accounts;
+pub mod bundle;
+pub mod credential_helper;
+pub mod providers;
+
 const SERVICE_NAME: &str = "gitspace";
 const TOKEN_FILE_NAME: &str = "tokens.enc";
 const HOST_FILE_NAME: &str = "token-hosts.json";
@@ -24,10 +34,13 @@ const TOKEN_SALT_FILE: &str = "token-salt.bin";
 const TOKEN_PEPPER_FILE: &str = "token-pepper.bin";
 const TOKEN_KEYRING_ENTRY: &str = "token-key";
 const MASTER_PASSWORD_ENV: &str = "GITSPACE_TOKEN_MASTER_PASSWORD";
+const TOKEN_FILE_VERSION: u32 = 2;
+const LOCKED_MESSAGE: &str = "Token store is locked; unlock it with your master password";
 
 #[derive(Debug, Clone)]
 pub struct AuthManager {
     storage: TokenStorage,
+    accounts_path: PathBuf,
 }
 
 impl Default for AuthManager {
@@ -40,61 +53,275 @@ impl AuthManager {
     pub fn with_encrypted_fallback(allow_encrypted_fallback: bool) -> Self {
         Self {
             storage: TokenStorage::new(allow_encrypted_fallback),
+            accounts_path: accounts_path(),
         }
     }
 
+    pub fn accounts(&self) -> AccountIndex {
+        let mut index = AccountIndex::load(&self.accounts_path).unwrap_or_else(|err| {
+            warn!(target: "gitspace::auth", error = %err, "failed to load account list");
+            AccountIndex::default()
+        });
+        index.adopt_legacy_hosts(self.storage.known_hosts());
+        index
+    }
+
+    pub fn account_for(&self, repo_path: Option<&str>, url: &str) -> Option<Account> {
+        self.accounts().select(repo_path, url).cloned()
+    }
+
+    pub fn resolve_for_repo(&self, repo_path: &str, url: &str) -> Option<String> {
+        self.account_for(Some(repo_path), url)
+            .and_then(|account| self.account_token(&account))
+            .or_else(|| self.resolve_for_host(url))
+    }
+
     pub fn resolve_for_host(&self, host: &str) -> Option<String> {
-        self.storage.get_token(host).ok().flatten()
+        self.accounts()
+            .default_for(host)
+            .and_then(|account| self.account_token(account))
+            .or_else(|| self.storage.get_token(host).ok().flatten())
     }
 
     pub fn resolve_for_url(&self, url: &str) -> Option<String> {
-        let host = extract_host(url)?;
-        self.resolve_for_host(&host)
-            .or_else(|| match host.as_str() {
-                "github.com" => self.resolve_for_host("api.github.com"),
-                _ => None,
-            })
+        self.account_for(None, url)
+            .and_then(|account| self.account_token(&account))
     }
 
-    #[allow(dead_code)]
-    pub fn set_token(&self, host: &str, token: &str) -> Result<(), String> {
-        self.storage.set_token(host, token)
+    pub fn account_token(&self, account: &Account) -> Option<String> {
+        self.storage.get_token(account.storage_key()).ok().flatten()
     }
 
-    pub fn clear_token(&self, host: &str) -> Result<(), String> {
-        self.storage.clear_token(host)
+    pub fn record_rejection(&self, account: &Account) -> Result<(), String> {
+        let mut index = self.accounts();
+        let Some(saved) = index
+            .accounts
+            .iter_mut()
+            .find(|saved| saved.host == account.host && saved.name == account.name)
+        else {
+            return Ok(());
+        };
+        saved.rejected_at = Some(chrono::Utc::now().timestamp());
+        index.save(&self.accounts_path)
     }
 
-    pub fn known_hosts(&self) -> Vec<String> {
-        self.storage.known_hosts()
+    pub fn save_account(
+        &self,
+        host: &str,
+        name: &str,
+        token: &str,
+        details: Option<(ProviderKind, TokenDetails)>,
+    ) -> Result<Account, String> {
+        let host = host.trim().trim_end_matches('/');
+        let name = name.trim();
+        if name.is_empty() || name.contains('#') {
+            return Err("Account name cannot be empty or contain '#'".to_string());
+        }
+        let mut index = self.accounts();
+        let account = index.upsert(host, name);
+        if let Some((kind, details)) = details {
+            account.record_details(kind, details, chrono::Utc::now().timestamp());
+        }
+        let account = account.clone();
+        self.storage.set_token(account.storage_key(), token)?;
+        index.save(&self.accounts_path)?;
+        Ok(account)
     }
 
-    pub fn validate_token(&self, host: &str, token: &str) -> Result<(), String> {
-        if token.trim().is_empty() {
-            return Err("Token cannot be empty".to_string());
+    pub fn refresh_account(
+        &self,
+        host: &str,
+        name: &str,
+        network: &NetworkOptions,
+    ) -> Result<Account, String> {
+        let mut index = self.accounts();
+        let account = index
+            .accounts
+            .iter_mut()
+            .find(|account| account.host == host && account.name == name)
+            .ok_or_else(|| format!("No account named {name} for {host}"))?;
+        let token = self
+            .account_token(account)
+            .ok_or_else(|| format!("No token stored for {}", account.label()))?;
+        let (kind, details) = self.inspect_token(host, &token, network)?;
+        account.record_details(kind, details, chrono::Utc::now().timestamp());
+        let account = account.clone();
+        index.save(&self.accounts_path)?;
+        Ok(account)
+    }
+
+    pub fn remove_account(&self, host: &str, name: &str) -> Result<(), String> {
+        let mut index = self.accounts();
+        let Some(account) = index.remove(host, name) else {
+            return Ok(());
+        };
+        self.storage.clear_token(account.storage_key())?;
+        index.save(&self.accounts_path)
+    }
+
+    pub fn set_default_account(&self, host: &str, name: &str) -> Result<(), String> {
+        let mut index = self.accounts();
+        if index.find(host, name).is_none() {
+            return Err(format!("No account named {name} for {host}"));
         }
-        let normalized_host = normalize_host(host);
-        let client = Client::builder()
-            .user_agent("gitspace")
-            .build()
-            .map_err(|err| err.to_string())?;
+        index.defaults.insert(host.to_string(), name.to_string());
+        index.save(&self.accounts_path)
+    }
 
-        if normalized_host.contains("github") {
-            validate_github(&client, &normalized_host, token)
-        } else if normalized_host.contains("gitlab") {
-            validate_gitlab(&client, &normalized_host, token)
-        } else {
-            Ok(())
+    pub fn add_account_rule(&self, rule: AccountRule) -> Result<(), String> {
+        if rule.pattern.trim().is_empty() {
+            return Err("Pattern cannot be empty".to_string());
+        }
+        let mut index = self.accounts();
+        if index.find(&rule.host, &rule.account).is_none() {
+            return Err(format!(
+                "No account named {} for {}",
+                rule.account, rule.host
+            ));
         }
+        index
+            .rules
+            .retain(|existing| existing.pattern != rule.pattern || existing.host != rule.host);
+        index.rules.push(rule);
+        index.save(&self.accounts_path)
     }
 
-    pub fn validate_and_store(&self, host: &str, token: &str) -> Result<(), String> {
-        self.validate_token(host, token)?;
-        self.storage.set_token(host, token)
+    pub fn remove_account_rule(&self, position: usize) -> Result<(), String> {
+        let mut index = self.accounts();
+        if position < index.rules.len() {
+            index.rules.remove(position);
+        }
+        index.save(&self.accounts_path)
     }
+
+    // Accounts without a stored token are left out.
+    pub fn bundle_entries(&self, hosts: &[String]) -> Result<Vec<BundleEntry>, String> {
+        let mut entries = Vec::new();
+        for account in self.accounts().accounts {
+            if !hosts.contains(&account.host) {
+                continue;
+            }
+            if let Some(token) = self.storage.get_token(account.storage_key())? {
+                entries.push(BundleEntry { account, token });
+            }
+        }
+        if entries.is_empty() {
+            return Err("No saved tokens for the selected hosts".to_string());
+        }
+        Ok(entries)
+    }
+
+    pub fn import_conflict(&self, entry: &BundleEntry) -> bool {
+        self.accounts()
+            .find(&entry.account.host, &entry.account.name)
+            .is_some_and(|existing| {
+                self.account_token(existing).as_deref() != Some(entry.token.as_str())
+            })
+    }
+
+    pub fn import_credentials(
+        &self,
+        choices: &[(BundleEntry, ImportChoice)],
+    ) -> Result<usize, String> {
+        let mut index = self.accounts();
+        let mut imported = 0;
+        for (entry, choice) in choices {
+            let host = entry.account.host.trim().trim_end_matches('/');
+            let name = match choice {
+                ImportChoice::Skip => continue,
+                ImportChoice::Import => entry.account.name.clone(),
+                ImportChoice::KeepBoth => index.unused_name(host, &entry.account.name),
+            };
+            if host.is_empty() || name.is_empty() || name.contains('#') {
+                return Err(format!(
+                    "Bundle contains an invalid account: {}",
+                    entry.account.label()
+                ));
+            }
+            let existed = index.find(host, &name).is_some();
+            let account = index.upsert(host, &name);
+            account.copy_details_from(&entry.account);
+            let key = account.storage_key().to_string();
+            if let Err(err) = self.storage.set_token(&key, &entry.token) {
+                if !existed {
+                    index.remove(host, &name);
+                }
+                index.save(&self.accounts_path)?;
+                return Err(err);
+            }
+            imported += 1;
+        }
+        index.save(&self.accounts_path)?;
+        Ok(imported)
+    }
+
+    pub fn validate_and_store(
+        &self,
+        host: &str,
+        name: &str,
+        token: &str,
+        network: &NetworkOptions,
+    ) -> Result<Account, String> {
+        let details = self.inspect_token(host, token, network)?;
+        self.save_account(host, name, token.trim(), Some(details))
+    }
+
+    fn inspect_token(
+        &self,
+        host: &str,
+        token: &str,
+        network: &NetworkOptions,
+    ) -> Result<(ProviderKind, TokenDetails), String> {
+        if token.trim().is_empty() {
+            return Err("Token cannot be empty".to_string());
+        }
+        let provider = detect_provider(host, network).map_err(|err| err.detail().to_string())?;
+        let details = provider
+            .token_details(token.trim(), network)
+            .map_err(|err| err.detail().to_string())?;
+        Ok((provider.kind(), details))
+    }
+
     pub fn set_encrypted_fallback(&mut self, allowed: bool) {
         self.storage.set_allow_encrypted_fallback(allowed);
     }
+
+    pub fn is_master_password_enabled(&self) -> bool {
+        self.storage.is_master_password_enabled()
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.storage.is_locked()
+    }
+
+    pub fn unlock(&self, password: &str) -> Result<(), String> {
+        self.storage.unlock(password)
+    }
+
+    pub fn lock(&self) {
+        self.storage.lock();
+    }
+
+    pub fn enable_master_password(&self, password: &str) -> Result<(), String> {
+        self.storage.enable_master_password(password)
+    }
+
+    pub fn change_master_password(&self, current: &str, new_password: &str) -> Result<(), String> {
+        self.storage.change_master_password(current, new_password)
+    }
+
+    pub fn disable_master_password(&self, current: &str) -> Result<(), String> {
+        self.storage.disable_master_password(current)
+    }
+
+    pub fn set_auto_lock_minutes(&self, minutes: u64) {
+        self.storage
+            .set_auto_lock((minutes > 0).then(|| Duration::from_secs(minutes * 60)));
+    }
+
+    pub fn record_activity(&self) {
+        self.storage.record_activity();
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -103,6 +330,19 @@ pub struct TokenStorage {
     path: PathBuf,
     host_path: PathBuf,
     allow_encrypted_fallback: bool,
+    vault: Arc<Mutex<VaultState>>,
+}
+
+// Shared by every clone of the storage, so unlocking in the prompt unlocks
+// the whole app.
+#[derive(Debug, Default)]
+struct VaultState {
+    // `None` in device-key mode.
+    kdf: Option<KdfParams>,
+    // Present while unlocked.
+    key: Option<[u8; 32]>,
+    last_activity: Option<Instant>,
+    auto_lock: Option<Duration>,
 }
 
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
@@ -115,12 +355,64 @@ struct HostIndex {
     hosts: Vec<String>,
 }
 
+// Files written before the header existed have neither `version` nor `kdf`
+// and are encrypted with the device key.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct EncryptedTokenFile {
+    #[serde(default = "legacy_file_version")]
+    version: u32,
+    // Present when the key is derived from a master password.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    kdf: Option<KdfParams>,
     nonce: String,
     ciphertext: String,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct KdfParams {
+    algorithm: String,
+    memory_kib: u32,
+    iterations: u32,
+    parallelism: u32,
+    salt: String,
+}
+
+fn legacy_file_version() -> u32 {
+    1
+}
+
+impl KdfParams {
+    // Tests use minimal costs to stay fast.
+    fn generate() -> Self {
+        let (memory_kib, iterations) = if cfg!(test) { (64, 1) } else { (19_456, 2) };
+        let mut salt = [0u8; 16];
+        OsRng.fill_bytes(&mut salt);
+        Self {
+            algorithm: "argon2id".to_string(),
+            memory_kib,
+            iterations,
+            parallelism: 1,
+            salt: general_purpose::STANDARD.encode(salt),
+        }
+    }
+
+    fn derive(&self, password: &str) -> Result<[u8; 32], String> {
+        if self.algorithm != "argon2id" {
+            return Err(format!("Unsupported key derivation: {}", self.algorithm));
+        }
+        let salt = general_purpose::STANDARD
+            .decode(&self.salt)
+            .map_err(|err| format!("Failed to decode salt: {err}"))?;
+        let params = Params::new(self.memory_kib, self.iterations, self.parallelism, None)
+            .map_err(|err| format!("Invalid key derivation parameters: {err}"))?;
+        let mut key = [0u8; 32];
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(password.as_bytes(), &salt, &mut key)
+            .map_err(|err| format!("Failed to derive key: {err}"))?;
+        Ok(key)
+    }
+}
+
 impl TokenStorage {
     pub fn new(allow_encrypted_fallback: bool) -> Self {
         let key = load_or_create_keyring_key()
@@ -128,34 +420,201 @@ impl TokenStorage {
                 warn!(target: "gitspace::auth", error = %err, "failed to access keyring encryption key");
             })
             .unwrap_or_else(|_| derive_local_key());
-        let path = dirs::config_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join(SERVICE_NAME)
-            .join(TOKEN_FILE_NAME);
-        let host_path = dirs::config_dir()
+        let dir = dirs::config_dir()
             .unwrap_or_else(|| PathBuf::from("."))
-            .join(SERVICE_NAME)
-            .join(HOST_FILE_NAME);
+            .join(SERVICE_NAME);
+        let storage = Self::with_paths(
+            key,
+            dir.join(TOKEN_FILE_NAME),
+            dir.join(HOST_FILE_NAME),
+            allow_encrypted_fallback,
+        );
+        // Non-interactive runs such as the credential helper unlock from the
+        // environment.
+        if storage.is_master_password_enabled()
+            && let Ok(password) = std::env::var(MASTER_PASSWORD_ENV)
+            && let Err(err) = storage.unlock(&password)
+        {
+            warn!(target: "gitspace::auth", error = %err, "failed to unlock token store from environment");
+        }
+        storage
+    }
+
+    fn with_paths(
+        key: [u8; 32],
+        path: PathBuf,
+        host_path: PathBuf,
+        allow_encrypted_fallback: bool,
+    ) -> Self {
+        let kdf = read_token_file(&path)
+            .ok()
+            .flatten()
+            .and_then(|blob| blob.kdf);
         Self {
             key,
             path,
             host_path,
             allow_encrypted_fallback,
+            vault: Arc::new(Mutex::new(VaultState {
+                kdf,
+                ..VaultState::default()
+            })),
         }
     }
 
-    pub fn set_token(&self, host: &str, token: &str) -> Result<(), String> {
-        let keyring_result = self.store_in_keyring(host, token);
-        if let Err(ref err) = keyring_result {
-            warn!(target: "gitspace::auth", error = %err, "failed to store token in native keyring");
+    fn vault(&self) -> MutexGuard<'_, VaultState> {
+        self.vault
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    // In master-password mode tokens live only in the encrypted file, so the
+    // password actually guards them.
+    pub fn is_master_password_enabled(&self) -> bool {
+        self.vault().kdf.is_some()
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.lock_if_idle();
+        let vault = self.vault();
+        vault.kdf.is_some() && vault.key.is_none()
+    }
+
+    pub fn unlock(&self, password: &str) -> Result<(), String> {
+        let Some(blob) = read_token_file(&self.path)? else {
+            return Err("No encrypted token file to unlock".to_string());
+        };
+        let Some(kdf) = blob.kdf.clone() else {
+            return Ok(());
+        };
+        let key = kdf.derive(password)?;
+        decrypt_tokens(&blob, &key).map_err(|_| "Incorrect master password".to_string())?;
+        let mut vault = self.vault();
+        vault.kdf = Some(kdf);
+        vault.key = Some(key);
+        vault.last_activity = Some(Instant::now());
+        Ok(())
+    }
+
+    pub fn lock(&self) {
+        self.vault().key = None;
+    }
+
+    pub fn set_auto_lock(&self, after: Option<Duration>) {
+        self.vault().auto_lock = after;
+    }
+
+    pub fn record_activity(&self) {
+        let mut vault = self.vault();
+        if vault.key.is_some() {
+            vault.last_activity = Some(Instant::now());
+        }
+    }
+
+    fn lock_if_idle(&self) {
+        let mut vault = self.vault();
+        if let (Some(after), Some(last)) = (vault.auto_lock, vault.last_activity)
+            && last.elapsed() >= after
+        {
+            vault.key = None;
+            vault.last_activity = None;
+        }
+    }
+
+    pub fn enable_master_password(&self, password: &str) -> Result<(), String> {
+        if password.is_empty() {
+            return Err("Master password cannot be empty".to_string());
+        }
+        if self.is_master_password_enabled() {
+            return Err("A master password is already set".to_string());
+        }
+        let hosts = self.known_hosts();
+        let mut map = TokenMap::default();
+        for host in &hosts {
+            if let Some(token) = self.get_token(host)? {
+                map.tokens.insert(host.clone(), token);
+            }
+        }
+        let kdf = KdfParams::generate();
+        let key = kdf.derive(password)?;
+        self.write_token_file(&map, &key, Some(kdf.clone()))?;
+        {
+            let mut vault = self.vault();
+            vault.kdf = Some(kdf);
+            vault.key = Some(key);
+            vault.last_activity = Some(Instant::now());
+        }
+        for host in map.tokens.keys() {
+            if let Err(err) = self.remove_from_keyring(host) {
+                warn!(target: "gitspace::auth", error = %err, "failed to remove migrated token from keyring");
+            }
         }
+        Ok(())
+    }
 
-        let result = if self.allow_encrypted_fallback {
+    // Passing the current password again rotates the key without changing it.
+    pub fn change_master_password(&self, current: &str, new_password: &str) -> Result<(), String> {
+        if new_password.is_empty() {
+            return Err("Master password cannot be empty".to_string());
+        }
+        let map = self.open_with_password(current)?;
+        let kdf = KdfParams::generate();
+        let key = kdf.derive(new_password)?;
+        self.write_token_file(&map, &key, Some(kdf.clone()))?;
+        let mut vault = self.vault();
+        vault.kdf = Some(kdf);
+        vault.key = Some(key);
+        vault.last_activity = Some(Instant::now());
+        Ok(())
+    }
+
+    pub fn disable_master_password(&self, current: &str) -> Result<(), String> {
+        let map = self.open_with_password(current)?;
+        if self.allow_encrypted_fallback {
+            self.write_token_file(&map, &self.key, None)?;
+        } else {
+            for (host, token) in &map.tokens {
+                self.store_in_keyring(host, token)?;
+            }
+            fs::remove_file(&self.path)
+                .map_err(|err| format!("Failed to remove encrypted token file: {err}"))?;
+        }
+        let mut vault = self.vault();
+        vault.kdf = None;
+        vault.key = None;
+        vault.last_activity = None;
+        Ok(())
+    }
+
+    fn open_with_password(&self, password: &str) -> Result<TokenMap, String> {
+        let blob = read_token_file(&self.path)?;
+        let kdf = blob
+            .as_ref()
+            .and_then(|blob| blob.kdf.as_ref())
+            .ok_or_else(|| "No master password is set".to_string())?;
+        let key = kdf.derive(password)?;
+        blob.as_ref()
+            .map(|blob| decrypt_tokens(blob, &key))
+            .unwrap_or_else(|| Ok(TokenMap::default()))
+            .map_err(|_| "Incorrect master password".to_string())
+    }
+
+    pub fn set_token(&self, host: &str, token: &str) -> Result<(), String> {
+        let result = if self.is_master_password_enabled() {
             self.persist_fallback(host, token)
-        } else if keyring_result.is_err() {
-            Err("Native keyring unavailable and encrypted storage is disabled".to_string())
         } else {
-            Ok(())
+            let keyring_result = self.store_in_keyring(host, token);
+            if let Err(ref err) = keyring_result {
+                warn!(target: "gitspace::auth", error = %err, "failed to store token in native keyring");
+            }
+
+            if self.allow_encrypted_fallback {
+                self.persist_fallback(host, token)
+            } else if keyring_result.is_err() {
+                Err("Native keyring unavailable and encrypted storage is disabled".to_string())
+            } else {
+                Ok(())
+            }
         };
 
         if result.is_ok() {
@@ -168,6 +627,10 @@ impl TokenStorage {
     }
 
     pub fn get_token(&self, host: &str) -> Result<Option<String>, String> {
+        if self.is_master_password_enabled() {
+            let tokens = self.read_fallback()?;
+            return Ok(tokens.tokens.get(host).cloned());
+        }
         match self.fetch_from_keyring(host) {
             Ok(Some(token)) => return Ok(Some(token)),
             Ok(None) => {}
@@ -187,10 +650,11 @@ impl TokenStorage {
     }
 
     pub fn clear_token(&self, host: &str) -> Result<(), String> {
-        if let Err(err) = self.remove_from_keyring(host) {
+        let master = self.is_master_password_enabled();
+        if !master && let Err(err) = self.remove_from_keyring(host) {
             warn!(target: "gitspace::auth", error = %err, "failed to clear token from native keyring");
         }
-        let mut result = if self.allow_encrypted_fallback {
+        let mut result = if master || self.allow_encrypted_fallback {
             let mut map = self.read_fallback()?;
             map.tokens.remove(host);
             self.write_fallback(&map)
@@ -215,7 +679,7 @@ impl TokenStorage {
                 hosts.insert(host);
             }
         }
-        if self.allow_encrypted_fallback {
+        if self.allow_encrypted_fallback || self.is_master_password_enabled() {
             if let Ok(map) = self.read_fallback() {
                 for host in map.tokens.keys() {
                     hosts.insert(host.clone());
@@ -256,32 +720,63 @@ impl TokenStorage {
     }
 
     fn persist_fallback(&self, host: &str, token: &str) -> Result<(), String> {
-        let mut tokens = self.read_fallback().unwrap_or_default();
+        let mut tokens = if self.is_master_password_enabled() {
+            self.read_fallback()?
+        } else {
+            self.read_fallback().unwrap_or_default()
+        };
         tokens.tokens.insert(host.to_string(), token.to_string());
         self.write_fallback(&tokens)
     }
 
+    // Writing in the current format also migrates files that predate the
+    // header.
     fn write_fallback(&self, map: &TokenMap) -> Result<(), String> {
+        let (key, kdf) = {
+            let vault = self.vault();
+            match (&vault.kdf, vault.key) {
+                (Some(kdf), Some(key)) => (key, Some(kdf.clone())),
+                (Some(_), None) => return Err(LOCKED_MESSAGE.to_string()),
+                (None, _) => (self.key, None),
+            }
+        };
+        self.record_activity();
+        self.write_token_file(map, &key, kdf)
+    }
+
+    fn write_token_file(
+        &self,
+        map: &TokenMap,
+        key: &[u8; 32],
+        kdf: Option<KdfParams>,
+    ) -> Result<(), String> {
         if let Some(parent) = self.path.parent() {
             fs::create_dir_all(parent)
                 .map_err(|err| format!("Failed to prepare credential directory: {err}"))?;
         }
-        let blob = encrypt_tokens(map, &self.key)?;
+        let mut blob = encrypt_tokens(map, key)?;
+        blob.kdf = kdf;
         let serialized = serde_json::to_string_pretty(&blob)
             .map_err(|err| format!("Failed to serialize credentials: {err}"))?;
-        fs::write(&self.path, serialized)
-            .map_err(|err| format!("Failed to write credentials: {err}"))
+        let temp = self.path.with_extension("enc.tmp");
+        fs::write(&temp, serialized)
+            .map_err(|err| format!("Failed to write credentials: {err}"))?;
+        fs::rename(&temp, &self.path).map_err(|err| format!("Failed to write credentials: {err}"))
     }
 
     fn read_fallback(&self) -> Result<TokenMap, String> {
-        if !self.path.exists() {
+        let Some(blob) = read_token_file(&self.path)? else {
             return Ok(TokenMap::default());
-        }
-        let data = fs::read_to_string(&self.path)
-            .map_err(|err| format!("Failed to read credential file: {err}"))?;
-        let blob: EncryptedTokenFile = serde_json::from_str(&data)
-            .map_err(|err| format!("Failed to parse credential file: {err}"))?;
-        decrypt_tokens(&blob, &self.key)
+        };
+        let key = if blob.kdf.is_some() {
+            self.lock_if_idle();
+            let key = self.vault().key.ok_or_else(|| LOCKED_MESSAGE.to_string())?;
+            self.record_activity();
+            key
+        } else {
+            self.key
+        };
+        decrypt_tokens(&blob, &key)
     }
 
     fn record_host(&self, host: &str) -> Result<(), String> {
@@ -330,40 +825,64 @@ impl TokenStorage {
 }
 
 fn encrypt_tokens(map: &TokenMap, key: &[u8; 32]) -> Result<EncryptedTokenFile, String> {
+    let serialized =
+        serde_json::to_string(map).map_err(|err| format!("Failed to serialize tokens: {err}"))?;
+    let (nonce, ciphertext) = seal(serialized.as_bytes(), key)?;
+    Ok(EncryptedTokenFile {
+        version: TOKEN_FILE_VERSION,
+        kdf: None,
+        nonce,
+        ciphertext,
+    })
+}
+
+fn seal(plaintext: &[u8], key: &[u8; 32]) -> Result<(String, String), String> {
     let cipher = ChaCha20Poly1305::new(Key::from_slice(key));
     let mut nonce = [0u8; 12];
     OsRng.fill_bytes(&mut nonce);
     let nonce_obj = Nonce::from_slice(&nonce);
-
-    let serialized =
-        serde_json::to_string(map).map_err(|err| format!("Failed to serialize tokens: {err}"))?;
     let encrypted = cipher
-        .encrypt(nonce_obj, serialized.as_bytes())
+        .encrypt(nonce_obj, plaintext)
         .map_err(|err| format!("Failed to encrypt tokens: {err}"))?;
+    Ok((
+        general_purpose::STANDARD.encode(nonce),
+        general_purpose::STANDARD.encode(encrypted),
+    ))
+}
 
-    Ok(EncryptedTokenFile {
-        nonce: general_purpose::STANDARD.encode(nonce),
-        ciphertext: general_purpose::STANDARD.encode(encrypted),
-    })
+fn read_token_file(path: &Path) -> Result<Option<EncryptedTokenFile>, String> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let data =
+        fs::read_to_string(path).map_err(|err| format!("Failed to read credential file: {err}"))?;
+    serde_json::from_str(&data)
+        .map(Some)
+        .map_err(|err| format!("Failed to parse credential file: {err}"))
 }
 
 fn decrypt_tokens(blob: &EncryptedTokenFile, key: &[u8; 32]) -> Result<TokenMap, String> {
+    let plaintext = open(&blob.nonce, &blob.ciphertext, key)?;
+    let content = String::from_utf8(plaintext)
+        .map_err(|err| format!("Invalid credential encoding: {err}"))?;
+    serde_json::from_str(&content)
+        .map_err(|err| format!("Failed to parse decrypted credentials: {err}"))
+}
+
+fn open(nonce: &str, ciphertext: &str, key: &[u8; 32]) -> Result<Vec<u8>, String> {
     let cipher = ChaCha20Poly1305::new(Key::from_slice(key));
     let nonce_bytes = general_purpose::STANDARD
-        .decode(&blob.nonce)
+        .decode(nonce)
         .map_err(|err| format!("Failed to decode nonce: {err}"))?;
-    let nonce = Nonce::from_slice(&nonce_bytes);
+    if nonce_bytes.len() != 12 {
+        return Err("Failed to decode nonce: unexpected length".to_string());
+    }
     let cipher_bytes = general_purpose::STANDARD
-        .decode(&blob.ciphertext)
+        .decode(ciphertext)
         .map_err(|err| format!("Failed to decode ciphertext: {err}"))?;
-
-    let plaintext = cipher
-        .decrypt(nonce, cipher_bytes.as_ref())
-        .map_err(|err| format!("Failed to decrypt credentials: {err}"))?;
-    let content = String::from_utf8(plaintext)
-        .map_err(|err| format!("Invalid credential encoding: {err}"))?;
-    serde_json::from_str(&content)
-        .map_err(|err| format!("Failed to parse decrypted credentials: {err}"))
+    cipher
+        .decrypt(Nonce::from_slice(&nonce_bytes), cipher_bytes.as_ref())
+        .map_err(|err| format!("Failed to decrypt credentials: {err}"))
 }
 
 fn derive_local_key() -> [u8; 32] {
@@ -448,68 +967,6 @@ fn load_or_create_secret(name: &str, len: usize) -> Vec<u8> {
     secret
 }
 
-fn normalize_host(host: &str) -> String {
-    let trimmed = host.trim().trim_end_matches('/');
-    let with_scheme = if trimmed.starts_with("http://") || trimmed.starts_with("https://") {
-        trimmed.to_string()
-    } else {
-        format!("https://{}", trimmed)
-    };
-    if let Ok(parsed) = Url::parse(&with_scheme) {
-        if let Some(host) = parsed.host_str() {
-            let scheme = parsed.scheme();
-            return format!("{}://{}", scheme, host);
-        }
-    }
-    with_scheme
-}
-
-fn validate_github(client: &Client, host: &str, token: &str) -> Result<(), String> {
-    let api_base = if host.contains("api.github.com") {
-        host.to_string()
-    } else if host.contains("github.com") {
-        "https://api.github.com".to_string()
-    } else {
-        format!("{}/api/v3", host)
-    };
-    let url = format!("{}/user", api_base.trim_end_matches('/'));
-
-    let response = client
-        .get(url)
-        .header(USER_AGENT, HeaderValue::from_static("gitspace"))
-        .header(AUTHORIZATION, format!("Bearer {}", token))
-        .send()
-        .map_err(|err| format!("GitHub validation failed: {err}"))?;
-
-    if response.status().is_success() {
-        Ok(())
-    } else {
-        Err(format!("GitHub rejected token ({}).", response.status()))
-    }
-}
-
-fn validate_gitlab(client: &Client, host: &str, token: &str) -> Result<(), String> {
-    let url = format!("{}/api/v4/user", host.trim_end_matches('/'));
-    let mut headers = HeaderMap::new();
-    headers.insert(USER_AGENT, HeaderValue::from_static("gitspace"));
-    headers.insert(
-        HeaderName::from_static("private-token"),
-        HeaderValue::from_str(token).map_err(|err| err.to_string())?,
-    );
-
-    let response = client
-        .get(url)
-        .headers(headers)
-        .send()
-        .map_err(|err| format!("GitLab validation failed: {err}"))?;
-
-    if response.status().is_success() {
-        Ok(())
-    } else {
-        Err(format!("GitLab rejected token ({}).", response.status()))
-    }
-}
-
 pub fn extract_host(target: &str) -> Option<String> {
     if let Ok(url) = Url::parse(target) {
         return url.host_str().map(|h| h.to_string());
@@ -531,3 +988,138 @@ pub fn extract_host(target: &str) -> Option<String> {
         .filter(|segment| !segment.is_empty())
         .map(|h| h.to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LEGACY_HOST: &str = "legacy.gitspace.test";
+
+    fn storage_in(dir: &Path) -> TokenStorage {
+        TokenStorage::with_paths(
+            [7u8; 32],
+            dir.join(TOKEN_FILE_NAME),
+            dir.join(HOST_FILE_NAME),
+            true,
+        )
+    }
+
+    fn read_blob(dir: &Path) -> EncryptedTokenFile {
+        read_token_file(&dir.join(TOKEN_FILE_NAME))
+            .unwrap()
+            .unwrap()
+    }
+
+    // A `tokens.enc` as written before the header existed.
+    fn write_legacy_file(dir: &Path) {
+        let mut map = TokenMap::default();
+        map.tokens
+            .insert(LEGACY_HOST.to_string(), "legacy-token".to_string());
+        let blob = encrypt_tokens(&map, &[7u8; 32]).unwrap();
+        let legacy = serde_json::json!({ "nonce": blob.nonce, "ciphertext": blob.ciphertext });
+        fs::write(dir.join(TOKEN_FILE_NAME), legacy.to_string()).unwrap();
+    }
+
+    #[test]
+    fn master_password_migrates_locks_and_rotates() {
+        let dir = tempfile::tempdir().unwrap();
+        write_legacy_file(dir.path());
+        assert_eq!(read_blob(dir.path()).version, 1);
+
+        let storage = storage_in(dir.path());
+        assert!(!storage.is_master_password_enabled());
+        assert_eq!(
+            storage.get_token(LEGACY_HOST).unwrap().as_deref(),
+            Some("legacy-token")
+        );
+
+        storage.enable_master_password("hunter2").unwrap();
+        let blob = read_blob(dir.path());
+        assert_eq!(blob.version, TOKEN_FILE_VERSION);
+        let kdf = blob.kdf.clone().expect("argon2 header");
+        assert_eq!(kdf.algorithm, "argon2id");
+        assert!(decrypt_tokens(&blob, &[7u8; 32]).is_err());
+        storage
+            .set_token("second.gitspace.test", "second-token")
+            .unwrap();
+
+        let reopened = storage_in(dir.path());
+        assert!(reopened.is_locked());
+        assert!(reopened.get_token(LEGACY_HOST).is_err());
+        assert_eq!(
+            reopened.unlock("wrong").unwrap_err(),
+            "Incorrect master password"
+        );
+        reopened.unlock("hunter2").unwrap();
+        assert_eq!(
+            reopened
+                .get_token("second.gitspace.test")
+                .unwrap()
+                .as_deref(),
+            Some("second-token")
+        );
+
+        reopened
+            .change_master_password("hunter2", "hunter3")
+            .unwrap();
+        assert_ne!(read_blob(dir.path()).kdf.unwrap().salt, kdf.salt);
+        let reopened = storage_in(dir.path());
+        assert!(reopened.unlock("hunter2").is_err());
+        reopened.unlock("hunter3").unwrap();
+
+        reopened.set_auto_lock(Some(Duration::ZERO));
+        assert!(reopened.is_locked());
+        reopened.set_auto_lock(None);
+
+        reopened.disable_master_password("hunter3").unwrap();
+        assert!(read_blob(dir.path()).kdf.is_none());
+        let reopened = storage_in(dir.path());
+        assert!(!reopened.is_locked());
+        assert_eq!(
+            reopened.get_token(LEGACY_HOST).unwrap().as_deref(),
+            Some("legacy-token")
+        );
+    }
+
+    #[test]
+    fn credential_import_resolves_conflicts() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = storage_in(dir.path());
+        storage.enable_master_password("hunter2").unwrap();
+        let auth = AuthManager {
+            storage,
+            accounts_path: dir.path().join("accounts.json"),
+        };
+        auth.save_account("github.com", "work", "ghp_local", None)
+            .unwrap();
+
+        let exported = auth.bundle_entries(&["github.com".to_string()]).unwrap();
+        assert_eq!(exported.len(), 1);
+        assert!(!auth.import_conflict(&exported[0]));
+        assert!(auth.bundle_entries(&["gitlab.com".to_string()]).is_err());
+
+        let mut incoming = exported[0].clone();
+        incoming.token = "ghp_remote".to_string();
+        incoming.account.username = Some("octocat".to_string());
+        assert!(auth.import_conflict(&incoming));
+
+        let imported = auth
+            .import_credentials(&[
+                (incoming.clone(), ImportChoice::KeepBoth),
+                (incoming.clone(), ImportChoice::Skip),
+            ])
+            .unwrap();
+        assert_eq!(imported, 1);
+        let index = auth.accounts();
+        let copy = index.find("github.com", "work-imported").unwrap();
+        assert_eq!(copy.username.as_deref(), Some("octocat"));
+        assert_eq!(auth.account_token(copy).as_deref(), Some("ghp_remote"));
+        let original = index.find("github.com", "work").unwrap();
+        assert_eq!(auth.account_token(original).as_deref(), Some("ghp_local"));
+
+        auth.import_credentials(&[(incoming, ImportChoice::Import)])
+            .unwrap();
+        let original = auth.accounts().find("github.com", "work").cloned().unwrap();
+        assert_eq!(auth.account_token(&original).as_deref(), Some("ghp_remote"));
+    }
+}
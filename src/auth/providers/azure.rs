@@ -0,0 +1,127 @@
+use serde::Deserialize;
+
+use super::{
+    GitProvider, ProviderKind, RemoteRepo, TokenAuth, UserProfile, get_json, get_profile,
+    http_client,
+};
+use crate::config::NetworkOptions;
+use crate::error::AppError;
+
+const API_VERSION: (&str, &str) = ("api-version", "7.0");
+
+// Azure DevOps is scoped to an organization, so the base is
+// `https://dev.azure.com/<organization>` (or a legacy `*.visualstudio.com`).
+pub struct AzureDevOpsProvider {
+    api_base: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct List<T> {
+    #[serde(default = "Vec::new")]
+    value: Vec<T>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Project {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Repository {
+    name: String,
+    project: Project,
+    remote_url: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ConnectionData {
+    authenticated_user: AuthenticatedUser,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AuthenticatedUser {
+    #[serde(default)]
+    provider_display_name: String,
+    custom_display_name: Option<String>,
+}
+
+impl AzureDevOpsProvider {
+    pub fn new(organization_url: impl Into<String>) -> Self {
+        Self {
+            api_base: organization_url.into().trim_end_matches('/').to_string(),
+        }
+    }
+
+    fn ensure_organization(&self) -> Result<(), AppError> {
+        let host = self
+            .api_base
+            .trim_start_matches("https://")
+            .trim_start_matches("http://");
+        if host == "dev.azure.com" {
+            return Err(AppError::Validation(
+                "Azure DevOps needs an organization, e.g. dev.azure.com/contoso".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl GitProvider for AzureDevOpsProvider {
+    fn kind(&self) -> ProviderKind {
+        ProviderKind::AzureDevOps
+    }
+
+    // The repositories API has no name filter, so this lists the
+    // organization's repositories and matches names locally.
+    fn search(
+        &self,
+        query: &str,
+        token: Option<&str>,
+        network: &NetworkOptions,
+    ) -> Result<Vec<RemoteRepo>, AppError> {
+        self.ensure_organization()?;
+        let client = http_client(network)?;
+        let url = format!("{}/_apis/git/repositories", self.api_base);
+        let repos: List<Repository> = get_json(
+            &client,
+            &url,
+            &[API_VERSION],
+            token,
+            TokenAuth::BasicPassword,
+            network,
+        )?;
+        let needle = query.trim().to_lowercase();
+        Ok(repos
+            .value
+            .into_iter()
+            .filter(|repo| {
+                repo.name.to_lowercase().contains(&needle)
+                    || repo.project.name.to_lowercase().contains(&needle)
+            })
+            .map(|repo| RemoteRepo {
+                name: format!("{}/{}", repo.project.name, repo.name),
+                url: repo.remote_url,
+            })
+            .collect())
+    }
+
+    fn user_profile(&self, token: &str, network: &NetworkOptions) -> Result<UserProfile, AppError> {
+        self.ensure_organization()?;
+        let url = format!("{}/_apis/connectionData", self.api_base);
+        let data: ConnectionData =
+            get_profile(self.kind(), &url, token, TokenAuth::BasicPassword, network)?;
+        let user = data.authenticated_user;
+        if user.provider_display_name.is_empty() || user.provider_display_name == "Anonymous" {
+            return Err(AppError::Validation(
+                "Azure DevOps did not accept the token for this organization.".to_string(),
+            ));
+        }
+        Ok(UserProfile {
+            username: user.provider_display_name,
+            display_name: user.custom_display_name,
+        })
+    }
+}
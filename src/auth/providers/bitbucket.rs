@@ -0,0 +1,240 @@
+use serde::Deserialize;
+
+use super::{
+    GitProvider, ProviderKind, RemoteRepo, TokenAuth, UserProfile, get, get_json, get_profile,
+    http_client,
+};
+use crate::config::NetworkOptions;
+use crate::error::AppError;
+
+pub struct BitbucketCloudProvider {
+    api_base: String,
+}
+
+pub struct BitbucketServerProvider {
+    web_base: String,
+    api_base: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Page<T> {
+    #[serde(default = "Vec::new")]
+    values: Vec<T>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CloneLink {
+    name: String,
+    href: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Links {
+    #[serde(default)]
+    clone: Vec<CloneLink>,
+}
+
+impl Links {
+    fn http_clone(self) -> Option<String> {
+        self.clone
+            .into_iter()
+            .find(|link| link.name == "https" || link.name == "http")
+            .map(|link| link.href)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct CloudRepository {
+    full_name: String,
+    #[serde(default)]
+    links: Links,
+}
+
+#[derive(Debug, Deserialize)]
+struct CloudProfile {
+    username: Option<String>,
+    nickname: Option<String>,
+    display_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ServerProject {
+    key: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ServerRepository {
+    slug: String,
+    project: ServerProject,
+    #[serde(default)]
+    links: Links,
+}
+
+#[derive(Debug, Deserialize)]
+struct ServerUser {
+    #[serde(rename = "displayName")]
+    display_name: Option<String>,
+}
+
+impl BitbucketCloudProvider {
+    pub fn new(api_base: impl Into<String>) -> Self {
+        Self {
+            api_base: api_base.into().trim_end_matches('/').to_string(),
+        }
+    }
+
+    pub fn for_host(web: &str) -> Self {
+        let host = web
+            .trim_start_matches("https://")
+            .trim_start_matches("http://");
+        if host == "bitbucket.org" || host == "api.bitbucket.org" {
+            Self::new("https://api.bitbucket.org/2.0")
+        } else {
+            Self::new(format!("{web}/2.0"))
+        }
+    }
+}
+
+impl GitProvider for BitbucketCloudProvider {
+    fn kind(&self) -> ProviderKind {
+        ProviderKind::BitbucketCloud
+    }
+
+    fn search(
+        &self,
+        query: &str,
+        token: Option<&str>,
+        network: &NetworkOptions,
+    ) -> Result<Vec<RemoteRepo>, AppError> {
+        let query = query.trim();
+        let filter;
+        let (url, params) = match query.split_once('/') {
+            Some((workspace, term)) => {
+                filter = name_filter(term);
+                (
+                    format!("{}/repositories/{workspace}", self.api_base),
+                    vec![("pagelen", "25"), ("q", filter.as_str())],
+                )
+            }
+            None if token.is_some() => {
+                filter = name_filter(query);
+                (
+                    format!("{}/repositories", self.api_base),
+                    vec![
+                        ("pagelen", "25"),
+                        ("role", "member"),
+                        ("q", filter.as_str()),
+                    ],
+                )
+            }
+            None => (
+                format!("{}/repositories/{query}", self.api_base),
+                vec![("pagelen", "25")],
+            ),
+        };
+
+        let client = http_client(network)?;
+        let page: Page<CloudRepository> =
+            get_json(&client, &url, &params, token, TokenAuth::Bearer, network)?;
+        Ok(page
+            .values
+            .into_iter()
+            .filter_map(|repo| {
+                Some(RemoteRepo {
+                    url: repo.links.http_clone()?,
+                    name: repo.full_name,
+                })
+            })
+            .collect())
+    }
+
+    fn user_profile(&self, token: &str, network: &NetworkOptions) -> Result<UserProfile, AppError> {
+        let url = format!("{}/user", self.api_base);
+        let profile: CloudProfile =
+            get_profile(self.kind(), &url, token, TokenAuth::Bearer, network)?;
+        Ok(UserProfile {
+            username: profile.username.or(profile.nickname).unwrap_or_default(),
+            display_name: profile.display_name,
+        })
+    }
+}
+
+fn name_filter(term: &str) -> String {
+    format!("name ~ \"{}\"", term.replace('"', ""))
+}
+
+impl BitbucketServerProvider {
+    pub fn new(web_base: impl Into<String>) -> Self {
+        let web_base = web_base.into().trim_end_matches('/').to_string();
+        Self {
+            api_base: format!("{web_base}/rest/api/1.0"),
+            web_base,
+        }
+    }
+}
+
+impl GitProvider for BitbucketServerProvider {
+    fn kind(&self) -> ProviderKind {
+        ProviderKind::BitbucketServer
+    }
+
+    fn search(
+        &self,
+        query: &str,
+        token: Option<&str>,
+        network: &NetworkOptions,
+    ) -> Result<Vec<RemoteRepo>, AppError> {
+        let client = http_client(network)?;
+        let url = format!("{}/repos", self.api_base);
+        let page: Page<ServerRepository> = get_json(
+            &client,
+            &url,
+            &[("name", query.trim()), ("limit", "25")],
+            token,
+            TokenAuth::Bearer,
+            network,
+        )?;
+        Ok(page
+            .values
+            .into_iter()
+            .filter_map(|repo| {
+                Some(RemoteRepo {
+                    name: format!("{}/{}", repo.project.key, repo.slug),
+                    url: repo.links.http_clone()?,
+                })
+            })
+            .collect())
+    }
+
+    // Server has no "current user" resource; the applinks `whoami` servlet
+    // returns the username for the token as plain text.
+    fn user_profile(&self, token: &str, network: &NetworkOptions) -> Result<UserProfile, AppError> {
+        let client = http_client(network)?;
+        let url = format!("{}/plugins/servlet/applinks/whoami", self.web_base);
+        let response = get(&client, &url, &[], Some(token), TokenAuth::Bearer, network)?;
+        let status = response.status();
+        let username = response.text().unwrap_or_default().trim().to_string();
+        if !status.is_success() || username.is_empty() {
+            return Err(AppError::Validation(format!(
+                "{} rejected token ({status}).",
+                self.kind().label()
+            )));
+        }
+
+        let user_url = format!("{}/users/{username}", self.api_base);
+        let display_name = get_json::<ServerUser>(
+            &client,
+            &user_url,
+            &[],
+            Some(token),
+            TokenAuth::Bearer,
+            network,
+        )
+        .ok()
+        .and_then(|user| user.display_name);
+        Ok(UserProfile {
+            username,
+            display_name,
+        })
+    }
+}
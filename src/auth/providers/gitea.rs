@@ -0,0 +1,81 @@
+use serde::Deserialize;
+
+use super::{
+    GitProvider, ProviderKind, RemoteRepo, TokenAuth, UserProfile, get_json, get_profile,
+    http_client,
+};
+use crate::config::NetworkOptions;
+use crate::error::AppError;
+
+// Forgejo keeps Gitea's API, so one adapter serves both.
+pub struct GiteaProvider {
+    api_base: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchResponse {
+    #[serde(default)]
+    data: Vec<Repository>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Repository {
+    full_name: String,
+    clone_url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Profile {
+    login: String,
+    full_name: Option<String>,
+}
+
+impl GiteaProvider {
+    pub fn new(api_base: impl Into<String>) -> Self {
+        Self {
+            api_base: api_base.into().trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+impl GitProvider for GiteaProvider {
+    fn kind(&self) -> ProviderKind {
+        ProviderKind::Gitea
+    }
+
+    fn search(
+        &self,
+        query: &str,
+        token: Option<&str>,
+        network: &NetworkOptions,
+    ) -> Result<Vec<RemoteRepo>, AppError> {
+        let client = http_client(network)?;
+        let url = format!("{}/repos/search", self.api_base);
+        let response: SearchResponse = get_json(
+            &client,
+            &url,
+            &[("q", query), ("limit", "20")],
+            token,
+            TokenAuth::TokenPrefix,
+            network,
+        )?;
+        Ok(response
+            .data
+            .into_iter()
+            .map(|repo| RemoteRepo {
+                name: repo.full_name,
+                url: repo.clone_url,
+            })
+            .collect())
+    }
+
+    fn user_profile(&self, token: &str, network: &NetworkOptions) -> Result<UserProfile, AppError> {
+        let url = format!("{}/user", self.api_base);
+        let profile: Profile =
+            get_profile(self.kind(), &url, token, TokenAuth::TokenPrefix, network)?;
+        Ok(UserProfile {
+            username: profile.login,
+            display_name: profile.full_name.filter(|name| !name.is_empty()),
+        })
+    }
+}
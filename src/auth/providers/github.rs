@@ -0,0 +1,196 @@
+use std::collections::HashSet;
+
+use reqwest::StatusCode;
+use reqwest::blocking::Client;
+use serde::Deserialize;
+
+use super::{
+    GitProvider, ProviderKind, RemoteRepo, TokenAuth, TokenDetails, UserProfile, get, get_profile,
+    get_profile_with_headers, header_str, http_client, rate_limit_from, scopes_from,
+};
+use crate::config::NetworkOptions;
+use crate::error::AppError;
+
+const MAX_PAGES: usize = 10;
+const PER_PAGE: usize = 100;
+
+pub struct GitHubProvider {
+    api_base: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RepoOwner {
+    login: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RepoItem {
+    full_name: String,
+    html_url: String,
+    owner: RepoOwner,
+}
+
+#[derive(Debug, Deserialize)]
+struct Profile {
+    login: String,
+    name: Option<String>,
+}
+
+impl GitHubProvider {
+    pub fn new(api_base: impl Into<String>) -> Self {
+        Self {
+            api_base: api_base.into().trim_end_matches('/').to_string(),
+        }
+    }
+
+    // github.com uses a separate API host; Enterprise Server serves the API
+    // under `/api/v3`.
+    pub fn for_host(web: &str) -> Self {
+        let host = web
+            .trim_start_matches("https://")
+            .trim_start_matches("http://");
+        if host == "github.com" || host == "api.github.com" {
+            Self::new("https://api.github.com")
+        } else {
+            Self::new(format!("{web}/api/v3"))
+        }
+    }
+
+    fn fetch_repos(
+        &self,
+        client: &Client,
+        url: &str,
+        params: &[(&str, &str)],
+        token: Option<&str>,
+        network: &NetworkOptions,
+    ) -> Result<Vec<RepoItem>, AppError> {
+        let mut all_repos = Vec::new();
+        for page in 1..=MAX_PAGES {
+            let per_page = PER_PAGE.to_string();
+            let page = page.to_string();
+            let mut query = params.to_vec();
+            query.push(("per_page", &per_page));
+            query.push(("page", &page));
+
+            let response = get(client, url, &query, token, TokenAuth::Bearer, network)?;
+            if response.status() == StatusCode::NOT_FOUND {
+                break;
+            }
+            let mut repos: Vec<RepoItem> = response.error_for_status()?.json()?;
+            let count = repos.len();
+            all_repos.append(&mut repos);
+            if count < PER_PAGE {
+                break;
+            }
+        }
+        Ok(all_repos)
+    }
+
+    fn login(
+        &self,
+        client: &Client,
+        token: &str,
+        network: &NetworkOptions,
+    ) -> Result<Option<String>, AppError> {
+        let url = format!("{}/user", self.api_base);
+        let response = get(client, &url, &[], Some(token), TokenAuth::Bearer, network)?;
+        if response.status() == StatusCode::UNAUTHORIZED {
+            return Ok(None);
+        }
+        let profile: Profile = response.error_for_status()?.json()?;
+        Ok(Some(profile.login))
+    }
+}
+
+impl GitProvider for GitHubProvider {
+    fn kind(&self) -> ProviderKind {
+        ProviderKind::GitHub
+    }
+
+    fn search(
+        &self,
+        query: &str,
+        token: Option<&str>,
+        network: &NetworkOptions,
+    ) -> Result<Vec<RemoteRepo>, AppError> {
+        let account = query.trim();
+        if account.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let client = http_client(network)?;
+        let mut repositories = Vec::new();
+        for kind in ["users", "orgs"] {
+            let url = format!("{}/{kind}/{account}/repos", self.api_base);
+            repositories.extend(
+                self.fetch_repos(&client, &url, &[("type", "public")], token, network)
+                    .unwrap_or_default(),
+            );
+        }
+
+        if let Some(token) = token
+            && let Some(login) = self.login(&client, token, network)?
+            && login.eq_ignore_ascii_case(account)
+        {
+            let url = format!("{}/user/repos", self.api_base);
+            let private_repos = self.fetch_repos(
+                &client,
+                &url,
+                &[
+                    ("visibility", "all"),
+                    ("affiliation", "owner,collaborator,organization_member"),
+                ],
+                Some(token),
+                network,
+            )?;
+            repositories.extend(
+                private_repos
+                    .into_iter()
+                    .filter(|repo| repo.owner.login.eq_ignore_ascii_case(account)),
+            );
+        }
+
+        let mut unique = HashSet::new();
+        Ok(repositories
+            .into_iter()
+            .filter(|repo| unique.insert(repo.full_name.clone()))
+            .map(|item| RemoteRepo {
+                name: item.full_name,
+                url: item.html_url,
+            })
+            .collect())
+    }
+
+    fn user_profile(&self, token: &str, network: &NetworkOptions) -> Result<UserProfile, AppError> {
+        let url = format!("{}/user", self.api_base);
+        let profile: Profile = get_profile(self.kind(), &url, token, TokenAuth::Bearer, network)?;
+        Ok(UserProfile {
+            username: profile.login,
+            display_name: profile.name,
+        })
+    }
+
+    // Classic tokens list their scopes in `X-OAuth-Scopes`; tokens with an
+    // expiry report it as `github-authentication-token-expiration`, e.g.
+    // `2026-11-01 12:00:00 UTC`.
+    fn token_details(
+        &self,
+        token: &str,
+        network: &NetworkOptions,
+    ) -> Result<TokenDetails, AppError> {
+        let url = format!("{}/user", self.api_base);
+        let (profile, headers): (Profile, _) =
+            get_profile_with_headers(self.kind(), &url, token, TokenAuth::Bearer, network)?;
+        Ok(TokenDetails {
+            profile: UserProfile {
+                username: profile.login,
+                display_name: profile.name,
+            },
+            scopes: scopes_from(header_str(&headers, "x-oauth-scopes")),
+            expires_at: header_str(&headers, "github-authentication-token-expiration")
+                .and_then(|value| value.split_whitespace().next())
+                .map(str::to_string),
+            rate_limit: rate_limit_from(&headers),
+        })
+    }
+}
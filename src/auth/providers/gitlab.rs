@@ -0,0 +1,109 @@
+use serde::Deserialize;
+
+use super::{
+    GitProvider, ProviderKind, RemoteRepo, TokenAuth, TokenDetails, UserProfile, get_json,
+    get_profile, get_profile_with_headers, http_client, rate_limit_from,
+};
+use crate::config::NetworkOptions;
+use crate::error::AppError;
+
+const AUTH: TokenAuth = TokenAuth::Header("PRIVATE-TOKEN");
+
+pub struct GitLabProvider {
+    api_base: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Project {
+    name_with_namespace: String,
+    http_url_to_repo: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Profile {
+    username: String,
+    name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PersonalAccessToken {
+    #[serde(default)]
+    scopes: Vec<String>,
+    expires_at: Option<String>,
+}
+
+impl GitLabProvider {
+    pub fn new(api_base: impl Into<String>) -> Self {
+        Self {
+            api_base: api_base.into().trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+impl GitProvider for GitLabProvider {
+    fn kind(&self) -> ProviderKind {
+        ProviderKind::GitLab
+    }
+
+    fn search(
+        &self,
+        query: &str,
+        token: Option<&str>,
+        network: &NetworkOptions,
+    ) -> Result<Vec<RemoteRepo>, AppError> {
+        let client = http_client(network)?;
+        let url = format!("{}/projects", self.api_base);
+        let projects: Vec<Project> = get_json(
+            &client,
+            &url,
+            &[("search", query), ("per_page", "6"), ("simple", "true")],
+            token,
+            AUTH,
+            network,
+        )?;
+        Ok(projects
+            .into_iter()
+            .map(|project| RemoteRepo {
+                name: project.name_with_namespace,
+                url: project.http_url_to_repo,
+            })
+            .collect())
+    }
+
+    fn user_profile(&self, token: &str, network: &NetworkOptions) -> Result<UserProfile, AppError> {
+        let url = format!("{}/user", self.api_base);
+        let profile: Profile = get_profile(self.kind(), &url, token, AUTH, network)?;
+        Ok(UserProfile {
+            username: profile.username,
+            display_name: profile.name,
+        })
+    }
+
+    // Scopes and expiry come from `/personal_access_tokens/self`, which
+    // OAuth and older instances do not serve; the token is still valid then.
+    fn token_details(
+        &self,
+        token: &str,
+        network: &NetworkOptions,
+    ) -> Result<TokenDetails, AppError> {
+        let url = format!("{}/user", self.api_base);
+        let (profile, headers): (Profile, _) =
+            get_profile_with_headers(self.kind(), &url, token, AUTH, network)?;
+        let client = http_client(network)?;
+        let self_url = format!("{}/personal_access_tokens/self", self.api_base);
+        let pat: Option<PersonalAccessToken> =
+            get_json(&client, &self_url, &[], Some(token), AUTH, network).ok();
+        let (scopes, expires_at) = pat
+            .map(|pat| (pat.scopes, pat.expires_at))
+            .unwrap_or_default();
+        Ok(TokenDetails {
+            profile: UserProfile {
+                username: profile.username,
+                display_name: profile.name,
+            },
+            scopes,
+            expires_at,
+            rate_limit: rate_limit_from(&headers),
+        })
+    }
+}
@@ -0,0 +1,420 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+use reqwest::StatusCode;
+use reqwest::blocking::{Client, RequestBuilder, Response};
+use reqwest::header::{ACCEPT, AUTHORIZATION, HeaderMap, HeaderName, HeaderValue, USER_AGENT};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::config::NetworkOptions;
+use crate::error::AppError;
+
+mod azure;
+mod bitbucket;
+mod gitea;
+mod github;
+mod gitlab;
+
+pub use azure::AzureDevOpsProvider;
+pub use bitbucket::{BitbucketCloudProvider, BitbucketServerProvider};
+pub use gitea::GiteaProvider;
+pub use github::GitHubProvider;
+pub use gitlab::GitLabProvider;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProviderKind {
+    GitHub,
+    GitLab,
+    Gitea,
+    BitbucketCloud,
+    BitbucketServer,
+    AzureDevOps,
+}
+
+impl ProviderKind {
+    pub const ALL: [ProviderKind; 6] = [
+        ProviderKind::GitHub,
+        ProviderKind::GitLab,
+        ProviderKind::Gitea,
+        ProviderKind::BitbucketCloud,
+        ProviderKind::BitbucketServer,
+        ProviderKind::AzureDevOps,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ProviderKind::GitHub => "GitHub",
+            ProviderKind::GitLab => "GitLab",
+            ProviderKind::Gitea => "Gitea/Forgejo",
+            ProviderKind::BitbucketCloud => "Bitbucket",
+            ProviderKind::BitbucketServer => "Bitbucket Server",
+            ProviderKind::AzureDevOps => "Azure DevOps",
+        }
+    }
+
+    pub fn icon(self) -> char {
+        match self {
+            ProviderKind::GitHub => '\u{f408}',
+            ProviderKind::GitLab => '\u{f296}',
+            ProviderKind::Gitea => '\u{f1d3}',
+            ProviderKind::BitbucketCloud | ProviderKind::BitbucketServer => '\u{f171}',
+            ProviderKind::AzureDevOps => '\u{f17a}',
+        }
+    }
+
+    pub fn default_host(self) -> &'static str {
+        match self {
+            ProviderKind::GitHub => "github.com",
+            ProviderKind::GitLab => "gitlab.com",
+            ProviderKind::Gitea => "codeberg.org",
+            ProviderKind::BitbucketCloud => "bitbucket.org",
+            ProviderKind::BitbucketServer => "",
+            ProviderKind::AzureDevOps => "dev.azure.com/",
+        }
+    }
+
+    pub fn host_hint(self) -> &'static str {
+        match self {
+            ProviderKind::GitHub => "github.com or github.example.com",
+            ProviderKind::GitLab => "gitlab.com or gitlab.example.com",
+            ProviderKind::Gitea => "codeberg.org or gitea.example.com",
+            ProviderKind::BitbucketCloud => "bitbucket.org",
+            ProviderKind::BitbucketServer => "bitbucket.example.com",
+            ProviderKind::AzureDevOps => "dev.azure.com/<organization>",
+        }
+    }
+
+    pub fn search_hint(self) -> &'static str {
+        match self {
+            ProviderKind::GitHub => "User or organization",
+            ProviderKind::BitbucketCloud => "Workspace or workspace/name",
+            _ => "Repository name",
+        }
+    }
+
+    // Each entry lists alternatives, any one of which satisfies it. Providers
+    // that do not report scopes have no requirements here.
+    pub fn required_scopes(self) -> &'static [&'static [&'static str]] {
+        match self {
+            ProviderKind::GitHub => &[&["repo"]],
+            ProviderKind::GitLab => &[&["api", "read_api"], &["api", "write_repository"]],
+            _ => &[],
+        }
+    }
+
+    pub fn from_host(host: &str) -> Option<Self> {
+        let host = host.trim().to_ascii_lowercase();
+        let host = host
+            .strip_prefix("https://")
+            .or_else(|| host.strip_prefix("http://"))
+            .unwrap_or(&host);
+        let name = host.split('/').next().unwrap_or_default();
+        if name == "bitbucket.org" || name == "api.bitbucket.org" {
+            Some(ProviderKind::BitbucketCloud)
+        } else if name == "dev.azure.com" || name.ends_with(".visualstudio.com") {
+            Some(ProviderKind::AzureDevOps)
+        } else if name == "codeberg.org" || name.contains("gitea") || name.contains("forgejo") {
+            Some(ProviderKind::Gitea)
+        } else if name.contains("github") {
+            Some(ProviderKind::GitHub)
+        } else if name.contains("gitlab") {
+            Some(ProviderKind::GitLab)
+        } else if name.contains("bitbucket") {
+            Some(ProviderKind::BitbucketServer)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteRepo {
+    pub name: String,
+    pub url: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserProfile {
+    pub username: String,
+    pub display_name: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimit {
+    pub limit: u64,
+    pub remaining: u64,
+    // Unix time.
+    pub reset_at: Option<i64>,
+}
+
+// Scopes are empty when the provider does not report them (e.g. GitHub
+// fine-grained tokens).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenDetails {
+    pub profile: UserProfile,
+    pub scopes: Vec<String>,
+    // `YYYY-MM-DD`.
+    pub expires_at: Option<String>,
+    pub rate_limit: Option<RateLimit>,
+}
+
+pub trait GitProvider: Send + Sync {
+    fn kind(&self) -> ProviderKind;
+
+    fn search(
+        &self,
+        query: &str,
+        token: Option<&str>,
+        network: &NetworkOptions,
+    ) -> Result<Vec<RemoteRepo>, AppError>;
+
+    fn user_profile(&self, token: &str, network: &NetworkOptions) -> Result<UserProfile, AppError>;
+
+    fn token_details(
+        &self,
+        token: &str,
+        network: &NetworkOptions,
+    ) -> Result<TokenDetails, AppError> {
+        Ok(TokenDetails {
+            profile: self.user_profile(token, network)?,
+            scopes: Vec::new(),
+            expires_at: None,
+            rate_limit: None,
+        })
+    }
+}
+
+pub fn provider_for(kind: ProviderKind, host: &str) -> Box<dyn GitProvider> {
+    let web = web_base(host);
+    match kind {
+        ProviderKind::GitHub => Box::new(GitHubProvider::for_host(&web)),
+        ProviderKind::GitLab => Box::new(GitLabProvider::new(format!("{web}/api/v4"))),
+        ProviderKind::Gitea => Box::new(GiteaProvider::new(format!("{web}/api/v1"))),
+        ProviderKind::BitbucketCloud => Box::new(BitbucketCloudProvider::for_host(&web)),
+        ProviderKind::BitbucketServer => Box::new(BitbucketServerProvider::new(web)),
+        ProviderKind::AzureDevOps => Box::new(AzureDevOpsProvider::new(web)),
+    }
+}
+
+pub fn detect_provider(
+    host: &str,
+    network: &NetworkOptions,
+) -> Result<Box<dyn GitProvider>, AppError> {
+    if let Some(kind) = ProviderKind::from_host(host) {
+        return Ok(provider_for(kind, host));
+    }
+
+    let web = web_base(host);
+    enforce_https_policy(&web, network)?;
+    let client = http_client(network)?;
+    let probes = [
+        (ProviderKind::Gitea, "/api/v1/version"),
+        (ProviderKind::GitLab, "/api/v4/version"),
+        (
+            ProviderKind::BitbucketServer,
+            "/rest/api/1.0/application-properties",
+        ),
+        (ProviderKind::GitHub, "/api/v3/meta"),
+    ];
+    for (kind, path) in probes {
+        let Ok(response) = client.get(format!("{web}{path}")).send() else {
+            continue;
+        };
+        let status = response.status();
+        let is_json = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.contains("json"));
+        // GitLab answers its version endpoint with 401 when signed out.
+        let recognised = (status.is_success() && is_json)
+            || (kind == ProviderKind::GitLab && status == StatusCode::UNAUTHORIZED && is_json);
+        if recognised {
+            return Ok(provider_for(kind, host));
+        }
+    }
+
+    Err(AppError::Validation(format!(
+        "Could not identify the Git provider at {web}"
+    )))
+}
+
+pub fn web_base(host: &str) -> String {
+    let host = host.trim().trim_end_matches('/');
+    if host.starts_with("http://") || host.starts_with("https://") {
+        host.to_string()
+    } else {
+        format!("https://{host}")
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum TokenAuth {
+    Bearer,
+    TokenPrefix,
+    Header(&'static str),
+    // HTTP basic auth with an empty user, as Azure DevOps expects for PATs.
+    BasicPassword,
+}
+
+fn http_client(network: &NetworkOptions) -> Result<Client, AppError> {
+    let mut headers = HeaderMap::new();
+    headers.insert(USER_AGENT, HeaderValue::from_static("gitspace-ui/0.1"));
+    headers.insert(ACCEPT, HeaderValue::from_static("application/json"));
+    let mut builder = Client::builder()
+        .default_headers(headers)
+        .timeout(Duration::from_secs(network.network_timeout_secs.max(1)));
+
+    if !network.http_proxy.is_empty() {
+        builder = builder.proxy(
+            reqwest::Proxy::http(&network.http_proxy)
+                .map_err(|err| AppError::Validation(err.to_string()))?,
+        );
+    }
+    if !network.https_proxy.is_empty() {
+        builder = builder.proxy(
+            reqwest::Proxy::https(&network.https_proxy)
+                .map_err(|err| AppError::Validation(err.to_string()))?,
+        );
+    }
+
+    builder.build().map_err(AppError::from)
+}
+
+fn authorize(
+    request: RequestBuilder,
+    token: Option<&str>,
+    auth: TokenAuth,
+) -> Result<RequestBuilder, AppError> {
+    let Some(token) = token else {
+        return Ok(request);
+    };
+    let invalid = |err: reqwest::header::InvalidHeaderValue| AppError::Validation(err.to_string());
+    Ok(match auth {
+        TokenAuth::Bearer => request.header(
+            AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {token}")).map_err(invalid)?,
+        ),
+        TokenAuth::TokenPrefix => request.header(
+            AUTHORIZATION,
+            HeaderValue::from_str(&format!("token {token}")).map_err(invalid)?,
+        ),
+        TokenAuth::Header(name) => request.header(
+            HeaderName::from_str(name).map_err(|err| AppError::Validation(err.to_string()))?,
+            HeaderValue::from_str(token).map_err(invalid)?,
+        ),
+        TokenAuth::BasicPassword => request.basic_auth("", Some(token)),
+    })
+}
+
+fn get(
+    client: &Client,
+    url: &str,
+    query: &[(&str, &str)],
+    token: Option<&str>,
+    auth: TokenAuth,
+    network: &NetworkOptions,
+) -> Result<Response, AppError> {
+    enforce_https_policy(url, network)?;
+    authorize(client.get(url).query(query), token, auth)?
+        .send()
+        .map_err(AppError::from)
+}
+
+fn get_json<T: DeserializeOwned>(
+    client: &Client,
+    url: &str,
+    query: &[(&str, &str)],
+    token: Option<&str>,
+    auth: TokenAuth,
+    network: &NetworkOptions,
+) -> Result<T, AppError> {
+    get(client, url, query, token, auth, network)?
+        .error_for_status()?
+        .json()
+        .map_err(AppError::from)
+}
+
+fn get_profile<T: DeserializeOwned>(
+    kind: ProviderKind,
+    url: &str,
+    token: &str,
+    auth: TokenAuth,
+    network: &NetworkOptions,
+) -> Result<T, AppError> {
+    get_profile_with_headers(kind, url, token, auth, network).map(|(profile, _)| profile)
+}
+
+fn get_profile_with_headers<T: DeserializeOwned>(
+    kind: ProviderKind,
+    url: &str,
+    token: &str,
+    auth: TokenAuth,
+    network: &NetworkOptions,
+) -> Result<(T, HeaderMap), AppError> {
+    let client = http_client(network)?;
+    let response = get(&client, url, &[], Some(token), auth, network)?;
+    let status = response.status();
+    // Azure DevOps answers bad PATs with a 203 sign-in page.
+    if !status.is_success() || status == StatusCode::NON_AUTHORITATIVE_INFORMATION {
+        return Err(AppError::Validation(format!(
+            "{} rejected token ({status}).",
+            kind.label()
+        )));
+    }
+    let headers = response.headers().clone();
+    let profile = response.json().map_err(AppError::from)?;
+    Ok((profile, headers))
+}
+
+fn header_str<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+}
+
+// Reads GitHub's `X-RateLimit-*` or GitLab's `RateLimit-*` headers.
+fn rate_limit_from(headers: &HeaderMap) -> Option<RateLimit> {
+    let number = |suffix: &str| {
+        header_str(headers, &format!("x-ratelimit-{suffix}"))
+            .or_else(|| header_str(headers, &format!("ratelimit-{suffix}")))
+            .and_then(|value| value.parse::<i64>().ok())
+    };
+    Some(RateLimit {
+        limit: number("limit")?.try_into().ok()?,
+        remaining: number("remaining")?.try_into().ok()?,
+        reset_at: number("reset"),
+    })
+}
+
+fn scopes_from(value: Option<&str>) -> Vec<String> {
+    value
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|scope| !scope.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn enforce_https_policy(url: &str, network: &NetworkOptions) -> Result<(), AppError> {
+    if url.starts_with("https://") && !network.use_https {
+        return Err(AppError::Validation(
+            "HTTPS endpoints are disabled in your network settings.".to_string(),
+        ));
+    }
+
+    if url.starts_with("http://") && network.use_https {
+        return Err(AppError::Validation(
+            "HTTP requests are blocked. Enable HTTP in network settings or use HTTPS.".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests;
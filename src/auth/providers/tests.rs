@@ -0,0 +1,397 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+use super::*;
+
+struct Route {
+    path: &'static str,
+    status: u16,
+    content_type: &'static str,
+    headers: Vec<(&'static str, &'static str)>,
+    body: String,
+}
+
+impl Route {
+    fn header(mut self, name: &'static str, value: &'static str) -> Self {
+        self.headers.push((name, value));
+        self
+    }
+}
+
+fn json(path: &'static str, body: serde_json::Value) -> Route {
+    Route {
+        path,
+        status: 200,
+        content_type: "application/json",
+        headers: Vec::new(),
+        body: body.to_string(),
+    }
+}
+
+fn status(path: &'static str, status: u16) -> Route {
+    Route {
+        path,
+        status,
+        content_type: "text/plain",
+        headers: Vec::new(),
+        body: String::new(),
+    }
+}
+
+fn start_server(routes: Vec<Route>) -> (String, Receiver<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind stand-in server");
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { break };
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            if reader.read_line(&mut request_line).is_err() {
+                continue;
+            }
+            let mut authorization = String::new();
+            loop {
+                let mut header = String::new();
+                if reader.read_line(&mut header).is_err() || header.trim().is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = header.split_once(':')
+                    && name.eq_ignore_ascii_case("authorization")
+                {
+                    authorization = value.trim().to_string();
+                }
+            }
+            let target = request_line.split_whitespace().nth(1).unwrap_or_default();
+            let path = target.split('?').next().unwrap_or_default().to_string();
+            let _ = sender.send(format!("{target} {authorization}").trim().to_string());
+
+            let route = routes.iter().find(|route| route.path == path);
+            let (code, content_type, body) = match route {
+                Some(route) => (route.status, route.content_type, route.body.as_str()),
+                None => (404, "text/plain", ""),
+            };
+            let extra: String = route
+                .map(|route| {
+                    route
+                        .headers
+                        .iter()
+                        .map(|(name, value)| format!("{name}: {value}\r\n"))
+                        .collect()
+                })
+                .unwrap_or_default();
+            let response = format!(
+                "HTTP/1.1 {code} Stand-in\r\nContent-Type: {content_type}\r\n{extra}Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            let _ = stream.write_all(response.as_bytes());
+        }
+    });
+    (base, receiver)
+}
+
+fn local_network() -> NetworkOptions {
+    NetworkOptions {
+        network_timeout_secs: 5,
+        http_proxy: String::new(),
+        https_proxy: String::new(),
+        use_https: false,
+        allow_ssh: true,
+    }
+}
+
+#[test]
+fn from_host_recognises_well_known_hosts() {
+    assert_eq!(
+        ProviderKind::from_host("api.github.com"),
+        Some(ProviderKind::GitHub)
+    );
+    assert_eq!(
+        ProviderKind::from_host("https://bitbucket.org"),
+        Some(ProviderKind::BitbucketCloud)
+    );
+    assert_eq!(
+        ProviderKind::from_host("dev.azure.com/contoso"),
+        Some(ProviderKind::AzureDevOps)
+    );
+    assert_eq!(
+        ProviderKind::from_host("codeberg.org"),
+        Some(ProviderKind::Gitea)
+    );
+    assert_eq!(ProviderKind::from_host("git.example.com"), None);
+}
+
+#[test]
+fn gitea_is_detected_searched_and_validated() {
+    let (base, requests) = start_server(vec![
+        json(
+            "/api/v1/version",
+            serde_json::json!({ "version": "1.21.0" }),
+        ),
+        json(
+            "/api/v1/repos/search",
+            serde_json::json!({
+                "ok": true,
+                "data": [{
+                    "full_name": "team/widgets",
+                    "clone_url": "http://forge.local/team/widgets.git"
+                }]
+            }),
+        ),
+        json(
+            "/api/v1/user",
+            serde_json::json!({ "login": "ada", "full_name": "Ada L" }),
+        ),
+    ]);
+    let network = local_network();
+
+    let provider = detect_provider(&base, &network).expect("detect gitea");
+    assert_eq!(provider.kind(), ProviderKind::Gitea);
+
+    let repos = provider
+        .search("widg", Some("abc"), &network)
+        .expect("search");
+    assert_eq!(
+        repos,
+        vec![RemoteRepo {
+            name: "team/widgets".to_string(),
+            url: "http://forge.local/team/widgets.git".to_string(),
+        }]
+    );
+    let profile = provider.user_profile("abc", &network).expect("profile");
+    assert_eq!(profile.username, "ada");
+    assert_eq!(profile.display_name.as_deref(), Some("Ada L"));
+
+    let seen: Vec<String> = requests.try_iter().collect();
+    assert_eq!(seen[0], "/api/v1/version");
+    assert_eq!(seen[1], "/api/v1/repos/search?q=widg&limit=20 token abc");
+    assert_eq!(seen[2], "/api/v1/user token abc");
+}
+
+#[test]
+fn detection_falls_through_to_bitbucket_server() {
+    let (base, _requests) = start_server(vec![
+        json(
+            "/rest/api/1.0/application-properties",
+            serde_json::json!({ "version": "8.9.0", "displayName": "Bitbucket" }),
+        ),
+        json(
+            "/rest/api/1.0/repos",
+            serde_json::json!({
+                "values": [{
+                    "slug": "api",
+                    "project": { "key": "CORE" },
+                    "links": { "clone": [
+                        { "name": "ssh", "href": "ssh://git@bb.local/core/api.git" },
+                        { "name": "http", "href": "http://bb.local/scm/core/api.git" }
+                    ]}
+                }]
+            }),
+        ),
+        Route {
+            path: "/plugins/servlet/applinks/whoami",
+            status: 200,
+            content_type: "text/plain",
+            headers: Vec::new(),
+            body: "grace".to_string(),
+        },
+        json(
+            "/rest/api/1.0/users/grace",
+            serde_json::json!({ "name": "grace", "displayName": "Grace H" }),
+        ),
+    ]);
+    let network = local_network();
+
+    let provider = detect_provider(&base, &network).expect("detect bitbucket server");
+    assert_eq!(provider.kind(), ProviderKind::BitbucketServer);
+    let repos = provider.search("api", None, &network).expect("search");
+    assert_eq!(repos[0].name, "CORE/api");
+    assert_eq!(repos[0].url, "http://bb.local/scm/core/api.git");
+
+    let profile = provider.user_profile("tok", &network).expect("profile");
+    assert_eq!(profile.username, "grace");
+    assert_eq!(profile.display_name.as_deref(), Some("Grace H"));
+}
+
+#[test]
+fn unknown_servers_are_not_accepted() {
+    let (base, _requests) = start_server(Vec::new());
+    let err = match detect_provider(&base, &local_network()) {
+        Ok(provider) => panic!("detected {:?}", provider.kind()),
+        Err(err) => err,
+    };
+    assert!(err.detail().contains("Could not identify"));
+}
+
+#[test]
+fn bitbucket_cloud_searches_workspaces() {
+    let (base, requests) = start_server(vec![json(
+        "/2.0/repositories/acme",
+        serde_json::json!({
+            "values": [{
+                "full_name": "acme/rocket",
+                "links": { "clone": [
+                    { "name": "https", "href": "https://bitbucket.org/acme/rocket.git" }
+                ]}
+            }]
+        }),
+    )]);
+    let provider = BitbucketCloudProvider::new(format!("{base}/2.0"));
+    let network = local_network();
+
+    let repos = provider
+        .search("acme/rock", None, &network)
+        .expect("search");
+    assert_eq!(repos[0].name, "acme/rocket");
+    assert_eq!(repos[0].url, "https://bitbucket.org/acme/rocket.git");
+    let seen: Vec<String> = requests.try_iter().collect();
+    assert_eq!(
+        seen,
+        vec!["/2.0/repositories/acme?pagelen=25&q=name+%7E+%22rock%22".to_string()]
+    );
+}
+
+#[test]
+fn azure_devops_filters_repositories_and_rejects_bad_tokens() {
+    let (base, requests) = start_server(vec![
+        json(
+            "/contoso/_apis/git/repositories",
+            serde_json::json!({
+                "count": 2,
+                "value": [
+                    {
+                        "name": "Payments",
+                        "project": { "name": "Fabrikam" },
+                        "remoteUrl": "https://dev.azure.com/contoso/Fabrikam/_git/Payments"
+                    },
+                    {
+                        "name": "Docs",
+                        "project": { "name": "Fabrikam" },
+                        "remoteUrl": "https://dev.azure.com/contoso/Fabrikam/_git/Docs"
+                    }
+                ]
+            }),
+        ),
+        status("/contoso/_apis/connectionData", 401),
+    ]);
+    let provider = provider_for(ProviderKind::AzureDevOps, &format!("{base}/contoso"));
+    let network = local_network();
+
+    let repos = provider
+        .search("pay", Some("pat"), &network)
+        .expect("search");
+    assert_eq!(repos.len(), 1);
+    assert_eq!(repos[0].name, "Fabrikam/Payments");
+    // Basic auth with an empty user name: base64(":pat").
+    assert_eq!(
+        requests.try_iter().next().unwrap(),
+        "/contoso/_apis/git/repositories?api-version=7.0 Basic OnBhdA=="
+    );
+
+    let err = provider
+        .user_profile("pat", &network)
+        .expect_err("401 rejects the token");
+    assert!(err.detail().contains("Azure DevOps rejected token"));
+}
+
+#[test]
+fn gitlab_and_github_use_their_api_bases() {
+    let (base, requests) = start_server(vec![
+        json(
+            "/api/v4/projects",
+            serde_json::json!([{
+                "name_with_namespace": "Group / Tool",
+                "http_url_to_repo": "http://gl.local/group/tool.git"
+            }]),
+        ),
+        json(
+            "/api/v3/users/octo/repos",
+            serde_json::json!([{
+                "full_name": "octo/hello",
+                "html_url": "http://ghe.local/octo/hello",
+                "owner": { "login": "octo" }
+            }]),
+        ),
+    ]);
+    let network = local_network();
+
+    let gitlab = provider_for(ProviderKind::GitLab, &base);
+    let repos = gitlab
+        .search("tool", None, &network)
+        .expect("gitlab search");
+    assert_eq!(repos[0].url, "http://gl.local/group/tool.git");
+
+    let github = provider_for(ProviderKind::GitHub, &base);
+    let repos = github
+        .search("octo", None, &network)
+        .expect("github search");
+    assert_eq!(repos.len(), 1);
+    assert_eq!(repos[0].name, "octo/hello");
+
+    let seen: Vec<String> = requests.try_iter().collect();
+    assert!(seen[0].starts_with("/api/v4/projects?search=tool"));
+    assert!(
+        seen.iter()
+            .any(|line| line.starts_with("/api/v3/orgs/octo/repos"))
+    );
+}
+
+#[test]
+fn github_and_gitlab_report_scopes_expiry_and_rate_limits() {
+    let (base, requests) = start_server(vec![
+        json(
+            "/api/v3/user",
+            serde_json::json!({ "login": "octo", "name": "Octo Cat" }),
+        )
+        .header("X-OAuth-Scopes", "repo, read:org")
+        .header(
+            "github-authentication-token-expiration",
+            "2026-11-01 12:00:00 UTC",
+        )
+        .header("X-RateLimit-Limit", "5000")
+        .header("X-RateLimit-Remaining", "4990")
+        .header("X-RateLimit-Reset", "1793000000"),
+        json("/api/v4/user", serde_json::json!({ "username": "ada" }))
+            .header("RateLimit-Limit", "2000")
+            .header("RateLimit-Remaining", "1999"),
+        json(
+            "/api/v4/personal_access_tokens/self",
+            serde_json::json!({ "scopes": ["read_api", "read_repository"], "expires_at": "2027-03-01" }),
+        ),
+    ]);
+    let network = local_network();
+
+    let github = provider_for(ProviderKind::GitHub, &base)
+        .token_details("ghp", &network)
+        .expect("github details");
+    assert_eq!(github.profile.username, "octo");
+    assert_eq!(
+        github.scopes,
+        vec!["repo".to_string(), "read:org".to_string()]
+    );
+    assert_eq!(github.expires_at.as_deref(), Some("2026-11-01"));
+    assert_eq!(
+        github.rate_limit,
+        Some(RateLimit {
+            limit: 5000,
+            remaining: 4990,
+            reset_at: Some(1793000000),
+        })
+    );
+
+    let gitlab = provider_for(ProviderKind::GitLab, &base)
+        .token_details("glpat", &network)
+        .expect("gitlab details");
+    assert_eq!(gitlab.profile.username, "ada");
+    assert_eq!(
+        gitlab.scopes,
+        vec!["read_api".to_string(), "read_repository".to_string()]
+    );
+    assert_eq!(gitlab.expires_at.as_deref(), Some("2027-03-01"));
+    assert_eq!(gitlab.rate_limit.map(|limit| limit.remaining), Some(1999));
+    let seen: Vec<String> = requests.try_iter().collect();
+    assert_eq!(seen[0], "/api/v3/user Bearer ghp");
+    assert_eq!(seen[2], "/api/v4/personal_access_tokens/self");
+}
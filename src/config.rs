@@ -1,7 +1,15 @@
-use std::fs;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, Write};
 use std::path::{Path, PathBuf};
 
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tracing::warn;
+
+use crate::git::remote::PullStrategy;
+use crate::profiles::{Profile, ProfileRule};
+use crate::repo_settings::RepoSettings;
 
 const MAX_RECENT: usize = 15;
 const CONFIG_FILE_NAME: &str = "config.json";
@@ -9,8 +17,18 @@ const APP_CONFIG_DIR: &str = "gitspace";
 pub const MIN_LOG_RETENTION_FILES: usize = 1;
 pub const MAX_LOG_RETENTION_FILES: usize = 30;
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+// Files without `schema_version` are version 1.
+pub const CONFIG_SCHEMA_VERSION: u32 = 2;
+
+type Migration = fn(&mut Map<String, Value>) -> Result<(), String>;
+
+// `MIGRATIONS[n]` upgrades a version `n + 1` config to version `n + 2`.
+const MIGRATIONS: [Migration; 1] = [migrate_v1_to_v2];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
+    #[serde(default = "legacy_schema_version")]
+    schema_version: u32,
     #[serde(default)]
     recent_repos: Vec<RecentRepo>,
     #[serde(default)]
@@ -19,6 +37,20 @@ pub struct AppConfig {
     logging: LoggingOptions,
     #[serde(default)]
     telemetry_prompt_shown: bool,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    repositories: BTreeMap<String, RepoSettings>,
+    #[serde(default)]
+    profiles: Vec<Profile>,
+    #[serde(default)]
+    active_profile: Option<String>,
+    #[serde(default)]
+    auto_select_profiles: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct ConfigRecovery {
+    pub error: String,
+    pub backup: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -28,11 +60,9 @@ pub struct RecentRepo {
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 pub enum ThemeMode {
-    #[serde(alias = "Light")]
     Latte,
     Frappe,
     Macchiato,
-    #[serde(alias = "Dark")]
     Mocha,
 }
 
@@ -142,6 +172,8 @@ pub struct Preferences {
     telemetry_enabled: bool,
     #[serde(default)]
     allow_encrypted_tokens: bool,
+    #[serde(default = "default_token_auto_lock_minutes")]
+    token_auto_lock_minutes: u64,
     #[serde(default = "default_control_height")]
     control_height: f32,
     #[serde(default = "default_branch_box_height")]
@@ -158,6 +190,12 @@ pub struct Preferences {
     auto_fetch_enabled: bool,
     #[serde(default = "default_auto_fetch_interval_minutes")]
     auto_fetch_interval_minutes: u64,
+    #[serde(default)]
+    pull_strategy: PullStrategy,
+    #[serde(default = "default_remote_name")]
+    default_remote: String,
+    #[serde(default)]
+    commit_template: String,
 }
 
 impl Default for Preferences {
@@ -172,6 +210,7 @@ impl Default for Preferences {
             update_feed_override: None,
             telemetry_enabled: false,
             allow_encrypted_tokens: false,
+            token_auto_lock_minutes: default_token_auto_lock_minutes(),
             control_height: default_control_height(),
             branch_box_height: default_branch_box_height(),
             pinned_branches: Vec::new(),
@@ -180,28 +219,94 @@ impl Default for Preferences {
             performance_mode: false,
             auto_fetch_enabled: default_auto_fetch_enabled(),
             auto_fetch_interval_minutes: default_auto_fetch_interval_minutes(),
+            pull_strategy: PullStrategy::default(),
+            default_remote: default_remote_name(),
+            commit_template: String::new(),
+        }
+    }
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            schema_version: CONFIG_SCHEMA_VERSION,
+            recent_repos: Vec::new(),
+            preferences: Preferences::default(),
+            logging: LoggingOptions::default(),
+            telemetry_prompt_shown: false,
+            repositories: BTreeMap::new(),
+            profiles: Vec::new(),
+            active_profile: None,
+            auto_select_profiles: false,
         }
     }
 }
 
 impl AppConfig {
+    // A broken file is left in place for `load_or_recover` to back up.
     pub fn load() -> Self {
-        let path = config_path();
-        if let Ok(contents) = fs::read_to_string(&path)
-            && let Ok(config) = serde_json::from_str::<Self>(&contents)
-        {
-            return config;
+        Self::read(&config_path())
+            .ok()
+            .flatten()
+            .unwrap_or_default()
+    }
+
+    pub fn load_or_recover() -> (Self, Option<ConfigRecovery>) {
+        Self::load_or_recover_from(&config_path())
+    }
+
+    fn load_or_recover_from(path: &Path) -> (Self, Option<ConfigRecovery>) {
+        match Self::read(path) {
+            Ok(config) => (config.unwrap_or_default(), None),
+            Err(error) => {
+                let backup = backup_path(path);
+                let backup = match fs::rename(path, &backup) {
+                    Ok(()) => Some(backup),
+                    Err(err) => {
+                        warn!(target: "gitspace::config", error = %err, "failed to back up unreadable config");
+                        None
+                    }
+                };
+                (Self::default(), Some(ConfigRecovery { error, backup }))
+            }
         }
-        Self::default()
     }
 
-    pub fn save(&self) -> std::io::Result<()> {
-        let path = config_path();
+    fn read(path: &Path) -> Result<Option<Self>, String> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(format!("Failed to read {}: {err}", path.display())),
+        };
+        let mut value: Value = serde_json::from_str(&contents)
+            .map_err(|err| format!("Failed to parse {}: {err}", path.display()))?;
+        migrate(&mut value)?;
+        serde_json::from_value(value)
+            .map(Some)
+            .map_err(|err| format!("Failed to parse {}: {err}", path.display()))
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        self.save_to(&config_path())
+    }
+
+    // Writing to a temporary file first means a crash mid-write leaves the
+    // previous config intact.
+    fn save_to(&self, path: &Path) -> io::Result<()> {
         if let Some(parent) = path.parent() {
             fs::create_dir_all(parent)?;
         }
         let content = serde_json::to_string_pretty(self).unwrap_or_else(|_| "{}".to_string());
-        fs::write(path, content)
+        let temp = path.with_extension("json.tmp");
+        let written = File::create(&temp).and_then(|mut file| {
+            file.write_all(content.as_bytes())?;
+            file.sync_all()
+        });
+        if let Err(err) = written.and_then(|_| fs::rename(&temp, path)) {
+            let _ = fs::remove_file(&temp);
+            return Err(err);
+        }
+        Ok(())
     }
 
     pub fn touch_recent<P: AsRef<Path>>(&mut self, path: P) -> bool {
@@ -231,7 +336,16 @@ impl AppConfig {
         &self.preferences
     }
 
+    // Keeps the active profile in step.
     pub fn set_preferences(&mut self, preferences: Preferences) {
+        if let Some(active) = &self.active_profile
+            && let Some(profile) = self
+                .profiles
+                .iter_mut()
+                .find(|profile| &profile.name == active)
+        {
+            profile.preferences = preferences.clone();
+        }
         self.preferences = preferences;
     }
 
@@ -243,6 +357,101 @@ impl AppConfig {
         self.logging = logging;
     }
 
+    pub fn repo_settings(&self, repo_path: &str) -> RepoSettings {
+        self.repositories
+            .get(repo_path)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn set_repo_settings(&mut self, repo_path: &str, settings: RepoSettings) {
+        if settings.is_empty() {
+            self.repositories.remove(repo_path);
+        } else {
+            self.repositories.insert(repo_path.to_string(), settings);
+        }
+    }
+
+    pub fn profiles(&self) -> &[Profile] {
+        &self.profiles
+    }
+
+    pub fn active_profile(&self) -> Option<&str> {
+        self.active_profile.as_deref()
+    }
+
+    pub fn save_profile(&mut self, name: &str) -> Result<(), String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Profile name cannot be empty".to_string());
+        }
+        match self
+            .profiles
+            .iter_mut()
+            .find(|profile| profile.name == name)
+        {
+            Some(profile) => profile.preferences = self.preferences.clone(),
+            None => self.profiles.push(Profile {
+                name: name.to_string(),
+                preferences: self.preferences.clone(),
+                rules: Vec::new(),
+            }),
+        }
+        self.active_profile = Some(name.to_string());
+        Ok(())
+    }
+
+    // The profile being left already holds any changes, since they are saved
+    // into it as they are made.
+    pub fn switch_profile(&mut self, name: &str) -> Result<&Preferences, String> {
+        let profile = self
+            .profiles
+            .iter()
+            .find(|profile| profile.name == name)
+            .ok_or_else(|| format!("No profile named {name}"))?;
+        self.preferences = profile.preferences.clone();
+        self.active_profile = Some(profile.name.clone());
+        Ok(&self.preferences)
+    }
+
+    pub fn delete_profile(&mut self, name: &str) {
+        self.profiles.retain(|profile| profile.name != name);
+        if self.active_profile.as_deref() == Some(name) {
+            self.active_profile = None;
+        }
+    }
+
+    pub fn add_profile_rule(&mut self, name: &str, rule: ProfileRule) -> Result<(), String> {
+        rule.validate()?;
+        let profile = self
+            .profiles
+            .iter_mut()
+            .find(|profile| profile.name == name)
+            .ok_or_else(|| format!("No profile named {name}"))?;
+        if !profile.rules.contains(&rule) {
+            profile.rules.push(rule);
+        }
+        Ok(())
+    }
+
+    pub fn remove_profile_rule(&mut self, name: &str, rule: &ProfileRule) {
+        if let Some(profile) = self
+            .profiles
+            .iter_mut()
+            .find(|profile| profile.name == name)
+        {
+            profile.rules.retain(|existing| existing != rule);
+        }
+    }
+
+    pub fn auto_select_profiles(&self) -> bool {
+        self.auto_select_profiles
+    }
+
+    pub fn set_auto_select_profiles(&mut self, enabled: bool) {
+        self.auto_select_profiles = enabled;
+    }
+
     pub fn telemetry_prompt_shown(&self) -> bool {
         self.telemetry_prompt_shown
     }
@@ -257,12 +466,71 @@ fn config_path() -> PathBuf {
     base.join(APP_CONFIG_DIR).join(CONFIG_FILE_NAME)
 }
 
+fn migrate(value: &mut Value) -> Result<(), String> {
+    let Some(config) = value.as_object_mut() else {
+        return Err("Config is not a JSON object".to_string());
+    };
+    let version = match config.get("schema_version") {
+        None => 1,
+        Some(version) => version
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .filter(|version| *version >= 1)
+            .ok_or_else(|| format!("Invalid schema_version: {version}"))?,
+    };
+    if version > CONFIG_SCHEMA_VERSION {
+        return Err(format!(
+            "Config schema version {version} is newer than this GitSpace supports ({CONFIG_SCHEMA_VERSION})"
+        ));
+    }
+    for step in &MIGRATIONS[version as usize - 1..] {
+        step(config)?;
+    }
+    config.insert("schema_version".to_string(), CONFIG_SCHEMA_VERSION.into());
+    Ok(())
+}
+
+// Version 1 files predate the Catppuccin themes and may still say `Light`
+// or `Dark`.
+fn migrate_v1_to_v2(config: &mut Map<String, Value>) -> Result<(), String> {
+    if let Some(preferences) = config.get_mut("preferences") {
+        rename_legacy_theme(preferences);
+    }
+    Ok(())
+}
+
+fn rename_legacy_theme(preferences: &mut Value) {
+    let Some(theme) = preferences.get_mut("theme") else {
+        return;
+    };
+    let renamed = match theme.as_str() {
+        Some("Light") => "Latte",
+        Some("Dark") => "Mocha",
+        _ => return,
+    };
+    *theme = Value::from(renamed);
+}
+
+// `config.json` becomes `config.json.20240131-154500.bak`.
+fn backup_path(path: &Path) -> PathBuf {
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| CONFIG_FILE_NAME.to_string());
+    path.with_file_name(format!("{name}.{stamp}.bak"))
+}
+
 pub fn app_data_dir() -> PathBuf {
     dirs::config_dir()
         .unwrap_or_else(|| PathBuf::from("."))
         .join(APP_CONFIG_DIR)
 }
 
+fn legacy_schema_version() -> u32 {
+    1
+}
+
 fn default_clone_path() -> String {
     dirs::home_dir()
         .or_else(|| std::env::current_dir().ok())
@@ -323,6 +591,10 @@ fn default_log_retention_files() -> usize {
     7
 }
 
+fn default_token_auto_lock_minutes() -> u64 {
+    15
+}
+
 fn default_auto_fetch_enabled() -> bool {
     false
 }
@@ -331,6 +603,10 @@ fn default_auto_fetch_interval_minutes() -> u64 {
     5
 }
 
+fn default_remote_name() -> String {
+    "origin".to_string()
+}
+
 impl Preferences {
     pub fn theme_mode(&self) -> ThemeMode {
         self.theme
@@ -404,6 +680,15 @@ impl Preferences {
         self.allow_encrypted_tokens = allowed;
     }
 
+    // Zero keeps the token store unlocked.
+    pub fn token_auto_lock_minutes(&self) -> u64 {
+        self.token_auto_lock_minutes
+    }
+
+    pub fn set_token_auto_lock_minutes(&mut self, minutes: u64) {
+        self.token_auto_lock_minutes = minutes;
+    }
+
     pub fn control_height(&self) -> f32 {
         self.control_height
     }
@@ -468,6 +753,30 @@ impl Preferences {
         self.auto_fetch_interval_minutes = minutes.max(1);
     }
 
+    pub fn pull_strategy(&self) -> PullStrategy {
+        self.pull_strategy
+    }
+
+    pub fn set_pull_strategy(&mut self, strategy: PullStrategy) {
+        self.pull_strategy = strategy;
+    }
+
+    pub fn default_remote(&self) -> &str {
+        &self.default_remote
+    }
+
+    pub fn set_default_remote(&mut self, remote: String) {
+        self.default_remote = remote;
+    }
+
+    pub fn commit_template(&self) -> &str {
+        &self.commit_template
+    }
+
+    pub fn set_commit_template(&mut self, template: String) {
+        self.commit_template = template;
+    }
+
     pub fn save_to_path<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
         let contents = serde_json::to_string_pretty(self).unwrap_or_else(|_| "{}".to_string());
         if let Some(parent) = path.as_ref().parent() {
@@ -478,12 +787,19 @@ impl Preferences {
 
     pub fn from_path<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
         let contents = fs::read_to_string(path)?;
-        serde_json::from_str(&contents).map_err(|err| {
-            std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                format!("Failed to parse preferences: {err}"),
-            )
-        })
+        // Exported preferences carry no schema version; older ones may still
+        // name the `Light` or `Dark` theme.
+        serde_json::from_str(&contents)
+            .and_then(|mut value| {
+                rename_legacy_theme(&mut value);
+                serde_json::from_value(value)
+            })
+            .map_err(|err| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("Failed to parse preferences: {err}"),
+                )
+            })
     }
 }
 
@@ -497,3 +813,59 @@ impl LoggingOptions {
             retention_files.clamp(MIN_LOG_RETENTION_FILES, MAX_LOG_RETENTION_FILES);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn legacy_config_is_migrated_and_saved_atomically() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(CONFIG_FILE_NAME);
+        fs::write(
+            &path,
+            r#"{"recent_repos":[{"path":"/tmp/repo"}],"preferences":{"theme":"Light"}}"#,
+        )
+        .unwrap();
+
+        let (config, recovery) = AppConfig::load_or_recover_from(&path);
+        assert!(recovery.is_none());
+        assert_eq!(config.schema_version, CONFIG_SCHEMA_VERSION);
+        assert_eq!(config.preferences().theme_mode(), ThemeMode::Latte);
+        assert_eq!(config.recent_repos()[0].path, "/tmp/repo");
+
+        config.save_to(&path).unwrap();
+        let saved: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved["schema_version"], CONFIG_SCHEMA_VERSION);
+        assert_eq!(saved["preferences"]["theme"], "Latte");
+        assert!(!path.with_extension("json.tmp").exists());
+
+        let exported = dir.path().join("preferences.json");
+        fs::write(&exported, r#"{"theme":"Dark"}"#).unwrap();
+        let preferences = Preferences::from_path(&exported).unwrap();
+        assert_eq!(preferences.theme_mode(), ThemeMode::Mocha);
+    }
+
+    #[test]
+    fn unreadable_config_is_backed_up() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(CONFIG_FILE_NAME);
+
+        let (config, recovery) = AppConfig::load_or_recover_from(&path);
+        assert!(recovery.is_none());
+        assert!(config.recent_repos().is_empty());
+
+        fs::write(&path, "{ not json").unwrap();
+        let (config, recovery) = AppConfig::load_or_recover_from(&path);
+        assert!(config.recent_repos().is_empty());
+        let recovery = recovery.expect("parse failure is reported");
+        let backup = recovery.backup.expect("broken file is kept");
+        assert_eq!(fs::read_to_string(&backup).unwrap(), "{ not json");
+        assert!(!path.exists());
+
+        fs::write(&path, r#"{"schema_version":99}"#).unwrap();
+        let (_, recovery) = AppConfig::load_or_recover_from(&path);
+        assert!(recovery.unwrap().error.contains("newer"));
+    }
+}
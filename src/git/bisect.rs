@@ -0,0 +1,235 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+use git2::{Repository, Sort, StatusOptions};
+use serde::{Deserialize, Serialize};
+
+use crate::git::branch::checkout_branch;
+
+const SESSION_FILE: &str = "gitspace-bisect.json";
+// Same as `git bisect run`: the commit cannot be tested.
+const SKIP_EXIT_CODE: i32 = 125;
+
+// Kept in the git dir so a session survives restarts.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BisectSession {
+    pub original_head: String,
+    pub bad: Option<String>,
+    pub good: Vec<String>,
+    pub skipped: Vec<String>,
+    pub current: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BisectVerdict {
+    Good,
+    Bad,
+    Skip,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BisectStep {
+    NeedsMarks,
+    Testing {
+        commit: String,
+        remaining: usize,
+        steps: usize,
+    },
+    Found(String),
+    // Only skipped commits are left; the first bad commit is one of these.
+    Inconclusive(Vec<String>),
+}
+
+pub fn load_bisect(repo_path: &str) -> Result<Option<BisectSession>, git2::Error> {
+    let repo = Repository::open(repo_path)?;
+    let path = session_path(&repo);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let contents =
+        fs::read_to_string(&path).map_err(|err| git2::Error::from_str(&err.to_string()))?;
+    serde_json::from_str(&contents)
+        .map(Some)
+        .map_err(|err| git2::Error::from_str(&err.to_string()))
+}
+
+pub fn start_bisect(repo_path: &str) -> Result<BisectSession, git2::Error> {
+    if load_bisect(repo_path)?.is_some() {
+        return Err(git2::Error::from_str("A bisect session is already in progress"));
+    }
+    let repo = Repository::open(repo_path)?;
+    ensure_clean(&repo)?;
+    let head = repo.head()?;
+    let original_head = match head.shorthand() {
+        Some(name) if head.is_branch() => name.to_string(),
+        _ => head
+            .target()
+            .ok_or_else(|| git2::Error::from_str("HEAD does not point at a commit"))?
+            .to_string(),
+    };
+    let session = BisectSession {
+        original_head,
+        ..Default::default()
+    };
+    save_session(&repo, &session)?;
+    Ok(session)
+}
+
+pub fn mark_bisect(
+    repo_path: &str,
+    commit: &str,
+    verdict: BisectVerdict,
+) -> Result<(BisectSession, BisectStep), git2::Error> {
+    let repo = Repository::open(repo_path)?;
+    let mut session = load_bisect(repo_path)?
+        .ok_or_else(|| git2::Error::from_str("No bisect session is in progress"))?;
+    let id = repo.revparse_single(commit)?.peel_to_commit()?.id().to_string();
+    match verdict {
+        BisectVerdict::Bad => session.bad = Some(id),
+        BisectVerdict::Good if !session.good.contains(&id) => session.good.push(id),
+        BisectVerdict::Skip if !session.skipped.contains(&id) => session.skipped.push(id),
+        _ => {}
+    }
+
+    let step = next_step(&repo, &session)?;
+    session.current = match &step {
+        BisectStep::Testing { commit, .. } => {
+            ensure_clean(&repo)?;
+            checkout_branch(repo_path, commit)?;
+            Some(commit.clone())
+        }
+        _ => None,
+    };
+    save_session(&repo, &session)?;
+    Ok((session, step))
+}
+
+pub fn bisect_status(repo_path: &str) -> Result<BisectStep, git2::Error> {
+    let repo = Repository::open(repo_path)?;
+    match load_bisect(repo_path)? {
+        Some(session) => next_step(&repo, &session),
+        None => Ok(BisectStep::NeedsMarks),
+    }
+}
+
+// Exit codes map like `git bisect run`: 0 is good, 125 is skip, 1-127 is bad.
+pub fn run_bisect_command(repo_path: &str, command: &str) -> Result<BisectVerdict, git2::Error> {
+    let mut shell = if cfg!(target_os = "windows") {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    let status = shell
+        .arg(command)
+        .current_dir(repo_path)
+        .status()
+        .map_err(|err| git2::Error::from_str(&format!("Failed to run test command: {err}")))?;
+    match status.code() {
+        Some(0) => Ok(BisectVerdict::Good),
+        Some(SKIP_EXIT_CODE) => Ok(BisectVerdict::Skip),
+        Some(1..=127) => Ok(BisectVerdict::Bad),
+        Some(code) => Err(git2::Error::from_str(&format!(
+            "Test command exited with {code}; stopping bisect"
+        ))),
+        None => Err(git2::Error::from_str(
+            "Test command was terminated by a signal; stopping bisect",
+        )),
+    }
+}
+
+pub fn run_bisect(
+    repo_path: &str,
+    command: &str,
+    mut on_step: impl FnMut(&BisectStep) -> bool,
+) -> Result<BisectStep, git2::Error> {
+    let mut step = bisect_status(repo_path)?;
+    while let BisectStep::Testing { commit, .. } = &step {
+        let verdict = run_bisect_command(repo_path, command)?;
+        step = mark_bisect(repo_path, commit, verdict)?.1;
+        if !on_step(&step) {
+            break;
+        }
+    }
+    Ok(step)
+}
+
+pub fn reset_bisect(repo_path: &str) -> Result<(), git2::Error> {
+    let repo = Repository::open(repo_path)?;
+    let Some(session) = load_bisect(repo_path)? else {
+        return Ok(());
+    };
+    checkout_branch(repo_path, &session.original_head)?;
+    fs::remove_file(session_path(&repo)).map_err(|err| git2::Error::from_str(&err.to_string()))
+}
+
+// Candidates are commits reachable from the bad commit but from no good one.
+// The midpoint of their topological order halves a linear range exactly and
+// is a close enough split across merges.
+fn next_step(repo: &Repository, session: &BisectSession) -> Result<BisectStep, git2::Error> {
+    let Some(bad) = &session.bad else {
+        return Ok(BisectStep::NeedsMarks);
+    };
+    if session.good.is_empty() {
+        return Ok(BisectStep::NeedsMarks);
+    }
+
+    let mut walk = repo.revwalk()?;
+    walk.set_sorting(Sort::TOPOLOGICAL)?;
+    walk.push(git2::Oid::from_str(bad)?)?;
+    for good in &session.good {
+        walk.hide(git2::Oid::from_str(good)?)?;
+    }
+    let candidates: Vec<String> = walk
+        .map(|oid| oid.map(|oid| oid.to_string()))
+        .collect::<Result<_, _>>()?;
+    if candidates.first() != Some(bad) {
+        return Err(git2::Error::from_str(
+            "The bad commit is an ancestor of a good commit",
+        ));
+    }
+    if candidates.len() == 1 {
+        return Ok(BisectStep::Found(bad.clone()));
+    }
+
+    let testable: Vec<&String> = candidates
+        .iter()
+        .skip(1)
+        .filter(|id| !session.skipped.contains(id))
+        .collect();
+    if testable.is_empty() {
+        return Ok(BisectStep::Inconclusive(candidates));
+    }
+    let remaining = candidates.len() - 1;
+    Ok(BisectStep::Testing {
+        commit: testable[testable.len() / 2].clone(),
+        remaining,
+        steps: remaining.next_power_of_two().trailing_zeros() as usize,
+    })
+}
+
+fn ensure_clean(repo: &Repository) -> Result<(), git2::Error> {
+    let mut options = StatusOptions::new();
+    options.include_untracked(false).include_ignored(false);
+    if repo.statuses(Some(&mut options))?.is_empty() {
+        Ok(())
+    } else {
+        Err(git2::Error::from_str(
+            "Commit or stash local changes before bisecting",
+        ))
+    }
+}
+
+fn session_path(repo: &Repository) -> PathBuf {
+    repo.path().join(SESSION_FILE)
+}
+
+fn save_session(repo: &Repository, session: &BisectSession) -> Result<(), git2::Error> {
+    let contents = serde_json::to_string_pretty(session)
+        .map_err(|err| git2::Error::from_str(&err.to_string()))?;
+    fs::write(session_path(repo), contents).map_err(|err| git2::Error::from_str(&err.to_string()))
+}
@@ -1,9 +1,10 @@
 use std::path::Path;
-use std::process::Command;
 
 use chrono::Utc;
 use git2::{BranchType, Error, Repository, build::CheckoutBuilder};
 
+use crate::git::git_command;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum BranchKind {
     Local,
@@ -188,10 +189,10 @@ pub fn restore_file_from_branch<P: AsRef<Path>>(
     repo_path: P,
     branch: &str,
     path: &str,
+    credential_helper: Option<&str>,
 ) -> Result<(), String> {
-    let output = Command::new("git")
+    let output = git_command(repo_path, credential_helper)
         .args(["checkout", branch, "--", path])
-        .current_dir(repo_path.as_ref())
         .output()
         .map_err(|err| err.to_string())?;
 
@@ -0,0 +1,146 @@
+use std::path::Path;
+
+use git2::{Error, ObjectType, Oid, Repository, Signature, message_prettify};
+
+use crate::git::signing::SigningConfig;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CreatedObject {
+    pub id: String,
+    pub signed: bool,
+}
+
+pub fn create_commit<P: AsRef<Path>>(repo_path: P, message: &str) -> Result<CreatedObject, Error> {
+    let repo = Repository::open(repo_path)?;
+    let message = message_prettify(message, None)?;
+    if message.trim().is_empty() {
+        return Err(Error::from_str("commit message cannot be empty"));
+    }
+
+    let signature = repo.signature()?;
+    let mut index = repo.index()?;
+    let tree = repo.find_tree(index.write_tree()?)?;
+    let parent = match repo.head() {
+        Ok(head) => Some(head.peel_to_commit()?),
+        Err(err) if err.code() == git2::ErrorCode::UnbornBranch => None,
+        Err(err) => return Err(err),
+    };
+    let parents: Vec<&git2::Commit> = parent.iter().collect();
+
+    let signing = SigningConfig::from_repo(&repo)?;
+    if !signing.sign_commits {
+        let oid = repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            &message,
+            &tree,
+            &parents,
+        )?;
+        return Ok(CreatedObject {
+            id: oid.to_string(),
+            signed: false,
+        });
+    }
+
+    let buffer = repo.commit_create_buffer(&signature, &signature, &message, &tree, &parents)?;
+    let buffer = buffer
+        .as_str()
+        .ok_or_else(|| Error::from_str("commit buffer is not valid UTF-8"))?;
+    let gpg_signature = signing.sign(buffer, &identity(&signature))?;
+    let oid = repo.commit_signed(buffer, &gpg_signature, None)?;
+
+    let summary = message.lines().next().unwrap_or_default();
+    let reflog_message = if parent.is_some() {
+        format!("commit: {summary}")
+    } else {
+        format!("commit (initial): {summary}")
+    };
+    move_head_to(&repo, oid, &reflog_message)?;
+
+    Ok(CreatedObject {
+        id: oid.to_string(),
+        signed: true,
+    })
+}
+
+pub fn create_annotated_tag<P: AsRef<Path>>(
+    repo_path: P,
+    name: &str,
+    target: &str,
+    message: &str,
+) -> Result<CreatedObject, Error> {
+    let repo = Repository::open(repo_path)?;
+    let target = repo.revparse_single(target)?;
+    let tagger = repo.signature()?;
+    let message = message_prettify(message, None)?;
+    let signing = SigningConfig::from_repo(&repo)?;
+
+    if !signing.sign_tags {
+        let oid = repo.tag(name, &target, &tagger, &message, false)?;
+        return Ok(CreatedObject {
+            id: oid.to_string(),
+            signed: false,
+        });
+    }
+
+    let reference_name = format!("refs/tags/{name}");
+    if !git2::Reference::is_valid_name(&reference_name) {
+        return Err(Error::from_str(&format!(
+            "'{name}' is not a valid tag name"
+        )));
+    }
+    if repo.find_reference(&reference_name).is_ok() {
+        return Err(Error::from_str(&format!("tag '{name}' already exists")));
+    }
+
+    let kind = target.kind().unwrap_or(ObjectType::Commit).str();
+    let payload = format!(
+        "object {}\ntype {kind}\ntag {name}\ntagger {}\n\n{message}",
+        target.id(),
+        signature_line(&tagger)
+    );
+    let tag_signature = signing.sign(&payload, &identity(&tagger))?;
+    let oid = repo.odb()?.write(
+        ObjectType::Tag,
+        format!("{payload}{tag_signature}").as_bytes(),
+    )?;
+    repo.reference(&reference_name, oid, false, "tag: signed")?;
+
+    Ok(CreatedObject {
+        id: oid.to_string(),
+        signed: true,
+    })
+}
+
+fn move_head_to(repo: &Repository, oid: Oid, reflog_message: &str) -> Result<(), Error> {
+    let head = repo.find_reference("HEAD")?;
+    match head.symbolic_target() {
+        Some(branch) => {
+            repo.reference(branch, oid, true, reflog_message)?;
+        }
+        None => repo.set_head_detached(oid)?,
+    }
+    Ok(())
+}
+
+fn identity(signature: &Signature<'_>) -> String {
+    format!(
+        "{} <{}>",
+        signature.name().unwrap_or_default(),
+        signature.email().unwrap_or_default()
+    )
+}
+
+fn signature_line(signature: &Signature<'_>) -> String {
+    let when = signature.when();
+    let offset = when.offset_minutes();
+    format!(
+        "{} {} {}{:02}{:02}",
+        identity(signature),
+        when.seconds(),
+        if offset < 0 { '-' } else { '+' },
+        offset.abs() / 60,
+        offset.abs() % 60
+    )
+}
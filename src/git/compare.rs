@@ -1,5 +1,8 @@
 use git2::{Diff, DiffOptions, Oid, Repository};
 
+use crate::git::diff::{FileDiff, collect_diff_files};
+use crate::git::log::{CommitInfo, commits_between_refs};
+
 #[derive(Debug, Clone)]
 pub struct BranchComparison {
     pub commit: Option<BranchCommit>,
@@ -21,6 +24,17 @@ pub struct DiffSummary {
     pub deletions: usize,
 }
 
+#[derive(Debug, Clone)]
+pub struct RefComparison {
+    pub base: String,
+    pub target: String,
+    pub merge_base: Option<BranchCommit>,
+    pub only_in_base: Vec<CommitInfo>,
+    pub only_in_target: Vec<CommitInfo>,
+    pub diff: DiffSummary,
+    pub files: Vec<FileDiff>,
+}
+
 pub fn compare_branch_with_head(
     repo_path: &str,
     branch_name: &str,
@@ -104,3 +118,52 @@ fn diff_summary(diff: Diff) -> Result<DiffSummary, git2::Error> {
         deletions: stats.deletions(),
     })
 }
+
+pub fn compare_refs(
+    repo_path: &str,
+    base: &str,
+    target: &str,
+    commit_limit: usize,
+) -> Result<RefComparison, git2::Error> {
+    let repo = Repository::open(repo_path)?;
+    let base_commit = repo.revparse_single(base)?.peel_to_commit()?;
+    let target_commit = repo.revparse_single(target)?.peel_to_commit()?;
+
+    let merge_base = match repo.merge_base(base_commit.id(), target_commit.id()) {
+        Ok(oid) => {
+            let commit = repo.find_commit(oid)?;
+            Some(BranchCommit {
+                id: commit.id().to_string(),
+                summary: commit.summary().unwrap_or_default().to_string(),
+                author: commit.author().name().unwrap_or("Unknown").to_string(),
+                time: commit.time(),
+            })
+        }
+        Err(err) if err.code() == git2::ErrorCode::NotFound => None,
+        Err(err) => return Err(err),
+    };
+
+    let diff = repo.diff_tree_to_tree(
+        Some(&base_commit.tree()?),
+        Some(&target_commit.tree()?),
+        None,
+    )?;
+    let stats = diff.stats()?;
+    let summary = DiffSummary {
+        files_changed: stats.files_changed(),
+        additions: stats.insertions(),
+        deletions: stats.deletions(),
+    };
+    let mut files = collect_diff_files(diff)?;
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(RefComparison {
+        base: base.to_string(),
+        target: target.to_string(),
+        merge_base,
+        only_in_base: commits_between_refs(repo_path, target, base, commit_limit)?,
+        only_in_target: commits_between_refs(repo_path, base, target, commit_limit)?,
+        diff: summary,
+        files,
+    })
+}
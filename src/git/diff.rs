@@ -1,41 +1,104 @@
 use std::collections::HashMap;
 
-use git2::{Diff, DiffFormat, DiffOptions, Oid, Repository, Tree};
+use git2::{
+    Delta, Diff, DiffDelta, DiffFindOptions, DiffFormat, DiffOptions, Oid, Repository, Tree,
+};
 
-#[derive(Debug, Clone)]
+use crate::git::lfs::{LfsPointer, lfs_pointer_change, resolve_lfs_content};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DiffStatus {
+    Added,
+    Deleted,
+    #[default]
+    Modified,
+    Renamed,
+    Copied,
+    TypeChange,
+}
+
+impl DiffStatus {
+    pub fn label(self) -> &'static str {
+        match self {
+            DiffStatus::Added => "added",
+            DiffStatus::Deleted => "deleted",
+            DiffStatus::Modified => "modified",
+            DiffStatus::Renamed => "renamed",
+            DiffStatus::Copied => "copied",
+            DiffStatus::TypeChange => "type changed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffSide {
+    Old,
+    New,
+}
+
+#[derive(Debug, Clone, Default)]
 pub struct FileDiff {
     pub path: String,
+    pub status: DiffStatus,
+    pub old_path: Option<String>,
+    pub similarity: Option<u16>,
+    pub old_mode: Option<u32>,
+    pub new_mode: Option<u32>,
+    pub binary: bool,
+    pub lfs: bool,
+    pub old_size: Option<u64>,
+    pub new_size: Option<u64>,
+    pub old_id: Option<String>,
+    pub new_id: Option<String>,
     pub additions: usize,
     pub deletions: usize,
     pub patch: String,
 }
 
-fn collect_diff_files(diff: Diff) -> Result<Vec<FileDiff>, git2::Error> {
-    let mut files: HashMap<String, FileDiff> = HashMap::new();
+impl FileDiff {
+    pub fn mode_changed(&self) -> bool {
+        matches!((self.old_mode, self.new_mode), (Some(old), Some(new)) if old != new)
+    }
+}
+
+pub fn collect_diff_files(mut diff: Diff) -> Result<Vec<FileDiff>, git2::Error> {
+    diff.find_similar(Some(
+        DiffFindOptions::new()
+            .renames(true)
+            .copies(true)
+            .for_untracked(true),
+    ))?;
+
+    let mut files: Vec<FileDiff> = diff.deltas().map(|delta| file_from_delta(&delta)).collect();
+    let mut positions: HashMap<(String, Option<String>), usize> = HashMap::new();
+    for (idx, file) in files.iter().enumerate() {
+        positions
+            .entry((file.path.clone(), file.old_path.clone()))
+            .or_insert(idx);
+    }
 
     diff.print(DiffFormat::Patch, |delta, _hunk, line| {
-        let path = delta
-            .new_file()
-            .path()
-            .or_else(|| delta.old_file().path())
-            .and_then(|p| p.to_str())
-            .unwrap_or("(unknown)")
-            .to_string();
-
-        let entry = files.entry(path.clone()).or_insert_with(|| FileDiff {
-            path: path.clone(),
-            additions: 0,
-            deletions: 0,
-            patch: String::new(),
-        });
+        let Some(&idx) = positions.get(&delta_paths(&delta)) else {
+            return true;
+        };
+        let entry = &mut files[idx];
+        record_sizes(entry, &delta);
+        let content = std::str::from_utf8(line.content()).unwrap_or("");
 
         match line.origin() {
             '+' => entry.additions += 1,
             '-' => entry.deletions += 1,
+            'B' => entry.binary = true,
+            'F' => {
+                entry.similarity = content.lines().find_map(|line| {
+                    line.strip_prefix("similarity index ")
+                        .or_else(|| line.strip_prefix("dissimilarity index "))
+                        .and_then(|value| value.trim_end_matches('%').parse().ok())
+                });
+            }
             _ => {}
         }
 
-        let content = std::str::from_utf8(line.content()).unwrap_or("");
         match line.origin() {
             '\\' => entry.patch.push(' '),
             other => entry.patch.push(other),
@@ -45,28 +108,129 @@ fn collect_diff_files(diff: Diff) -> Result<Vec<FileDiff>, git2::Error> {
         true
     })?;
 
-    // For files without textual output (e.g., binary), gather stats separately.
-    let stats = diff.stats()?;
-    if stats.files_changed() > files.len() {
-        for delta in diff.deltas() {
-            let path = delta
-                .new_file()
-                .path()
-                .or_else(|| delta.old_file().path())
-                .and_then(|p| p.to_str())
-                .unwrap_or("(unknown)")
-                .to_string();
-
-            files.entry(path.clone()).or_insert_with(|| FileDiff {
-                path: path.clone(),
-                additions: 0,
-                deletions: 0,
-                patch: String::from("Binary file change\n"),
-            });
+    for file in &mut files {
+        if file.binary && !file.patch.contains("@@") {
+            file.patch = String::from("Binary file change\n");
+        }
+        if let Some((old, new)) = lfs_pointer_change(&file.patch) {
+            let describe = |pointer: &Option<LfsPointer>| match pointer {
+                Some(pointer) => format!("{} ({} bytes)", &pointer.oid[..12], pointer.size),
+                None => "none".to_string(),
+            };
+            file.lfs = true;
+            file.patch = format!("Git LFS object {} → {}\n", describe(&old), describe(&new));
+            if let Some(old) = old {
+                file.old_size = Some(old.size);
+            }
+            if let Some(new) = new {
+                file.new_size = Some(new.size);
+            }
         }
     }
 
-    Ok(files.into_values().collect())
+    Ok(files)
+}
+
+fn file_from_delta(delta: &DiffDelta<'_>) -> FileDiff {
+    let (path, old_path) = delta_paths(delta);
+    let status = match delta.status() {
+        Delta::Added | Delta::Untracked => DiffStatus::Added,
+        Delta::Deleted => DiffStatus::Deleted,
+        Delta::Renamed => DiffStatus::Renamed,
+        Delta::Copied => DiffStatus::Copied,
+        Delta::Typechange => DiffStatus::TypeChange,
+        _ => DiffStatus::Modified,
+    };
+    let mode = |file: git2::DiffFile<'_>| {
+        let mode = u32::from(file.mode());
+        (mode != 0).then_some(mode)
+    };
+
+    let mut file = FileDiff {
+        path,
+        status,
+        old_path,
+        old_mode: mode(delta.old_file()),
+        new_mode: mode(delta.new_file()),
+        binary: delta.flags().is_binary(),
+        old_id: blob_id(delta.old_file()),
+        new_id: blob_id(delta.new_file()),
+        ..Default::default()
+    };
+    record_sizes(&mut file, delta);
+    file
+}
+
+fn blob_id(file: git2::DiffFile<'_>) -> Option<String> {
+    (file.exists() && !file.id().is_zero()).then(|| file.id().to_string())
+}
+
+// Falls back to the working tree file for the new side, and LFS pointers
+// resolve to their downloaded object. `None` when the side does not exist
+// (for example the old side of an added file).
+pub fn read_diff_side(
+    repo_path: &str,
+    diff: &FileDiff,
+    side: DiffSide,
+) -> Result<Option<Vec<u8>>, git2::Error> {
+    let repo = Repository::open(repo_path)?;
+    let (id, path, exists) = match side {
+        DiffSide::Old => (
+            &diff.old_id,
+            diff.old_path.as_deref().unwrap_or(&diff.path),
+            diff.status != DiffStatus::Added,
+        ),
+        DiffSide::New => (
+            &diff.new_id,
+            diff.path.as_str(),
+            diff.status != DiffStatus::Deleted,
+        ),
+    };
+    if !exists {
+        return Ok(None);
+    }
+    // Working tree sides carry an id that was hashed but never written.
+    if let Some(id) = id
+        && let Ok(blob) = repo.find_blob(Oid::from_str(id)?)
+    {
+        let content =
+            resolve_lfs_content(&repo, blob.content()).unwrap_or_else(|| blob.content().to_vec());
+        return Ok(Some(content));
+    }
+    if side == DiffSide::New
+        && let Some(workdir) = repo.workdir()
+    {
+        return match std::fs::read(workdir.join(path)) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(git2::Error::from_str(&err.to_string())),
+        };
+    }
+    Ok(None)
+}
+
+fn delta_paths(delta: &DiffDelta<'_>) -> (String, Option<String>) {
+    let path_of =
+        |file: git2::DiffFile<'_>| file.path().and_then(|p| p.to_str()).map(str::to_string);
+    let new_path = path_of(delta.new_file());
+    let old_path = path_of(delta.old_file());
+    let path = new_path
+        .clone()
+        .or_else(|| old_path.clone())
+        .unwrap_or_else(|| "(unknown)".to_string());
+    let old_path = match delta.status() {
+        Delta::Renamed | Delta::Copied => old_path.filter(|old| *old != path),
+        _ => None,
+    };
+    (path, old_path)
+}
+
+fn record_sizes(file: &mut FileDiff, delta: &DiffDelta<'_>) {
+    let size = |diff_file: git2::DiffFile<'_>| {
+        (diff_file.exists() && diff_file.size() > 0).then(|| diff_file.size())
+    };
+    file.old_size = size(delta.old_file()).or(file.old_size);
+    file.new_size = size(delta.new_file()).or(file.new_size);
 }
 
 fn head_tree(repo: &Repository) -> Result<Option<Tree<'_>>, git2::Error> {
@@ -101,6 +265,27 @@ pub fn commit_diff(repo_path: &str, oid: &str) -> Result<Vec<FileDiff>, git2::Er
     collect_diff_files(diff)
 }
 
+pub fn commit_path_diff(
+    repo_path: &str,
+    oid: &str,
+    path: &str,
+) -> Result<Vec<FileDiff>, git2::Error> {
+    let repo = Repository::open(repo_path)?;
+    let commit = repo.find_commit(Oid::from_str(oid)?)?;
+    let tree = commit.tree()?;
+    let parent_tree = if let Ok(parent) = commit.parent(0) {
+        Some(parent.tree()?)
+    } else {
+        None
+    };
+
+    let mut options = DiffOptions::new();
+    options.pathspec(path).context_lines(3);
+    let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), Some(&mut options))?;
+
+    collect_diff_files(diff)
+}
+
 pub fn working_tree_diff(repo_path: &str) -> Result<Vec<FileDiff>, git2::Error> {
     let repo = Repository::open(repo_path)?;
     let mut index = repo.index()?;
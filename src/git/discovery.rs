@@ -35,23 +35,6 @@ pub fn find_repo_root<P: AsRef<Path>>(path: P) -> Result<Option<PathBuf>, Error>
     }
 }
 
-#[allow(dead_code)]
-pub fn list_worktrees<P: AsRef<Path>>(repo_path: P) -> Result<Vec<String>, Error> {
-    let repo = Repository::open(repo_path)?;
-    let names = repo.worktrees()?;
-    let mut entries = Vec::new();
-
-    for name in names.iter().flatten() {
-        if let Ok(worktree) = repo.find_worktree(name) {
-            let path = worktree.path();
-            entries.push(path.to_string_lossy().to_string());
-        }
-    }
-
-    entries.sort();
-    Ok(entries)
-}
-
 #[allow(dead_code)]
 pub fn list_submodules<P: AsRef<Path>>(repo_path: P) -> Result<Vec<SubmoduleEntry>, Error> {
     let repo = Repository::open(repo_path)?;
@@ -0,0 +1,548 @@
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use git2::{AttrCheckFlags, ObjectType, Repository, TreeWalkMode, TreeWalkResult};
+use reqwest::Proxy;
+use reqwest::blocking::Client;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tempfile::NamedTempFile;
+use url::Url;
+
+use crate::config::NetworkOptions;
+use crate::error::AppError;
+use crate::git::remote::validate_transport_url;
+
+const POINTER_VERSION: &str = "version https://git-lfs.github.com/spec/v1";
+// Pointer files are tiny; anything larger is real content.
+const MAX_POINTER_SIZE: usize = 1024;
+const LFS_MEDIA_TYPE: &str = "application/vnd.git-lfs+json";
+const TRACK_ATTRIBUTES: &str = "filter=lfs diff=lfs merge=lfs -text";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LfsPointer {
+    pub oid: String,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct LfsFile {
+    pub path: String,
+    pub pointer: LfsPointer,
+    pub downloaded: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct LfsFetchSummary {
+    pub downloaded: usize,
+    pub already_present: usize,
+    pub checked_out: usize,
+    pub failed: Vec<(String, String)>,
+}
+
+#[derive(Serialize)]
+struct BatchRequest<'a> {
+    operation: &'a str,
+    transfers: [&'a str; 1],
+    objects: Vec<BatchObject>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct BatchObject {
+    oid: String,
+    size: u64,
+}
+
+#[derive(Deserialize)]
+struct BatchResponse {
+    #[serde(default)]
+    objects: Vec<BatchResponseObject>,
+}
+
+#[derive(Deserialize)]
+struct BatchResponseObject {
+    oid: String,
+    #[serde(default)]
+    actions: Option<BatchActions>,
+    #[serde(default)]
+    error: Option<BatchError>,
+}
+
+#[derive(Deserialize)]
+struct BatchActions {
+    download: Option<BatchAction>,
+}
+
+#[derive(Deserialize)]
+struct BatchAction {
+    href: String,
+    #[serde(default)]
+    header: std::collections::HashMap<String, String>,
+}
+
+#[derive(Deserialize)]
+struct BatchError {
+    message: String,
+}
+
+pub fn parse_lfs_pointer(contents: &[u8]) -> Option<LfsPointer> {
+    if contents.len() > MAX_POINTER_SIZE {
+        return None;
+    }
+    let text = std::str::from_utf8(contents).ok()?;
+    let mut lines = text.lines();
+    if lines.next()? != POINTER_VERSION {
+        return None;
+    }
+    let (mut oid, mut size) = (None, None);
+    for line in lines {
+        if let Some(value) = line.strip_prefix("oid sha256:") {
+            oid = Some(value.trim().to_string());
+        } else if let Some(value) = line.strip_prefix("size ") {
+            size = value.trim().parse().ok();
+        }
+    }
+    let oid = oid.filter(|oid| oid.len() == 64 && oid.chars().all(|c| c.is_ascii_hexdigit()))?;
+    Some(LfsPointer { oid, size: size? })
+}
+
+// Lets diffs report object sizes instead of pointer hashes.
+pub fn lfs_pointer_change(patch: &str) -> Option<(Option<LfsPointer>, Option<LfsPointer>)> {
+    if !patch.contains(POINTER_VERSION) {
+        return None;
+    }
+    let (mut old, mut new) = (String::new(), String::new());
+    // File header lines such as `+++ b/path` come before the first hunk.
+    for line in patch.lines().skip_while(|line| !line.starts_with("H@@")) {
+        let mut chars = line.chars();
+        let (origin, content) = (chars.next(), chars.as_str());
+        if matches!(origin, Some('-' | ' ')) {
+            old.push_str(content);
+            old.push('\n');
+        }
+        if matches!(origin, Some('+' | ' ')) {
+            new.push_str(content);
+            new.push('\n');
+        }
+    }
+    let old = parse_lfs_pointer(old.as_bytes());
+    let new = parse_lfs_pointer(new.as_bytes());
+    (old.is_some() || new.is_some()).then_some((old, new))
+}
+
+pub fn is_lfs_tracked(repo: &Repository, path: &str) -> bool {
+    repo.get_attr(Path::new(path), "filter", AttrCheckFlags::default())
+        .ok()
+        .flatten()
+        == Some("lfs")
+}
+
+pub fn lfs_tracked_patterns(repo_path: &str) -> Result<Vec<String>, git2::Error> {
+    let contents = read_attributes(repo_path)?;
+    Ok(contents
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let pattern = fields.next()?;
+            (!pattern.starts_with('#') && fields.any(|field| field == "filter=lfs"))
+                .then(|| pattern.to_string())
+        })
+        .collect())
+}
+
+// Writes the same attributes as `git lfs track`.
+pub fn track_lfs_pattern(repo_path: &str, pattern: &str) -> Result<bool, git2::Error> {
+    let pattern = pattern.trim();
+    if pattern.is_empty() || pattern.contains(char::is_whitespace) {
+        return Err(git2::Error::from_str(
+            "LFS patterns cannot be empty or contain spaces",
+        ));
+    }
+    if lfs_tracked_patterns(repo_path)?
+        .iter()
+        .any(|tracked| tracked == pattern)
+    {
+        return Ok(false);
+    }
+    let mut contents = read_attributes(repo_path)?;
+    if !contents.is_empty() && !contents.ends_with('\n') {
+        contents.push('\n');
+    }
+    contents.push_str(&format!("{pattern} {TRACK_ATTRIBUTES}\n"));
+    write_attributes(repo_path, &contents)?;
+    Ok(true)
+}
+
+pub fn untrack_lfs_pattern(repo_path: &str, pattern: &str) -> Result<bool, git2::Error> {
+    let contents = read_attributes(repo_path)?;
+    let mut removed = false;
+    let kept: String = contents
+        .lines()
+        .filter(|line| {
+            let mut fields = line.split_whitespace();
+            let matches =
+                fields.next() == Some(pattern.trim()) && fields.any(|field| field == "filter=lfs");
+            removed |= matches;
+            !matches
+        })
+        .map(|line| format!("{line}\n"))
+        .collect();
+    if removed {
+        write_attributes(repo_path, &kept)?;
+    }
+    Ok(removed)
+}
+
+pub fn list_lfs_files(repo_path: &str) -> Result<Vec<LfsFile>, git2::Error> {
+    let repo = Repository::open(repo_path)?;
+    let Ok(head) = repo.head() else {
+        return Ok(Vec::new());
+    };
+    let tree = head.peel_to_tree()?;
+    let odb = repo.odb()?;
+    let mut files = Vec::new();
+    tree.walk(TreeWalkMode::PreOrder, |dir, entry| {
+        let small_blob = odb.read_header(entry.id()).is_ok_and(|(size, kind)| {
+            kind == ObjectType::Blob && size <= MAX_POINTER_SIZE
+        });
+        if !small_blob {
+            return TreeWalkResult::Ok;
+        }
+        if let Ok(blob) = repo.find_blob(entry.id())
+            && let Some(pointer) = parse_lfs_pointer(blob.content())
+        {
+            files.push(LfsFile {
+                path: format!("{dir}{}", entry.name().unwrap_or_default()),
+                downloaded: lfs_object_path(&repo, &pointer.oid).exists(),
+                pointer,
+            });
+        }
+        TreeWalkResult::Ok
+    })?;
+    Ok(files)
+}
+
+// `.git/lfs/objects/ab/cd/abcd…`, the layout git-lfs uses.
+pub fn lfs_object_path(repo: &Repository, oid: &str) -> PathBuf {
+    repo.path()
+        .join("lfs")
+        .join("objects")
+        .join(&oid[..2])
+        .join(&oid[2..4])
+        .join(oid)
+}
+
+pub fn resolve_lfs_content(repo: &Repository, contents: &[u8]) -> Option<Vec<u8>> {
+    let pointer = parse_lfs_pointer(contents)?;
+    fs::read(lfs_object_path(repo, &pointer.oid)).ok()
+}
+
+// Plain status reports a downloaded file as modified against its pointer.
+pub fn is_materialized_lfs_file(repo: &Repository, path: &str) -> bool {
+    let Some(workdir) = repo.workdir() else {
+        return false;
+    };
+    let Some(pointer) = repo
+        .index()
+        .ok()
+        .and_then(|index| index.get_path(Path::new(path), 0))
+        .and_then(|entry| repo.find_blob(entry.id).ok())
+        .and_then(|blob| parse_lfs_pointer(blob.content()))
+    else {
+        return false;
+    };
+    let file = workdir.join(path);
+    if fs::metadata(&file).map(|meta| meta.len()).ok() != Some(pointer.size) {
+        return false;
+    }
+    sha256_file(&file).is_some_and(|digest| digest == pointer.oid)
+}
+
+// `lfs.url`, then `remote.<name>.lfsurl`, then the remote URL with
+// `.git/info/lfs` appended as git-lfs does. SSH remotes map to HTTPS.
+pub fn lfs_endpoint(repo_path: &str, remote_name: &str) -> Result<String, git2::Error> {
+    let repo = Repository::open(repo_path)?;
+    let config = repo.config()?;
+    for key in [
+        "lfs.url".to_string(),
+        format!("remote.{remote_name}.lfsurl"),
+    ] {
+        if let Ok(url) = config.get_string(&key)
+            && !url.trim().is_empty()
+        {
+            return Ok(url.trim().trim_end_matches('/').to_string());
+        }
+    }
+
+    let remote = repo.find_remote(remote_name)?;
+    let url = remote
+        .url()
+        .ok_or_else(|| git2::Error::from_str("Remote has no URL"))?
+        .trim_end_matches('/');
+    let base = if url.starts_with("https://") || url.starts_with("http://") {
+        url.to_string()
+    } else if let Some(rest) = url.strip_prefix("ssh://") {
+        let rest = rest.split_once('@').map_or(rest, |(_, host)| host);
+        format!("https://{rest}")
+    } else if let Some((user_host, path)) = url.split_once(':')
+        && !user_host.contains('/')
+    {
+        let host = user_host
+            .split_once('@')
+            .map_or(user_host, |(_, host)| host);
+        format!("https://{host}/{}", path.trim_start_matches('/'))
+    } else {
+        return Err(git2::Error::from_str(
+            "Git LFS needs an HTTP(S) endpoint; set lfs.url for this repository",
+        ));
+    };
+    Ok(if base.ends_with(".git") {
+        format!("{base}/info/lfs")
+    } else {
+        format!("{base}.git/info/lfs")
+    })
+}
+
+pub fn fetch_lfs_objects(
+    repo_path: &str,
+    remote_name: &str,
+    network: &NetworkOptions,
+    token: Option<String>,
+) -> Result<LfsFetchSummary, AppError> {
+    let repo = Repository::open(repo_path)?;
+    let endpoint = lfs_endpoint(repo_path, remote_name)?;
+    validate_transport_url(&endpoint, network)?;
+
+    let mut summary = LfsFetchSummary::default();
+    let mut missing: Vec<LfsPointer> = Vec::new();
+    for file in list_lfs_files(repo_path)? {
+        if file.downloaded {
+            summary.already_present += 1;
+        } else if !missing.contains(&file.pointer) {
+            missing.push(file.pointer);
+        }
+    }
+    if missing.is_empty() {
+        return Ok(summary);
+    }
+
+    let client = build_client(network)?;
+    let request = BatchRequest {
+        operation: "download",
+        transfers: ["basic"],
+        objects: missing
+            .iter()
+            .map(|pointer| BatchObject {
+                oid: pointer.oid.clone(),
+                size: pointer.size,
+            })
+            .collect(),
+    };
+    let mut batch = client
+        .post(format!("{endpoint}/objects/batch"))
+        .header("Accept", LFS_MEDIA_TYPE)
+        .header("Content-Type", LFS_MEDIA_TYPE)
+        .body(serde_json::to_vec(&request).map_err(|err| AppError::Unknown(err.to_string()))?);
+    if let Some(token) = &token {
+        batch = batch.basic_auth("git", Some(token));
+    }
+    let response: BatchResponse = batch
+        .send()?
+        .error_for_status()?
+        .json()
+        .map_err(|err| AppError::Network(format!("Invalid LFS batch response: {err}")))?;
+
+    for object in response.objects {
+        let Some(pointer) = missing.iter().find(|pointer| pointer.oid == object.oid) else {
+            continue;
+        };
+        let action = match (object.error, object.actions.and_then(|a| a.download)) {
+            (Some(error), _) => {
+                summary.failed.push((object.oid, error.message));
+                continue;
+            }
+            (None, Some(action)) => action,
+            (None, None) => {
+                summary
+                    .failed
+                    .push((object.oid, "No download action".to_string()));
+                continue;
+            }
+        };
+        match download_object(
+            &client,
+            &repo,
+            &endpoint,
+            pointer,
+            &action,
+            token.as_deref(),
+        ) {
+            Ok(()) => summary.downloaded += 1,
+            Err(err) => summary.failed.push((object.oid, err.detail().to_string())),
+        }
+    }
+    Ok(summary)
+}
+
+pub fn pull_lfs_objects(
+    repo_path: &str,
+    remote_name: &str,
+    network: &NetworkOptions,
+    token: Option<String>,
+) -> Result<LfsFetchSummary, AppError> {
+    let mut summary = fetch_lfs_objects(repo_path, remote_name, network, token)?;
+    summary.checked_out = checkout_lfs_files(repo_path)?;
+    Ok(summary)
+}
+
+pub fn checkout_lfs_files(repo_path: &str) -> Result<usize, git2::Error> {
+    let repo = Repository::open(repo_path)?;
+    let Some(workdir) = repo.workdir() else {
+        return Ok(0);
+    };
+    let mut replaced = 0;
+    for file in list_lfs_files(repo_path)? {
+        let target = workdir.join(&file.path);
+        let is_pointer = fs::read(&target)
+            .ok()
+            .and_then(|contents| parse_lfs_pointer(&contents))
+            .is_some_and(|pointer| pointer == file.pointer);
+        if !is_pointer || !file.downloaded {
+            continue;
+        }
+        fs::copy(lfs_object_path(&repo, &file.pointer.oid), &target)
+            .map_err(|err| git2::Error::from_str(&err.to_string()))?;
+        replaced += 1;
+    }
+    Ok(replaced)
+}
+
+fn download_object(
+    client: &Client,
+    repo: &Repository,
+    endpoint: &str,
+    pointer: &LfsPointer,
+    action: &BatchAction,
+    token: Option<&str>,
+) -> Result<(), AppError> {
+    let mut request = client.get(&action.href);
+    for (name, value) in &action.header {
+        request = request.header(name, value);
+    }
+    // Hrefs often point at object storage on another host; the stored token is
+    // only meant for the LFS server itself.
+    if let Some(token) = token
+        && same_origin(&action.href, endpoint)
+        && !action
+            .header
+            .keys()
+            .any(|name| name.eq_ignore_ascii_case("authorization"))
+    {
+        request = request.basic_auth("git", Some(token));
+    }
+    let mut response = request.send()?.error_for_status()?;
+
+    let path = lfs_object_path(repo, &pointer.oid);
+    let parent = path
+        .parent()
+        .ok_or_else(|| AppError::Unknown("Invalid LFS object path".to_string()))?;
+    fs::create_dir_all(parent)?;
+    let mut partial = NamedTempFile::new_in(parent)?;
+    let mut hasher = Sha256::new();
+    let mut received = 0u64;
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let read = response.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        received += read as u64;
+        if received > pointer.size {
+            return Err(AppError::Validation(format!(
+                "Expected {} bytes but received more",
+                pointer.size
+            )));
+        }
+        hasher.update(&buffer[..read]);
+        partial.write_all(&buffer[..read])?;
+    }
+    if received != pointer.size {
+        return Err(AppError::Validation(format!(
+            "Expected {} bytes but received {received}",
+            pointer.size
+        )));
+    }
+    if to_hex(&hasher.finalize()) != pointer.oid {
+        return Err(AppError::Validation(
+            "Downloaded content does not match its LFS object id".to_string(),
+        ));
+    }
+    partial
+        .persist(&path)
+        .map_err(|err| AppError::from(err.error))?;
+    Ok(())
+}
+
+fn same_origin(href: &str, endpoint: &str) -> bool {
+    match (Url::parse(href), Url::parse(endpoint)) {
+        (Ok(href), Ok(endpoint)) => {
+            href.scheme() == endpoint.scheme()
+                && href.host_str() == endpoint.host_str()
+                && href.port_or_known_default() == endpoint.port_or_known_default()
+        }
+        _ => false,
+    }
+}
+
+fn build_client(network: &NetworkOptions) -> Result<Client, AppError> {
+    let mut builder = Client::builder()
+        .user_agent("GitSpace-LFS/0.1")
+        .timeout(Duration::from_secs(network.network_timeout_secs.max(1)));
+    if !network.http_proxy.is_empty() {
+        builder = builder.proxy(Proxy::http(&network.http_proxy)?);
+    }
+    if !network.https_proxy.is_empty() {
+        builder = builder.proxy(Proxy::https(&network.https_proxy)?);
+    }
+    Ok(builder.build()?)
+}
+
+fn to_hex(digest: &[u8]) -> String {
+    digest.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn sha256_file(path: &Path) -> Option<String> {
+    let mut file = fs::File::open(path).ok()?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer).ok()?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Some(to_hex(&hasher.finalize()))
+}
+
+fn attributes_path(repo_path: &str) -> Result<PathBuf, git2::Error> {
+    let repo = Repository::open(repo_path)?;
+    repo.workdir()
+        .map(|workdir| workdir.join(".gitattributes"))
+        .ok_or_else(|| git2::Error::from_str("Bare repositories have no .gitattributes"))
+}
+
+fn read_attributes(repo_path: &str) -> Result<String, git2::Error> {
+    match fs::read_to_string(attributes_path(repo_path)?) {
+        Ok(contents) => Ok(contents),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(String::new()),
+        Err(err) => Err(git2::Error::from_str(&err.to_string())),
+    }
+}
+
+fn write_attributes(repo_path: &str, contents: &str) -> Result<(), git2::Error> {
+    fs::write(attributes_path(repo_path)?, contents)
+        .map_err(|err| git2::Error::from_str(&err.to_string()))
+}
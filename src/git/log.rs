@@ -1,4 +1,6 @@
-use git2::{BranchType, Repository, Time};
+use std::sync::mpsc::Receiver;
+
+use git2::{BranchType, Delta, DiffFindOptions, Oid, Repository, Revwalk, Time, Tree};
 
 #[derive(Debug, Clone, Default)]
 pub struct CommitFilter {
@@ -7,6 +9,8 @@ pub struct CommitFilter {
     pub search: Option<String>,
     pub since: Option<i64>,
     pub until: Option<i64>,
+    pub path: Option<String>,
+    pub follow: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -21,6 +25,7 @@ pub struct CommitInfo {
     pub files_changed: Option<usize>,
     pub additions: Option<usize>,
     pub deletions: Option<usize>,
+    pub path: Option<String>,
 }
 
 pub fn list_local_branches(repo_path: &str) -> Result<Vec<String>, git2::Error> {
@@ -39,105 +44,283 @@ pub fn list_local_branches(repo_path: &str) -> Result<Vec<String>, git2::Error>
     Ok(branches)
 }
 
-pub fn read_commit_log(
-    repo_path: &str,
-    filter: &CommitFilter,
-    limit: usize,
+const PROGRESS_INTERVAL: usize = 1_000;
+
+#[derive(Debug, Clone)]
+pub enum LogEvent {
+    Progress {
+        scanned: usize,
+    },
+    Page {
+        commits: Vec<CommitInfo>,
+        scanned: usize,
+        exhausted: bool,
+    },
+}
+
+pub struct CommitLogCursor<'repo> {
+    repo: &'repo Repository,
+    revwalk: Revwalk<'repo>,
+    filter: CommitFilter,
     include_stats: bool,
-) -> Result<Vec<CommitInfo>, git2::Error> {
-    let repo = Repository::open(repo_path)?;
-    let mut revwalk = repo.revwalk()?;
-    revwalk.set_sorting(git2::Sort::TIME | git2::Sort::TOPOLOGICAL)?;
+    path: Option<String>,
+    scanned: usize,
+    exhausted: bool,
+    // Scanned when `on_progress` stopped the last page; served first next time.
+    pending: Option<Oid>,
+}
+
+enum PathChange {
+    Untouched,
+    Touched,
+    RenamedFrom(String),
+}
 
-    if let Some(branch) = &filter.branch {
-        let reference_name = format!("refs/heads/{branch}");
-        if let Ok(reference) = repo.find_reference(&reference_name)
-            && let Some(oid) = reference.target()
-        {
-            revwalk.push(oid)?;
+impl<'repo> CommitLogCursor<'repo> {
+    pub fn new(
+        repo: &'repo Repository,
+        filter: CommitFilter,
+        include_stats: bool,
+    ) -> Result<Self, git2::Error> {
+        let mut revwalk = repo.revwalk()?;
+        revwalk.set_sorting(git2::Sort::TIME | git2::Sort::TOPOLOGICAL)?;
+
+        if let Some(branch) = &filter.branch {
+            let reference_name = format!("refs/heads/{branch}");
+            if let Ok(reference) = repo.find_reference(&reference_name)
+                && let Some(oid) = reference.target()
+            {
+                revwalk.push(oid)?;
+            }
+        } else {
+            revwalk.push_head()?;
         }
-    } else {
-        revwalk.push_head()?;
+
+        let path = filter
+            .path
+            .as_deref()
+            .map(|path| path.trim().trim_matches('/').to_string())
+            .filter(|path| !path.is_empty());
+
+        Ok(Self {
+            repo,
+            revwalk,
+            filter,
+            include_stats,
+            path,
+            scanned: 0,
+            exhausted: false,
+            pending: None,
+        })
     }
 
-    let mut commits = Vec::new();
+    pub fn scanned(&self) -> usize {
+        self.scanned
+    }
 
-    for oid_result in revwalk.take(limit) {
-        let oid = oid_result?;
-        let commit = repo.find_commit(oid)?;
+    pub fn is_exhausted(&self) -> bool {
+        self.exhausted
+    }
+
+    // `on_progress` can return `false` to stop early with a partial page.
+    pub fn next_page(
+        &mut self,
+        page_size: usize,
+        mut on_progress: impl FnMut(usize) -> bool,
+    ) -> Result<Vec<CommitInfo>, git2::Error> {
+        let mut commits = Vec::new();
+
+        while commits.len() < page_size {
+            let oid = match self.pending.take() {
+                Some(oid) => oid,
+                None => {
+                    let Some(oid_result) = self.revwalk.next() else {
+                        self.exhausted = true;
+                        break;
+                    };
+                    let oid = oid_result?;
+                    self.scanned += 1;
+                    if self.scanned.is_multiple_of(PROGRESS_INTERVAL) && !on_progress(self.scanned)
+                    {
+                        self.pending = Some(oid);
+                        break;
+                    }
+                    oid
+                }
+            };
+            let commit = self.repo.find_commit(oid)?;
 
-        if let Some(author_filter) = &filter.author {
-            let author_text = format!(
-                "{} {}",
-                commit.author().name().unwrap_or_default(),
-                commit.author().email().unwrap_or_default()
-            )
-            .to_lowercase();
-            if !author_text.contains(&author_filter.to_lowercase()) {
-                continue;
+            let mut renamed_from = None;
+            if let Some(path) = &self.path {
+                match path_change(self.repo, &commit, path, self.filter.follow)? {
+                    PathChange::Untouched => continue,
+                    PathChange::Touched => {}
+                    PathChange::RenamedFrom(old_path) => renamed_from = Some(old_path),
+                }
             }
-        }
 
-        if let Some(search) = &filter.search {
-            let search_lower = search.to_lowercase();
-            let message = commit.message().unwrap_or_default().to_lowercase();
-            let summary = commit.summary().unwrap_or_default().to_lowercase();
-            if !message.contains(&search_lower) && !summary.contains(&search_lower) {
-                continue;
+            if matches_filter(&commit, &self.filter) {
+                let mut info = commit_info(self.repo, &commit, self.include_stats)?;
+                info.path = self.path.clone();
+                commits.push(info);
+            }
+            // Older commits know the file by its previous name.
+            if let Some(old_path) = renamed_from {
+                self.path = Some(old_path);
             }
         }
 
-        let timestamp = commit.time().seconds();
-        if let Some(since) = filter.since
-            && timestamp < since
-        {
-            continue;
-        }
+        Ok(commits)
+    }
+}
+
+// Each request is the number of matching commits wanted.
+pub fn stream_commit_log(
+    repo_path: &str,
+    filter: CommitFilter,
+    requests: Receiver<usize>,
+    mut on_event: impl FnMut(LogEvent) -> bool,
+) -> Result<(), git2::Error> {
+    let repo = Repository::open(repo_path)?;
+    let mut cursor = CommitLogCursor::new(&repo, filter, false)?;
 
-        if let Some(until) = filter.until
-            && timestamp > until
-        {
-            continue;
+    for page_size in requests {
+        let mut keep_going = true;
+        let commits = cursor.next_page(page_size, |scanned| {
+            keep_going = on_event(LogEvent::Progress { scanned });
+            keep_going
+        })?;
+        if !keep_going {
+            break;
         }
 
-        let parents = commit
-            .parents()
-            .map(|p| p.id().to_string())
-            .collect::<Vec<_>>();
-
-        let (files_changed, additions, deletions) = if include_stats {
-            let tree = commit.tree()?;
-            let parent_tree = commit
-                .parent(0)
-                .ok()
-                .map(|parent| parent.tree())
-                .transpose()?;
-            let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)?;
-            let stats = diff.stats()?;
-            (
-                Some(stats.files_changed()),
-                Some(stats.insertions()),
-                Some(stats.deletions()),
-            )
-        } else {
-            (None, None, None)
+        let exhausted = cursor.is_exhausted();
+        let page = LogEvent::Page {
+            commits,
+            scanned: cursor.scanned(),
+            exhausted,
         };
+        if !on_event(page) || exhausted {
+            break;
+        }
+    }
+
+    Ok(())
+}
 
-        commits.push(CommitInfo {
-            id: oid.to_string(),
-            summary: commit.summary().unwrap_or_default().to_string(),
-            message: commit.message().unwrap_or_default().to_string(),
-            author: commit.author().name().unwrap_or("Unknown").to_string(),
-            email: commit.author().email().map(|s| s.to_string()),
-            time: commit.time(),
-            parents,
-            files_changed,
-            additions,
-            deletions,
-        });
+fn matches_filter(commit: &git2::Commit<'_>, filter: &CommitFilter) -> bool {
+    if let Some(author_filter) = &filter.author {
+        let author_text = format!(
+            "{} {}",
+            commit.author().name().unwrap_or_default(),
+            commit.author().email().unwrap_or_default()
+        )
+        .to_lowercase();
+        if !author_text.contains(&author_filter.to_lowercase()) {
+            return false;
+        }
     }
 
-    Ok(commits)
+    if let Some(search) = &filter.search {
+        let search_lower = search.to_lowercase();
+        let message = commit.message().unwrap_or_default().to_lowercase();
+        let summary = commit.summary().unwrap_or_default().to_lowercase();
+        if !message.contains(&search_lower) && !summary.contains(&search_lower) {
+            return false;
+        }
+    }
+
+    let timestamp = commit.time().seconds();
+    if let Some(since) = filter.since
+        && timestamp < since
+    {
+        return false;
+    }
+
+    if let Some(until) = filter.until
+        && timestamp > until
+    {
+        return false;
+    }
+
+    true
+}
+
+fn path_change(
+    repo: &Repository,
+    commit: &git2::Commit<'_>,
+    path: &str,
+    follow: bool,
+) -> Result<PathChange, git2::Error> {
+    let tree = commit.tree()?;
+    let parent_tree = commit
+        .parent(0)
+        .ok()
+        .map(|parent| parent.tree())
+        .transpose()?;
+
+    let current = entry_id(&tree, path);
+    let previous = parent_tree
+        .as_ref()
+        .and_then(|parent_tree| entry_id(parent_tree, path));
+    if current == previous {
+        return Ok(PathChange::Untouched);
+    }
+    if !follow || current.is_none() || previous.is_some() {
+        return Ok(PathChange::Touched);
+    }
+    let Some(parent_tree) = parent_tree else {
+        return Ok(PathChange::Touched);
+    };
+
+    let mut diff = repo.diff_tree_to_tree(Some(&parent_tree), Some(&tree), None)?;
+    diff.find_similar(Some(DiffFindOptions::new().renames(true)))?;
+    let renamed_from = diff.deltas().find_map(|delta| {
+        let new_path = delta.new_file().path()?.to_str()?;
+        if delta.status() != Delta::Renamed || new_path != path {
+            return None;
+        }
+        delta.old_file().path()?.to_str().map(str::to_string)
+    });
+
+    Ok(match renamed_from {
+        Some(old_path) => PathChange::RenamedFrom(old_path),
+        None => PathChange::Touched,
+    })
+}
+
+fn entry_id(tree: &Tree<'_>, path: &str) -> Option<Oid> {
+    tree.get_path(std::path::Path::new(path))
+        .ok()
+        .map(|entry| entry.id())
+}
+
+fn commit_info(
+    repo: &Repository,
+    commit: &git2::Commit<'_>,
+    include_stats: bool,
+) -> Result<CommitInfo, git2::Error> {
+    let mut info = commit_info_from_commit(commit);
+    if include_stats {
+        let tree = commit.tree()?;
+        let parent_tree = commit
+            .parent(0)
+            .ok()
+            .map(|parent| parent.tree())
+            .transpose()?;
+        let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)?;
+        let stats = diff.stats()?;
+        info.files_changed = Some(stats.files_changed());
+        info.additions = Some(stats.insertions());
+        info.deletions = Some(stats.deletions());
+    }
+    Ok(info)
+}
+
+pub fn find_commit_info(repo_path: &str, id: &str) -> Result<CommitInfo, git2::Error> {
+    let repo = Repository::open(repo_path)?;
+    let commit = repo.revparse_single(id)?.peel_to_commit()?;
+    commit_info(&repo, &commit, true)
 }
 
 pub fn latest_commit_for_branch(
@@ -227,5 +410,6 @@ fn commit_info_from_commit(commit: &git2::Commit<'_>) -> CommitInfo {
         files_changed: None,
         additions: None,
         deletions: None,
+        path: None,
     }
 }
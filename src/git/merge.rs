@@ -1,8 +1,9 @@
 use std::path::Path;
-use std::process::Command;
 
 use git2::Repository;
 
+use crate::git::git_command;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MergeStrategy {
     Merge,
@@ -20,6 +21,7 @@ pub fn merge_branch<P: AsRef<Path>>(
     repo_path: P,
     target: &str,
     strategy: MergeStrategy,
+    credential_helper: Option<&str>,
 ) -> Result<MergeOutcome, String> {
     let repo_path_ref = repo_path.as_ref();
     let (command, args) = match strategy {
@@ -27,10 +29,9 @@ pub fn merge_branch<P: AsRef<Path>>(
         MergeStrategy::Rebase => ("rebase", vec![target]),
     };
 
-    let output = Command::new("git")
+    let output = git_command(repo_path_ref, credential_helper)
         .arg(command)
         .args(args)
-        .current_dir(repo_path_ref)
         .output()
         .map_err(|err| err.to_string())?;
 
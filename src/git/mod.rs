@@ -1,13 +1,36 @@
+use std::path::Path;
+use std::process::Command;
+
+pub mod bisect;
 pub mod branch;
 pub mod clone;
+pub mod commit;
 pub mod compare;
 pub mod diff;
 pub mod discovery;
+pub mod lfs;
 pub mod log;
 pub mod merge;
+pub mod patch;
+pub mod reflog;
 pub mod remote;
+pub mod search;
+pub mod signing;
 pub mod stash;
 pub mod status;
+pub mod submodule;
+pub mod worktree;
 
 #[cfg(test)]
 mod tests;
+
+// `credential_helper` is appended after the user's own helpers, so git only
+// falls back to it when those have nothing for the remote.
+pub fn git_command<P: AsRef<Path>>(repo_path: P, credential_helper: Option<&str>) -> Command {
+    let mut command = Command::new("git");
+    command.current_dir(repo_path);
+    if let Some(helper) = credential_helper {
+        command.arg("-c").arg(format!("credential.helper={helper}"));
+    }
+    command
+}
@@ -0,0 +1,307 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use git2::{ApplyLocation, ApplyOptions, Diff, DiffOptions, Email, EmailCreateOptions, Repository};
+
+const MAX_SLUG_LEN: usize = 52;
+
+#[derive(Debug, Clone)]
+pub struct FormattedPatch {
+    pub file_name: String,
+    pub contents: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApplyTarget {
+    WorkingTree,
+    Index,
+    Both,
+}
+
+impl ApplyTarget {
+    pub fn label(self) -> &'static str {
+        match self {
+            ApplyTarget::WorkingTree => "Working tree",
+            ApplyTarget::Index => "Index",
+            ApplyTarget::Both => "Working tree and index",
+        }
+    }
+
+    fn location(self) -> ApplyLocation {
+        match self {
+            ApplyTarget::WorkingTree => ApplyLocation::WorkDir,
+            ApplyTarget::Index => ApplyLocation::Index,
+            ApplyTarget::Both => ApplyLocation::Both,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileApplyStatus {
+    Clean,
+    Conflict(String),
+    // Dry runs cannot check a file against changes an earlier patch in the
+    // same series would have made, so such files are left unchecked.
+    Unchecked,
+}
+
+#[derive(Debug, Clone)]
+pub struct FileApplyResult {
+    pub patch: usize,
+    pub subject: Option<String>,
+    pub path: String,
+    pub status: FileApplyStatus,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ApplyReport {
+    pub dry_run: bool,
+    pub patches: usize,
+    pub applied: usize,
+    pub files: Vec<FileApplyResult>,
+}
+
+impl ApplyReport {
+    pub fn conflicts(&self) -> impl Iterator<Item = &FileApplyResult> {
+        self.files
+            .iter()
+            .filter(|file| matches!(file.status, FileApplyStatus::Conflict(_)))
+    }
+
+    pub fn has_conflicts(&self) -> bool {
+        self.conflicts().next().is_some()
+    }
+}
+
+// Numbered in the order given (oldest first). Merge commits are rejected like
+// format-patch.
+pub fn format_patches(
+    repo_path: &str,
+    commit_ids: &[String],
+) -> Result<Vec<FormattedPatch>, git2::Error> {
+    let repo = Repository::open(repo_path)?;
+    let total = commit_ids.len();
+    let mut patches = Vec::with_capacity(total);
+
+    for (index, id) in commit_ids.iter().enumerate() {
+        let commit = repo.revparse_single(id)?.peel_to_commit()?;
+        if commit.parent_count() > 1 {
+            return Err(git2::Error::from_str(&format!(
+                "{} is a merge commit and cannot be exported as a patch",
+                &commit.id().to_string()[..8]
+            )));
+        }
+        let parent_tree = match commit.parent(0) {
+            Ok(parent) => Some(parent.tree()?),
+            Err(_) => None,
+        };
+        let mut diff_opts = DiffOptions::new();
+        diff_opts.show_binary(true);
+        let mut diff = repo.diff_tree_to_tree(
+            parent_tree.as_ref(),
+            Some(&commit.tree()?),
+            Some(&mut diff_opts),
+        )?;
+        diff.find_similar(None)?;
+
+        let summary = commit.summary().unwrap_or_default().to_string();
+        let body = commit.body().unwrap_or_default().to_string();
+        let email = Email::from_diff(
+            &diff,
+            index + 1,
+            total,
+            &commit.id(),
+            summary.as_str(),
+            body.as_str(),
+            &commit.author(),
+            &mut EmailCreateOptions::new(),
+        )?;
+
+        patches.push(FormattedPatch {
+            file_name: format!("{:04}-{}.patch", index + 1, patch_slug(&summary)),
+            contents: String::from_utf8_lossy(email.as_slice()).into_owned(),
+        });
+    }
+
+    Ok(patches)
+}
+
+pub fn write_patch_series(
+    repo_path: &str,
+    commit_ids: &[String],
+    dir: impl AsRef<Path>,
+) -> Result<Vec<PathBuf>, git2::Error> {
+    let dir = dir.as_ref();
+    fs::create_dir_all(dir).map_err(|err| git2::Error::from_str(&err.to_string()))?;
+    format_patches(repo_path, commit_ids)?
+        .into_iter()
+        .map(|patch| {
+            let path = dir.join(&patch.file_name);
+            fs::write(&path, patch.contents)
+                .map_err(|err| git2::Error::from_str(&err.to_string()))?;
+            Ok(path)
+        })
+        .collect()
+}
+
+pub fn write_mbox(
+    repo_path: &str,
+    commit_ids: &[String],
+    path: impl AsRef<Path>,
+) -> Result<(), git2::Error> {
+    let contents: String = format_patches(repo_path, commit_ids)?
+        .into_iter()
+        .map(|patch| patch.contents)
+        .collect();
+    fs::write(path, contents).map_err(|err| git2::Error::from_str(&err.to_string()))
+}
+
+pub fn apply_patch_file(
+    repo_path: &str,
+    patch_path: impl AsRef<Path>,
+    target: ApplyTarget,
+    dry_run: bool,
+) -> Result<ApplyReport, git2::Error> {
+    let contents = fs::read(patch_path).map_err(|err| git2::Error::from_str(&err.to_string()))?;
+    apply_patch(repo_path, &contents, target, dry_run)
+}
+
+// Every file is checked on its own so the report can name each conflict. A
+// real run applies messages in order and stops at the first one with a
+// conflict, like `git am`; a dry run checks everything and changes nothing.
+pub fn apply_patch(
+    repo_path: &str,
+    contents: &[u8],
+    target: ApplyTarget,
+    dry_run: bool,
+) -> Result<ApplyReport, git2::Error> {
+    let repo = Repository::open(repo_path)?;
+    let messages = split_mbox(contents);
+    let mut report = ApplyReport {
+        dry_run,
+        patches: messages.len(),
+        ..Default::default()
+    };
+    let mut touched = HashSet::new();
+
+    for (index, message) in messages.iter().enumerate() {
+        let diff = Diff::from_buffer(message)?;
+        let subject = mail_subject(message);
+        let mut clean = true;
+
+        for (delta_index, delta) in diff.deltas().enumerate() {
+            let paths: Vec<String> = [delta.old_file().path(), delta.new_file().path()]
+                .into_iter()
+                .flatten()
+                .map(|path| path.to_string_lossy().into_owned())
+                .collect();
+            let path = paths.last().cloned().unwrap_or_default();
+            let status = if dry_run && paths.iter().any(|path| touched.contains(path)) {
+                FileApplyStatus::Unchecked
+            } else {
+                check_delta(&repo, &diff, delta_index, target)
+            };
+            clean &= !matches!(status, FileApplyStatus::Conflict(_));
+            touched.extend(paths);
+            report.files.push(FileApplyResult {
+                patch: index + 1,
+                subject: subject.clone(),
+                path,
+                status,
+            });
+        }
+
+        if dry_run {
+            continue;
+        }
+        if !clean {
+            break;
+        }
+        repo.apply(&diff, target.location(), None)?;
+        report.applied += 1;
+    }
+
+    Ok(report)
+}
+
+fn check_delta(
+    repo: &Repository,
+    diff: &Diff<'_>,
+    delta_index: usize,
+    target: ApplyTarget,
+) -> FileApplyStatus {
+    let mut seen = 0;
+    let mut options = ApplyOptions::new();
+    options.check(true).delta_callback(move |_| {
+        let selected = seen == delta_index;
+        seen += 1;
+        selected
+    });
+    match repo.apply(diff, target.location(), Some(&mut options)) {
+        Ok(()) => FileApplyStatus::Clean,
+        Err(err) => FileApplyStatus::Conflict(err.message().to_string()),
+    }
+}
+
+// Anything without `From <sha> ` separators is treated as a single patch.
+fn split_mbox(contents: &[u8]) -> Vec<&[u8]> {
+    let mut starts = Vec::new();
+    let mut offset = 0;
+    for line in contents.split_inclusive(|byte| *byte == b'\n') {
+        if is_mbox_separator(line) {
+            starts.push(offset);
+        }
+        offset += line.len();
+    }
+    if starts.first() != Some(&0) {
+        starts.insert(0, 0);
+    }
+    starts
+        .iter()
+        .enumerate()
+        .map(|(index, start)| {
+            let end = starts.get(index + 1).copied().unwrap_or(contents.len());
+            &contents[*start..end]
+        })
+        .collect()
+}
+
+fn is_mbox_separator(line: &[u8]) -> bool {
+    line.strip_prefix(b"From ").is_some_and(|rest| {
+        rest.len() > 40 && rest[..40].iter().all(u8::is_ascii_hexdigit) && rest[40] == b' '
+    })
+}
+
+fn mail_subject(message: &[u8]) -> Option<String> {
+    let text = String::from_utf8_lossy(message);
+    let subject = text
+        .lines()
+        .take_while(|line| !line.is_empty())
+        .find_map(|line| line.strip_prefix("Subject: "))?;
+    let subject = match subject.strip_prefix('[') {
+        Some(rest) if rest.starts_with("PATCH") => rest.split_once("] ")?.1,
+        _ => subject,
+    };
+    Some(subject.trim().to_string())
+}
+
+fn patch_slug(summary: &str) -> String {
+    let mut slug = String::new();
+    for ch in summary.chars() {
+        if ch.is_ascii_alphanumeric() || ch == '.' || ch == '_' {
+            slug.push(ch);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+        if slug.len() >= MAX_SLUG_LEN {
+            break;
+        }
+    }
+    let slug = slug.trim_matches(['-', '.']).to_string();
+    if slug.is_empty() {
+        "patch".to_string()
+    } else {
+        slug
+    }
+}
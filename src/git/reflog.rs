@@ -0,0 +1,126 @@
+use std::path::Path;
+
+use git2::{BranchType, Error, Oid, Repository, ResetType, Time};
+
+const ARCHIVE_TAG_PREFIX: &str = "archive/";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResetMode {
+    Soft,
+    Mixed,
+    Hard,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReflogEntry {
+    pub reference: String,
+    pub index: usize,
+    pub old_id: String,
+    pub new_id: String,
+    pub message: String,
+    pub committer: String,
+    pub time: Time,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchivedBranch {
+    pub tag: String,
+    pub branch: String,
+    pub commit_id: String,
+}
+
+pub fn list_reflog_refs<P: AsRef<Path>>(repo_path: P) -> Result<Vec<String>, Error> {
+    let repo = Repository::open(repo_path)?;
+    let mut refs = vec!["HEAD".to_string()];
+    let mut branches = Vec::new();
+
+    for branch_result in repo.branches(Some(BranchType::Local))? {
+        let (branch, _) = branch_result?;
+        if let Some(name) = branch.get().name()
+            && repo.reference_has_log(name)?
+        {
+            branches.push(name.to_string());
+        }
+    }
+
+    branches.sort();
+    refs.extend(branches);
+    Ok(refs)
+}
+
+pub fn read_reflog<P: AsRef<Path>>(
+    repo_path: P,
+    reference: &str,
+) -> Result<Vec<ReflogEntry>, Error> {
+    let repo = Repository::open(repo_path)?;
+    let reflog = repo.reflog(reference)?;
+    let mut entries = Vec::new();
+
+    for (index, entry) in reflog.iter().enumerate() {
+        let committer = entry.committer();
+        entries.push(ReflogEntry {
+            reference: reference.to_string(),
+            index,
+            old_id: entry.id_old().to_string(),
+            new_id: entry.id_new().to_string(),
+            message: entry.message().unwrap_or_default().to_string(),
+            committer: committer.name().unwrap_or("Unknown").to_string(),
+            time: committer.when(),
+        });
+    }
+
+    Ok(entries)
+}
+
+pub fn reset_head_to<P: AsRef<Path>>(
+    repo_path: P,
+    commit_id: &str,
+    mode: ResetMode,
+) -> Result<(), Error> {
+    let repo = Repository::open(repo_path)?;
+    let commit = repo.find_commit(Oid::from_str(commit_id)?)?;
+    let reset_type = match mode {
+        ResetMode::Soft => ResetType::Soft,
+        ResetMode::Mixed => ResetType::Mixed,
+        ResetMode::Hard => ResetType::Hard,
+    };
+    repo.reset(commit.as_object(), reset_type, None)
+}
+
+pub fn list_archived_branches<P: AsRef<Path>>(repo_path: P) -> Result<Vec<ArchivedBranch>, Error> {
+    let repo = Repository::open(repo_path)?;
+    let tag_names = repo.tag_names(Some(&format!("{ARCHIVE_TAG_PREFIX}*")))?;
+    let mut entries = Vec::new();
+
+    for tag in tag_names.iter().flatten() {
+        let Some(branch) = archived_branch_name(tag) else {
+            continue;
+        };
+        let reference = repo.find_reference(&format!("refs/tags/{tag}"))?;
+        let commit = reference.peel_to_commit()?;
+        entries.push(ArchivedBranch {
+            tag: tag.to_string(),
+            branch,
+            commit_id: commit.id().to_string(),
+        });
+    }
+
+    entries.sort_by(|a, b| b.tag.cmp(&a.tag));
+    Ok(entries)
+}
+
+fn archived_branch_name(tag: &str) -> Option<String> {
+    // Archive tags are named `archive/<branch>-<YYYYMMDD>` with an optional `-<n>` suffix.
+    let rest = tag.strip_prefix(ARCHIVE_TAG_PREFIX)?;
+    let mut parts: Vec<&str> = rest.rsplitn(3, '-').collect();
+    parts.reverse();
+    let is_date = |value: &str| value.len() == 8 && value.chars().all(|c| c.is_ascii_digit());
+    let is_counter = |value: &str| !value.is_empty() && value.chars().all(|c| c.is_ascii_digit());
+
+    match parts.as_slice() {
+        [name, date, counter] if is_date(date) && is_counter(counter) => Some(name.to_string()),
+        [name, date] if is_date(date) => Some(name.to_string()),
+        [name, hyphenated, date] if is_date(date) => Some(format!("{name}-{hyphenated}")),
+        _ => None,
+    }
+}
@@ -7,8 +7,11 @@ use git2::{
     RemoteCallbacks, Repository,
 };
 
+use serde::{Deserialize, Serialize};
+
 use crate::config::NetworkOptions;
 use crate::error::AppError;
+use crate::git::merge::{MergeStrategy, merge_branch};
 
 #[derive(Debug, Clone)]
 pub struct RemoteInfo {
@@ -16,10 +19,49 @@ pub struct RemoteInfo {
     pub url: String,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PullOutcome {
     UpToDate,
     FastForward,
+    Integrated {
+        conflicts: Vec<String>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PullStrategy {
+    #[default]
+    FastForwardOnly,
+    Merge,
+    Rebase,
+}
+
+impl PullStrategy {
+    pub const ALL: [PullStrategy; 3] = [
+        PullStrategy::FastForwardOnly,
+        PullStrategy::Merge,
+        PullStrategy::Rebase,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PullStrategy::FastForwardOnly => "Fast-forward only",
+            PullStrategy::Merge => "Merge",
+            PullStrategy::Rebase => "Rebase",
+        }
+    }
+
+    pub fn key(self) -> &'static str {
+        match self {
+            PullStrategy::FastForwardOnly => "ff-only",
+            PullStrategy::Merge => "merge",
+            PullStrategy::Rebase => "rebase",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|strategy| strategy.key() == key)
+    }
 }
 
 pub fn list_remotes<P: AsRef<Path>>(path: P) -> Result<Vec<RemoteInfo>, git2::Error> {
@@ -87,11 +129,13 @@ pub fn pull_branch<P: AsRef<Path>>(
     path: P,
     remote_name: &str,
     branch: &str,
+    strategy: PullStrategy,
     network: &NetworkOptions,
     token: Option<String>,
+    credential_helper: Option<&str>,
 ) -> Result<PullOutcome, AppError> {
     fetch_remote(&path, remote_name, network, token)?;
-    let repo = Repository::open(path)?;
+    let repo = Repository::open(&path)?;
 
     let remote_ref_name = format!("refs/remotes/{remote_name}/{branch}");
     let remote_ref = repo.find_reference(&remote_ref_name)?;
@@ -108,9 +152,21 @@ pub fn pull_branch<P: AsRef<Path>>(
         return Ok(PullOutcome::FastForward);
     }
 
-    Err(AppError::Git(
-        "Non-fast-forward pull required. Please merge or rebase manually.".to_string(),
-    ))
+    let merge_strategy = match strategy {
+        PullStrategy::FastForwardOnly => {
+            return Err(AppError::Git(
+                "Non-fast-forward pull required. Please merge or rebase manually.".to_string(),
+            ));
+        }
+        PullStrategy::Merge => MergeStrategy::Merge,
+        PullStrategy::Rebase => MergeStrategy::Rebase,
+    };
+    let target = format!("{remote_name}/{branch}");
+    let outcome =
+        merge_branch(&path, &target, merge_strategy, credential_helper).map_err(AppError::Git)?;
+    Ok(PullOutcome::Integrated {
+        conflicts: outcome.conflicts,
+    })
 }
 
 #[allow(dead_code)]
@@ -190,8 +246,7 @@ pub fn prune_remotes<P: AsRef<Path>>(
     Ok(())
 }
 
-#[allow(dead_code)]
-fn configure_proxy_options(network: &NetworkOptions) -> ProxyOptions<'_> {
+pub fn configure_proxy_options(network: &NetworkOptions) -> ProxyOptions<'_> {
     let mut proxy_options = ProxyOptions::new();
 
     if !network.https_proxy.is_empty() {
@@ -203,8 +258,7 @@ fn configure_proxy_options(network: &NetworkOptions) -> ProxyOptions<'_> {
     proxy_options
 }
 
-#[allow(dead_code)]
-fn validate_transport_url(url: &str, network: &NetworkOptions) -> Result<(), AppError> {
+pub fn validate_transport_url(url: &str, network: &NetworkOptions) -> Result<(), AppError> {
     let url = url.to_lowercase();
 
     let is_ssh = url.starts_with("ssh://") || url.contains('@');
@@ -0,0 +1,390 @@
+use std::collections::HashSet;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use chrono::{NaiveDate, TimeZone, Utc};
+use git2::{Error, Oid, Repository, Time};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tempfile::NamedTempFile;
+
+use crate::git::log::CommitInfo;
+
+const INDEX_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CommitIndex {
+    pub version: u32,
+    pub repo_path: String,
+    pub tips: Vec<String>,
+    pub commits: Vec<IndexedCommit>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexedCommit {
+    pub id: String,
+    pub summary: String,
+    pub message: String,
+    pub author: String,
+    pub email: String,
+    pub time: i64,
+    pub offset_minutes: i32,
+    pub parents: Vec<String>,
+    pub paths: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SearchQuery {
+    pub terms: Vec<String>,
+    pub authors: Vec<String>,
+    pub paths: Vec<String>,
+    pub before: Option<i64>,
+    pub after: Option<i64>,
+    pub regex: Option<Regex>,
+    pub pickaxe: Option<String>,
+}
+
+impl SearchQuery {
+    // Free text plus `author:`, `path:`, `before:`, `after:`, `re:` (or
+    // `/pattern/`) and `-S`/`pickaxe:` tokens. Values may be quoted.
+    pub fn parse(input: &str) -> Result<Self, Error> {
+        let mut query = SearchQuery::default();
+        for token in tokenize(input) {
+            if let Some(value) = token.strip_prefix("author:") {
+                query.authors.push(value.to_lowercase());
+            } else if let Some(value) = token.strip_prefix("path:") {
+                query.paths.push(value.trim_matches('/').to_string());
+            } else if let Some(value) = token.strip_prefix("before:") {
+                query.before = Some(parse_day(value)?);
+            } else if let Some(value) = token.strip_prefix("after:") {
+                query.after = Some(parse_day(value)?);
+            } else if let Some(value) = token
+                .strip_prefix("-S")
+                .or_else(|| token.strip_prefix("pickaxe:"))
+            {
+                query.pickaxe = Some(value.to_string()).filter(|value| !value.is_empty());
+            } else if let Some(pattern) = token.strip_prefix("re:").or_else(|| {
+                (token.len() > 2 && token.starts_with('/') && token.ends_with('/'))
+                    .then(|| &token[1..token.len() - 1])
+            }) {
+                let regex = Regex::new(pattern)
+                    .map_err(|err| Error::from_str(&format!("Invalid regex: {err}")))?;
+                query.regex = Some(regex);
+            } else {
+                query.terms.push(token.to_lowercase());
+            }
+        }
+        Ok(query)
+    }
+
+    fn matches(&self, commit: &IndexedCommit) -> bool {
+        if self.before.is_some_and(|before| commit.time >= before)
+            || self.after.is_some_and(|after| commit.time < after)
+        {
+            return false;
+        }
+
+        let author = format!("{} {}", commit.author, commit.email).to_lowercase();
+        if !self.authors.iter().all(|needle| author.contains(needle)) {
+            return false;
+        }
+
+        if !self
+            .paths
+            .iter()
+            .all(|needle| commit.paths.iter().any(|path| path_matches(path, needle)))
+        {
+            return false;
+        }
+
+        if let Some(regex) = &self.regex
+            && !regex.is_match(&commit.message)
+        {
+            return false;
+        }
+
+        if !self.terms.is_empty() {
+            let haystack = format!(
+                "{}\n{}\n{}",
+                commit.message.to_lowercase(),
+                author,
+                commit.paths.join("\n").to_lowercase()
+            );
+            if !self.terms.iter().all(|term| haystack.contains(term)) {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+pub fn commit_index_path(index_dir: &Path, repo_path: &str) -> PathBuf {
+    let canonical = fs::canonicalize(repo_path).unwrap_or_else(|_| PathBuf::from(repo_path));
+    let hash = format!(
+        "{:x}",
+        Sha256::digest(canonical.to_string_lossy().as_bytes())
+    );
+    index_dir.join(format!("{}.json", &hash[..16]))
+}
+
+pub fn load_commit_index(index_dir: &Path, repo_path: &str) -> CommitIndex {
+    fs::read_to_string(commit_index_path(index_dir, repo_path))
+        .ok()
+        .and_then(|data| serde_json::from_str::<CommitIndex>(&data).ok())
+        .filter(|index| index.version == INDEX_VERSION)
+        .unwrap_or_else(|| CommitIndex {
+            version: INDEX_VERSION,
+            repo_path: repo_path.to_string(),
+            ..Default::default()
+        })
+}
+
+pub fn update_commit_index(
+    index_dir: &Path,
+    repo_path: &str,
+    index: &mut CommitIndex,
+) -> Result<usize, Error> {
+    let repo = Repository::open(repo_path)?;
+    let tips = current_tips(&repo)?;
+    let mut tip_ids: Vec<String> = tips.iter().map(Oid::to_string).collect();
+    tip_ids.sort();
+    if tip_ids == index.tips && !index.commits.is_empty() {
+        return Ok(0);
+    }
+
+    // A tip that vanished from the object database means history was
+    // rewritten and pruned, so start again rather than keep stale entries.
+    let stale = index
+        .tips
+        .iter()
+        .any(|tip| Oid::from_str(tip).map_or(true, |oid| repo.find_commit(oid).is_err()));
+    if stale {
+        index.commits.clear();
+        index.tips.clear();
+    }
+
+    let mut revwalk = repo.revwalk()?;
+    revwalk.set_sorting(git2::Sort::TIME | git2::Sort::TOPOLOGICAL)?;
+    for tip in &tips {
+        revwalk.push(*tip)?;
+    }
+    for tip in &index.tips {
+        if let Ok(oid) = Oid::from_str(tip) {
+            revwalk.hide(oid)?;
+        }
+    }
+
+    let known: HashSet<String> = index.commits.iter().map(|c| c.id.clone()).collect();
+    let mut added = Vec::new();
+    for oid in revwalk {
+        let commit = repo.find_commit(oid?)?;
+        if known.contains(&commit.id().to_string()) {
+            continue;
+        }
+        added.push(index_commit(&repo, &commit)?);
+    }
+
+    let count = added.len();
+    index.tips = tip_ids;
+    index.repo_path = repo_path.to_string();
+    added.append(&mut index.commits);
+    index.commits = added;
+    index.commits.sort_by_key(|commit| std::cmp::Reverse(commit.time));
+    save_commit_index(index_dir, repo_path, index)?;
+    Ok(count)
+}
+
+pub fn search_commit_index(
+    repo_path: &str,
+    index: &CommitIndex,
+    query: &SearchQuery,
+    limit: usize,
+) -> Result<Vec<CommitInfo>, Error> {
+    let repo = match &query.pickaxe {
+        Some(_) => Some(Repository::open(repo_path)?),
+        None => None,
+    };
+
+    let mut results = Vec::new();
+    for commit in &index.commits {
+        if results.len() >= limit {
+            break;
+        }
+        if !query.matches(commit) {
+            continue;
+        }
+        if let (Some(repo), Some(needle)) = (&repo, &query.pickaxe)
+            && !changes_occurrences(repo, &commit.id, needle, &query.paths)?
+        {
+            continue;
+        }
+        results.push(CommitInfo {
+            id: commit.id.clone(),
+            summary: commit.summary.clone(),
+            message: commit.message.clone(),
+            author: commit.author.clone(),
+            email: Some(commit.email.clone()).filter(|email| !email.is_empty()),
+            time: Time::new(commit.time, commit.offset_minutes),
+            parents: commit.parents.clone(),
+            files_changed: Some(commit.paths.len()),
+            additions: None,
+            deletions: None,
+            path: None,
+        });
+    }
+    Ok(results)
+}
+
+fn save_commit_index(index_dir: &Path, repo_path: &str, index: &CommitIndex) -> Result<(), Error> {
+    fs::create_dir_all(index_dir).map_err(|err| Error::from_str(&err.to_string()))?;
+    let data = serde_json::to_string(index).map_err(|err| Error::from_str(&err.to_string()))?;
+    // Write to a temporary file and rename it into place, so a crash or a
+    // concurrent refresh never leaves a truncated index behind.
+    let mut temp =
+        NamedTempFile::new_in(index_dir).map_err(|err| Error::from_str(&err.to_string()))?;
+    temp.write_all(data.as_bytes())
+        .and_then(|_| temp.as_file().sync_all())
+        .map_err(|err| Error::from_str(&err.to_string()))?;
+    temp.persist(commit_index_path(index_dir, repo_path))
+        .map_err(|err| Error::from_str(&err.error.to_string()))?;
+    Ok(())
+}
+
+fn current_tips(repo: &Repository) -> Result<Vec<Oid>, Error> {
+    let mut tips = Vec::new();
+    for reference in repo.references()? {
+        let reference = reference?;
+        let Some(name) = reference.name() else {
+            continue;
+        };
+        if !(name.starts_with("refs/heads/")
+            || name.starts_with("refs/remotes/")
+            || name.starts_with("refs/tags/"))
+        {
+            continue;
+        }
+        if let Ok(commit) = reference.peel_to_commit() {
+            tips.push(commit.id());
+        }
+    }
+    if let Ok(head) = repo.head()
+        && let Ok(commit) = head.peel_to_commit()
+    {
+        tips.push(commit.id());
+    }
+    tips.sort();
+    tips.dedup();
+    Ok(tips)
+}
+
+fn index_commit(repo: &Repository, commit: &git2::Commit<'_>) -> Result<IndexedCommit, Error> {
+    let tree = commit.tree()?;
+    let parent_tree = commit
+        .parent(0)
+        .ok()
+        .map(|parent| parent.tree())
+        .transpose()?;
+    let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)?;
+
+    let mut paths = Vec::new();
+    for delta in diff.deltas() {
+        for file in [delta.old_file(), delta.new_file()] {
+            if let Some(path) = file.path().and_then(|path| path.to_str())
+                && !paths.iter().any(|existing| existing == path)
+            {
+                paths.push(path.to_string());
+            }
+        }
+    }
+
+    let author = commit.author();
+    Ok(IndexedCommit {
+        id: commit.id().to_string(),
+        summary: commit.summary().unwrap_or_default().to_string(),
+        message: commit.message().unwrap_or_default().to_string(),
+        author: author.name().unwrap_or("Unknown").to_string(),
+        email: author.email().unwrap_or_default().to_string(),
+        time: commit.time().seconds(),
+        offset_minutes: commit.time().offset_minutes(),
+        parents: commit.parents().map(|p| p.id().to_string()).collect(),
+        paths,
+    })
+}
+
+// Matches `git log -S`: the commit changes how often `needle` occurs in a file.
+fn changes_occurrences(
+    repo: &Repository,
+    commit_id: &str,
+    needle: &str,
+    paths: &[String],
+) -> Result<bool, Error> {
+    let commit = repo.find_commit(Oid::from_str(commit_id)?)?;
+    let tree = commit.tree()?;
+    let parent_tree = commit
+        .parent(0)
+        .ok()
+        .map(|parent| parent.tree())
+        .transpose()?;
+    let mut options = git2::DiffOptions::new();
+    for path in paths {
+        options.pathspec(path);
+    }
+    let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), Some(&mut options))?;
+
+    let count = |oid: Oid| -> usize {
+        if oid.is_zero() {
+            return 0;
+        }
+        repo.find_blob(oid)
+            .map(|blob| {
+                String::from_utf8_lossy(blob.content())
+                    .matches(needle)
+                    .count()
+            })
+            .unwrap_or(0)
+    };
+    Ok(diff
+        .deltas()
+        .any(|delta| count(delta.old_file().id()) != count(delta.new_file().id())))
+}
+
+fn path_matches(path: &str, needle: &str) -> bool {
+    path == needle
+        || path
+            .strip_prefix(needle)
+            .is_some_and(|rest| rest.starts_with('/'))
+        || (!needle.contains('/') && path.rsplit('/').next() == Some(needle))
+}
+
+fn parse_day(value: &str) -> Result<i64, Error> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .and_then(|date| Utc.from_local_datetime(&date).earliest())
+        .map(|date| date.timestamp())
+        .ok_or_else(|| Error::from_str(&format!("Expected a YYYY-MM-DD date, got '{value}'")))
+}
+
+fn tokenize(input: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for ch in input.chars() {
+        match ch {
+            '"' => quoted = !quoted,
+            ch if ch.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            ch => current.push(ch),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
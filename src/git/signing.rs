@@ -0,0 +1,313 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use git2::{Config, Error, ErrorCode, Oid, Repository};
+use tempfile::NamedTempFile;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SigningFormat {
+    OpenPgp,
+    Ssh,
+    X509,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SigningConfig {
+    pub sign_commits: bool,
+    pub sign_tags: bool,
+    pub format: SigningFormat,
+    pub key: Option<String>,
+    pub program: String,
+    pub allowed_signers: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignatureState {
+    Verified(String),
+    Untrusted(String),
+    Unverified(String),
+    Unknown(String),
+    Unsigned,
+}
+
+impl SigningConfig {
+    pub fn from_repo(repo: &Repository) -> Result<Self, Error> {
+        let config = repo.config()?.snapshot()?;
+        let format = match config_string(&config, "gpg.format")?.as_deref() {
+            Some("ssh") => SigningFormat::Ssh,
+            Some("x509") => SigningFormat::X509,
+            _ => SigningFormat::OpenPgp,
+        };
+        let program = match format {
+            SigningFormat::OpenPgp => config_string(&config, "gpg.openpgp.program")?
+                .or(config_string(&config, "gpg.program")?)
+                .unwrap_or_else(|| "gpg".to_string()),
+            SigningFormat::Ssh => config_string(&config, "gpg.ssh.program")?
+                .unwrap_or_else(|| "ssh-keygen".to_string()),
+            SigningFormat::X509 => {
+                config_string(&config, "gpg.x509.program")?.unwrap_or_else(|| "gpgsm".to_string())
+            }
+        };
+
+        Ok(Self {
+            sign_commits: config_bool(&config, "commit.gpgsign")?,
+            sign_tags: config_bool(&config, "tag.gpgsign")?,
+            format,
+            key: config_string(&config, "user.signingkey")?.filter(|key| !key.trim().is_empty()),
+            program,
+            allowed_signers: config_string(&config, "gpg.ssh.allowedsignersfile")?
+                .map(|path| expand_home(&path)),
+        })
+    }
+
+    pub fn format_label(&self) -> &'static str {
+        match self.format {
+            SigningFormat::OpenPgp => "GPG",
+            SigningFormat::Ssh => "SSH",
+            SigningFormat::X509 => "X.509",
+        }
+    }
+
+    pub fn sign(&self, payload: &str, committer: &str) -> Result<String, Error> {
+        match self.format {
+            SigningFormat::Ssh => self.sign_ssh(payload),
+            SigningFormat::OpenPgp | SigningFormat::X509 => {
+                let key = self.key.clone().unwrap_or_else(|| committer.to_string());
+                let output = run_with_stdin(
+                    Command::new(&self.program).args(["--status-fd=2", "-bsau", &key]),
+                    payload.as_bytes(),
+                )?;
+                signature_from_output(output, "gpg")
+            }
+        }
+    }
+
+    fn sign_ssh(&self, payload: &str) -> Result<String, Error> {
+        let key = self.key.as_deref().ok_or_else(|| {
+            Error::from_str("user.signingkey must be set to sign with gpg.format=ssh")
+        })?;
+
+        // A literal public key is signed through the agent, like git does.
+        let literal = key
+            .strip_prefix("key::")
+            .or_else(|| key.starts_with("ssh-").then_some(key));
+        let key_file = literal
+            .map(|public_key| write_temp_file(public_key.as_bytes()))
+            .transpose()?;
+
+        let mut command = Command::new(&self.program);
+        command.args(["-Y", "sign", "-n", "git", "-f"]);
+        match &key_file {
+            Some(key_file) => command.arg(key_file.path()).arg("-U"),
+            None => command.arg(expand_home(key)),
+        };
+        let output = run_with_stdin(&mut command, payload.as_bytes());
+        signature_from_output(output?, "ssh-keygen")
+    }
+}
+
+pub fn read_signing_config<P: AsRef<Path>>(repo_path: P) -> Result<SigningConfig, Error> {
+    let repo = Repository::open(repo_path)?;
+    SigningConfig::from_repo(&repo)
+}
+
+pub fn verify_commit_signature<P: AsRef<Path>>(
+    repo_path: P,
+    commit_id: &str,
+) -> Result<SignatureState, Error> {
+    let repo = Repository::open(repo_path)?;
+    let oid = Oid::from_str(commit_id)?;
+    let (signature, signed_data) = match repo.extract_signature(&oid, None) {
+        Ok(parts) => parts,
+        Err(err) if err.code() == ErrorCode::NotFound => return Ok(SignatureState::Unsigned),
+        Err(err) => return Err(err),
+    };
+    let signature = signature.as_str().unwrap_or_default().to_string();
+    let config = SigningConfig::from_repo(&repo)?;
+
+    if signature.contains("BEGIN SSH SIGNATURE") {
+        let committer = repo
+            .find_commit(oid)?
+            .committer()
+            .email()
+            .map(str::to_string);
+        return verify_ssh(&config, &signature, &signed_data, committer.as_deref());
+    }
+
+    let program = if signature.contains("BEGIN SIGNED MESSAGE") {
+        "gpgsm"
+    } else if config.format == SigningFormat::OpenPgp {
+        config.program.as_str()
+    } else {
+        "gpg"
+    };
+    verify_gpg(program, &signature, &signed_data)
+}
+
+fn verify_ssh(
+    config: &SigningConfig,
+    signature: &str,
+    signed_data: &[u8],
+    principal: Option<&str>,
+) -> Result<SignatureState, Error> {
+    let program = if config.format == SigningFormat::Ssh {
+        config.program.as_str()
+    } else {
+        "ssh-keygen"
+    };
+    let signature_file = write_temp_file(signature.as_bytes())?;
+    let result = match (&config.allowed_signers, principal) {
+        (Some(allowed), Some(principal)) if allowed.exists() => {
+            let output = run_with_stdin(
+                Command::new(program)
+                    .args(["-Y", "verify", "-n", "git", "-f"])
+                    .arg(allowed)
+                    .args(["-I", principal, "-s"])
+                    .arg(signature_file.path()),
+                signed_data,
+            );
+            output.map(|output| {
+                let message = first_line(&output.stdout, &output.stderr);
+                if output.status.success() {
+                    SignatureState::Verified(message)
+                } else {
+                    SignatureState::Unverified(message)
+                }
+            })
+        }
+        _ => {
+            let output = run_with_stdin(
+                Command::new(program)
+                    .args(["-Y", "check-novalidate", "-n", "git", "-s"])
+                    .arg(signature_file.path()),
+                signed_data,
+            );
+            output.map(|output| {
+                if output.status.success() {
+                    SignatureState::Unknown(
+                        "Valid SSH signature, but no allowed signers file to trust it".to_string(),
+                    )
+                } else {
+                    SignatureState::Unverified(first_line(&output.stdout, &output.stderr))
+                }
+            })
+        }
+    };
+
+    Ok(result.unwrap_or_else(|err| SignatureState::Unknown(err.message().to_string())))
+}
+
+fn verify_gpg(program: &str, signature: &str, signed_data: &[u8]) -> Result<SignatureState, Error> {
+    let signature_file = write_temp_file(signature.as_bytes())?;
+    let output = run_with_stdin(
+        Command::new(program)
+            .args(["--status-fd=1", "--keyid-format=long", "--verify"])
+            .arg(signature_file.path())
+            .arg("-"),
+        signed_data,
+    );
+
+    let output = match output {
+        Ok(output) => output,
+        Err(err) => return Ok(SignatureState::Unknown(err.message().to_string())),
+    };
+    let status = String::from_utf8_lossy(&output.stdout);
+    let status_line = |tag: &str| {
+        status
+            .lines()
+            .find_map(|line| line.strip_prefix(&format!("[GNUPG:] {tag} ")))
+            .map(str::to_string)
+    };
+
+    // Like git, a good signature only counts as verified when the key is
+    // trusted beyond "undefined".
+    let trusted = status.lines().any(|line| {
+        ["TRUST_MARGINAL", "TRUST_FULLY", "TRUST_ULTIMATE"]
+            .iter()
+            .any(|level| line.starts_with(&format!("[GNUPG:] {level}")))
+    });
+    Ok(if let Some(signer) = status_line("GOODSIG") {
+        if trusted {
+            SignatureState::Verified(signer)
+        } else {
+            SignatureState::Untrusted(signer)
+        }
+    } else if let Some(signer) = status_line("BADSIG") {
+        SignatureState::Unverified(signer)
+    } else {
+        SignatureState::Unknown(first_line(&output.stderr, &output.stdout))
+    })
+}
+
+fn run_with_stdin(command: &mut Command, input: &[u8]) -> Result<std::process::Output, Error> {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| Error::from_str(&format!("Failed to start signing program: {err}")))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(input)
+            .map_err(|err| Error::from_str(&err.to_string()))?;
+    }
+    child
+        .wait_with_output()
+        .map_err(|err| Error::from_str(&err.to_string()))
+}
+
+fn signature_from_output(output: std::process::Output, program: &str) -> Result<String, Error> {
+    let signature = String::from_utf8_lossy(&output.stdout).to_string();
+    if !output.status.success() || signature.trim().is_empty() {
+        return Err(Error::from_str(&format!(
+            "{program} failed to sign: {}",
+            first_line(&output.stderr, &output.stdout)
+        )));
+    }
+    Ok(signature)
+}
+
+fn first_line(primary: &[u8], fallback: &[u8]) -> String {
+    let text = String::from_utf8_lossy(primary);
+    let text = if text.trim().is_empty() {
+        String::from_utf8_lossy(fallback)
+    } else {
+        text
+    };
+    text.lines().next().unwrap_or_default().trim().to_string()
+}
+
+fn write_temp_file(contents: &[u8]) -> Result<NamedTempFile, Error> {
+    let mut file =
+        NamedTempFile::with_prefix("gitspace-").map_err(|err| Error::from_str(&err.to_string()))?;
+    file.write_all(contents)
+        .and_then(|_| file.flush())
+        .map_err(|err| Error::from_str(&err.to_string()))?;
+    Ok(file)
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir()
+            .map(|home| home.join(rest))
+            .unwrap_or_else(|| PathBuf::from(path)),
+        None => PathBuf::from(path),
+    }
+}
+
+fn config_string(config: &Config, key: &str) -> Result<Option<String>, Error> {
+    match config.get_string(key) {
+        Ok(value) => Ok(Some(value)),
+        Err(err) if err.code() == ErrorCode::NotFound => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+fn config_bool(config: &Config, key: &str) -> Result<bool, Error> {
+    match config.get_bool(key) {
+        Ok(value) => Ok(value),
+        Err(err) if err.code() == ErrorCode::NotFound => Ok(false),
+        Err(err) => Err(err),
+    }
+}
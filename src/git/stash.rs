@@ -1,40 +1,101 @@
 use git2::build::CheckoutBuilder;
-use git2::{Repository, Signature, StashApplyOptions};
+use git2::{Oid, Repository, Signature, StashApplyOptions, StashFlags, StashSaveOptions, Time};
+
+use crate::git::diff::{FileDiff, collect_diff_files};
 
 #[derive(Debug, Clone)]
 pub struct StashEntry {
     pub index: usize,
     pub message: String,
+    pub id: String,
+    pub branch: Option<String>,
+    pub time: Time,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct StashOptions {
+    pub include_untracked: bool,
+    pub keep_index: bool,
+    pub staged_only: bool,
+    pub paths: Vec<String>,
 }
 
 pub fn list_stashes(repo_path: &str) -> Result<Vec<StashEntry>, git2::Error> {
     let mut repo = Repository::open(repo_path)?;
-    let mut entries = Vec::new();
+    let mut stashed = Vec::new();
 
-    repo.stash_foreach(|index, name, _oid| {
-        entries.push(StashEntry {
-            index: index as usize,
-            message: name.to_string(),
-        });
+    repo.stash_foreach(|index, name, oid| {
+        stashed.push((index, name.to_string(), *oid));
         true
     })?;
 
+    let mut entries = Vec::new();
+    for (index, message, oid) in stashed {
+        let time = repo.find_commit(oid)?.time();
+        entries.push(StashEntry {
+            index,
+            branch: stash_branch_name(&message),
+            message,
+            id: oid.to_string(),
+            time,
+        });
+    }
+
     Ok(entries)
 }
 
-pub fn create_stash(
+pub fn create_stash_with_options(
     repo_path: &str,
     message: &str,
-    include_untracked: bool,
+    options: &StashOptions,
 ) -> Result<(), git2::Error> {
     let mut repo = Repository::open(repo_path)?;
-    let signature = Signature::now("GitSpace", "gitspace@example.com")?;
-    let mut flags = git2::StashFlags::DEFAULT;
-    if include_untracked {
-        flags |= git2::StashFlags::INCLUDE_UNTRACKED;
+    let signature = stash_signature(&repo)?;
+
+    if options.staged_only {
+        return stash_staged_changes(&repo, &signature, message);
+    }
+
+    let mut flags = StashFlags::DEFAULT;
+    if options.include_untracked {
+        flags |= StashFlags::INCLUDE_UNTRACKED;
+    }
+    if options.keep_index {
+        flags |= StashFlags::KEEP_INDEX;
+    }
+
+    let paths: Vec<&str> = options
+        .paths
+        .iter()
+        .map(|path| path.trim())
+        .filter(|path| !path.is_empty())
+        .collect();
+    if paths.is_empty() {
+        repo.stash_save2(&signature, Some(message), Some(flags))?;
+        return Ok(());
     }
 
-    repo.stash_save2(&signature, Some(message), Some(flags))?;
+    // libgit2 resets the whole working tree after a path-limited stash, so keep everything
+    // and only reset the stashed paths here.
+    let mut save_options = StashSaveOptions::new(signature.clone());
+    save_options.flags(Some(flags | StashFlags::KEEP_ALL));
+    for path in &paths {
+        save_options.pathspec(*path);
+    }
+    let oid = repo.stash_save_ext(Some(&mut save_options))?;
+    reset_stashed_paths(&repo, &paths, options)?;
+
+    if !message.is_empty() {
+        let branch = repo
+            .find_commit(oid)?
+            .message()
+            .and_then(stash_branch_name)
+            .unwrap_or_else(|| "(no branch)".to_string());
+        let mut reflog = repo.reflog("refs/stash")?;
+        reflog.remove(0, false)?;
+        reflog.append(oid, &signature, Some(&format!("On {branch}: {message}")))?;
+        reflog.write()?;
+    }
     Ok(())
 }
 
@@ -49,7 +110,181 @@ pub fn apply_stash(repo_path: &str, index: usize) -> Result<(), git2::Error> {
     repo.stash_apply(index, Some(&mut options))
 }
 
+pub fn pop_stash(repo_path: &str, index: usize) -> Result<(), git2::Error> {
+    let mut repo = Repository::open(repo_path)?;
+    let mut checkout = CheckoutBuilder::new();
+    checkout.force();
+
+    let mut options = StashApplyOptions::new();
+    options.checkout_options(checkout);
+
+    repo.stash_pop(index, Some(&mut options))
+}
+
 pub fn drop_stash(repo_path: &str, index: usize) -> Result<(), git2::Error> {
     let mut repo = Repository::open(repo_path)?;
     repo.stash_drop(index)
 }
+
+pub fn stash_diff(repo_path: &str, index: usize) -> Result<Vec<FileDiff>, git2::Error> {
+    let repo = Repository::open(repo_path)?;
+    let stash = repo.find_commit(stash_oid(&repo, index)?)?;
+    let base_tree = stash.parent(0)?.tree()?;
+    let stash_tree = stash.tree()?;
+    let diff = repo.diff_tree_to_tree(Some(&base_tree), Some(&stash_tree), None)?;
+
+    let mut files = collect_diff_files(diff)?;
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(files)
+}
+
+pub fn branch_from_stash(repo_path: &str, index: usize, branch: &str) -> Result<(), git2::Error> {
+    let mut repo = Repository::open(repo_path)?;
+    {
+        let stash = repo.find_commit(stash_oid(&repo, index)?)?;
+        let base = stash.parent(0)?;
+        let reference = repo.branch(branch, &base, false)?.into_reference();
+        let reference_name = reference
+            .name()
+            .ok_or_else(|| git2::Error::from_str("branch name is not valid UTF-8"))?
+            .to_string();
+        repo.checkout_tree(base.as_object(), Some(CheckoutBuilder::new().safe()))?;
+        repo.set_head(&reference_name)?;
+    }
+
+    let mut options = StashApplyOptions::new();
+    options.reinstantiate_index();
+    repo.stash_pop(index, Some(&mut options))
+}
+
+fn reset_stashed_paths(
+    repo: &Repository,
+    paths: &[&str],
+    options: &StashOptions,
+) -> Result<(), git2::Error> {
+    let mut checkout = CheckoutBuilder::new();
+    checkout.force();
+    if options.include_untracked {
+        checkout.remove_untracked(true);
+    }
+    for path in paths {
+        checkout.path(*path);
+    }
+
+    if options.keep_index {
+        return repo.checkout_index(None, Some(&mut checkout));
+    }
+
+    let head = repo.head()?.peel_to_commit()?;
+    repo.reset_default(Some(head.as_object()), paths.iter().copied())?;
+    repo.checkout_head(Some(&mut checkout))
+}
+
+fn stash_oid(repo: &Repository, index: usize) -> Result<Oid, git2::Error> {
+    let reflog = repo.reflog("refs/stash")?;
+    reflog
+        .get(index)
+        .map(|entry| entry.id_new())
+        .ok_or_else(|| git2::Error::from_str(&format!("stash@{{{index}}} does not exist")))
+}
+
+fn stash_signature(repo: &Repository) -> Result<Signature<'static>, git2::Error> {
+    repo.signature()
+        .or_else(|_| Signature::now("GitSpace", "gitspace@example.com"))
+}
+
+fn stash_branch_name(message: &str) -> Option<String> {
+    let rest = message
+        .strip_prefix("WIP on ")
+        .or_else(|| message.strip_prefix("On "))?;
+    let (branch, _) = rest.split_once(':')?;
+    Some(branch.to_string())
+}
+
+fn stash_staged_changes(
+    repo: &Repository,
+    signature: &Signature<'_>,
+    message: &str,
+) -> Result<(), git2::Error> {
+    let head = repo.head()?.peel_to_commit()?;
+    let branch = repo
+        .head()?
+        .shorthand()
+        .filter(|name| *name != "HEAD")
+        .unwrap_or("(no branch)")
+        .to_string();
+
+    let mut index = repo.index()?;
+    index.read(true)?;
+    let index_tree = repo.find_tree(index.write_tree()?)?;
+    let head_tree = head.tree()?;
+    let staged = repo.diff_tree_to_index(Some(&head_tree), Some(&index), None)?;
+    if staged.deltas().len() == 0 {
+        return Err(git2::Error::from_str("no staged changes to stash"));
+    }
+
+    let short_head = head.as_object().short_id()?;
+    let head_summary = format!(
+        "{} {}",
+        short_head.as_str().unwrap_or_default(),
+        head.summary().unwrap_or_default()
+    );
+    let index_commit = repo.commit(
+        None,
+        signature,
+        signature,
+        &format!("index on {branch}: {head_summary}"),
+        &index_tree,
+        &[&head],
+    )?;
+    let index_commit = repo.find_commit(index_commit)?;
+
+    let stash_message = if message.is_empty() {
+        format!("WIP on {branch}: {head_summary}")
+    } else {
+        format!("On {branch}: {message}")
+    };
+    let stash_commit = repo.commit(
+        None,
+        signature,
+        signature,
+        &stash_message,
+        &index_tree,
+        &[&head, &index_commit],
+    )?;
+
+    repo.reference_ensure_log("refs/stash")?;
+    repo.reference("refs/stash", stash_commit, true, &stash_message)?;
+
+    // Drop the staged changes from the index and from files whose working copy matches it.
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| git2::Error::from_str("cannot stash in a bare repository"))?
+        .to_path_buf();
+    let mut restore = CheckoutBuilder::new();
+    restore.force();
+    let mut paths = Vec::new();
+    let mut restore_count = 0;
+    for delta in staged.deltas() {
+        let Some(path) = delta.new_file().path().or_else(|| delta.old_file().path()) else {
+            continue;
+        };
+        paths.push(path.to_path_buf());
+        let on_disk = std::fs::read(workdir.join(path)).ok();
+        let staged_blob = index
+            .get_path(path, 0)
+            .and_then(|entry| repo.find_blob(entry.id).ok())
+            .map(|blob| blob.content().to_vec());
+        if on_disk == staged_blob {
+            restore.path(path);
+            restore_count += 1;
+        }
+    }
+
+    repo.reset_default(Some(head.as_object()), paths.iter().map(|p| p.as_path()))?;
+    if restore_count > 0 {
+        restore.remove_untracked(true);
+        repo.checkout_head(Some(&mut restore))?;
+    }
+    Ok(())
+}
@@ -2,6 +2,8 @@ use std::path::Path;
 
 use git2::{BranchType, Repository, Status, StatusOptions};
 
+use crate::git::lfs::is_materialized_lfs_file;
+
 #[derive(Debug, Clone, Default)]
 pub struct RepoStatus {
     pub branch: Option<String>,
@@ -100,6 +102,10 @@ pub fn read_working_tree_status<P: AsRef<Path>>(
             staged.insert(path.to_string());
         }
 
+        if status.contains(Status::WT_MODIFIED) && is_materialized_lfs_file(&repo, path) {
+            continue;
+        }
+
         if status.intersects(
             Status::WT_MODIFIED
                 | Status::WT_DELETED
@@ -0,0 +1,126 @@
+use std::path::Path;
+use std::time::Instant;
+
+use git2::{
+    ConfigLevel, Cred, Error, FetchOptions, RemoteCallbacks, Repository, SubmoduleIgnore,
+    SubmoduleStatus, SubmoduleUpdateOptions,
+};
+
+use crate::config::NetworkOptions;
+use crate::error::AppError;
+use crate::git::clone::CloneProgress;
+use crate::git::remote::{configure_proxy_options, validate_transport_url};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubmoduleInfo {
+    pub name: String,
+    pub path: String,
+    pub url: Option<String>,
+    pub recorded_id: Option<String>,
+    pub checked_out_id: Option<String>,
+    pub initialized: bool,
+    pub checked_out: bool,
+    pub out_of_date: bool,
+    pub dirty: bool,
+}
+
+pub fn read_submodule_status<P: AsRef<Path>>(repo_path: P) -> Result<Vec<SubmoduleInfo>, Error> {
+    let repo = Repository::open(repo_path)?;
+    let local_config = repo.config()?.open_level(ConfigLevel::Local)?;
+    let mut entries = Vec::new();
+
+    for submodule in repo.submodules()? {
+        let name = submodule
+            .name()
+            .map(str::to_string)
+            .unwrap_or_else(|| submodule.path().to_string_lossy().to_string());
+        let status = repo.submodule_status(&name, SubmoduleIgnore::None)?;
+        let recorded_id = submodule.index_id().or_else(|| submodule.head_id());
+        let checked_out_id = submodule.workdir_id();
+        // `IN_CONFIG` only reflects .gitmodules; `git submodule init` writes the URL locally.
+        let initialized = local_config
+            .get_string(&format!("submodule.{name}.url"))
+            .is_ok();
+        let checked_out = status.contains(SubmoduleStatus::IN_WD)
+            && !status.contains(SubmoduleStatus::WD_UNINITIALIZED);
+
+        entries.push(SubmoduleInfo {
+            path: submodule.path().to_string_lossy().to_string(),
+            url: submodule.url().map(str::to_string),
+            out_of_date: checked_out && recorded_id.is_some() && recorded_id != checked_out_id,
+            dirty: status.intersects(
+                SubmoduleStatus::WD_INDEX_MODIFIED
+                    | SubmoduleStatus::WD_WD_MODIFIED
+                    | SubmoduleStatus::WD_UNTRACKED,
+            ),
+            recorded_id: recorded_id.map(|oid| oid.to_string()),
+            checked_out_id: checked_out_id.map(|oid| oid.to_string()),
+            initialized,
+            checked_out,
+            name,
+        });
+    }
+
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(entries)
+}
+
+pub fn init_submodule<P: AsRef<Path>>(repo_path: P, name: &str) -> Result<(), Error> {
+    let repo = Repository::open(repo_path)?;
+    repo.find_submodule(name)?.init(false)
+}
+
+pub fn sync_submodule<P: AsRef<Path>>(repo_path: P, name: &str) -> Result<(), Error> {
+    let repo = Repository::open(repo_path)?;
+    repo.find_submodule(name)?.sync()
+}
+
+pub fn update_submodule<P: AsRef<Path>>(
+    repo_path: P,
+    name: &str,
+    network: &NetworkOptions,
+    token: Option<String>,
+    mut on_progress: impl FnMut(CloneProgress),
+) -> Result<(), AppError> {
+    let repo = Repository::open(repo_path)?;
+    let mut submodule = repo.find_submodule(name)?;
+    if let Some(url) = submodule.url() {
+        validate_transport_url(url, network)?;
+    }
+
+    let mut callbacks = RemoteCallbacks::new();
+    let start = Instant::now();
+    let timeout_secs = network.network_timeout_secs;
+
+    callbacks.credentials(move |_url, username_from_url, _allowed| {
+        if let Some(token) = token.clone() {
+            let username = username_from_url.unwrap_or("git");
+            Cred::userpass_plaintext(username, &token)
+        } else {
+            Cred::default()
+        }
+    });
+
+    callbacks.transfer_progress(move |stats| {
+        on_progress(CloneProgress {
+            received_objects: stats.received_objects(),
+            total_objects: stats.total_objects(),
+            total_deltas: stats.total_deltas(),
+            indexed_deltas: stats.indexed_deltas(),
+            received_bytes: stats.received_bytes(),
+        });
+        if timeout_secs > 0 && start.elapsed().as_secs() >= timeout_secs {
+            return false;
+        }
+        true
+    });
+
+    let mut fetch = FetchOptions::new();
+    fetch.remote_callbacks(callbacks);
+    fetch.proxy_options(configure_proxy_options(network));
+
+    let mut options = SubmoduleUpdateOptions::new();
+    options.fetch(fetch);
+    submodule.update(true, Some(&mut options))?;
+    Ok(())
+}
@@ -8,19 +8,51 @@ use git2::{
 };
 
 use crate::config::NetworkOptions;
+use crate::git::bisect::{
+    BisectStep, BisectVerdict, bisect_status, load_bisect, mark_bisect, reset_bisect, run_bisect,
+    start_bisect,
+};
 use crate::git::branch;
 use crate::git::branch::{
     BranchKind, list_branches, list_tracking_branches, rename_branch, set_upstream,
     unset_upstream,
 };
-use crate::git::discovery::{find_repo_root, is_git_repo, list_submodules, list_worktrees};
-use crate::git::diff::{commit_diff, diff_file, staged_diff, working_tree_diff};
-use crate::git::log::{CommitFilter, read_commit_log};
+use crate::git::discovery::{find_repo_root, is_git_repo, list_submodules};
+use crate::git::commit::{create_annotated_tag, create_commit};
+use crate::git::compare::compare_refs;
+use crate::git::diff::{
+    DiffSide, DiffStatus, commit_diff, commit_path_diff, diff_file, read_diff_side, staged_diff,
+    working_tree_diff,
+};
+use crate::git::lfs::{
+    fetch_lfs_objects, lfs_object_path, lfs_tracked_patterns, list_lfs_files, parse_lfs_pointer,
+    pull_lfs_objects, track_lfs_pattern, untrack_lfs_pattern,
+};
+use crate::git::log::{CommitFilter, CommitInfo, CommitLogCursor, LogEvent, stream_commit_log};
+use crate::git::patch::{
+    ApplyTarget, FileApplyStatus, apply_patch, format_patches, write_mbox, write_patch_series,
+};
+use crate::git::reflog::{
+    ResetMode, list_archived_branches, list_reflog_refs, read_reflog, reset_head_to,
+};
 use crate::git::remote::{
-    PullOutcome, fetch_remote, list_remotes, pull_branch, prune_remotes, push_branch,
+    PullOutcome, PullStrategy, fetch_remote, list_remotes, pull_branch, prune_remotes, push_branch,
+};
+use crate::git::search::{
+    SearchQuery, load_commit_index, search_commit_index, update_commit_index,
+};
+use crate::git::signing::{SignatureState, verify_commit_signature};
+use crate::git::stash::{
+    StashOptions, apply_stash, branch_from_stash, create_stash_with_options,
+    drop_stash, list_stashes, pop_stash, stash_diff,
 };
-use crate::git::stash::{apply_stash, create_stash, drop_stash, list_stashes};
 use crate::git::status::{read_repo_status, read_working_tree_status};
+use crate::git::submodule::{
+    init_submodule, read_submodule_status, sync_submodule, update_submodule,
+};
+use crate::git::worktree::{
+    WorktreeTarget, add_worktree, list_worktrees, lock_worktree, prune_worktrees, unlock_worktree,
+};
 
 fn init_temp_repo() -> (tempfile::TempDir, Repository) {
     let temp_dir = tempfile::tempdir().expect("create temp dir");
@@ -30,6 +62,17 @@ fn init_temp_repo() -> (tempfile::TempDir, Repository) {
     (temp_dir, repo)
 }
 
+fn first_log_page(
+    repo_path: &str,
+    filter: &CommitFilter,
+    limit: usize,
+    include_stats: bool,
+) -> Result<Vec<CommitInfo>, git2::Error> {
+    let repo = Repository::open(repo_path)?;
+    let mut cursor = CommitLogCursor::new(&repo, filter.clone(), include_stats)?;
+    cursor.next_page(limit, |_| true)
+}
+
 fn write_commit(repo: &Repository, path: impl AsRef<Path>, contents: &str, message: &str) -> Oid {
     let file_path = repo.path().parent().unwrap().join(path.as_ref());
     fs::write(&file_path, contents).expect("write file");
@@ -210,7 +253,7 @@ fn commit_logs_respect_filters() {
         search: Some("beta".to_string()),
         ..Default::default()
     };
-    let commits = read_commit_log(
+    let commits = first_log_page(
         repo.path().parent().unwrap().to_str().unwrap(),
         &filter,
         10,
@@ -222,13 +265,153 @@ fn commit_logs_respect_filters() {
     assert!(commits[0].summary.to_lowercase().contains("beta"));
 }
 
+#[test]
+fn commit_log_filters_apply_before_the_limit() {
+    let (_dir, repo) = init_temp_repo();
+    write_commit(&repo, "log.txt", "old", "needle in old history");
+    for idx in 0..5 {
+        write_commit(&repo, "log.txt", &format!("{idx}"), &format!("filler {idx}"));
+    }
+
+    let filter = CommitFilter {
+        search: Some("needle".to_string()),
+        ..Default::default()
+    };
+    let commits = first_log_page(
+        repo.path().parent().unwrap().to_str().unwrap(),
+        &filter,
+        2,
+        false,
+    )
+    .expect("log");
+
+    assert_eq!(commits.len(), 1);
+    assert_eq!(commits[0].summary, "needle in old history");
+}
+
+#[test]
+fn commit_log_keeps_the_commit_scanned_when_progress_stops_a_page() {
+    let (_dir, repo) = init_temp_repo();
+    write_commit(&repo, "log.txt", "base", "commit 0");
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    let tree = head.tree().unwrap();
+    let sig = Signature::now("Tester", "tester@example.com").expect("signature");
+    let mut parent = head;
+    for idx in 1..1_005 {
+        let oid = repo
+            .commit(Some("HEAD"), &sig, &sig, &format!("commit {idx}"), &tree, &[&parent])
+            .expect("commit");
+        parent = repo.find_commit(oid).unwrap();
+    }
+
+    let mut cursor = CommitLogCursor::new(&repo, CommitFilter::default(), false).expect("cursor");
+    let mut seen = cursor.next_page(2_000, |_| false).expect("stopped page");
+    assert_eq!(seen.len(), 999);
+    seen.extend(cursor.next_page(2_000, |_| true).expect("resumed page"));
+
+    assert_eq!(seen.len(), 1_005);
+    let unique: std::collections::HashSet<&str> =
+        seen.iter().map(|commit| commit.id.as_str()).collect();
+    assert_eq!(unique.len(), 1_005);
+}
+
+#[test]
+fn commit_log_streams_pages_on_request() {
+    let (_dir, repo) = init_temp_repo();
+    for idx in 0..5 {
+        write_commit(&repo, "log.txt", &format!("{idx}"), &format!("commit {idx}"));
+    }
+    let repo_path = repo.path().parent().unwrap().to_str().unwrap().to_string();
+
+    let (request_tx, request_rx) = std::sync::mpsc::channel();
+    request_tx.send(2).unwrap();
+    request_tx.send(2).unwrap();
+    request_tx.send(2).unwrap();
+    request_tx.send(2).unwrap();
+    drop(request_tx);
+
+    let mut pages = Vec::new();
+    stream_commit_log(&repo_path, CommitFilter::default(), request_rx, |event| {
+        if let LogEvent::Page {
+            commits, exhausted, ..
+        } = event
+        {
+            pages.push((
+                commits.into_iter().map(|c| c.summary).collect::<Vec<_>>(),
+                exhausted,
+            ));
+        }
+        true
+    })
+    .expect("stream");
+
+    assert_eq!(
+        pages,
+        vec![
+            (vec!["commit 4".to_string(), "commit 3".to_string()], false),
+            (vec!["commit 2".to_string(), "commit 1".to_string()], false),
+            (vec!["commit 0".to_string()], true),
+        ]
+    );
+}
+
+#[test]
+fn file_history_follows_renames() {
+    let (_dir, repo) = init_temp_repo();
+    let workdir = repo.path().parent().unwrap().to_path_buf();
+    let contents = "line one\nline two\nline three\nline four\n";
+    write_commit(&repo, "old.txt", contents, "add old");
+    write_commit(&repo, "other.txt", "unrelated", "unrelated change");
+    fs::remove_file(workdir.join("old.txt")).expect("remove old");
+    let mut index = repo.index().expect("index");
+    index.remove_path(Path::new("old.txt")).expect("unstage old");
+    index.write().expect("write index");
+    write_commit(&repo, "new.txt", contents, "rename to new");
+    let edit = write_commit(
+        &repo,
+        "new.txt",
+        "line one\nline two\nline three\nline 4\n",
+        "edit new",
+    );
+    let repo_path = workdir.to_str().unwrap();
+
+    let mut filter = CommitFilter {
+        path: Some("new.txt".to_string()),
+        ..Default::default()
+    };
+    let unfollowed = first_log_page(repo_path, &filter, 10, false).expect("log");
+    assert_eq!(
+        unfollowed.iter().map(|c| c.summary.as_str()).collect::<Vec<_>>(),
+        vec!["edit new", "rename to new"]
+    );
+
+    filter.follow = true;
+    let followed = first_log_page(repo_path, &filter, 10, false).expect("log");
+    assert_eq!(
+        followed
+            .iter()
+            .map(|c| (c.summary.as_str(), c.path.as_deref().unwrap()))
+            .collect::<Vec<_>>(),
+        vec![
+            ("edit new", "new.txt"),
+            ("rename to new", "new.txt"),
+            ("add old", "old.txt"),
+        ]
+    );
+
+    let diffs = commit_path_diff(repo_path, &edit.to_string(), "new.txt").expect("path diff");
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].path, "new.txt");
+    assert_eq!((diffs[0].additions, diffs[0].deletions), (1, 1));
+}
+
 #[test]
 fn commit_logs_can_include_diff_stats() {
     let (_dir, repo) = init_temp_repo();
     write_commit(&repo, "stats.txt", "one\n", "initial");
     write_commit(&repo, "stats.txt", "one\ntwo\n", "add line");
 
-    let commits = read_commit_log(
+    let commits = first_log_page(
         repo.path().parent().unwrap().to_str().unwrap(),
         &CommitFilter::default(),
         1,
@@ -302,12 +485,67 @@ fn fetch_push_pull_and_prune_work_with_local_remote() {
         .find_reference("refs/remotes/origin/obsolete")
         .is_err());
 
-    let outcome = pull_branch(fetch_dir.path(), "origin", "main", &network, None).expect("pull");
+    let outcome = pull_branch(
+        fetch_dir.path(),
+        "origin",
+        "main",
+        PullStrategy::FastForwardOnly,
+        &network,
+        None,
+        None,
+    )
+    .expect("pull");
     assert_eq!(outcome, PullOutcome::FastForward);
     let head = fetch_repo.head().expect("head");
     assert_eq!(head.target(), Some(commit));
 }
 
+#[test]
+fn pull_merges_diverged_branch_when_strategy_allows() {
+    let (_dir, repo) = init_temp_repo();
+    write_commit(&repo, "shared.txt", "base", "initial");
+    let remote_dir = tempfile::tempdir().expect("create remote dir");
+    Repository::init_bare(remote_dir.path()).expect("init bare");
+    let remote_path = remote_dir.path().to_str().expect("remote path");
+    repo.remote("origin", remote_path).expect("add remote");
+    let network = NetworkOptions::default();
+    let upstream_path = repo.path().parent().unwrap();
+    push_branch(upstream_path, "origin", "main", &network, None).expect("push");
+
+    let (_local_dir, local) = init_temp_repo();
+    let mut config = local.config().expect("config");
+    config.set_str("user.name", "Tester").expect("user name");
+    config.set_str("user.email", "tester@example.com").expect("user email");
+    local.remote("origin", remote_path).expect("add remote");
+    let local_path = local.path().parent().unwrap();
+    pull_branch(local_path, "origin", "main", PullStrategy::FastForwardOnly, &network, None, None)
+        .expect("initial pull");
+
+    write_commit(&repo, "upstream.txt", "upstream", "upstream change");
+    push_branch(upstream_path, "origin", "main", &network, None).expect("push upstream");
+    write_commit(&local, "local.txt", "local", "local change");
+
+    assert!(
+        pull_branch(
+            local_path,
+            "origin",
+            "main",
+            PullStrategy::FastForwardOnly,
+            &network,
+            None,
+            None
+        )
+        .is_err()
+    );
+    let outcome =
+        pull_branch(local_path, "origin", "main", PullStrategy::Merge, &network, None, None)
+            .expect("merge pull");
+    assert_eq!(outcome, PullOutcome::Integrated { conflicts: Vec::new() });
+    let head = local.head().expect("head").peel_to_commit().expect("head commit");
+    assert_eq!(head.parent_count(), 2);
+    assert!(local_path.join("upstream.txt").exists());
+}
+
 #[test]
 fn stashes_round_trip_changes() {
     let (_dir, repo) = init_temp_repo();
@@ -317,7 +555,12 @@ fn stashes_round_trip_changes() {
     let file_path = working_dir.join("stash.txt");
     fs::write(&file_path, "modified").expect("modify file");
 
-    create_stash(working_dir.to_str().unwrap(), "test stash", true).expect("stash create");
+    let options = StashOptions {
+        include_untracked: true,
+        ..StashOptions::default()
+    };
+    create_stash_with_options(working_dir.to_str().unwrap(), "test stash", &options)
+        .expect("stash create");
     let mut checkout = CheckoutBuilder::new();
     repo.checkout_head(Some(&mut checkout.force()))
         .expect("clean checkout");
@@ -416,5 +659,897 @@ fn worktrees_and_submodules_are_listed() {
 
     let worktrees = list_worktrees(repo.path().parent().unwrap()).expect("list worktrees");
     let worktree_path = worktree_dir.to_string_lossy().to_string();
-    assert!(worktrees.iter().any(|path| path == &worktree_path));
+    assert!(worktrees.iter().any(|entry| entry.path == worktree_path));
+}
+
+#[test]
+fn reflog_entries_allow_recovering_reset_commits() {
+    let (_dir, repo) = init_temp_repo();
+    let root = repo.path().parent().unwrap();
+    let first = write_commit(&repo, "reflog.txt", "one", "first");
+    let second = write_commit(&repo, "reflog.txt", "two", "second");
+
+    reset_head_to(root, &first.to_string(), ResetMode::Hard).expect("reset");
+    assert_eq!(repo.head().expect("head").target(), Some(first));
+
+    let refs = list_reflog_refs(root).expect("reflog refs");
+    assert_eq!(refs.first().map(String::as_str), Some("HEAD"));
+    assert!(refs.iter().any(|name| name == "refs/heads/main"));
+
+    let entries = read_reflog(root, "HEAD").expect("head reflog");
+    assert_eq!(entries[0].new_id, first.to_string());
+    let lost = entries
+        .iter()
+        .find(|entry| entry.new_id == second.to_string())
+        .expect("reflog keeps reset commit");
+
+    branch::create_branch(root, "recovered", Some(&lost.new_id)).expect("recover branch");
+    let recovered = repo
+        .find_branch("recovered", BranchType::Local)
+        .expect("recovered branch");
+    assert_eq!(recovered.get().target(), Some(second));
+}
+
+#[test]
+fn archived_branches_are_listed_for_recovery() {
+    let (_dir, repo) = init_temp_repo();
+    let root = repo.path().parent().unwrap();
+    write_commit(&repo, "archive.txt", "base", "base");
+    branch::create_branch(root, "feature/old-work", None).expect("create branch");
+
+    let tag = branch::archive_branch(root, "feature/old-work").expect("archive");
+    let second_tag = branch::archive_branch(root, "feature/old-work").expect("archive again");
+    branch::delete_branch(root, "feature/old-work").expect("delete");
+
+    let archived = list_archived_branches(root).expect("archived branches");
+    assert_eq!(archived.len(), 2);
+    assert!(archived.iter().all(|entry| entry.branch == "feature/old-work"));
+    assert!(archived.iter().any(|entry| entry.tag == tag));
+    assert!(archived.iter().any(|entry| entry.tag == second_tag));
+}
+
+#[test]
+fn stashes_report_metadata_and_preview_diffs() {
+    let (_dir, repo) = init_temp_repo();
+    let working_dir = repo.path().parent().unwrap();
+    let root = working_dir.to_str().unwrap();
+    write_commit(&repo, "stash.txt", "first\n", "initial");
+    fs::write(working_dir.join("stash.txt"), "first\nsecond\n").expect("modify file");
+
+    create_stash_with_options(root, "preview me", &StashOptions::default())
+        .expect("stash create");
+    let stashes = list_stashes(root).expect("list stashes");
+    assert_eq!(stashes.len(), 1);
+    assert_eq!(stashes[0].branch.as_deref(), Some("main"));
+    assert_eq!(stashes[0].id.len(), 40);
+
+    let diffs = stash_diff(root, 0).expect("stash diff");
+    assert_eq!(diffs.len(), 1);
+    assert!(diffs[0].patch.contains("+second"));
+
+    pop_stash(root, 0).expect("pop stash");
+    assert!(list_stashes(root).expect("list stashes").is_empty());
+    let restored = fs::read_to_string(working_dir.join("stash.txt")).expect("read file");
+    assert_eq!(restored, "first\nsecond\n");
+}
+
+#[test]
+fn stashes_can_be_limited_to_paths_and_staged_changes() {
+    let (_dir, repo) = init_temp_repo();
+    let working_dir = repo.path().parent().unwrap();
+    let root = working_dir.to_str().unwrap();
+    write_commit(&repo, "a.txt", "a", "add a");
+    write_commit(&repo, "b.txt", "b", "add b");
+    fs::write(working_dir.join("a.txt"), "a changed").expect("modify a");
+    fs::write(working_dir.join("b.txt"), "b changed").expect("modify b");
+
+    let options = StashOptions {
+        paths: vec!["a.txt".to_string()],
+        ..Default::default()
+    };
+    create_stash_with_options(root, "only a", &options).expect("path stash");
+    assert_eq!(fs::read_to_string(working_dir.join("a.txt")).unwrap(), "a");
+    assert_eq!(fs::read_to_string(working_dir.join("b.txt")).unwrap(), "b changed");
+    let stashes = list_stashes(root).expect("list stashes");
+    assert!(stashes[0].message.contains("only a"));
+
+    let mut index = repo.index().expect("index");
+    index.add_path(Path::new("b.txt")).expect("stage b");
+    index.write().expect("write index");
+    let options = StashOptions {
+        staged_only: true,
+        ..Default::default()
+    };
+    create_stash_with_options(root, "staged b", &options).expect("staged stash");
+    assert_eq!(fs::read_to_string(working_dir.join("b.txt")).unwrap(), "b");
+    assert!(staged_diff(root).expect("staged diff").is_empty());
+
+    let stashes = list_stashes(root).expect("list stashes");
+    assert_eq!(stashes.len(), 2);
+    assert!(stashes[0].message.contains("staged b"));
+    let diffs = stash_diff(root, 0).expect("stash diff");
+    assert_eq!(diffs.len(), 1);
+    assert!(diffs[0].path.ends_with("b.txt"));
+}
+
+#[test]
+fn branches_can_be_created_from_stashes() {
+    let (_dir, repo) = init_temp_repo();
+    let working_dir = repo.path().parent().unwrap();
+    let root = working_dir.to_str().unwrap();
+    let base = write_commit(&repo, "branch.txt", "base", "base");
+    fs::write(working_dir.join("branch.txt"), "stashed").expect("modify file");
+    create_stash_with_options(root, "for branch", &StashOptions::default())
+        .expect("stash create");
+    write_commit(&repo, "other.txt", "later", "later work");
+
+    branch_from_stash(root, 0, "from-stash").expect("branch from stash");
+
+    let head = repo.head().expect("head");
+    assert_eq!(head.shorthand(), Some("from-stash"));
+    assert_eq!(head.target(), Some(base));
+    assert_eq!(
+        fs::read_to_string(working_dir.join("branch.txt")).unwrap(),
+        "stashed"
+    );
+    assert!(list_stashes(root).expect("list stashes").is_empty());
+}
+
+#[test]
+fn worktrees_can_be_created_locked_and_pruned() {
+    let (_dir, repo) = init_temp_repo();
+    let root = repo.path().parent().unwrap();
+    let head = write_commit(&repo, "worktree.txt", "base", "base");
+    let head_commit = repo.find_commit(head).expect("head commit");
+    repo.branch("existing", &head_commit, false)
+        .expect("create branch");
+
+    let parent = tempfile::tempdir().expect("worktree parent");
+    let existing_dir = parent.path().join("existing-tree");
+    let existing = add_worktree(
+        root,
+        &existing_dir,
+        &WorktreeTarget::ExistingBranch("existing".to_string()),
+    )
+    .expect("add existing branch worktree");
+    assert_eq!(existing.branch.as_deref(), Some("existing"));
+    assert_eq!(existing.head, Some(head.to_string()));
+
+    let new_dir = parent.path().join("new-tree");
+    let created = add_worktree(
+        root,
+        &new_dir,
+        &WorktreeTarget::NewBranch {
+            name: "feature/tree".to_string(),
+            start_point: None,
+        },
+    )
+    .expect("add new branch worktree");
+    assert_eq!(created.branch.as_deref(), Some("feature/tree"));
+    assert!(repo.find_branch("feature/tree", BranchType::Local).is_ok());
+
+    let clash = add_worktree(
+        root,
+        &new_dir,
+        &WorktreeTarget::NewBranch {
+            name: "feature/orphan".to_string(),
+            start_point: None,
+        },
+    );
+    assert!(clash.is_err());
+    assert!(repo.find_branch("feature/orphan", BranchType::Local).is_err());
+
+    fs::create_dir(parent.path().join("elsewhere")).expect("second parent");
+    let same_name_dir = parent.path().join("elsewhere").join("new-tree");
+    let same_name = add_worktree(
+        root,
+        &same_name_dir,
+        &WorktreeTarget::NewBranch {
+            name: "feature/same-name".to_string(),
+            start_point: None,
+        },
+    )
+    .expect("add worktree with a taken folder name");
+    assert_eq!(created.name, "new-tree");
+    assert!(same_name.name.starts_with("new-tree-"));
+
+    lock_worktree(root, &created.name, Some("on a USB drive")).expect("lock");
+    let worktrees = list_worktrees(root).expect("list worktrees");
+    assert_eq!(worktrees.len(), 4);
+    assert!(worktrees[0].is_main);
+    assert_eq!(worktrees[0].branch.as_deref(), Some("main"));
+    let locked = worktrees
+        .iter()
+        .find(|entry| entry.name == created.name)
+        .expect("locked worktree");
+    assert!(locked.locked);
+    assert_eq!(locked.lock_reason.as_deref(), Some("on a USB drive"));
+    unlock_worktree(root, &created.name).expect("unlock");
+
+    fs::remove_dir_all(&existing_dir).expect("remove worktree dir");
+    let pruned = prune_worktrees(root).expect("prune");
+    assert_eq!(pruned, vec![existing.name.clone()]);
+    let remaining = list_worktrees(root).expect("list after prune");
+    assert_eq!(remaining.len(), 3);
+    assert!(remaining.iter().all(|entry| !entry.locked));
+}
+
+#[test]
+fn submodules_report_status_and_can_be_updated() {
+    let (sub_dir, sub_repo) = init_temp_repo();
+    let first = write_commit(&sub_repo, "lib.txt", "v1", "lib v1");
+
+    let (_dir, repo) = init_temp_repo();
+    let root = repo.path().parent().unwrap().to_path_buf();
+    write_commit(&repo, "README.md", "parent", "init");
+    let sub_url = sub_dir.path().to_str().expect("submodule url").to_string();
+    let mut submodule = repo
+        .submodule(&sub_url, Path::new("vendor/lib"), true)
+        .expect("add submodule");
+    submodule.clone(None).expect("clone submodule");
+    submodule.add_finalize().expect("finalize submodule");
+    let mut index = repo.index().expect("index");
+    let tree = repo
+        .find_tree(index.write_tree().expect("write tree"))
+        .expect("tree");
+    let sig = Signature::now("Tester", "tester@example.com").expect("signature");
+    let parent = repo.head().expect("head").peel_to_commit().expect("parent");
+    repo.commit(Some("HEAD"), &sig, &sig, "add lib", &tree, &[&parent])
+        .expect("commit submodule");
+
+    let clone_dir = tempfile::tempdir().expect("clone dir");
+    Repository::clone(root.to_str().unwrap(), clone_dir.path()).expect("clone parent");
+
+    let status = read_submodule_status(clone_dir.path()).expect("status");
+    assert_eq!(status.len(), 1);
+    assert_eq!(status[0].path, "vendor/lib");
+    assert!(!status[0].initialized);
+    assert!(!status[0].checked_out);
+    assert_eq!(status[0].recorded_id, Some(first.to_string()));
+
+    init_submodule(clone_dir.path(), "vendor/lib").expect("init");
+    assert!(read_submodule_status(clone_dir.path()).expect("status")[0].initialized);
+
+    update_submodule(
+        clone_dir.path(),
+        "vendor/lib",
+        &NetworkOptions::default(),
+        None,
+        |_| {},
+    )
+    .expect("update");
+    let status = read_submodule_status(clone_dir.path()).expect("status");
+    assert!(status[0].checked_out);
+    assert!(!status[0].out_of_date);
+    assert!(!status[0].dirty);
+    assert_eq!(status[0].checked_out_id, Some(first.to_string()));
+
+    let checkout = clone_dir.path().join("vendor/lib");
+    let checkout_repo = Repository::open(&checkout).expect("open submodule");
+    write_commit(&checkout_repo, "lib.txt", "v2", "lib v2");
+    fs::write(checkout.join("scratch.txt"), "scratch").expect("write scratch");
+    let status = read_submodule_status(clone_dir.path()).expect("status");
+    assert!(status[0].out_of_date);
+    assert!(status[0].dirty);
+
+    sync_submodule(clone_dir.path(), "vendor/lib").expect("sync");
+    let origin = checkout_repo.find_remote("origin").expect("origin");
+    assert_eq!(origin.url(), Some(sub_url.as_str()));
+}
+
+#[test]
+fn refs_can_be_compared_in_both_directions() {
+    let (_dir, repo) = init_temp_repo();
+    let root = repo.path().parent().unwrap().to_str().unwrap().to_string();
+    let base = write_commit(&repo, "shared.txt", "base\n", "base");
+    let base_commit = repo.find_commit(base).expect("base commit");
+    repo.branch("feature", &base_commit, false)
+        .expect("create feature");
+
+    let main_only = write_commit(&repo, "main.txt", "main\n", "main work");
+    repo.set_head("refs/heads/feature").expect("switch to feature");
+    repo.checkout_head(Some(CheckoutBuilder::new().force()))
+        .expect("checkout feature");
+    let feature_only = write_commit(&repo, "shared.txt", "base\nfeature\n", "feature work");
+    repo.tag_lightweight("v-feature", repo.find_commit(feature_only).unwrap().as_object(), false)
+        .expect("tag feature");
+
+    let comparison = compare_refs(&root, "main", "v-feature", 50).expect("compare");
+    assert_eq!(
+        comparison.merge_base.as_ref().map(|commit| commit.id.clone()),
+        Some(base.to_string())
+    );
+    assert_eq!(comparison.only_in_base.len(), 1);
+    assert_eq!(comparison.only_in_base[0].id, main_only.to_string());
+    assert_eq!(comparison.only_in_target.len(), 1);
+    assert_eq!(comparison.only_in_target[0].id, feature_only.to_string());
+
+    let paths: Vec<&str> = comparison.files.iter().map(|f| f.path.as_str()).collect();
+    assert_eq!(paths, vec!["main.txt", "shared.txt"]);
+    assert_eq!(comparison.diff.files_changed, 2);
+    let shared = &comparison.files[1];
+    assert_eq!(shared.additions, 1);
+    assert!(shared.patch.contains("+feature"));
+}
+
+#[test]
+fn commits_and_tags_are_signed_with_ssh_keys() {
+    let (dir, repo) = init_temp_repo();
+    let root = dir.path();
+    let key_dir = tempfile::tempdir().expect("key dir");
+    let key_path = key_dir.path().join("signing_key");
+    let status = std::process::Command::new("ssh-keygen")
+        .args(["-q", "-t", "ed25519", "-N", "", "-C", "tester", "-f"])
+        .arg(&key_path)
+        .status()
+        .expect("run ssh-keygen");
+    assert!(status.success());
+
+    let mut config = repo.config().expect("config");
+    config.set_str("user.name", "Tester").expect("name");
+    config.set_str("user.email", "tester@example.com").expect("email");
+    config.set_bool("commit.gpgsign", true).expect("gpgsign");
+    config.set_bool("tag.gpgsign", true).expect("tag gpgsign");
+    config.set_str("gpg.format", "ssh").expect("format");
+    config
+        .set_str("user.signingkey", key_path.to_str().unwrap())
+        .expect("signing key");
+
+    fs::write(root.join("signed.txt"), "signed").expect("write file");
+    let mut index = repo.index().expect("index");
+    index.add_path(Path::new("signed.txt")).expect("add");
+    index.write().expect("write index");
+
+    let commit = create_commit(root, "Signed commit").expect("create commit");
+    assert!(commit.signed);
+    assert_eq!(
+        repo.head().unwrap().target().map(|oid| oid.to_string()),
+        Some(commit.id.clone())
+    );
+    let (signature, _) = repo
+        .extract_signature(&Oid::from_str(&commit.id).unwrap(), None)
+        .expect("signature");
+    assert!(signature.as_str().unwrap().contains("BEGIN SSH SIGNATURE"));
+
+    let state = verify_commit_signature(root, &commit.id).expect("verify");
+    assert!(matches!(state, SignatureState::Unknown(_)), "{state:?}");
+
+    let public_key = fs::read_to_string(key_path.with_extension("pub")).expect("public key");
+    let allowed = key_dir.path().join("allowed_signers");
+    fs::write(&allowed, format!("tester@example.com {public_key}")).expect("allowed signers");
+    config
+        .set_str("gpg.ssh.allowedSignersFile", allowed.to_str().unwrap())
+        .expect("allowed signers config");
+    let state = verify_commit_signature(root, &commit.id).expect("verify");
+    assert!(matches!(state, SignatureState::Verified(_)), "{state:?}");
+
+    config.set_bool("commit.gpgsign", false).expect("disable signing");
+    fs::write(root.join("plain.txt"), "plain").expect("write file");
+    index.add_path(Path::new("plain.txt")).expect("add");
+    index.write().expect("write index");
+    let plain = create_commit(root, "Plain commit").expect("plain commit");
+    assert!(!plain.signed);
+    assert_eq!(
+        verify_commit_signature(root, &plain.id).expect("verify"),
+        SignatureState::Unsigned
+    );
+
+    let tag = create_annotated_tag(root, "v1.0", &commit.id, "Release 1.0").expect("tag");
+    assert!(tag.signed);
+    let tag_object = repo
+        .find_tag(Oid::from_str(&tag.id).unwrap())
+        .expect("find tag");
+    assert_eq!(tag_object.name(), Some("v1.0"));
+    assert_eq!(tag_object.target_id().to_string(), commit.id);
+    assert!(tag_object.message().unwrap().contains("BEGIN SSH SIGNATURE"));
+    assert!(repo.find_reference("refs/tags/v1.0").is_ok());
+}
+
+// Stand-in for gpg: signs with a fixed armour block and verifies as a good
+// signature with whatever trust level is written next to the script.
+#[cfg(unix)]
+#[test]
+fn gpg_signatures_from_untrusted_keys_are_not_verified() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let (dir, repo) = init_temp_repo();
+    let root = dir.path();
+    let gpg_dir = tempfile::tempdir().expect("gpg dir");
+    let program = gpg_dir.path().join("fake-gpg");
+    let trust = gpg_dir.path().join("trust");
+    fs::write(
+        &program,
+        format!(
+            "#!/bin/sh\ncat > /dev/null\ncase \"$*\" in\n*--verify*)\n\
+             echo '[GNUPG:] GOODSIG 0123456789ABCDEF Tester <tester@example.com>'\n\
+             echo \"[GNUPG:] $(cat '{}') 0 pgp\" ;;\n\
+             *) printf -- '-----BEGIN PGP SIGNATURE-----\\n\\nZmFrZQ==\\n-----END PGP SIGNATURE-----\\n' ;;\n\
+             esac\n",
+            trust.display()
+        ),
+    )
+    .expect("write fake gpg");
+    fs::set_permissions(&program, fs::Permissions::from_mode(0o755)).expect("make executable");
+
+    let mut config = repo.config().expect("config");
+    config.set_str("user.name", "Tester").expect("name");
+    config.set_str("user.email", "tester@example.com").expect("email");
+    config.set_bool("commit.gpgsign", true).expect("gpgsign");
+    config
+        .set_str("gpg.program", program.to_str().unwrap())
+        .expect("gpg program");
+
+    fs::write(root.join("signed.txt"), "signed").expect("write file");
+    let mut index = repo.index().expect("index");
+    index.add_path(Path::new("signed.txt")).expect("add");
+    index.write().expect("write index");
+    let commit = create_commit(root, "Signed commit").expect("create commit");
+    assert!(commit.signed);
+
+    fs::write(&trust, "TRUST_UNDEFINED").expect("write trust");
+    let state = verify_commit_signature(root, &commit.id).expect("verify");
+    assert!(matches!(state, SignatureState::Untrusted(_)), "{state:?}");
+
+    fs::write(&trust, "TRUST_FULLY").expect("write trust");
+    let state = verify_commit_signature(root, &commit.id).expect("verify");
+    assert!(matches!(state, SignatureState::Verified(_)), "{state:?}");
+}
+
+#[test]
+fn commit_index_supports_field_regex_and_pickaxe_queries() {
+    let (_dir, repo) = init_temp_repo();
+    let index_dir = tempfile::tempdir().expect("index dir");
+    let repo_path = repo.path().parent().unwrap().to_str().unwrap().to_string();
+    fs::create_dir_all(repo.path().parent().unwrap().join("src")).expect("src dir");
+    fs::create_dir_all(repo.path().parent().unwrap().join("docs")).expect("docs dir");
+    write_commit(&repo, "src/lib.rs", "fn alpha() {}\n", "Add alpha");
+    write_commit(&repo, "docs/guide.md", "Guide\n", "Write the guide");
+
+    let mut index = load_commit_index(index_dir.path(), &repo_path);
+    assert_eq!(
+        update_commit_index(index_dir.path(), &repo_path, &mut index).expect("index"),
+        2
+    );
+
+    write_commit(
+        &repo,
+        "src/lib.rs",
+        "fn alpha() {}\nfn beta() {}\n",
+        "Fix beta handling",
+    );
+    let mut reloaded = load_commit_index(index_dir.path(), &repo_path);
+    assert_eq!(reloaded.commits.len(), 2);
+    assert_eq!(
+        update_commit_index(index_dir.path(), &repo_path, &mut reloaded).expect("update"),
+        1
+    );
+    assert_eq!(
+        update_commit_index(index_dir.path(), &repo_path, &mut reloaded).expect("noop"),
+        0
+    );
+
+    let search = |query: &str| -> Vec<String> {
+        let query = SearchQuery::parse(query).expect("query");
+        search_commit_index(&repo_path, &reloaded, &query, 10)
+            .expect("search")
+            .into_iter()
+            .map(|commit| commit.summary)
+            .collect()
+    };
+
+    assert_eq!(search("path:src"), vec!["Fix beta handling", "Add alpha"]);
+    assert_eq!(search("path:guide.md author:tester"), vec!["Write the guide"]);
+    assert_eq!(search("re:^(Fix|Write)"), vec!["Fix beta handling", "Write the guide"]);
+    assert_eq!(search("-Sbeta()"), vec!["Fix beta handling"]);
+    assert_eq!(search("\"pickaxe:fn alpha\""), vec!["Add alpha"]);
+    assert!(search("before:2000-01-01").is_empty());
+    assert!(SearchQuery::parse("re:(").is_err());
+}
+
+#[test]
+fn commit_diffs_report_renames_modes_and_binary_sizes() {
+    let (_dir, repo) = init_temp_repo();
+    let workdir = repo.path().parent().unwrap().to_path_buf();
+    let contents = "one\ntwo\nthree\nfour\nfive\n";
+    write_commit(&repo, "before.txt", contents, "add before");
+    write_commit(&repo, "tool.sh", "echo hi\n", "add tool");
+
+    fs::remove_file(workdir.join("before.txt")).expect("remove before");
+    let mut index = repo.index().expect("index");
+    index.remove_path(Path::new("before.txt")).expect("unstage");
+    index.write().expect("write index");
+    fs::write(workdir.join("after.txt"), "one\ntwo\nthree\nfour\nfive!\n").expect("write");
+    index.add_path(Path::new("after.txt")).expect("add after");
+    let mut tool = index.get_path(Path::new("tool.sh"), 0).expect("tool entry");
+    tool.mode = 0o100755;
+    index.add(&tool).expect("chmod tool");
+    index.write().expect("write index");
+    let commit = write_commit(&repo, "image.bin", "\0\u{1}\u{2}binary", "rename and chmod");
+
+    let diffs = commit_diff(workdir.to_str().unwrap(), &commit.to_string()).expect("diff");
+
+    let renamed = diffs.iter().find(|d| d.path == "after.txt").expect("rename");
+    assert_eq!(renamed.status, DiffStatus::Renamed);
+    assert_eq!(renamed.old_path.as_deref(), Some("before.txt"));
+    assert!(renamed.similarity.is_some_and(|score| score > 50));
+    assert!(!diffs.iter().any(|d| d.path == "before.txt"));
+
+    let chmod = diffs.iter().find(|d| d.path == "tool.sh").expect("chmod");
+    assert!(chmod.mode_changed());
+    assert_eq!(chmod.new_mode, Some(0o100755));
+
+    let binary = diffs.iter().find(|d| d.path == "image.bin").expect("binary");
+    assert_eq!(binary.status, DiffStatus::Added);
+    assert!(binary.binary);
+    assert_eq!(binary.new_size, Some(9));
+    assert_eq!(binary.patch, "Binary file change\n");
+}
+
+#[test]
+fn read_diff_side_returns_blob_and_working_tree_bytes() {
+    let (_dir, repo) = init_temp_repo();
+    let workdir = repo.path().parent().unwrap().to_path_buf();
+    let repo_path = workdir.to_str().unwrap();
+    let added = write_commit(&repo, "logo.png", "\0png-v1", "add logo");
+    let changed = write_commit(&repo, "logo.png", "\0png-v2!", "update logo");
+
+    let diffs = commit_diff(repo_path, &added.to_string()).expect("added diff");
+    let logo = diffs.iter().find(|d| d.path == "logo.png").expect("logo");
+    assert_eq!(read_diff_side(repo_path, logo, DiffSide::Old).expect("old"), None);
+    assert_eq!(
+        read_diff_side(repo_path, logo, DiffSide::New).expect("new"),
+        Some(b"\0png-v1".to_vec())
+    );
+
+    let diffs = commit_diff(repo_path, &changed.to_string()).expect("changed diff");
+    let logo = diffs.iter().find(|d| d.path == "logo.png").expect("logo");
+    assert_eq!(
+        read_diff_side(repo_path, logo, DiffSide::Old).expect("old"),
+        Some(b"\0png-v1".to_vec())
+    );
+    assert_eq!(
+        read_diff_side(repo_path, logo, DiffSide::New).expect("new"),
+        Some(b"\0png-v2!".to_vec())
+    );
+
+    fs::write(workdir.join("logo.png"), b"\0png-v3!!").expect("edit logo");
+    let diffs = working_tree_diff(repo_path).expect("workdir diff");
+    let logo = diffs.iter().find(|d| d.path == "logo.png").expect("logo");
+    assert!(logo.binary);
+    assert_eq!(
+        read_diff_side(repo_path, logo, DiffSide::Old).expect("old"),
+        Some(b"\0png-v2!".to_vec())
+    );
+    assert_eq!(
+        read_diff_side(repo_path, logo, DiffSide::New).expect("new"),
+        Some(b"\0png-v3!!".to_vec())
+    );
+}
+
+#[test]
+fn patches_export_as_mbox_and_apply_with_conflict_report() {
+    let base = "one\ntwo\nthree\nfour\nfive\nsix\nseven\n";
+    let (_source_dir, source) = init_temp_repo();
+    write_commit(&source, "notes.txt", base, "add notes");
+    let first = write_commit(
+        &source,
+        "notes.txt",
+        "ONE\ntwo\nthree\nfour\nfive\nsix\nseven\n",
+        "Shout the first line",
+    );
+    let second = write_commit(
+        &source,
+        "notes.txt",
+        "ONE\ntwo\nthree\nfour\nfive\nsix\nSEVEN\n",
+        "Shout the last line\n\nKeeps the middle quiet.",
+    );
+    let source_path = source.path().parent().unwrap().to_str().unwrap().to_string();
+    let ids = vec![first.to_string(), second.to_string()];
+
+    let patches = format_patches(&source_path, &ids).expect("format patches");
+    assert_eq!(patches[0].file_name, "0001-Shout-the-first-line.patch");
+    assert!(patches[0].contents.starts_with(&format!("From {first} ")));
+    assert!(patches[1].contents.contains("Subject: [PATCH 2/2] Shout the last line"));
+    assert!(patches[1].contents.contains("Keeps the middle quiet."));
+
+    let export_dir = tempfile::tempdir().expect("export dir");
+    let written = write_patch_series(&source_path, &ids, export_dir.path().join("series"))
+        .expect("write series");
+    assert_eq!(written.len(), 2);
+    let mbox_path = export_dir.path().join("series.mbox");
+    write_mbox(&source_path, &ids, &mbox_path).expect("write mbox");
+    let mbox = fs::read(&mbox_path).expect("read mbox");
+
+    let (_target_dir, target) = init_temp_repo();
+    write_commit(&target, "notes.txt", base, "add notes");
+    let target_workdir = target.path().parent().unwrap().to_path_buf();
+    let target_path = target_workdir.to_str().unwrap();
+
+    let dry_run = apply_patch(target_path, &mbox, ApplyTarget::WorkingTree, true).expect("check");
+    assert_eq!((dry_run.patches, dry_run.applied), (2, 0));
+    assert_eq!(dry_run.files[0].status, FileApplyStatus::Clean);
+    assert_eq!(dry_run.files[1].status, FileApplyStatus::Unchecked);
+    assert_eq!(dry_run.files[1].subject.as_deref(), Some("Shout the last line"));
+    assert_eq!(fs::read_to_string(target_workdir.join("notes.txt")).unwrap(), base);
+
+    let applied = apply_patch(target_path, &mbox, ApplyTarget::Both, false).expect("apply");
+    assert_eq!(applied.applied, 2);
+    assert!(!applied.has_conflicts());
+    assert_eq!(
+        fs::read_to_string(target_workdir.join("notes.txt")).unwrap(),
+        "ONE\ntwo\nthree\nfour\nfive\nsix\nSEVEN\n"
+    );
+    let mut index = target.index().expect("index");
+    index.read(true).expect("reload index");
+    let staged = index.get_path(Path::new("notes.txt"), 0).expect("staged notes");
+    let committed = target.head().unwrap().peel_to_tree().unwrap();
+    assert_ne!(staged.id, committed.get_path(Path::new("notes.txt")).unwrap().id());
+
+    let (_diverged_dir, diverged) = init_temp_repo();
+    let diverged_base = "uno\ntwo\nthree\nfour\nfive\nsix\nseven\n";
+    write_commit(&diverged, "notes.txt", diverged_base, "add notes");
+    let diverged_path = diverged.path().parent().unwrap().to_str().unwrap().to_string();
+    let patch = patches[0].contents.as_bytes();
+    let conflicted =
+        apply_patch(&diverged_path, patch, ApplyTarget::Index, false).expect("apply conflict");
+    assert_eq!(conflicted.applied, 0);
+    let conflicts: Vec<_> = conflicted.conflicts().collect();
+    assert_eq!(conflicts.len(), 1);
+    assert_eq!(conflicts[0].path, "notes.txt");
+}
+
+#[test]
+fn bisect_finds_first_bad_commit_and_restores_head() {
+    let (_dir, repo) = init_temp_repo();
+    let workdir = repo.path().parent().unwrap().to_path_buf();
+    let repo_path = workdir.to_str().unwrap();
+    let commits: Vec<Oid> = (0..8)
+        .map(|n| {
+            let status = if n >= 5 { "broken" } else { "ok" };
+            write_commit(&repo, "status.txt", &format!("{status}\n{n}\n"), &format!("step {n}"))
+        })
+        .collect();
+    let first_bad = commits[5].to_string();
+
+    assert!(load_bisect(repo_path).expect("load").is_none());
+    start_bisect(repo_path).expect("start");
+    assert!(start_bisect(repo_path).is_err());
+    let (_, step) = mark_bisect(repo_path, "HEAD", BisectVerdict::Bad).expect("mark bad");
+    assert_eq!(step, BisectStep::NeedsMarks);
+    let (session, step) =
+        mark_bisect(repo_path, &commits[0].to_string(), BisectVerdict::Good).expect("mark good");
+    let BisectStep::Testing { commit, remaining, steps } = step.clone() else {
+        panic!("expected a commit to test, got {step:?}");
+    };
+    assert_eq!((remaining, steps), (6, 3));
+    assert_eq!(session.current.as_deref(), Some(commit.as_str()));
+    assert_eq!(repo.head().unwrap().target().unwrap().to_string(), commit);
+    assert!(repo.head_detached().unwrap());
+
+    let mut step = step;
+    while let BisectStep::Testing { commit, .. } = &step {
+        let contents = fs::read_to_string(workdir.join("status.txt")).expect("read status");
+        let verdict = if contents.starts_with("broken") {
+            BisectVerdict::Bad
+        } else {
+            BisectVerdict::Good
+        };
+        step = mark_bisect(repo_path, commit, verdict).expect("mark").1;
+    }
+    assert_eq!(step, BisectStep::Found(first_bad.clone()));
+    assert_eq!(bisect_status(repo_path).expect("status"), BisectStep::Found(first_bad.clone()));
+
+    reset_bisect(repo_path).expect("reset");
+    assert!(load_bisect(repo_path).expect("load").is_none());
+    assert_eq!(repo.head().unwrap().shorthand(), Some("main"));
+    assert_eq!(repo.head().unwrap().target().unwrap(), commits[7]);
+
+    start_bisect(repo_path).expect("restart");
+    mark_bisect(repo_path, "HEAD", BisectVerdict::Bad).expect("mark bad");
+    mark_bisect(repo_path, &commits[0].to_string(), BisectVerdict::Good).expect("mark good");
+    let mut visited = 0;
+    let step = run_bisect(repo_path, "grep -qx ok status.txt", |_| {
+        visited += 1;
+        true
+    })
+    .expect("run bisect");
+    assert_eq!(step, BisectStep::Found(first_bad));
+    assert!(visited >= 2);
+    reset_bisect(repo_path).expect("reset after run");
+}
+
+// Minimal Git LFS server: answers one batch request and then serves the
+// object it handed out, from a second port when `separate_storage` is set.
+// Returns the base URL and the request lines it saw.
+fn start_lfs_server(
+    content: &'static [u8],
+    separate_storage: bool,
+) -> (String, std::sync::mpsc::Receiver<String>) {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("bind lfs server");
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let object_url = if separate_storage {
+        let storage = std::net::TcpListener::bind("127.0.0.1:0").expect("bind storage server");
+        let object_url = format!("http://{}/objects/content", storage.local_addr().unwrap());
+        let sender = sender.clone();
+        std::thread::spawn(move || {
+            if let Some(Ok(stream)) = storage.incoming().next() {
+                serve_lfs_request(stream, "", content, &sender);
+            }
+        });
+        object_url
+    } else {
+        format!("{base}/objects/content")
+    };
+    let connections = if separate_storage { 1 } else { 2 };
+    std::thread::spawn(move || {
+        for stream in listener.incoming().take(connections) {
+            let Ok(stream) = stream else { break };
+            serve_lfs_request(stream, &object_url, content, &sender);
+        }
+    });
+    (base, receiver)
+}
+
+fn serve_lfs_request(
+    mut stream: std::net::TcpStream,
+    object_url: &str,
+    content: &[u8],
+    sender: &std::sync::mpsc::Sender<String>,
+) {
+    use std::io::{BufRead, BufReader, Read, Write};
+
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).unwrap();
+    let mut content_length = 0;
+    let mut authorized = false;
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header).unwrap();
+        if header.trim().is_empty() {
+            break;
+        }
+        let lower = header.to_ascii_lowercase();
+        if let Some(value) = lower.strip_prefix("content-length:") {
+            content_length = value.trim().parse().unwrap();
+        }
+        authorized |= lower.starts_with("authorization: basic");
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).unwrap();
+
+    let (content_type, payload) = if request_line.starts_with("POST") {
+        let request: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let object = &request["objects"][0];
+        let response = serde_json::json!({
+            "transfer": "basic",
+            "objects": [{
+                "oid": object["oid"],
+                "size": object["size"],
+                "actions": { "download": { "href": object_url } }
+            }]
+        });
+        ("application/vnd.git-lfs+json", response.to_string().into_bytes())
+    } else {
+        ("application/octet-stream", content.to_vec())
+    };
+    let _ = sender.send(format!("{} auth={authorized}", request_line.trim()));
+    let header = format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        payload.len()
+    );
+    stream.write_all(header.as_bytes()).unwrap();
+    stream.write_all(&payload).unwrap();
+}
+
+#[test]
+fn lfs_pointers_are_tracked_fetched_and_checked_out() {
+    use sha2::{Digest, Sha256};
+
+    const CONTENT: &[u8] = b"large binary asset\n";
+    let (temp_dir, repo) = init_temp_repo();
+    let repo_path = temp_dir.path().to_str().unwrap();
+
+    assert!(track_lfs_pattern(repo_path, "*.bin").expect("track pattern"));
+    assert!(!track_lfs_pattern(repo_path, "*.bin").expect("track twice"));
+    assert!(track_lfs_pattern(repo_path, "*.psd").expect("track second pattern"));
+    assert!(untrack_lfs_pattern(repo_path, "*.psd").expect("untrack pattern"));
+    assert_eq!(lfs_tracked_patterns(repo_path).unwrap(), vec!["*.bin"]);
+
+    let oid: String = Sha256::digest(CONTENT)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    let pointer = format!(
+        "version https://git-lfs.github.com/spec/v1\noid sha256:{oid}\nsize {}\n",
+        CONTENT.len()
+    );
+    assert_eq!(
+        parse_lfs_pointer(pointer.as_bytes()).map(|pointer| pointer.size),
+        Some(CONTENT.len() as u64)
+    );
+    let attributes = fs::read_to_string(temp_dir.path().join(".gitattributes")).unwrap();
+    write_commit(&repo, ".gitattributes", &attributes, "track bin");
+    let commit = write_commit(&repo, "asset.bin", &pointer, "add asset");
+
+    let diff = commit_diff(repo_path, &commit.to_string()).expect("commit diff");
+    let asset = diff.iter().find(|file| file.path == "asset.bin").unwrap();
+    assert!(asset.lfs);
+    assert_eq!(asset.new_size, Some(CONTENT.len() as u64));
+
+    let files = list_lfs_files(repo_path).expect("list lfs files");
+    assert_eq!(files.len(), 1);
+    assert!(!files[0].downloaded);
+
+    let (base, requests) = start_lfs_server(CONTENT, false);
+    repo.remote("origin", &format!("{base}/repo.git")).unwrap();
+    let network = NetworkOptions {
+        network_timeout_secs: 5,
+        http_proxy: String::new(),
+        https_proxy: String::new(),
+        use_https: false,
+        allow_ssh: true,
+    };
+    let summary = pull_lfs_objects(repo_path, "origin", &network, Some("secret".to_string()))
+        .expect("pull lfs objects");
+    assert_eq!(summary.downloaded, 1);
+    assert_eq!(summary.checked_out, 1);
+    assert!(summary.failed.is_empty());
+
+    let seen: Vec<String> = requests.try_iter().collect();
+    assert_eq!(
+        seen,
+        vec![
+            "POST /repo.git/info/lfs/objects/batch HTTP/1.1 auth=true".to_string(),
+            "GET /objects/content HTTP/1.1 auth=true".to_string(),
+        ]
+    );
+    assert_eq!(fs::read(temp_dir.path().join("asset.bin")).unwrap(), CONTENT);
+    assert!(list_lfs_files(repo_path).unwrap()[0].downloaded);
+    let status = read_working_tree_status(repo_path).expect("status");
+    assert!(status.unstaged.is_empty(), "{status:?}");
+}
+
+#[test]
+fn lfs_credentials_are_not_sent_to_other_origins() {
+    use sha2::{Digest, Sha256};
+
+    const CONTENT: &[u8] = b"asset kept in object storage\n";
+    let (temp_dir, repo) = init_temp_repo();
+    let repo_path = temp_dir.path().to_str().unwrap();
+
+    let oid: String = Sha256::digest(CONTENT)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    let pointer = format!(
+        "version https://git-lfs.github.com/spec/v1\noid sha256:{oid}\nsize {}\n",
+        CONTENT.len()
+    );
+    write_commit(&repo, "asset.bin", &pointer, "add asset");
+
+    let (base, requests) = start_lfs_server(CONTENT, true);
+    repo.remote("origin", &format!("{base}/repo.git")).unwrap();
+    let network = NetworkOptions {
+        network_timeout_secs: 5,
+        http_proxy: String::new(),
+        https_proxy: String::new(),
+        use_https: false,
+        allow_ssh: true,
+    };
+    let summary = fetch_lfs_objects(repo_path, "origin", &network, Some("secret".to_string()))
+        .expect("fetch lfs objects");
+    assert_eq!(summary.downloaded, 1);
+    assert!(summary.failed.is_empty());
+
+    let seen: Vec<String> = requests.try_iter().collect();
+    assert_eq!(
+        seen,
+        vec![
+            "POST /repo.git/info/lfs/objects/batch HTTP/1.1 auth=true".to_string(),
+            "GET /objects/content HTTP/1.1 auth=false".to_string(),
+        ]
+    );
+    assert_eq!(fs::read(lfs_object_path(&repo, &oid)).unwrap(), CONTENT);
 }
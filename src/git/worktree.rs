@@ -0,0 +1,199 @@
+use std::path::{Path, PathBuf};
+
+use git2::{
+    BranchType, Error, Repository, Worktree, WorktreeAddOptions, WorktreeLockStatus,
+    WorktreePruneOptions,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorktreeEntry {
+    pub name: String,
+    pub path: String,
+    pub branch: Option<String>,
+    pub head: Option<String>,
+    pub is_main: bool,
+    pub locked: bool,
+    pub lock_reason: Option<String>,
+    pub prunable: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WorktreeTarget {
+    ExistingBranch(String),
+    NewBranch {
+        name: String,
+        start_point: Option<String>,
+    },
+}
+
+pub fn list_worktrees<P: AsRef<Path>>(repo_path: P) -> Result<Vec<WorktreeEntry>, Error> {
+    let repo = Repository::open(repo_path)?;
+    let mut entries = Vec::new();
+
+    if let Some(workdir) = main_workdir(&repo) {
+        let (branch, head) = head_details(&repo);
+        entries.push(WorktreeEntry {
+            name: workdir
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| workdir.to_string_lossy().to_string()),
+            path: workdir.to_string_lossy().to_string(),
+            branch,
+            head,
+            is_main: true,
+            locked: false,
+            lock_reason: None,
+            prunable: false,
+        });
+    }
+
+    let mut linked = Vec::new();
+    for name in repo.worktrees()?.iter().flatten() {
+        let Ok(worktree) = repo.find_worktree(name) else {
+            continue;
+        };
+        linked.push(worktree_entry(name, &worktree)?);
+    }
+    linked.sort_by(|a, b| a.path.cmp(&b.path));
+    entries.extend(linked);
+
+    Ok(entries)
+}
+
+pub fn add_worktree<P: AsRef<Path>>(
+    repo_path: P,
+    destination: &Path,
+    target: &WorktreeTarget,
+) -> Result<WorktreeEntry, Error> {
+    let repo = Repository::open(repo_path)?;
+    let (branch_name, created) = match target {
+        WorktreeTarget::ExistingBranch(name) => (name.clone(), false),
+        WorktreeTarget::NewBranch { name, start_point } => {
+            let commit = match start_point {
+                Some(start_point) => repo.revparse_single(start_point)?.peel_to_commit()?,
+                None => repo.head()?.peel_to_commit()?,
+            };
+            repo.branch(name, &commit, false)?;
+            (name.clone(), true)
+        }
+    };
+
+    let mut branch = repo.find_branch(&branch_name, BranchType::Local)?;
+    let name = worktree_name(&repo, destination, &branch_name);
+    let mut options = WorktreeAddOptions::new();
+    options.reference(Some(branch.get()));
+    let worktree = match repo.worktree(&name, destination, Some(&options)) {
+        Ok(worktree) => worktree,
+        Err(err) => {
+            // Don't leave behind a branch that was only made for this worktree.
+            if created {
+                let _ = branch.delete();
+            }
+            return Err(err);
+        }
+    };
+
+    worktree_entry(&name, &worktree)
+}
+
+pub fn lock_worktree<P: AsRef<Path>>(
+    repo_path: P,
+    name: &str,
+    reason: Option<&str>,
+) -> Result<(), Error> {
+    let repo = Repository::open(repo_path)?;
+    repo.find_worktree(name)?.lock(reason)
+}
+
+pub fn unlock_worktree<P: AsRef<Path>>(repo_path: P, name: &str) -> Result<(), Error> {
+    let repo = Repository::open(repo_path)?;
+    repo.find_worktree(name)?.unlock()
+}
+
+pub fn prune_worktrees<P: AsRef<Path>>(repo_path: P) -> Result<Vec<String>, Error> {
+    let repo = Repository::open(repo_path)?;
+    let mut pruned = Vec::new();
+
+    for name in repo.worktrees()?.iter().flatten() {
+        let Ok(worktree) = repo.find_worktree(name) else {
+            continue;
+        };
+        if worktree.is_prunable(None)? {
+            worktree.prune(Some(&mut WorktreePruneOptions::new()))?;
+            pruned.push(name.to_string());
+        }
+    }
+
+    pruned.sort();
+    Ok(pruned)
+}
+
+fn worktree_entry(name: &str, worktree: &Worktree) -> Result<WorktreeEntry, Error> {
+    let (locked, lock_reason) = match worktree.is_locked()? {
+        WorktreeLockStatus::Unlocked => (false, None),
+        WorktreeLockStatus::Locked(reason) => (true, reason.filter(|r| !r.trim().is_empty())),
+    };
+    let (branch, head) = match Repository::open_from_worktree(worktree) {
+        Ok(repo) => head_details(&repo),
+        Err(_) => (None, None),
+    };
+
+    Ok(WorktreeEntry {
+        name: name.to_string(),
+        path: worktree.path().to_string_lossy().to_string(),
+        branch,
+        head,
+        is_main: false,
+        locked,
+        lock_reason,
+        prunable: worktree.is_prunable(None)?,
+    })
+}
+
+fn head_details(repo: &Repository) -> (Option<String>, Option<String>) {
+    match repo.head() {
+        Ok(head) => {
+            let branch = head
+                .is_branch()
+                .then(|| head.shorthand().map(str::to_string))
+                .flatten();
+            (branch, head.target().map(|oid| oid.to_string()))
+        }
+        Err(_) => (None, None),
+    }
+}
+
+fn main_workdir(repo: &Repository) -> Option<PathBuf> {
+    if !repo.is_worktree() {
+        return repo.workdir().map(Path::to_path_buf);
+    }
+    // Linked worktrees keep their git dir under `<common>/.git/worktrees/<name>`.
+    let common_dir = repo.path().parent()?.parent()?;
+    let main = Repository::open(common_dir).ok()?;
+    main.workdir().map(Path::to_path_buf)
+}
+
+// Git keeps each worktree under `.git/worktrees/<name>`, so destinations that
+// share a folder name get a numeric suffix.
+fn worktree_name(repo: &Repository, destination: &Path, branch: &str) -> String {
+    let base = destination
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| branch.replace('/', "-"));
+    let common_dir = if repo.is_worktree() {
+        repo.path().parent().and_then(Path::parent)
+    } else {
+        Some(repo.path())
+    };
+    let Some(admin) = common_dir.map(|dir| dir.join("worktrees")) else {
+        return base;
+    };
+    let mut name = base.clone();
+    let mut suffix = 2;
+    while admin.join(&name).exists() {
+        name = format!("{base}-{suffix}");
+        suffix += 1;
+    }
+    name
+}
@@ -6,6 +6,8 @@ mod dotnet;
 mod error;
 mod git;
 mod logging;
+mod profiles;
+mod repo_settings;
 mod telemetry;
 mod ui;
 mod update;
@@ -13,8 +15,14 @@ mod update;
 use ui::app::GitSpaceApp;
 
 fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some(auth::credential_helper::HELPER_ARG) {
+        std::process::exit(run_credential_helper(args.get(2).map_or("", String::as_str)));
+    }
+
     logging::init_tracing();
     log_dev_feature_flags();
+    update::remove_replaced_binary();
 
     let native_options = eframe::NativeOptions {
         renderer: eframe::Renderer::Glow,
@@ -29,6 +37,30 @@ fn main() {
     .expect("failed to start GitSpace UI");
 }
 
+// Git reads the protocol from stdout, so this runs before logging is set up
+// and must not print anything else there.
+fn run_credential_helper(action: &str) -> i32 {
+    let allow_encrypted = config::AppConfig::load()
+        .preferences()
+        .allow_encrypted_tokens();
+    let auth = auth::AuthManager::with_encrypted_fallback(allow_encrypted);
+    let stdin = std::io::stdin();
+    let repo_path = std::env::current_dir().ok();
+    match auth::credential_helper::run_credential_helper(
+        &auth,
+        action,
+        repo_path.as_deref(),
+        stdin.lock(),
+        std::io::stdout(),
+    ) {
+        Ok(()) => 0,
+        Err(err) => {
+            eprintln!("gitspace credential helper: {err}");
+            1
+        }
+    }
+}
+
 fn log_dev_feature_flags() {
     #[cfg(feature = "mock-providers")]
     tracing::warn!(
@@ -0,0 +1,211 @@
+use std::net::{IpAddr, UdpSocket};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::Preferences;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    pub name: String,
+    pub preferences: Preferences,
+    #[serde(default)]
+    pub rules: Vec<ProfileRule>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProfileRule {
+    // CIDR range such as `10.20.0.0/16`.
+    Network(String),
+    // `~` is the home directory.
+    Workspace(String),
+}
+
+impl ProfileRule {
+    pub fn label(&self) -> String {
+        match self {
+            ProfileRule::Network(cidr) => format!("Network {cidr}"),
+            ProfileRule::Workspace(path) => format!("Workspace {path}"),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            ProfileRule::Network(cidr) => parse_cidr(cidr).map(|_| ()),
+            ProfileRule::Workspace(path) if path.trim().is_empty() => {
+                Err("Workspace folder cannot be empty".to_string())
+            }
+            ProfileRule::Workspace(_) => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProfileContext {
+    pub local_address: Option<IpAddr>,
+    pub repo_path: Option<PathBuf>,
+}
+
+impl ProfileContext {
+    pub fn detect(repo_path: Option<&str>) -> Self {
+        Self {
+            local_address: local_address(),
+            repo_path: repo_path.map(PathBuf::from),
+        }
+    }
+}
+
+// Workspace rules win over network rules, and the deepest matching
+// workspace wins among those.
+pub fn select_profile<'a>(
+    profiles: &'a [Profile],
+    context: &ProfileContext,
+) -> Option<(&'a Profile, &'a ProfileRule)> {
+    let rules = || {
+        profiles
+            .iter()
+            .flat_map(|profile| profile.rules.iter().map(move |rule| (profile, rule)))
+    };
+
+    if let Some(repo_path) = &context.repo_path {
+        let workspace = rules()
+            .filter_map(|(profile, rule)| match rule {
+                ProfileRule::Workspace(folder) => {
+                    let folder = expand_home(folder);
+                    repo_path
+                        .starts_with(&folder)
+                        .then(|| (folder.components().count(), profile, rule))
+                }
+                ProfileRule::Network(_) => None,
+            })
+            .max_by_key(|(depth, _, _)| *depth);
+        if let Some((_, profile, rule)) = workspace {
+            return Some((profile, rule));
+        }
+    }
+
+    let address = context.local_address?;
+    rules().find(|(_, rule)| match rule {
+        ProfileRule::Network(cidr) => cidr_contains(cidr, address),
+        ProfileRule::Workspace(_) => false,
+    })
+}
+
+// Connecting a UDP socket only picks a route; no packet is sent.
+pub fn local_address() -> Option<IpAddr> {
+    let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("192.0.2.1:9").ok()?;
+    socket
+        .local_addr()
+        .ok()
+        .map(|addr| addr.ip())
+        .filter(|ip| !ip.is_unspecified())
+}
+
+fn expand_home(folder: &str) -> PathBuf {
+    let folder = folder.trim();
+    match folder.strip_prefix("~") {
+        Some(rest) if rest.is_empty() || rest.starts_with(['/', '\\']) => dirs::home_dir()
+            .map(|home| home.join(rest.trim_start_matches(['/', '\\'])))
+            .unwrap_or_else(|| PathBuf::from(folder)),
+        _ => Path::new(folder).to_path_buf(),
+    }
+}
+
+fn parse_cidr(cidr: &str) -> Result<(IpAddr, u32), String> {
+    let invalid = || format!("{cidr} is not a network range such as 10.20.0.0/16");
+    let (address, prefix) = match cidr.trim().split_once('/') {
+        Some((address, prefix)) => (address, Some(prefix)),
+        None => (cidr.trim(), None),
+    };
+    let address: IpAddr = address.parse().map_err(|_| invalid())?;
+    let max = if address.is_ipv4() { 32 } else { 128 };
+    let prefix = match prefix {
+        Some(prefix) => prefix.parse::<u32>().map_err(|_| invalid())?,
+        None => max,
+    };
+    if prefix > max {
+        return Err(invalid());
+    }
+    Ok((address, prefix))
+}
+
+fn cidr_contains(cidr: &str, address: IpAddr) -> bool {
+    let Ok((network, prefix)) = parse_cidr(cidr) else {
+        return false;
+    };
+    match (network, address) {
+        (IpAddr::V4(network), IpAddr::V4(address)) => {
+            let mask = u32::MAX.checked_shl(32 - prefix).unwrap_or(0);
+            u32::from(network) & mask == u32::from(address) & mask
+        }
+        (IpAddr::V6(network), IpAddr::V6(address)) => {
+            let mask = u128::MAX.checked_shl(128 - prefix).unwrap_or(0);
+            u128::from(network) & mask == u128::from(address) & mask
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(name: &str, rules: Vec<ProfileRule>) -> Profile {
+        Profile {
+            name: name.to_string(),
+            preferences: Preferences::default(),
+            rules,
+        }
+    }
+
+    #[test]
+    fn network_ranges_match_by_prefix() {
+        let address: IpAddr = "10.20.3.4".parse().unwrap();
+        assert!(cidr_contains("10.20.0.0/16", address));
+        assert!(!cidr_contains("10.21.0.0/16", address));
+        assert!(cidr_contains("0.0.0.0/0", address));
+        assert!(cidr_contains("10.20.3.4", address));
+        assert!(!cidr_contains("fd00::/8", address));
+        assert!(
+            ProfileRule::Network("10.0.0.0/33".to_string())
+                .validate()
+                .is_err()
+        );
+        assert!(
+            ProfileRule::Network("office".to_string())
+                .validate()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn workspace_rules_win_over_network_rules() {
+        let profiles = vec![
+            profile("work", vec![ProfileRule::Network("10.0.0.0/8".to_string())]),
+            profile("oss", vec![ProfileRule::Workspace("/src".to_string())]),
+            profile(
+                "client",
+                vec![ProfileRule::Workspace("/src/client".to_string())],
+            ),
+        ];
+        let mut context = ProfileContext {
+            local_address: Some("10.1.2.3".parse().unwrap()),
+            repo_path: None,
+        };
+        assert_eq!(select_profile(&profiles, &context).unwrap().0.name, "work");
+
+        context.repo_path = Some(PathBuf::from("/src/client/app"));
+        assert_eq!(
+            select_profile(&profiles, &context).unwrap().0.name,
+            "client"
+        );
+
+        context.repo_path = Some(PathBuf::from("/src/tool"));
+        assert_eq!(select_profile(&profiles, &context).unwrap().0.name, "oss");
+
+        context.repo_path = Some(PathBuf::from("/home/me/tool"));
+        context.local_address = Some("192.168.1.5".parse().unwrap());
+        assert!(select_profile(&profiles, &context).is_none());
+    }
+}
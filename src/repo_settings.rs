@@ -0,0 +1,349 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use toml_edit::{Array, Document, Item, value};
+
+use crate::config::{AppConfig, Preferences};
+use crate::git::remote::PullStrategy;
+
+pub const REPO_SETTINGS_FILE: &str = ".gitspace.toml";
+
+// `None` defers to the layer below.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RepoSettings {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pull_strategy: Option<PullStrategy>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_fetch_interval_minutes: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pinned_branches: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_remote: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit_template: Option<String>,
+}
+
+impl RepoSettings {
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    pub fn overlay(&mut self, other: &RepoSettings) {
+        if other.pull_strategy.is_some() {
+            self.pull_strategy = other.pull_strategy;
+        }
+        if other.auto_fetch_interval_minutes.is_some() {
+            self.auto_fetch_interval_minutes = other.auto_fetch_interval_minutes;
+        }
+        if other.pinned_branches.is_some() {
+            self.pinned_branches.clone_from(&other.pinned_branches);
+        }
+        if other.default_remote.is_some() {
+            self.default_remote.clone_from(&other.default_remote);
+        }
+        if other.commit_template.is_some() {
+            self.commit_template.clone_from(&other.commit_template);
+        }
+    }
+
+    pub fn load_file(repo_path: &Path) -> Result<Option<Self>, String> {
+        let path = repo_path.join(REPO_SETTINGS_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let text = fs::read_to_string(&path)
+            .map_err(|err| format!("Failed to read {}: {err}", path.display()))?;
+        Self::from_toml(&text)
+            .map(Some)
+            .map_err(|err| format!("{REPO_SETTINGS_FILE}: {err}"))
+    }
+
+    pub fn from_toml(text: &str) -> Result<Self, String> {
+        let document: Document = text.parse().map_err(|err| format!("{err}"))?;
+        let string = |key: &str| -> Result<Option<String>, String> {
+            document
+                .get(key)
+                .map(|item| {
+                    item.as_str()
+                        .map(str::to_string)
+                        .ok_or_else(|| format!("`{key}` must be a string"))
+                })
+                .transpose()
+        };
+
+        let pull_strategy = string("pull_strategy")?
+            .map(|key| {
+                PullStrategy::from_key(&key).ok_or_else(|| {
+                    format!("`pull_strategy` must be one of ff-only, merge or rebase, not {key}")
+                })
+            })
+            .transpose()?;
+        let auto_fetch_interval_minutes = document
+            .get("auto_fetch_interval_minutes")
+            .map(|item| {
+                item.as_integer()
+                    .filter(|minutes| *minutes >= 1)
+                    .map(|minutes| minutes as u64)
+                    .ok_or_else(|| {
+                        "`auto_fetch_interval_minutes` must be a positive integer".to_string()
+                    })
+            })
+            .transpose()?;
+        let pinned_branches = document
+            .get("pinned_branches")
+            .map(|item| {
+                item.as_array()
+                    .and_then(|array| {
+                        array
+                            .iter()
+                            .map(|branch| branch.as_str().map(str::to_string))
+                            .collect::<Option<Vec<_>>>()
+                    })
+                    .ok_or_else(|| "`pinned_branches` must be a list of strings".to_string())
+            })
+            .transpose()?;
+
+        Ok(Self {
+            pull_strategy,
+            auto_fetch_interval_minutes,
+            pinned_branches,
+            default_remote: string("default_remote")?,
+            commit_template: string("commit_template")?,
+        })
+    }
+
+    // Keeps comments and keys GitSpace does not know about.
+    pub fn write_file(&self, repo_path: &Path) -> Result<PathBuf, String> {
+        let path = repo_path.join(REPO_SETTINGS_FILE);
+        let mut document = match fs::read_to_string(&path) {
+            Ok(text) => text
+                .parse::<Document>()
+                .map_err(|err| format!("{REPO_SETTINGS_FILE}: {err}"))?,
+            Err(_) => Document::new(),
+        };
+        set_or_remove(
+            &mut document,
+            "pull_strategy",
+            self.pull_strategy.map(|strategy| value(strategy.key())),
+        );
+        set_or_remove(
+            &mut document,
+            "auto_fetch_interval_minutes",
+            self.auto_fetch_interval_minutes
+                .map(|minutes| value(minutes as i64)),
+        );
+        set_or_remove(
+            &mut document,
+            "pinned_branches",
+            self.pinned_branches
+                .as_ref()
+                .map(|branches| value(branches.iter().collect::<Array>())),
+        );
+        set_or_remove(
+            &mut document,
+            "default_remote",
+            self.default_remote.as_deref().map(value),
+        );
+        set_or_remove(
+            &mut document,
+            "commit_template",
+            self.commit_template.as_deref().map(value),
+        );
+        fs::write(&path, document.to_string())
+            .map_err(|err| format!("Failed to write {}: {err}", path.display()))?;
+        Ok(path)
+    }
+}
+
+fn set_or_remove(document: &mut Document, key: &str, item: Option<Item>) {
+    match item {
+        Some(item) => document[key] = item,
+        None => {
+            document.remove(key);
+        }
+    }
+}
+
+// Ordered lowest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingSource {
+    Default,
+    Global,
+    RepoFile,
+    Repo,
+}
+
+impl SettingSource {
+    pub fn label(self) -> &'static str {
+        match self {
+            SettingSource::Default => "Default",
+            SettingSource::Global => "Global",
+            SettingSource::RepoFile => REPO_SETTINGS_FILE,
+            SettingSource::Repo => "This repository",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Layered<T> {
+    pub value: T,
+    pub source: SettingSource,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EffectiveRepoSettings {
+    pub pull_strategy: Layered<PullStrategy>,
+    pub auto_fetch_interval_minutes: Layered<u64>,
+    pub pinned_branches: Layered<Vec<String>>,
+    pub default_remote: Layered<String>,
+    pub commit_template: Layered<String>,
+}
+
+impl EffectiveRepoSettings {
+    pub fn global(preferences: &Preferences) -> Self {
+        RepoLayers::default().resolve(preferences)
+    }
+}
+
+// Personal overrides stored in the app config win over the shared
+// `.gitspace.toml`, which wins over global preferences and built-in
+// defaults.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RepoLayers {
+    pub path: String,
+    pub file: Option<RepoSettings>,
+    pub file_error: Option<String>,
+    pub local: RepoSettings,
+}
+
+impl RepoLayers {
+    pub fn load(config: &AppConfig, repo_path: &str) -> Self {
+        let (file, file_error) = match RepoSettings::load_file(Path::new(repo_path)) {
+            Ok(file) => (file, None),
+            Err(err) => (None, Some(err)),
+        };
+        Self {
+            path: repo_path.to_string(),
+            file,
+            file_error,
+            local: config.repo_settings(repo_path),
+        }
+    }
+
+    pub fn resolve(&self, preferences: &Preferences) -> EffectiveRepoSettings {
+        let defaults = Preferences::default();
+        let file = self.file.clone().unwrap_or_default();
+        EffectiveRepoSettings {
+            pull_strategy: pick(
+                defaults.pull_strategy(),
+                preferences.pull_strategy(),
+                file.pull_strategy,
+                self.local.pull_strategy,
+            ),
+            auto_fetch_interval_minutes: pick(
+                defaults.auto_fetch_interval_minutes(),
+                preferences.auto_fetch_interval_minutes(),
+                file.auto_fetch_interval_minutes,
+                self.local.auto_fetch_interval_minutes,
+            ),
+            pinned_branches: pick(
+                defaults.pinned_branches().to_vec(),
+                preferences.pinned_branches().to_vec(),
+                file.pinned_branches,
+                self.local.pinned_branches.clone(),
+            ),
+            default_remote: pick(
+                defaults.default_remote().to_string(),
+                preferences.default_remote().to_string(),
+                file.default_remote,
+                self.local.default_remote.clone(),
+            ),
+            commit_template: pick(
+                defaults.commit_template().to_string(),
+                preferences.commit_template().to_string(),
+                file.commit_template,
+                self.local.commit_template.clone(),
+            ),
+        }
+    }
+}
+
+// Global preferences always hold a value, so they only count as a layer
+// when they differ from the default.
+fn pick<T: PartialEq>(default: T, global: T, file: Option<T>, local: Option<T>) -> Layered<T> {
+    let (value, source) = match (local, file) {
+        (Some(value), _) => (value, SettingSource::Repo),
+        (None, Some(value)) => (value, SettingSource::RepoFile),
+        (None, None) if global != default => (global, SettingSource::Global),
+        (None, None) => (default, SettingSource::Default),
+    };
+    Layered { value, source }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layers_resolve_in_order_and_report_their_source() {
+        let mut preferences = Preferences::default();
+        preferences.set_pull_strategy(PullStrategy::Merge);
+        preferences.set_pinned_branches(vec!["main".to_string()]);
+
+        let layers = RepoLayers {
+            path: "/tmp/repo".to_string(),
+            file: Some(
+                RepoSettings::from_toml(
+                    "pull_strategy = \"rebase\"\ndefault_remote = \"upstream\"\n",
+                )
+                .unwrap(),
+            ),
+            file_error: None,
+            local: RepoSettings {
+                default_remote: Some("fork".to_string()),
+                ..RepoSettings::default()
+            },
+        };
+        let effective = layers.resolve(&preferences);
+
+        assert_eq!(effective.pull_strategy.value, PullStrategy::Rebase);
+        assert_eq!(effective.pull_strategy.source, SettingSource::RepoFile);
+        assert_eq!(effective.default_remote.value, "fork");
+        assert_eq!(effective.default_remote.source, SettingSource::Repo);
+        assert_eq!(effective.pinned_branches.value, vec!["main".to_string()]);
+        assert_eq!(effective.pinned_branches.source, SettingSource::Global);
+        assert_eq!(
+            effective.auto_fetch_interval_minutes.source,
+            SettingSource::Default
+        );
+    }
+
+    #[test]
+    fn repo_file_round_trips_and_keeps_unknown_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join(REPO_SETTINGS_FILE),
+            "# team settings\nlfs = true\npull_strategy = \"merge\"\n",
+        )
+        .unwrap();
+
+        let settings = RepoSettings {
+            pull_strategy: None,
+            auto_fetch_interval_minutes: Some(30),
+            pinned_branches: Some(vec!["main".to_string(), "release".to_string()]),
+            default_remote: None,
+            commit_template: Some("feat: ".to_string()),
+        };
+        settings.write_file(dir.path()).unwrap();
+
+        let written = fs::read_to_string(dir.path().join(REPO_SETTINGS_FILE)).unwrap();
+        assert!(written.contains("# team settings"));
+        assert!(written.contains("lfs = true"));
+        assert!(!written.contains("pull_strategy"));
+        assert_eq!(RepoSettings::load_file(dir.path()).unwrap(), Some(settings));
+
+        assert!(RepoSettings::from_toml("pull_strategy = \"squash\"").is_err());
+        assert!(RepoSettings::from_toml("auto_fetch_interval_minutes = 0").is_err());
+    }
+}
@@ -1,3 +1,7 @@
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver};
+use std::time::Duration;
+
 use eframe::egui::{self, Key, Modifiers};
 use poll_promise::Promise;
 use serde_json::{Map, Value};
@@ -6,6 +10,8 @@ use sha2::{Digest, Sha256};
 use crate::auth::AuthManager;
 use crate::config::{AppConfig, Preferences};
 use crate::git::remote::fetch_remote;
+use crate::profiles::{ProfileContext, select_profile};
+use crate::repo_settings::{EffectiveRepoSettings, RepoLayers, RepoSettings};
 use crate::telemetry::TelemetryEmitter;
 use crate::ui::{
     animation::store_motion_settings,
@@ -15,17 +21,22 @@ use crate::ui::{
     context::RepoContext,
     fonts,
     history::HistoryPanel,
-    layout::{MainTab, NavigationTrigger, ShellLayout},
+    layout::{HeaderAction, MainTab, NavigationTrigger, ShellLayout},
     notifications::{Notification, NotificationAction, NotificationCenter},
     recent::RecentList,
     repo_overview::RepoOverviewPanel,
-    settings::SettingsPanel,
+    settings::{
+        ProfileAction, RepoSettingsChange, SettingsPanel, UpdateAction, UpdateInstallState,
+    },
     stage::StagePanel,
     dev_gallery::DevGalleryPanel,
     theme::Theme,
+    unlock::UnlockPrompt,
 };
 use crate::update;
 
+const PROFILE_CHECK_INTERVAL_SECS: f64 = 60.0;
+
 pub struct GitSpaceApp {
     theme: Theme,
     initialized: bool,
@@ -38,24 +49,32 @@ pub struct GitSpaceApp {
     stage_panel: StagePanel,
     config: AppConfig,
     current_repo: Option<RepoContext>,
+    repo_settings: EffectiveRepoSettings,
     auth_manager: AuthManager,
     auth_panel: AuthPanel,
+    unlock_prompt: UnlockPrompt,
     settings_panel: SettingsPanel,
     dev_gallery_panel: DevGalleryPanel,
     notifications: NotificationCenter,
     update_promise: Option<Promise<update::UpdateResult>>,
     update_checked: bool,
+    update_release: Option<update::ReleaseInfo>,
+    update_download: Option<Promise<Result<PathBuf, update::UpdateError>>>,
+    update_progress_rx: Option<Receiver<update::DownloadProgress>>,
+    staged_update: Option<PathBuf>,
     telemetry: TelemetryEmitter,
     telemetry_prompt_enqueued: bool,
     tab_order: Vec<MainTab>,
     auto_fetch_promise: Option<Promise<AutoFetchOutcome>>,
     auto_fetch_last_trigger: Option<f64>,
     auto_fetch_repo: Option<String>,
+    profile_checked_at: Option<f64>,
+    auto_profile_match: Option<String>,
 }
 
 impl GitSpaceApp {
     pub fn new() -> Self {
-        let config = AppConfig::load();
+        let (config, config_recovery) = AppConfig::load_or_recover();
         let preferences = config.preferences().clone();
         let logging = config.logging().clone();
         let default_clone_path = preferences.default_clone_path().to_string();
@@ -64,6 +83,19 @@ impl GitSpaceApp {
         let dev_gallery_theme = theme.clone();
         let auth_manager =
             AuthManager::with_encrypted_fallback(preferences.allow_encrypted_tokens());
+        auth_manager.set_auto_lock_minutes(preferences.token_auto_lock_minutes());
+        let mut notifications = NotificationCenter::default();
+        if let Some(recovery) = config_recovery {
+            let detail = match &recovery.backup {
+                Some(backup) => format!(
+                    "{} Defaults were restored and the old file was kept at {}.",
+                    recovery.error,
+                    backup.display()
+                ),
+                None => format!("{} Defaults were restored.", recovery.error),
+            };
+            notifications.push(Notification::error("Settings could not be loaded", detail));
+        }
         let current_repo = config
             .recent_repos()
             .first()
@@ -78,7 +110,7 @@ impl GitSpaceApp {
             );
             telemetry.record_event("app_launch", properties);
         }
-        Self {
+        let mut app = Self {
             clone_panel: ClonePanel::new(
                 theme.clone(),
                 default_clone_path,
@@ -93,18 +125,28 @@ impl GitSpaceApp {
             history_panel: HistoryPanel::new(theme.clone()),
             branches_panel: BranchPanel::new(theme.clone(), preferences.pinned_branches().to_vec()),
             stage_panel: StagePanel::new(theme.clone()),
+            repo_settings: EffectiveRepoSettings::global(config.preferences()),
             config,
             current_repo,
-            auth_panel: AuthPanel::new(theme.clone(), auth_manager.clone()),
+            auth_panel: AuthPanel::new(
+                theme.clone(),
+                auth_manager.clone(),
+                preferences.network().clone(),
+            ),
+            unlock_prompt: UnlockPrompt::new(theme.clone()),
             auth_manager,
             theme,
             initialized: false,
             active_tab: MainTab::Clone,
             settings_panel: SettingsPanel::new(settings_theme, preferences, logging),
             dev_gallery_panel: DevGalleryPanel::new(dev_gallery_theme),
-            notifications: NotificationCenter::default(),
+            notifications,
             update_promise: None,
             update_checked: false,
+            update_release: None,
+            update_download: None,
+            update_progress_rx: None,
+            staged_update: None,
             telemetry,
             telemetry_prompt_enqueued: false,
             tab_order: {
@@ -117,7 +159,12 @@ impl GitSpaceApp {
             auto_fetch_promise: None,
             auto_fetch_last_trigger: None,
             auto_fetch_repo: None,
-        }
+            profile_checked_at: None,
+            auto_profile_match: None,
+        };
+        app.refresh_repo_settings();
+        app.refresh_profiles();
+        app
     }
 
     fn initialize_if_needed(&mut self, ctx: &egui::Context) {
@@ -135,6 +182,8 @@ impl GitSpaceApp {
         if self.config.touch_recent(path_ref) {
             let _ = self.config.save();
         }
+        self.refresh_repo_settings();
+        self.profile_checked_at = None;
 
         if self.telemetry_enabled() {
             let mut properties = Map::new();
@@ -145,6 +194,157 @@ impl GitSpaceApp {
             self.telemetry.record_event("repo_opened", properties);
         }
     }
+
+    fn refresh_repo_settings(&mut self) {
+        let preferences = self.config.preferences();
+        let layers = self
+            .current_repo
+            .as_ref()
+            .map(|repo| RepoLayers::load(&self.config, &repo.path));
+        let effective = match &layers {
+            Some(layers) => layers.resolve(preferences),
+            None => EffectiveRepoSettings::global(preferences),
+        };
+        self.repo_overview.set_repo_settings(&effective);
+        self.branches_panel
+            .set_pinned_branches(effective.pinned_branches.value.clone());
+        self.stage_panel
+            .set_commit_template(effective.commit_template.value.clone());
+        self.settings_panel.set_repository(layers, effective.clone());
+        self.repo_settings = effective;
+    }
+
+    fn refresh_profiles(&mut self) {
+        self.settings_panel.set_profiles(
+            self.config.profiles().to_vec(),
+            self.config.active_profile().map(str::to_string),
+            self.config.auto_select_profiles(),
+        );
+    }
+
+    fn switch_profile(&mut self, name: &str, ctx: &egui::Context) -> Result<(), String> {
+        let preferences = self.config.switch_profile(name)?.clone();
+        self.apply_preferences(preferences, ctx);
+        self.refresh_profiles();
+        Ok(())
+    }
+
+    fn apply_profile_action(&mut self, action: ProfileAction, ctx: &egui::Context) {
+        let result = match action {
+            ProfileAction::SaveCurrent(name) => self
+                .config
+                .save_profile(&name)
+                .map(|()| format!("Saved profile {name}")),
+            ProfileAction::Switch(name) => self
+                .switch_profile(&name, ctx)
+                .map(|()| format!("Switched to profile {name}")),
+            ProfileAction::Delete(name) => {
+                self.config.delete_profile(&name);
+                Ok(format!("Deleted profile {name}"))
+            }
+            ProfileAction::AddRule(name, rule) => self
+                .config
+                .add_profile_rule(&name, rule)
+                .map(|()| format!("Added rule to {name}")),
+            ProfileAction::RemoveRule(name, rule) => {
+                self.config.remove_profile_rule(&name, &rule);
+                Ok(format!("Removed rule from {name}"))
+            }
+            ProfileAction::SetAutoSelect(enabled) => {
+                self.config.set_auto_select_profiles(enabled);
+                self.profile_checked_at = None;
+                self.auto_profile_match = None;
+                Ok(if enabled {
+                    "Profiles now switch automatically".to_string()
+                } else {
+                    "Automatic profile switching is off".to_string()
+                })
+            }
+        };
+        let _ = self.config.save();
+        self.refresh_profiles();
+        match result {
+            Ok(status) | Err(status) => self.settings_panel.set_profile_status(status),
+        }
+    }
+
+    // Only a change in the match switches, so a profile picked by hand stays
+    // until the workspace or network changes.
+    fn auto_select_profile(&mut self, ctx: &egui::Context) {
+        if !self.config.auto_select_profiles() || self.config.profiles().is_empty() {
+            return;
+        }
+        ctx.request_repaint_after(Duration::from_secs_f64(PROFILE_CHECK_INTERVAL_SECS));
+        let now = ctx.input(|input| input.time);
+        if self
+            .profile_checked_at
+            .is_some_and(|checked| now - checked < PROFILE_CHECK_INTERVAL_SECS)
+        {
+            return;
+        }
+        self.profile_checked_at = Some(now);
+
+        let context =
+            ProfileContext::detect(self.current_repo.as_ref().map(|repo| repo.path.as_str()));
+        let matched = select_profile(self.config.profiles(), &context)
+            .map(|(profile, rule)| (profile.name.clone(), rule.label()));
+        let matched_name = matched.as_ref().map(|(name, _)| name.clone());
+        if matched_name == self.auto_profile_match {
+            return;
+        }
+        self.auto_profile_match = matched_name;
+        let Some((name, rule)) = matched else {
+            return;
+        };
+        if self.config.active_profile() == Some(name.as_str()) {
+            return;
+        }
+        match self.switch_profile(&name, ctx) {
+            Ok(()) => {
+                let _ = self.config.save();
+                self.notifications.push(Notification::success(
+                    format!("Switched to profile {name}"),
+                    format!("Matched {rule}."),
+                ));
+            }
+            Err(err) => self
+                .notifications
+                .push(Notification::error("Profile switch failed", err)),
+        }
+    }
+
+    fn apply_repo_settings_change(&mut self, change: RepoSettingsChange) {
+        let Some(repo_path) = self.current_repo.as_ref().map(|repo| repo.path.clone()) else {
+            return;
+        };
+        match change {
+            RepoSettingsChange::Save(settings) => {
+                self.config.set_repo_settings(&repo_path, settings);
+            }
+            RepoSettingsChange::Share(settings) => {
+                let mut shared = RepoLayers::load(&self.config, &repo_path)
+                    .file
+                    .unwrap_or_default();
+                shared.overlay(&settings);
+                match shared.write_file(std::path::Path::new(&repo_path)) {
+                    Ok(path) => {
+                        self.config
+                            .set_repo_settings(&repo_path, RepoSettings::default());
+                        self.notifications.push(Notification::success(
+                            "Repository settings shared",
+                            format!("Wrote {}. Commit it to share these settings.", path.display()),
+                        ));
+                    }
+                    Err(err) => {
+                        self.notifications
+                            .push(Notification::error("Could not write repository settings", err));
+                    }
+                }
+            }
+        }
+        let _ = self.config.save();
+        self.refresh_repo_settings();
+    }
 }
 
 impl eframe::App for GitSpaceApp {
@@ -152,10 +352,34 @@ impl eframe::App for GitSpaceApp {
         self.initialize_if_needed(ctx);
         self.prompt_for_telemetry_if_needed();
         self.handle_keyboard_navigation(ctx);
+        if ctx.input(|input| !input.events.is_empty()) {
+            self.auth_manager.record_activity();
+        }
 
         let theme = self.theme.clone();
         let layout = ShellLayout::new(&theme);
-        layout.header(ctx);
+        let profile_names: Vec<String> = self
+            .config
+            .profiles()
+            .iter()
+            .map(|profile| profile.name.clone())
+            .collect();
+        match layout.header(ctx, &profile_names, self.config.active_profile()) {
+            Some(HeaderAction::SwitchProfile(name)) => {
+                if let Err(err) = self.switch_profile(&name, ctx) {
+                    self.notifications
+                        .push(Notification::error("Profile switch failed", err));
+                }
+            }
+            Some(HeaderAction::ManageProfiles) => {
+                self.settings_panel.show_global();
+                if self.active_tab != MainTab::Settings {
+                    self.active_tab = MainTab::Settings;
+                    self.record_tab_switch(MainTab::Settings, NavigationTrigger::Click);
+                }
+            }
+            None => {}
+        }
         if let Some(selection) = layout.sidebar(ctx, self.active_tab) {
             if self.active_tab != selection.tab {
                 self.active_tab = selection.tab;
@@ -205,6 +429,31 @@ impl eframe::App for GitSpaceApp {
                         self.load_repo_context(selected);
                     }
 
+                    if let Some(path) = self.repo_overview.take_open_request() {
+                        self.load_repo_context(path);
+                    }
+
+                    let history_changed = self.repo_overview.take_history_changed()
+                        | self.stage_panel.take_committed();
+                    if history_changed && let Some(repo) = self.current_repo.as_ref() {
+                        self.history_panel.update_search_index(&repo.path);
+                    }
+
+                    if let Some((base, target)) = self.history_panel.take_compare_request()
+                        && let Some(repo) = self.current_repo.clone()
+                    {
+                        self.active_tab = MainTab::Branches;
+                        self.record_tab_switch(MainTab::Branches, NavigationTrigger::ContextMenu);
+                        self.branches_panel.open_compare(&repo, &base, &target);
+                    }
+
+                    if let Some(path) = self.stage_panel.take_history_request() {
+                        self.active_tab = MainTab::History;
+                        self.record_tab_switch(MainTab::History, NavigationTrigger::ContextMenu);
+                        self.history_panel
+                            .show_file_history(path, self.current_repo.as_ref());
+                    }
+
                     if let Some(branch) = self.branches_panel.take_history_request() {
                         self.active_tab = MainTab::History;
                         self.record_tab_switch(MainTab::History, NavigationTrigger::ContextMenu);
@@ -224,10 +473,27 @@ impl eframe::App for GitSpaceApp {
         }
 
         if let Some(pinned_branches) = self.branches_panel.take_pinned_changes() {
-            let mut preferences = self.config.preferences().clone();
-            preferences.set_pinned_branches(pinned_branches);
-            self.config.set_preferences(preferences);
+            // Pins belong to the open repository; without one they become the
+            // global default.
+            if let Some(repo) = self.current_repo.as_ref() {
+                let mut settings = self.config.repo_settings(&repo.path);
+                settings.pinned_branches = Some(pinned_branches);
+                self.config.set_repo_settings(&repo.path, settings);
+            } else {
+                let mut preferences = self.config.preferences().clone();
+                preferences.set_pinned_branches(pinned_branches);
+                self.config.set_preferences(preferences);
+            }
             let _ = self.config.save();
+            self.refresh_repo_settings();
+        }
+
+        if let Some(change) = self.settings_panel.take_repo_change() {
+            self.apply_repo_settings_change(change);
+        }
+
+        if let Some(action) = self.settings_panel.take_profile_action() {
+            self.apply_profile_action(action, ctx);
         }
 
         if let Some(control_height) = self.settings_panel.take_control_height_change() {
@@ -268,6 +534,19 @@ impl eframe::App for GitSpaceApp {
             }
         }
 
+        match self.settings_panel.take_update_action() {
+            Some(UpdateAction::Install) => self.start_update_download(),
+            Some(UpdateAction::Restart) => self.restart_to_update(ctx),
+            None => {}
+        }
+        self.poll_update_download(ctx);
+
+        self.unlock_prompt.show(ctx, &self.auth_manager);
+        if self.auth_manager.is_master_password_enabled() && !self.auth_manager.is_locked() {
+            // Keep frames coming so auto-lock happens without user input.
+            ctx.request_repaint_after(Duration::from_secs(30));
+        }
+
         for action in self.notifications.show(ctx) {
             match action {
                 NotificationAction::RetryClone => self.clone_panel.retry_last_clone(),
@@ -282,6 +561,8 @@ impl eframe::App for GitSpaceApp {
                         });
                     });
                 }
+                NotificationAction::InstallUpdate => self.start_update_download(),
+                NotificationAction::RestartToUpdate => self.restart_to_update(ctx),
                 NotificationAction::EnableTelemetry => {
                     self.enable_telemetry(ctx);
                 }
@@ -291,6 +572,7 @@ impl eframe::App for GitSpaceApp {
             }
         }
 
+        self.auto_select_profile(ctx);
         self.handle_auto_fetch(ctx);
         self.telemetry.tick();
     }
@@ -380,14 +662,17 @@ impl GitSpaceApp {
             .set_network_preferences(preferences.network().clone());
         self.history_panel.set_theme(self.theme.clone());
         self.branches_panel.set_theme(self.theme.clone());
-        self.branches_panel
-            .set_pinned_branches(preferences.pinned_branches().to_vec());
         self.stage_panel.set_theme(self.theme.clone());
         self.auth_panel.set_theme(self.theme.clone());
+        self.unlock_prompt.set_theme(self.theme.clone());
         self.settings_panel.set_theme(self.theme.clone());
         self.auth_manager
             .set_encrypted_fallback(preferences.allow_encrypted_tokens());
+        self.auth_manager
+            .set_auto_lock_minutes(preferences.token_auto_lock_minutes());
         self.auth_panel.set_auth_manager(self.auth_manager.clone());
+        self.auth_panel
+            .set_network_preferences(preferences.network().clone());
         self.settings_panel.set_preferences(preferences.clone());
         self.clone_panel
             .set_network_preferences(preferences.network().clone());
@@ -404,6 +689,7 @@ impl GitSpaceApp {
         }
 
         let _ = self.config.save();
+        self.refresh_repo_settings();
 
         // Allow update settings to take effect immediately on the next frame.
         self.update_checked = false;
@@ -475,6 +761,16 @@ impl GitSpaceApp {
                 notification.detail = release.notes.clone();
                 notification =
                     notification.with_action(NotificationAction::OpenRelease(release.url.clone()));
+                // Keep the release being downloaded or staged until it is installed.
+                let installable = update::select_platform_asset(&release.assets).is_some();
+                if self.update_download.is_none() && self.staged_update.is_none() {
+                    if installable {
+                        notification = notification.with_action(NotificationAction::InstallUpdate);
+                        self.settings_panel
+                            .set_update_install(UpdateInstallState::Available(release.version.clone()));
+                    }
+                    self.update_release = Some(release.clone());
+                }
                 self.notifications.push(notification);
                 self.settings_panel.set_update_status(format!(
                     "Update {} available on the {:?} channel",
@@ -494,6 +790,123 @@ impl GitSpaceApp {
         }
     }
 
+    fn start_update_download(&mut self) {
+        if self.update_download.is_some() || self.staged_update.is_some() {
+            return;
+        }
+        let Some(release) = self.update_release.clone() else {
+            return;
+        };
+        let Some(asset) = update::select_platform_asset(&release.assets).cloned() else {
+            self.notifications.push(
+                Notification::error(
+                    "Update cannot be installed",
+                    format!("Release {} has no build for this platform.", release.version),
+                )
+                .with_action(NotificationAction::OpenRelease(release.url.clone())),
+            );
+            return;
+        };
+
+        let network = self.config.preferences().network().clone();
+        let (tx, rx) = mpsc::channel();
+        self.update_progress_rx = Some(rx);
+        self.settings_panel.set_update_install(UpdateInstallState::Downloading {
+            version: release.version.clone(),
+            progress: None,
+        });
+        self.settings_panel
+            .set_update_status(format!("Downloading {}...", asset.name));
+        self.update_download = Some(Promise::spawn_thread("update-download", move || {
+            update::download_update(&network, &asset, |progress| {
+                let _ = tx.send(progress);
+            })
+        }));
+    }
+
+    fn poll_update_download(&mut self, ctx: &egui::Context) {
+        let Some(version) = self.update_release.as_ref().map(|release| release.version.clone())
+        else {
+            return;
+        };
+        if let Some(rx) = &self.update_progress_rx
+            && let Some(progress) = rx.try_iter().last()
+        {
+            self.settings_panel.set_update_install(UpdateInstallState::Downloading {
+                version: version.clone(),
+                progress: progress.fraction(),
+            });
+        }
+
+        let Some(promise) = &self.update_download else {
+            return;
+        };
+        let Some(result) = promise.ready() else {
+            ctx.request_repaint_after(Duration::from_millis(100));
+            return;
+        };
+        let result = result.clone();
+        self.update_download = None;
+        self.update_progress_rx = None;
+
+        match result {
+            Ok(staged) => {
+                self.staged_update = Some(staged);
+                self.settings_panel
+                    .set_update_install(UpdateInstallState::Ready(version.clone()));
+                self.settings_panel.set_update_status(format!(
+                    "Update {version} is ready. Restart GitSpace to finish installing."
+                ));
+                self.notifications.push(
+                    Notification::success(
+                        format!("Update {version} ready"),
+                        "Restart GitSpace to finish installing.",
+                    )
+                    .with_action(NotificationAction::RestartToUpdate),
+                );
+            }
+            Err(err) => {
+                self.settings_panel
+                    .set_update_install(UpdateInstallState::Available(version));
+                self.settings_panel
+                    .set_update_status(format!("Update download failed: {err}"));
+                self.notifications
+                    .push(Notification::error("Update download failed", err.to_string()));
+            }
+        }
+    }
+
+    // Any failure leaves the current binary in place.
+    fn restart_to_update(&mut self, ctx: &egui::Context) {
+        let Some(staged) = self.staged_update.clone() else {
+            return;
+        };
+        let result = std::env::current_exe()
+            .map_err(update::UpdateError::from)
+            .and_then(|target| update::apply_staged_update(&staged, &target, update::relaunch));
+
+        match result {
+            Ok(()) => {
+                let _ = self.config.save();
+                ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+            }
+            Err(err) => {
+                self.staged_update = None;
+                if let Some(release) = &self.update_release {
+                    let version = release.version.clone();
+                    self.settings_panel
+                        .set_update_install(UpdateInstallState::Available(version));
+                }
+                self.settings_panel
+                    .set_update_status(format!("Update failed: {err}"));
+                self.notifications.push(Notification::error(
+                    "Update failed",
+                    format!("{err} GitSpace was left unchanged."),
+                ));
+            }
+        }
+    }
+
     fn handle_auto_fetch(&mut self, ctx: &egui::Context) {
         let now = ctx.input(|input| input.time);
 
@@ -517,7 +930,7 @@ impl GitSpaceApp {
             return;
         };
 
-        let interval_secs = preferences.auto_fetch_interval_minutes() as f64 * 60.0;
+        let interval_secs = self.repo_settings.auto_fetch_interval_minutes.value as f64 * 60.0;
         if self.auto_fetch_repo.as_deref() != Some(&repo.path) {
             self.auto_fetch_repo = Some(repo.path.clone());
             self.auto_fetch_last_trigger = Some(now - interval_secs);
@@ -568,6 +981,7 @@ impl GitSpaceApp {
                     && current_repo.path == outcome.repo_path
                 {
                     self.repo_overview.reload_repo_state(current_repo);
+                    self.history_panel.update_search_index(&current_repo.path);
                 }
                 self.repo_overview.set_action_status(Some(format!(
                     "Auto-fetched {}",
@@ -1,35 +1,81 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Local};
 use eframe::egui::{self, RichText, TextEdit, Ui};
 use poll_promise::Promise;
 
 use crate::auth::AuthManager;
+use crate::auth::accounts::{Account, AccountIndex, AccountRule, DEFAULT_ACCOUNT};
+use crate::auth::credential_helper::{
+    is_global_helper_registered, register_global_helper, unregister_global_helper,
+};
+use crate::config::NetworkOptions;
 use crate::ui::theme::Theme;
 
 pub struct AuthPanel {
     theme: Theme,
     auth: AuthManager,
+    network: NetworkOptions,
     github_host: String,
+    github_account: String,
     github_token: String,
     github_status: Option<String>,
-    github_validation: Option<Promise<Result<(), String>>>,
+    github_validation: Option<Promise<Result<Account, String>>>,
     gitlab_host: String,
+    gitlab_account: String,
     gitlab_token: String,
     gitlab_status: Option<String>,
-    gitlab_validation: Option<Promise<Result<(), String>>>,
+    gitlab_validation: Option<Promise<Result<Account, String>>>,
+    other_host: String,
+    other_account: String,
+    other_token: String,
+    other_status: Option<String>,
+    other_validation: Option<Promise<Result<Account, String>>>,
+    rule_pattern: String,
+    rule_target: Option<(String, String)>,
+    accounts_status: Option<String>,
+    health_check: Option<Promise<Result<Account, String>>>,
+    health_status: Option<String>,
+    store_current: String,
+    store_new: String,
+    store_confirm: String,
+    store_status: Option<String>,
+    helper_registered: bool,
+    helper_status: Option<String>,
 }
 
 impl AuthPanel {
-    pub fn new(theme: Theme, auth: AuthManager) -> Self {
+    pub fn new(theme: Theme, auth: AuthManager, network: NetworkOptions) -> Self {
         Self {
             theme,
             auth,
+            network,
             github_host: "github.com".to_string(),
+            github_account: DEFAULT_ACCOUNT.to_string(),
             github_token: String::new(),
             github_status: None,
             github_validation: None,
             gitlab_host: "gitlab.com".to_string(),
+            gitlab_account: DEFAULT_ACCOUNT.to_string(),
             gitlab_token: String::new(),
             gitlab_status: None,
             gitlab_validation: None,
+            other_host: String::new(),
+            other_account: DEFAULT_ACCOUNT.to_string(),
+            other_token: String::new(),
+            other_status: None,
+            other_validation: None,
+            rule_pattern: String::new(),
+            rule_target: None,
+            accounts_status: None,
+            health_check: None,
+            health_status: None,
+            store_current: String::new(),
+            store_new: String::new(),
+            store_confirm: String::new(),
+            store_status: None,
+            helper_registered: is_global_helper_registered(),
+            helper_status: None,
         }
     }
 
@@ -41,6 +87,10 @@ impl AuthPanel {
         self.auth = auth;
     }
 
+    pub fn set_network_preferences(&mut self, network: NetworkOptions) {
+        self.network = network;
+    }
+
     pub fn ui(&mut self, ui: &mut Ui) {
         poll_validation(
             &mut self.github_validation,
@@ -52,8 +102,24 @@ impl AuthPanel {
             &mut self.gitlab_status,
             &mut self.gitlab_token,
         );
+        poll_validation(
+            &mut self.other_validation,
+            &mut self.other_status,
+            &mut self.other_token,
+        );
 
-        let layout = AuthLayout::new(&self.theme);
+        if let Some(promise) = &self.health_check
+            && let Some(result) = promise.ready()
+        {
+            self.health_status = Some(match result {
+                Ok(account) => format!("Refreshed {}.", account.label()),
+                Err(err) => format!("Check failed: {err}"),
+            });
+            self.health_check = None;
+        }
+
+        let theme = self.theme.clone();
+        let layout = AuthLayout::new(&theme);
         ui.add_space(layout.spacing.md);
         layout.header(
             ui,
@@ -66,7 +132,9 @@ impl AuthPanel {
                 ui,
                 &layout,
                 &self.auth,
+                &self.network,
                 &mut self.github_host,
+                &mut self.github_account,
                 &mut self.github_token,
                 &mut self.github_status,
                 &mut self.github_validation,
@@ -80,7 +148,9 @@ impl AuthPanel {
                 ui,
                 &layout,
                 &self.auth,
+                &self.network,
                 &mut self.gitlab_host,
+                &mut self.gitlab_account,
                 &mut self.gitlab_token,
                 &mut self.gitlab_status,
                 &mut self.gitlab_validation,
@@ -88,6 +158,26 @@ impl AuthPanel {
             );
         });
 
+        ui.add_space(layout.spacing.sm);
+        layout.section(
+            ui,
+            AuthSection::provider("Gitea, Forgejo, Bitbucket & Azure DevOps", '\u{f1d3}'),
+            |ui| {
+                provider_section(
+                    ui,
+                    &layout,
+                    &self.auth,
+                    &self.network,
+                    &mut self.other_host,
+                    &mut self.other_account,
+                    &mut self.other_token,
+                    &mut self.other_status,
+                    &mut self.other_validation,
+                    "Example: codeberg.org, bitbucket.org or dev.azure.com/contoso",
+                );
+            },
+        );
+
         ui.add_space(layout.spacing.lg);
         ui.horizontal(|ui| {
             ui.vertical(|ui| {
@@ -115,51 +205,442 @@ impl AuthPanel {
             ui.vertical(|ui| {
                 layout.section(
                     ui,
-                    AuthSection::info("Saved hosts", "Stored credentials available to GitSpace."),
+                    AuthSection::info(
+                        "Accounts",
+                        "Stored credentials available to GitSpace. The default account is used unless a rule below picks another.",
+                    ),
                     |ui| {
-                        let hosts = self.auth.known_hosts();
-                        if hosts.is_empty() {
+                        let index = self.auth.accounts();
+                        if index.accounts.is_empty() {
                             ui.label(
                                 RichText::new(
                                     "No saved tokens yet. Add a host above to store a credential.",
                                 )
                                 .color(layout.theme.palette.text_secondary),
                             );
-                        } else {
-                            for host in hosts {
-                                ui.horizontal(|ui| {
-                                    ui.colored_label(
-                                        layout.theme.palette.text_primary,
-                                        host.clone(),
-                                    );
-                                    let remove_button = AuthActionButton::new("Remove")
-                                        .variant(ActionVariant::Secondary)
-                                        .small();
-                                    if remove_button.show(ui, layout.theme).clicked() {
-                                        let _ = self.auth.clear_token(&host);
-                                        let message = Some(format!("Removed token for {}", host));
-                                        self.github_status = message.clone();
-                                        self.gitlab_status = message;
-                                    }
-                                });
-                                ui.add_space(layout.spacing.xs);
-                            }
+                        }
+                        for account in &index.accounts {
+                            ui.horizontal(|ui| {
+                                self.account_row(ui, &layout, &index, account);
+                            });
+                            ui.add_space(layout.spacing.xs);
+                        }
+                        if let Some(status) = &self.accounts_status {
+                            ui.add_space(layout.spacing.sm);
+                            ui.colored_label(layout.theme.palette.text_secondary, status);
                         }
                     },
                 );
             });
         });
+
+        ui.add_space(layout.spacing.lg);
+        layout.section(
+            ui,
+            AuthSection::info(
+                "Token health",
+                "Scopes, expiry and rate limits reported when each token was last validated.",
+            ),
+            |ui| {
+                self.health_ui(ui, &layout);
+            },
+        );
+
+        ui.add_space(layout.spacing.lg);
+        layout.section(
+            ui,
+            AuthSection::info(
+                "Account rules",
+                "Use a specific account for matching remotes (host/owner/repo) or repository folders. The first matching rule wins.",
+            ),
+            |ui| {
+                self.rules_ui(ui, &layout);
+            },
+        );
+
+        ui.add_space(layout.spacing.lg);
+        layout.section(
+            ui,
+            AuthSection::info(
+                "Token store",
+                "Protect saved tokens with a master password. GitSpace asks for it at startup and locks again after the idle time set in Settings.",
+            ),
+            |ui| {
+                self.token_store_ui(ui, &layout);
+            },
+        );
+
+        ui.add_space(layout.spacing.lg);
+        layout.section(
+            ui,
+            AuthSection::info(
+                "Command-line git",
+                "GitSpace passes saved tokens to the git commands it runs. Register it globally to use them from your own terminal too.",
+            ),
+            |ui| {
+                ui.horizontal(|ui| {
+                    let (label, variant) = if self.helper_registered {
+                        ("Unregister credential helper", ActionVariant::Secondary)
+                    } else {
+                        ("Register as git credential helper", ActionVariant::Primary)
+                    };
+                    if AuthActionButton::new(label)
+                        .variant(variant)
+                        .show(ui, layout.theme)
+                        .clicked()
+                    {
+                        let result = if self.helper_registered {
+                            unregister_global_helper()
+                                .map(|_| "Removed GitSpace from your global git config.")
+                        } else {
+                            register_global_helper()
+                                .map(|_| "GitSpace is now a credential helper in your global git config.")
+                        };
+                        self.helper_status = Some(match result {
+                            Ok(message) => message.to_string(),
+                            Err(err) => format!("Failed to update git config: {err}"),
+                        });
+                        self.helper_registered = is_global_helper_registered();
+                    }
+                });
+                if let Some(status) = &self.helper_status {
+                    ui.add_space(layout.spacing.sm);
+                    ui.colored_label(layout.theme.palette.text_secondary, status);
+                }
+            },
+        );
+    }
+
+    fn account_row(
+        &mut self,
+        ui: &mut Ui,
+        layout: &AuthLayout<'_>,
+        index: &AccountIndex,
+        account: &Account,
+    ) {
+        ui.colored_label(layout.theme.palette.text_primary, account.label());
+        if index.is_default(account) {
+            ui.colored_label(layout.theme.palette.accent, "Default");
+        } else if AuthActionButton::new("Make default")
+            .variant(ActionVariant::Secondary)
+            .small()
+            .show(ui, layout.theme)
+            .clicked()
+        {
+            self.accounts_status = Some(
+                match self.auth.set_default_account(&account.host, &account.name) {
+                    Ok(()) => format!("{} is now the default for {}", account.name, account.host),
+                    Err(err) => format!("Failed to change default account: {err}"),
+                },
+            );
+        }
+        let remove_button = AuthActionButton::new("Remove")
+            .variant(ActionVariant::Secondary)
+            .small();
+        if remove_button.show(ui, layout.theme).clicked() {
+            self.accounts_status = Some(
+                match self.auth.remove_account(&account.host, &account.name) {
+                    Ok(()) => format!("Removed {}", account.label()),
+                    Err(err) => format!("Failed to remove account: {err}"),
+                },
+            );
+        }
+    }
+
+    fn rules_ui(&mut self, ui: &mut Ui, layout: &AuthLayout<'_>) {
+        let index = self.auth.accounts();
+        let mut removed = None;
+        for (position, rule) in index.rules.iter().enumerate() {
+            ui.horizontal(|ui| {
+                ui.colored_label(layout.theme.palette.text_primary, &rule.pattern);
+                ui.colored_label(
+                    layout.theme.palette.text_secondary,
+                    format!("\u{2192} {} on {}", rule.account, rule.host),
+                );
+                if AuthActionButton::new("Remove")
+                    .variant(ActionVariant::Secondary)
+                    .small()
+                    .show(ui, layout.theme)
+                    .clicked()
+                {
+                    removed = Some(position);
+                }
+            });
+            ui.add_space(layout.spacing.xs);
+        }
+        if let Some(position) = removed
+            && let Err(err) = self.auth.remove_account_rule(position)
+        {
+            self.accounts_status = Some(format!("Failed to remove rule: {err}"));
+        }
+
+        let control_height = ui.spacing().interact_size.y;
+        ui.horizontal(|ui| {
+            let edit = TextEdit::singleline(&mut self.rule_pattern)
+                .hint_text("github.com/acme/* or /home/me/work/*");
+            ui.add_sized([layout.metrics.host_width, control_height], edit);
+            let selected = self
+                .rule_target
+                .as_ref()
+                .and_then(|(host, name)| index.find(host, name))
+                .map_or_else(|| "Choose account".to_string(), Account::label);
+            egui::ComboBox::from_id_source("account_rule_target")
+                .selected_text(selected)
+                .show_ui(ui, |ui| {
+                    for account in &index.accounts {
+                        let target = Some((account.host.clone(), account.name.clone()));
+                        ui.selectable_value(&mut self.rule_target, target, account.label());
+                    }
+                });
+            let enabled = !self.rule_pattern.trim().is_empty() && self.rule_target.is_some();
+            if AuthActionButton::new("Add rule")
+                .variant(ActionVariant::Primary)
+                .show_enabled(ui, layout.theme, enabled)
+                .clicked()
+                && let Some((host, account)) = self.rule_target.clone()
+            {
+                let rule = AccountRule {
+                    pattern: self.rule_pattern.trim().to_string(),
+                    host,
+                    account,
+                };
+                match self.auth.add_account_rule(rule) {
+                    Ok(()) => self.rule_pattern.clear(),
+                    Err(err) => self.accounts_status = Some(format!("Failed to add rule: {err}")),
+                }
+            }
+        });
     }
+
+    fn token_store_ui(&mut self, ui: &mut Ui, layout: &AuthLayout<'_>) {
+        let enabled = self.auth.is_master_password_enabled();
+        let locked = enabled && self.auth.is_locked();
+        let state = match (enabled, locked) {
+            (false, _) => "No master password. Tokens are kept in the OS keyring.",
+            (true, true) => "Master password set. The token store is locked.",
+            (true, false) => "Master password set. The token store is unlocked.",
+        };
+        ui.colored_label(layout.theme.palette.text_primary, state);
+        ui.add_space(layout.spacing.sm);
+
+        let control_height = ui.spacing().interact_size.y;
+        let password_field = |ui: &mut Ui, label: &str, value: &mut String| {
+            ui.label(RichText::new(label).color(layout.theme.palette.text_secondary));
+            let edit = TextEdit::singleline(value).password(true);
+            ui.add_sized([layout.metrics.host_width, control_height], edit);
+        };
+
+        let mut result: Option<Result<&str, String>> = None;
+        if !enabled {
+            password_field(ui, "New master password", &mut self.store_new);
+            password_field(ui, "Confirm password", &mut self.store_confirm);
+            ui.add_space(layout.spacing.sm);
+            let ready = !self.store_new.is_empty() && self.store_new == self.store_confirm;
+            if AuthActionButton::new("Set master password")
+                .variant(ActionVariant::Primary)
+                .show_enabled(ui, layout.theme, ready)
+                .clicked()
+            {
+                result = Some(
+                    self.auth
+                        .enable_master_password(&self.store_new)
+                        .map(|_| "Tokens moved into the password-protected store."),
+                );
+            }
+        } else if locked {
+            password_field(ui, "Master password", &mut self.store_current);
+            ui.add_space(layout.spacing.sm);
+            if AuthActionButton::new("Unlock")
+                .variant(ActionVariant::Primary)
+                .show_enabled(ui, layout.theme, !self.store_current.is_empty())
+                .clicked()
+            {
+                result = Some(
+                    self.auth
+                        .unlock(&self.store_current)
+                        .map(|_| "Token store unlocked."),
+                );
+            }
+        } else {
+            password_field(ui, "Current password", &mut self.store_current);
+            password_field(
+                ui,
+                "New password (leave empty to keep it)",
+                &mut self.store_new,
+            );
+            password_field(ui, "Confirm new password", &mut self.store_confirm);
+            ui.add_space(layout.spacing.sm);
+            let has_current = !self.store_current.is_empty();
+            ui.horizontal(|ui| {
+                let ready = has_current
+                    && !self.store_new.is_empty()
+                    && self.store_new == self.store_confirm;
+                if AuthActionButton::new("Change password")
+                    .variant(ActionVariant::Primary)
+                    .show_enabled(ui, layout.theme, ready)
+                    .clicked()
+                {
+                    result = Some(
+                        self.auth
+                            .change_master_password(&self.store_current, &self.store_new)
+                            .map(|_| "Master password changed and tokens re-encrypted."),
+                    );
+                }
+                if AuthActionButton::new("Rotate key")
+                    .variant(ActionVariant::Secondary)
+                    .show_enabled(ui, layout.theme, has_current)
+                    .on_hover_text("Re-encrypt tokens.enc with a fresh salt and the same password.")
+                    .clicked()
+                {
+                    result = Some(
+                        self.auth
+                            .change_master_password(&self.store_current, &self.store_current)
+                            .map(|_| "Encryption key rotated."),
+                    );
+                }
+                if AuthActionButton::new("Remove master password")
+                    .variant(ActionVariant::Secondary)
+                    .show_enabled(ui, layout.theme, has_current)
+                    .clicked()
+                {
+                    result = Some(
+                        self.auth
+                            .disable_master_password(&self.store_current)
+                            .map(|_| "Master password removed."),
+                    );
+                }
+                if AuthActionButton::new("Lock now")
+                    .variant(ActionVariant::Secondary)
+                    .show(ui, layout.theme)
+                    .clicked()
+                {
+                    self.auth.lock();
+                    result = Some(Ok("Token store locked."));
+                }
+            });
+        }
+
+        if let Some(result) = result {
+            self.store_status = Some(match result {
+                Ok(message) => {
+                    self.store_current.clear();
+                    self.store_new.clear();
+                    self.store_confirm.clear();
+                    message.to_string()
+                }
+                Err(err) => err,
+            });
+        }
+        if let Some(status) = &self.store_status {
+            ui.add_space(layout.spacing.sm);
+            ui.colored_label(layout.theme.palette.text_secondary, status);
+        }
+    }
+
+    fn health_ui(&mut self, ui: &mut Ui, layout: &AuthLayout<'_>) {
+        let index = self.auth.accounts();
+        if index.accounts.is_empty() {
+            ui.label(
+                RichText::new("Save a token to see its scopes, expiry and rate limit here.")
+                    .color(layout.theme.palette.text_secondary),
+            );
+            return;
+        }
+
+        let today = Local::now().date_naive();
+        let mut by_host: BTreeMap<&str, Vec<&Account>> = BTreeMap::new();
+        for account in &index.accounts {
+            by_host.entry(&account.host).or_default().push(account);
+        }
+        let checking = self.health_check.is_some();
+        for (host, accounts) in by_host {
+            ui.label(
+                RichText::new(host)
+                    .color(layout.theme.palette.text_primary)
+                    .strong(),
+            );
+            for account in accounts {
+                ui.horizontal(|ui| {
+                    ui.colored_label(layout.theme.palette.text_primary, account.label());
+                    if let Some(kind) = account.provider {
+                        ui.colored_label(layout.theme.palette.text_secondary, kind.label());
+                    }
+                    if AuthActionButton::new("Re-check")
+                        .variant(ActionVariant::Secondary)
+                        .small()
+                        .show_enabled(ui, layout.theme, !checking)
+                        .clicked()
+                    {
+                        let auth = self.auth.clone();
+                        let network = self.network.clone();
+                        let (host, name) = (account.host.clone(), account.name.clone());
+                        self.health_status = Some(format!("Checking {}...", account.label()));
+                        self.health_check =
+                            Some(Promise::spawn_thread("refresh_token", move || {
+                                auth.refresh_account(&host, &name, &network)
+                            }));
+                    }
+                });
+                ui.colored_label(layout.theme.palette.text_secondary, token_summary(account));
+                let warnings = account.health(today);
+                if warnings.is_empty() {
+                    ui.colored_label(layout.theme.palette.diff_added, "\u{2714} Healthy");
+                }
+                for warning in warnings {
+                    let color = if warning.is_critical() {
+                        layout.theme.palette.diff_removed
+                    } else {
+                        layout.theme.palette.accent_weak
+                    };
+                    ui.colored_label(color, format!("\u{26a0} {}", warning.message()));
+                }
+                ui.add_space(layout.spacing.xs);
+            }
+            ui.add_space(layout.spacing.sm);
+        }
+        if let Some(status) = &self.health_status {
+            ui.colored_label(layout.theme.palette.text_secondary, status);
+        }
+    }
+}
+
+fn token_summary(account: &Account) -> String {
+    let scopes = if account.scopes.is_empty() {
+        "Scopes not reported".to_string()
+    } else {
+        format!("Scopes: {}", account.scopes.join(", "))
+    };
+    let expiry = match &account.expires_at {
+        Some(date) => format!("expires {date}"),
+        None => "no expiry reported".to_string(),
+    };
+    let mut parts = vec![scopes, expiry];
+    if let Some(limit) = &account.rate_limit {
+        parts.push(format!(
+            "{}/{} API requests left",
+            limit.remaining, limit.limit
+        ));
+    }
+    if let Some(checked) = account
+        .checked_at
+        .and_then(|secs| DateTime::from_timestamp(secs, 0))
+    {
+        let checked = checked.with_timezone(&Local);
+        parts.push(format!("checked {}", checked.format("%Y-%m-%d %H:%M")));
+    }
+    parts.join(" \u{2022} ")
 }
 
 fn provider_section(
     ui: &mut Ui,
     layout: &AuthLayout<'_>,
     auth: &AuthManager,
+    network: &NetworkOptions,
     host: &mut String,
+    account: &mut String,
     token: &mut String,
     status: &mut Option<String>,
-    validation: &mut Option<Promise<Result<(), String>>>,
+    validation: &mut Option<Promise<Result<Account, String>>>,
     host_hint: &str,
 ) {
     let control_height = ui.spacing().interact_size.y;
@@ -168,6 +649,11 @@ fn provider_section(
         .width(layout.metrics.host_width)
         .show(ui, layout.theme, control_height);
     ui.add_space(layout.spacing.sm);
+    AuthTextField::new("Account name", account)
+        .hint_text("default, work, personal...")
+        .width(layout.metrics.host_width)
+        .show(ui, layout.theme, control_height);
+    ui.add_space(layout.spacing.sm);
     ui.label(RichText::new("Access token").color(layout.theme.palette.text_secondary));
     ui.horizontal(|ui| {
         let edit = TextEdit::singleline(token)
@@ -175,12 +661,13 @@ fn provider_section(
             .password(true);
         ui.add_sized([layout.metrics.token_width, control_height], edit);
         ui.add_space(layout.spacing.sm);
-        let enabled = !host.trim().is_empty() && !token.trim().is_empty();
+        let enabled =
+            !host.trim().is_empty() && !account.trim().is_empty() && !token.trim().is_empty();
         let button = AuthActionButton::new("Validate & Save")
             .variant(ActionVariant::Primary)
             .show_enabled(ui, layout.theme, enabled);
         if button.clicked() {
-            start_validation(auth, host, token, status, validation);
+            start_validation(auth, network, host, account, token, status, validation);
         }
     });
     ui.add_space(layout.spacing.md);
@@ -193,22 +680,26 @@ fn provider_section(
 
 fn start_validation(
     auth: &AuthManager,
+    network: &NetworkOptions,
     host: &str,
+    account: &str,
     token: &str,
     status: &mut Option<String>,
-    validation: &mut Option<Promise<Result<(), String>>>,
+    validation: &mut Option<Promise<Result<Account, String>>>,
 ) {
     let host = host.trim().to_string();
+    let account = account.trim().to_string();
     let token = token.trim().to_string();
     let auth = auth.clone();
+    let network = network.clone();
     *status = Some("Validating token...".to_string());
     *validation = Some(Promise::spawn_thread("validate_token", move || {
-        auth.validate_and_store(&host, &token)
+        auth.validate_and_store(&host, &account, &token, &network)
     }));
 }
 
 fn poll_validation(
-    validation: &mut Option<Promise<Result<(), String>>>,
+    validation: &mut Option<Promise<Result<Account, String>>>,
     status: &mut Option<String>,
     token: &mut String,
 ) {
@@ -217,8 +708,8 @@ fn poll_validation(
             let result = result.clone();
             *validation = None;
             match result {
-                Ok(_) => {
-                    *status = Some("Token validated and saved.".to_string());
+                Ok(account) => {
+                    *status = Some(format!("Token validated and saved as {}.", account.label()));
                     token.clear();
                 }
                 Err(err) => *status = Some(format!("Validation failed: {}", err)),
@@ -0,0 +1,332 @@
+use std::collections::HashMap;
+
+use eframe::egui::{self, Color32, ColorImage, Rect, RichText, TextureHandle, Ui, Vec2};
+
+use crate::git::diff::{DiffSide, FileDiff, read_diff_side};
+use crate::ui::{diff_view::format_size, fonts, theme::Theme};
+
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "bmp", "ico"];
+const HEX_DUMP_BYTES: usize = 256;
+const MAX_PREVIEW_HEIGHT: f32 = 320.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageMode {
+    SideBySide,
+    Swipe,
+    OnionSkin,
+}
+
+struct Side {
+    size: usize,
+    texture: Option<TextureHandle>,
+    dimensions: Option<[usize; 2]>,
+    hex: String,
+}
+
+struct Preview {
+    old: Option<Side>,
+    new: Option<Side>,
+    error: Option<String>,
+}
+
+pub struct BinaryView {
+    theme: Theme,
+    mode: ImageMode,
+    swipe: f32,
+    opacity: f32,
+    previews: HashMap<String, Preview>,
+}
+
+impl BinaryView {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            theme,
+            mode: ImageMode::SideBySide,
+            swipe: 0.5,
+            opacity: 0.5,
+            previews: HashMap::new(),
+        }
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    pub fn show(&mut self, ui: &mut Ui, repo_path: &str, diff: &FileDiff) {
+        let key = format!(
+            "{}:{}:{}:{}",
+            diff.path,
+            diff.old_id.as_deref().unwrap_or_default(),
+            diff.new_id.as_deref().unwrap_or_default(),
+            diff.new_size.unwrap_or_default()
+        );
+        if !self.previews.contains_key(&key) {
+            let preview = load_preview(ui.ctx(), repo_path, diff);
+            self.previews.insert(key.clone(), preview);
+        }
+        let Some(preview) = self.previews.get(&key) else {
+            return;
+        };
+
+        if let Some(error) = &preview.error {
+            ui.colored_label(self.theme.palette.accent, error);
+            return;
+        }
+        self.size_summary(ui, preview);
+
+        let has_images = [&preview.old, &preview.new]
+            .into_iter()
+            .flatten()
+            .any(|side| side.texture.is_some());
+        if has_images {
+            let (old, new) = (
+                preview.old.as_ref().and_then(|side| side.texture.clone()),
+                preview.new.as_ref().and_then(|side| side.texture.clone()),
+            );
+            self.image_preview(ui, old, new);
+        } else {
+            self.hex_preview(ui, preview);
+        }
+    }
+
+    fn size_summary(&self, ui: &mut Ui, preview: &Preview) {
+        let describe = |side: &Option<Side>| match side {
+            Some(side) => match side.dimensions {
+                Some([width, height]) => {
+                    format!("{} • {width}×{height}", format_size(side.size as u64))
+                }
+                None => format_size(side.size as u64),
+            },
+            None => "—".to_string(),
+        };
+        let delta = match (&preview.old, &preview.new) {
+            (Some(old), Some(new)) => {
+                let change = new.size as i64 - old.size as i64;
+                let sign = if change >= 0 { "+" } else { "-" };
+                format!(" ({sign}{})", format_size(change.unsigned_abs()))
+            }
+            _ => String::new(),
+        };
+        ui.label(
+            RichText::new(format!(
+                "Before: {} • After: {}{delta}",
+                describe(&preview.old),
+                describe(&preview.new)
+            ))
+            .color(self.theme.palette.text_secondary),
+        );
+    }
+
+    fn image_preview(
+        &mut self,
+        ui: &mut Ui,
+        old: Option<TextureHandle>,
+        new: Option<TextureHandle>,
+    ) {
+        let both = old.is_some() && new.is_some();
+        if both {
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut self.mode, ImageMode::SideBySide, "Side by side");
+                ui.selectable_value(&mut self.mode, ImageMode::Swipe, "Swipe");
+                ui.selectable_value(&mut self.mode, ImageMode::OnionSkin, "Onion skin");
+                match self.mode {
+                    ImageMode::Swipe => {
+                        ui.add(egui::Slider::new(&mut self.swipe, 0.0..=1.0).show_value(false));
+                    }
+                    ImageMode::OnionSkin => {
+                        ui.add(
+                            egui::Slider::new(&mut self.opacity, 0.0..=1.0)
+                                .text("After opacity")
+                                .show_value(false),
+                        );
+                    }
+                    ImageMode::SideBySide => {}
+                }
+            });
+        }
+
+        let mode = if both {
+            self.mode
+        } else {
+            ImageMode::SideBySide
+        };
+        match mode {
+            ImageMode::SideBySide => {
+                ui.columns(2, |columns| {
+                    for (column, label, texture) in [(0, "Before", &old), (1, "After", &new)] {
+                        let ui = &mut columns[column];
+                        ui.label(RichText::new(label).color(self.theme.palette.text_secondary));
+                        match texture {
+                            Some(texture) => {
+                                let rect = self.allocate_canvas(ui, texture.size_vec2());
+                                ui.painter().image(
+                                    texture.id(),
+                                    fit(rect, texture.size_vec2()),
+                                    uv(),
+                                    Color32::WHITE,
+                                );
+                            }
+                            None => {
+                                ui.label(
+                                    RichText::new("(no image)")
+                                        .color(self.theme.palette.text_secondary),
+                                );
+                            }
+                        }
+                    }
+                });
+            }
+            ImageMode::Swipe | ImageMode::OnionSkin => {
+                let (Some(old), Some(new)) = (old, new) else {
+                    return;
+                };
+                let bounds = old.size_vec2().max(new.size_vec2());
+                let rect = self.allocate_canvas(ui, bounds);
+                let painter = ui.painter_at(rect);
+                painter.image(old.id(), fit(rect, old.size_vec2()), uv(), Color32::WHITE);
+                if mode == ImageMode::Swipe {
+                    let split = rect.left() + rect.width() * self.swipe;
+                    let clip = Rect::from_min_max(rect.min, egui::pos2(split, rect.bottom()));
+                    painter.with_clip_rect(clip).image(
+                        new.id(),
+                        fit(rect, new.size_vec2()),
+                        uv(),
+                        Color32::WHITE,
+                    );
+                    painter.vline(
+                        split,
+                        rect.y_range(),
+                        egui::Stroke::new(2.0, self.theme.palette.accent),
+                    );
+                } else {
+                    let alpha = (self.opacity * 255.0).round() as u8;
+                    painter.image(
+                        new.id(),
+                        fit(rect, new.size_vec2()),
+                        uv(),
+                        Color32::from_white_alpha(alpha),
+                    );
+                }
+            }
+        }
+    }
+
+    fn allocate_canvas(&self, ui: &mut Ui, image_size: Vec2) -> Rect {
+        let width = ui.available_width();
+        let scale = (width / image_size.x)
+            .min(MAX_PREVIEW_HEIGHT / image_size.y)
+            .min(1.0);
+        let (rect, _) = ui.allocate_exact_size(
+            egui::vec2(width, (image_size.y * scale).max(16.0)),
+            egui::Sense::hover(),
+        );
+        ui.painter()
+            .rect_filled(rect, 4.0, self.theme.palette.surface_highlight);
+        rect
+    }
+
+    fn hex_preview(&self, ui: &mut Ui, preview: &Preview) {
+        for (label, side) in [("Before", &preview.old), ("After", &preview.new)] {
+            let Some(side) = side else {
+                continue;
+            };
+            ui.collapsing(
+                RichText::new(format!("{label} (first {HEX_DUMP_BYTES} bytes)"))
+                    .color(self.theme.palette.text_primary),
+                |ui| {
+                    ui.label(
+                        RichText::new(&side.hex)
+                            .font(fonts::code_font(self.theme.typography.label))
+                            .color(self.theme.palette.text_secondary),
+                    );
+                },
+            );
+        }
+    }
+}
+
+pub fn is_image_path(path: &str) -> bool {
+    path.rsplit_once('.').is_some_and(|(_, extension)| {
+        IMAGE_EXTENSIONS.contains(&extension.to_ascii_lowercase().as_str())
+    })
+}
+
+// `xxd`-style rows: offset, sixteen hex bytes and their printable ASCII.
+pub fn hex_dump(bytes: &[u8], limit: usize) -> String {
+    let mut output = String::new();
+    for (row, chunk) in bytes[..bytes.len().min(limit)].chunks(16).enumerate() {
+        let hex: Vec<String> = chunk.iter().map(|byte| format!("{byte:02x}")).collect();
+        let ascii: String = chunk
+            .iter()
+            .map(|byte| {
+                if byte.is_ascii_graphic() || *byte == b' ' {
+                    *byte as char
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        output.push_str(&format!(
+            "{:08x}  {:<47}  {ascii}\n",
+            row * 16,
+            hex.join(" ")
+        ));
+    }
+    if bytes.len() > limit {
+        output.push_str(&format!("… {} more bytes\n", bytes.len() - limit));
+    }
+    output
+}
+
+fn load_preview(ctx: &egui::Context, repo_path: &str, diff: &FileDiff) -> Preview {
+    let image = is_image_path(&diff.path);
+    let load = |side: DiffSide| -> Result<Option<Side>, git2::Error> {
+        let Some(bytes) = read_diff_side(repo_path, diff, side)? else {
+            return Ok(None);
+        };
+        let decoded = image
+            .then(|| image::load_from_memory(&bytes).ok())
+            .flatten()
+            .map(|decoded| decoded.to_rgba8());
+        let (texture, dimensions) = match decoded {
+            Some(rgba) => {
+                let size = [rgba.width() as usize, rgba.height() as usize];
+                let color_image = ColorImage::from_rgba_unmultiplied(size, rgba.as_raw());
+                let name = format!("binary-diff:{}:{side:?}", diff.path);
+                let texture = ctx.load_texture(name, color_image, egui::TextureOptions::LINEAR);
+                (Some(texture), Some(size))
+            }
+            None => (None, None),
+        };
+        Ok(Some(Side {
+            size: bytes.len(),
+            texture,
+            dimensions,
+            hex: hex_dump(&bytes, HEX_DUMP_BYTES),
+        }))
+    };
+
+    match (load(DiffSide::Old), load(DiffSide::New)) {
+        (Ok(old), Ok(new)) => Preview {
+            old,
+            new,
+            error: None,
+        },
+        (Err(err), _) | (_, Err(err)) => Preview {
+            old: None,
+            new: None,
+            error: Some(format!("Failed to read file contents: {err}")),
+        },
+    }
+}
+
+fn fit(rect: Rect, image_size: Vec2) -> Rect {
+    let scale = (rect.width() / image_size.x)
+        .min(rect.height() / image_size.y)
+        .min(1.0);
+    Rect::from_center_size(rect.center(), image_size * scale)
+}
+
+fn uv() -> Rect {
+    Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0))
+}
@@ -3,14 +3,17 @@ use std::collections::BTreeMap;
 use chrono::Utc;
 use eframe::egui::{self, RichText, Sense, Ui};
 
+use crate::auth::credential_helper::helper_command;
 use crate::git::branch::{
     BranchEntry, BranchKind, archive_branch, checkout_branch, create_branch,
     create_tracking_branch, delete_branch, list_branches, rename_branch,
 };
-use crate::git::compare::{BranchComparison, DiffSummary, compare_branch_with_head};
-use crate::git::log::{CommitInfo, commits_between_refs, latest_commit_for_branch};
+use crate::git::compare::{BranchComparison, compare_branch_with_head};
+use crate::git::log::{CommitInfo, latest_commit_for_branch};
 use crate::git::merge::{MergeOutcome, MergeStrategy, detect_conflicts, merge_branch};
-use crate::ui::{context::RepoContext, menu, theme::Theme};
+use crate::ui::{
+    compare::ComparePanel, context::RepoContext, menu, reflog::ReflogPanel, theme::Theme,
+};
 
 const STALE_DAYS: i64 = 30;
 const REMOTE_PAGE_SIZE: usize = 25;
@@ -46,10 +49,6 @@ pub struct BranchPanel {
     selected_branch: Option<String>,
     selected_comparison: Option<BranchComparison>,
     selected_error: Option<String>,
-    compare_branch: Option<String>,
-    compare_commits: Vec<CommitInfo>,
-    compare_diff: Option<DiffSummary>,
-    compare_error: Option<String>,
     error: Option<String>,
     status: Option<String>,
     conflict_files: Vec<String>,
@@ -58,12 +57,13 @@ pub struct BranchPanel {
     pinned_branches: Vec<String>,
     pending_pinned: Option<Vec<String>>,
     remote_page: usize,
+    reflog_panel: ReflogPanel,
+    compare_panel: ComparePanel,
 }
 
 impl BranchPanel {
     pub fn new(theme: Theme, pinned_branches: Vec<String>) -> Self {
         Self {
-            theme,
             branches: Vec::new(),
             branch_commits: BTreeMap::new(),
             new_branch: String::new(),
@@ -72,10 +72,6 @@ impl BranchPanel {
             selected_branch: None,
             selected_comparison: None,
             selected_error: None,
-            compare_branch: None,
-            compare_commits: Vec::new(),
-            compare_diff: None,
-            compare_error: None,
             error: None,
             status: None,
             conflict_files: Vec::new(),
@@ -84,10 +80,15 @@ impl BranchPanel {
             pinned_branches,
             pending_pinned: None,
             remote_page: 0,
+            reflog_panel: ReflogPanel::new(theme.clone()),
+            compare_panel: ComparePanel::new(theme.clone()),
+            theme,
         }
     }
 
     pub fn set_theme(&mut self, theme: Theme) {
+        self.reflog_panel.set_theme(theme.clone());
+        self.compare_panel.set_theme(theme.clone());
         self.theme = theme;
     }
 
@@ -103,6 +104,10 @@ impl BranchPanel {
         self.open_history_branch.take()
     }
 
+    pub fn open_compare(&mut self, repo: &RepoContext, base: &str, target: &str) {
+        self.compare_panel.open(repo, base, target);
+    }
+
     pub fn ui(&mut self, ui: &mut Ui, repo: Option<&RepoContext>) {
         ui.add_space(8.0);
         ui.heading(RichText::new("Branch explorer").color(self.theme.palette.text_primary));
@@ -164,7 +169,11 @@ impl BranchPanel {
             ui.add_space(10.0);
             self.render_selection_panel(ui);
             ui.add_space(10.0);
-            self.render_compare_panel(ui);
+            self.compare_panel.ui(ui, repo);
+            ui.add_space(10.0);
+            if self.reflog_panel.ui(ui, repo) {
+                self.refresh(repo);
+            }
         } else {
             ui.add_space(8.0);
             ui.label(
@@ -221,10 +230,6 @@ impl BranchPanel {
             self.selected_branch = None;
             self.selected_comparison = None;
             self.selected_error = None;
-            self.compare_branch = None;
-            self.compare_commits.clear();
-            self.compare_diff = None;
-            self.compare_error = None;
             self.remote_page = 0;
         }
 
@@ -526,7 +531,7 @@ impl BranchPanel {
                 )
                 .clicked()
                 {
-                    self.compare_with_current(repo, &branch.name);
+                    self.compare_panel.open(repo, "HEAD", &branch.name);
                     ui.close_menu();
                 }
 
@@ -608,6 +613,7 @@ impl BranchPanel {
         match action() {
             Ok(_) => {
                 self.status = Some("Operation completed".to_string());
+                self.reflog_panel.mark_stale();
                 self.refresh(repo);
             }
             Err(err) => self.error = Some(err.to_string()),
@@ -617,7 +623,7 @@ impl BranchPanel {
     fn run_merge_action(&mut self, repo: &RepoContext, branch: &str, strategy: MergeStrategy) {
         self.status = None;
         self.error = None;
-        match merge_branch(&repo.path, branch, strategy) {
+        match merge_branch(&repo.path, branch, strategy, helper_command().as_deref()) {
             Ok(outcome) => self.handle_merge_outcome(repo, outcome),
             Err(err) => self.error = Some(err),
         }
@@ -707,82 +713,6 @@ impl BranchPanel {
         }
     }
 
-    fn render_compare_panel(&self, ui: &mut Ui) {
-        ui.heading(RichText::new("Compare with current").color(self.theme.palette.text_primary));
-        ui.add_space(6.0);
-
-        let Some(branch_name) = &self.compare_branch else {
-            ui.label(
-                RichText::new("Use the branch context menu to compare with current HEAD.")
-                    .color(self.theme.palette.text_secondary),
-            );
-            return;
-        };
-
-        if let Some(error) = &self.compare_error {
-            ui.colored_label(self.theme.palette.accent, error);
-            return;
-        }
-
-        ui.label(
-            RichText::new(branch_name)
-                .color(self.theme.palette.text_primary)
-                .strong(),
-        );
-
-        if let Some(diff) = &self.compare_diff {
-            ui.add_space(4.0);
-            ui.label(
-                RichText::new(format!(
-                    "{} files changed • +{} / -{}",
-                    diff.files_changed, diff.additions, diff.deletions
-                ))
-                .color(self.theme.palette.text_secondary),
-            );
-        }
-
-        ui.add_space(6.0);
-        ui.label(
-            RichText::new("Commits between current HEAD and branch")
-                .color(self.theme.palette.text_primary)
-                .strong(),
-        );
-
-        if self.compare_commits.is_empty() {
-            ui.label(
-                RichText::new("No commits found in the selected range.")
-                    .color(self.theme.palette.text_secondary),
-            );
-            return;
-        }
-
-        egui::ScrollArea::vertical()
-            .id_source("compare_commits")
-            .auto_shrink([false, false])
-            .max_height(220.0)
-            .show(ui, |ui| {
-                for commit in &self.compare_commits {
-                    ui.vertical(|ui| {
-                        ui.label(
-                            RichText::new(commit.summary.clone())
-                                .color(self.theme.palette.text_primary)
-                                .strong(),
-                        );
-                        ui.label(
-                            RichText::new(format!(
-                                "{} • {}",
-                                self.short_commit_id(&commit.id),
-                                commit.author
-                            ))
-                            .color(self.theme.palette.text_secondary),
-                        );
-                        ui.add_space(4.0);
-                    });
-                    ui.separator();
-                }
-            });
-    }
-
     fn handle_merge_outcome(&mut self, repo: &RepoContext, outcome: MergeOutcome) {
         if outcome.had_conflicts {
             self.conflict_files = outcome.conflicts;
@@ -852,29 +782,4 @@ impl BranchPanel {
         }
         self.pending_pinned = Some(self.pinned_branches.clone());
     }
-
-    fn compare_with_current(&mut self, repo: &RepoContext, branch_name: &str) {
-        self.compare_branch = Some(branch_name.to_string());
-        self.compare_error = None;
-        let comparison = compare_branch_with_head(&repo.path, branch_name);
-        match comparison {
-            Ok(comparison) => self.compare_diff = comparison.diff,
-            Err(err) => {
-                self.compare_diff = None;
-                self.compare_error = Some(format!("Failed to compare branch: {err}"));
-            }
-        }
-
-        match commits_between_refs(&repo.path, "HEAD", branch_name, 50) {
-            Ok(commits) => self.compare_commits = commits,
-            Err(err) => {
-                self.compare_commits.clear();
-                self.compare_error = Some(format!("Failed to load comparison commits: {err}"));
-            }
-        }
-    }
-
-    fn short_commit_id(&self, id: &str) -> String {
-        id.chars().take(7).collect()
-    }
 }
@@ -1,17 +1,12 @@
-use std::collections::HashSet;
 use std::path::PathBuf;
-use std::str::FromStr;
 use std::sync::mpsc::{self, Receiver};
 
 use eframe::egui::{self, Align, ComboBox, Layout, RichText, Sense, TextEdit, Ui};
 use poll_promise::Promise;
-use reqwest::StatusCode;
-use reqwest::blocking::Client;
-use reqwest::header::{HeaderMap, HeaderName, HeaderValue, USER_AGENT};
-use serde::Deserialize;
 use url::Url;
 
 use crate::auth::AuthManager;
+use crate::auth::providers::{ProviderKind, RemoteRepo, provider_for, web_base};
 use crate::config::NetworkOptions;
 use crate::error::{AppError, logs_directory};
 use crate::git::clone::{CloneProgress, CloneRequest, clone_repository};
@@ -19,52 +14,14 @@ use crate::ui::menu;
 use crate::ui::notifications::{Notification, NotificationAction, NotificationCenter};
 use crate::ui::theme::Theme;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum Provider {
-    GitHub,
-    GitLab,
-}
-
-impl Provider {
-    fn label(&self) -> &'static str {
-        match self {
-            Provider::GitHub => "GitHub",
-            Provider::GitLab => "GitLab",
-        }
-    }
-
-    fn host(&self) -> &'static str {
-        match self {
-            Provider::GitHub => "github.com",
-            Provider::GitLab => "gitlab.com",
-        }
-    }
-
-    fn icon(&self) -> char {
-        match self {
-            Provider::GitHub => '\u{f408}',
-            Provider::GitLab => '\u{f296}',
-        }
-    }
-
-    fn icon_label(&self) -> String {
-        format!("{} {}", self.icon(), self.label())
-    }
-}
-
-#[derive(Debug, Clone)]
-pub struct RemoteRepo {
-    pub name: String,
-    pub url: String,
-}
-
 enum CloneEvent {
     Progress(CloneProgress),
 }
 
 pub struct ClonePanel {
     theme: Theme,
-    provider: Provider,
+    provider: ProviderKind,
+    provider_host: String,
     repo_query: String,
     repo_url: String,
     destination: String,
@@ -88,7 +45,8 @@ impl ClonePanel {
     pub fn new(theme: Theme, destination: String, network: NetworkOptions) -> Self {
         Self {
             theme,
-            provider: Provider::GitHub,
+            provider: ProviderKind::GitHub,
+            provider_host: ProviderKind::GitHub.default_host().to_string(),
             repo_query: String::new(),
             repo_url: String::new(),
             base_destination: PathBuf::from(&destination),
@@ -157,11 +115,11 @@ impl ClonePanel {
     }
 
     fn provider_cards(&mut self, ui: &mut Ui) {
-        ui.horizontal(|ui| {
-            for provider in [Provider::GitHub, Provider::GitLab] {
+        ui.horizontal_wrapped(|ui| {
+            for provider in ProviderKind::ALL {
                 let is_active = self.provider == provider;
                 let (rect, response) =
-                    ui.allocate_exact_size(egui::vec2(140.0, 80.0), Sense::click());
+                    ui.allocate_exact_size(egui::vec2(150.0, 64.0), Sense::click());
                 if response.hovered() {
                     ui.output_mut(|o| o.cursor_icon = egui::CursorIcon::PointingHand);
                 }
@@ -177,13 +135,16 @@ impl ClonePanel {
                 painter.text(
                     rect.center(),
                     egui::Align2::CENTER_CENTER,
-                    provider.icon_label(),
+                    format!("{} {}", provider.icon(), provider.label()),
                     egui::FontId::proportional(self.theme.typography.title),
                     self.theme.palette.text_primary,
                 );
 
                 if response.clicked() {
-                    self.provider = provider;
+                    if self.provider != provider {
+                        self.provider = provider;
+                        self.provider_host = provider.default_host().to_string();
+                    }
                     self.search_results.clear();
                     self.selected_repo = None;
                 }
@@ -197,24 +158,41 @@ impl ClonePanel {
                 RichText::new("Remote repository search").color(self.theme.palette.text_primary),
             );
             let search_help = format!(
-                "Search {} or {} without leaving the app. Select a result to fill the clone URL.",
-                format!("{} GitHub", Provider::GitHub.icon()),
-                format!("{} GitLab", Provider::GitLab.icon()),
+                "Search {} {} without leaving the app. Select a result to fill the clone URL.",
+                self.provider.icon(),
+                self.provider.label(),
             );
             ui.label(RichText::new(search_help).color(self.theme.palette.text_secondary));
             ui.add_space(6.0);
 
+            ui.horizontal(|ui| {
+                ui.label(RichText::new("Host").color(self.theme.palette.text_secondary));
+                let host_edit = ui.add_sized(
+                    [220.0, 28.0],
+                    TextEdit::singleline(&mut self.provider_host)
+                        .hint_text(self.provider.host_hint()),
+                );
+                if host_edit.changed() {
+                    self.search_results.clear();
+                    self.selected_repo = None;
+                }
+            });
+            ui.add_space(4.0);
+
             ui.horizontal(|ui| {
                 let query_edit = ui.add_sized(
                     [320.0, 28.0],
-                    TextEdit::singleline(&mut self.repo_query).hint_text("Search repositories"),
+                    TextEdit::singleline(&mut self.repo_query)
+                        .hint_text(self.provider.search_hint()),
                 );
 
                 if query_edit.changed() {
                     self.search_status = None;
                 }
 
-                let search_enabled = self.repo_query.trim().len() >= 2 && !self.cloning;
+                let search_enabled = self.repo_query.trim().len() >= 2
+                    && !self.provider_host.trim().is_empty()
+                    && !self.cloning;
                 let button = ui.add_enabled(search_enabled, egui::Button::new("Search"));
                 if button.clicked() {
                     self.start_search(auth);
@@ -348,19 +326,19 @@ impl ClonePanel {
         if query.len() < 2 {
             return;
         }
-        let provider = self.provider;
+        let provider = provider_for(self.provider, self.provider_host.trim());
         let token = self.resolve_search_token(auth);
         let network = self.network.clone();
         self.search_status = Some("Searching...".to_string());
         self.search_promise = Some(Promise::spawn_thread("search_repos", move || {
-            search_repositories(provider, &query, token.as_deref(), network)
+            provider.search(&query, token.as_deref(), &network)
         }));
     }
 
     fn start_clone(&mut self, auth: &AuthManager) {
         let url = self.repo_url.trim().to_string();
         let destination = PathBuf::from(self.destination.trim());
-        let token = auth.resolve_for_url(&url);
+        let token = auth.resolve_for_repo(&destination.to_string_lossy(), &url);
 
         let request = CloneRequest {
             url,
@@ -399,17 +377,12 @@ impl ClonePanel {
         }));
     }
 
-    fn provider_host(&self) -> &str {
-        self.provider.host()
-    }
-
+    // Tokens are saved per host; Azure DevOps hosts may also be saved with
+    // their organization path.
     fn resolve_search_token(&self, auth: &AuthManager) -> Option<String> {
-        match self.provider {
-            Provider::GitHub => auth
-                .resolve_for_host(self.provider_host())
-                .or_else(|| auth.resolve_for_host("api.github.com")),
-            Provider::GitLab => auth.resolve_for_host(self.provider_host()),
-        }
+        let host = self.provider_host.trim().trim_end_matches('/');
+        auth.resolve_for_host(host)
+            .or_else(|| auth.resolve_for_url(&web_base(host)))
     }
 
     fn poll_search(&mut self, notifications: &mut NotificationCenter) {
@@ -527,257 +500,3 @@ fn repo_name_from_url(repo_url: &str) -> Option<String> {
         Some(name.to_string())
     }
 }
-
-fn search_repositories(
-    provider: Provider,
-    query: &str,
-    token: Option<&str>,
-    network: NetworkOptions,
-) -> Result<Vec<RemoteRepo>, AppError> {
-    match provider {
-        Provider::GitHub => search_github(query, token, &network),
-        Provider::GitLab => search_gitlab(query, token, &network),
-    }
-}
-
-fn client_with_headers(
-    token: Option<&str>,
-    token_header: Option<&str>,
-    header: Option<(&'static str, &'static str)>,
-    network: &NetworkOptions,
-) -> Result<Client, AppError> {
-    let mut headers = HeaderMap::new();
-    headers.insert(
-        USER_AGENT,
-        HeaderValue::from_str("gitspace-ui/0.1")
-            .map_err(|err| AppError::Validation(err.to_string()))?,
-    );
-    if let Some(token) = token {
-        let header_name = token_header.unwrap_or("Authorization");
-        let value = if header_name.eq_ignore_ascii_case("authorization") {
-            format!("Bearer {}", token)
-        } else {
-            token.to_string()
-        };
-        let name = HeaderName::from_str(header_name)
-            .map_err(|err| AppError::Validation(err.to_string()))?;
-        let auth_value =
-            HeaderValue::from_str(&value).map_err(|err| AppError::Validation(err.to_string()))?;
-        headers.insert(name, auth_value);
-    }
-    if let Some((key, value)) = header {
-        headers.insert(
-            key,
-            HeaderValue::from_str(value).map_err(|err| AppError::Validation(err.to_string()))?,
-        );
-    }
-    let mut builder =
-        Client::builder()
-            .default_headers(headers)
-            .timeout(std::time::Duration::from_secs(
-                network.network_timeout_secs.max(1),
-            ));
-
-    if !network.http_proxy.is_empty() {
-        builder = builder.proxy(
-            reqwest::Proxy::http(&network.http_proxy)
-                .map_err(|err| AppError::Validation(err.to_string()))?,
-        );
-    }
-
-    if !network.https_proxy.is_empty() {
-        builder = builder.proxy(
-            reqwest::Proxy::https(&network.https_proxy)
-                .map_err(|err| AppError::Validation(err.to_string()))?,
-        );
-    }
-
-    builder.build().map_err(AppError::from)
-}
-
-fn enforce_https_policy(url: &str, network: &NetworkOptions) -> Result<(), AppError> {
-    if url.starts_with("https://") && !network.use_https {
-        return Err(AppError::Validation(
-            "HTTPS endpoints are disabled in your network settings.".to_string(),
-        ));
-    }
-
-    if url.starts_with("http://") && network.use_https {
-        return Err(AppError::Validation(
-            "HTTP requests are blocked. Enable HTTP in network settings or use HTTPS.".to_string(),
-        ));
-    }
-
-    Ok(())
-}
-
-#[derive(Debug, Deserialize)]
-struct GithubRepoOwner {
-    login: String,
-}
-
-#[derive(Debug, Deserialize)]
-struct GithubRepoItem {
-    full_name: String,
-    html_url: String,
-    owner: GithubRepoOwner,
-}
-
-#[derive(Debug, Deserialize)]
-struct GithubUserProfile {
-    login: String,
-}
-
-fn search_github(
-    query: &str,
-    token: Option<&str>,
-    network: &NetworkOptions,
-) -> Result<Vec<RemoteRepo>, AppError> {
-    let account = query.trim();
-    if account.is_empty() {
-        return Ok(Vec::new());
-    }
-
-    let client = client_with_headers(token, None, None, network)?;
-    let mut unique = HashSet::new();
-    let mut repositories = Vec::new();
-
-    let public_user_url = format!("https://api.github.com/users/{}/repos", account);
-    repositories.extend(
-        fetch_github_repos(&client, &public_user_url, &[("type", "public")], network)
-            .unwrap_or_default(),
-    );
-
-    let public_org_url = format!("https://api.github.com/orgs/{}/repos", account);
-    repositories.extend(
-        fetch_github_repos(&client, &public_org_url, &[("type", "public")], network)
-            .unwrap_or_default(),
-    );
-
-    if token.is_some() {
-        if let Some(login) = fetch_github_login(&client, network)? {
-            if login.eq_ignore_ascii_case(account) {
-                let private_repos = fetch_github_repos(
-                    &client,
-                    "https://api.github.com/user/repos",
-                    &[
-                        ("visibility", "all"),
-                        ("affiliation", "owner,collaborator,organization_member"),
-                    ],
-                    network,
-                )?;
-
-                repositories.extend(
-                    private_repos
-                        .into_iter()
-                        .filter(|repo| repo.owner.login.eq_ignore_ascii_case(account)),
-                );
-            }
-        }
-    }
-
-    let results = repositories
-        .into_iter()
-        .filter(|repo| unique.insert(repo.full_name.clone()))
-        .map(|item| RemoteRepo {
-            name: item.full_name,
-            url: item.html_url,
-        })
-        .collect();
-
-    Ok(results)
-}
-
-#[derive(Debug, Deserialize)]
-struct GitlabProject {
-    name_with_namespace: String,
-    http_url_to_repo: String,
-}
-
-fn search_gitlab(
-    query: &str,
-    token: Option<&str>,
-    network: &NetworkOptions,
-) -> Result<Vec<RemoteRepo>, AppError> {
-    let url = "https://gitlab.com/api/v4/projects";
-    enforce_https_policy(url, network)?;
-    let client = client_with_headers(
-        token,
-        Some("PRIVATE-TOKEN"),
-        Some(("Accept", "application/json")),
-        network,
-    )?;
-    let response: Vec<GitlabProject> = client
-        .get(url)
-        .query(&[("search", query), ("per_page", "6"), ("simple", "true")])
-        .send()
-        .map_err(AppError::from)?
-        .error_for_status()
-        .map_err(AppError::from)?
-        .json()
-        .map_err(AppError::from)?;
-
-    Ok(response
-        .into_iter()
-        .map(|project| RemoteRepo {
-            name: project.name_with_namespace,
-            url: project.http_url_to_repo,
-        })
-        .collect())
-}
-
-fn fetch_github_login(
-    client: &Client,
-    network: &NetworkOptions,
-) -> Result<Option<String>, AppError> {
-    let url = "https://api.github.com/user";
-    enforce_https_policy(url, network)?;
-    let response = client.get(url).send().map_err(AppError::from)?;
-    if response.status() == StatusCode::UNAUTHORIZED {
-        return Ok(None);
-    }
-    let profile: GithubUserProfile = response
-        .error_for_status()?
-        .json()
-        .map_err(AppError::from)?;
-    Ok(Some(profile.login))
-}
-
-fn fetch_github_repos(
-    client: &Client,
-    base_url: &str,
-    params: &[(&str, &str)],
-    network: &NetworkOptions,
-) -> Result<Vec<GithubRepoItem>, AppError> {
-    enforce_https_policy(base_url, network)?;
-    let mut all_repos = Vec::new();
-    let per_page = 100;
-
-    for page in 1..=10 {
-        let mut query_params: Vec<(&str, String)> =
-            params.iter().map(|(k, v)| (*k, (*v).to_string())).collect();
-        query_params.push(("per_page", per_page.to_string()));
-        query_params.push(("page", page.to_string()));
-
-        let response = client
-            .get(base_url)
-            .query(&query_params)
-            .send()
-            .map_err(AppError::from)?;
-
-        if response.status() == StatusCode::NOT_FOUND {
-            break;
-        }
-
-        let response = response.error_for_status().map_err(AppError::from)?;
-        let mut repos: Vec<GithubRepoItem> = response.json().map_err(AppError::from)?;
-        let count = repos.len();
-        all_repos.append(&mut repos);
-
-        if count < per_page {
-            break;
-        }
-    }
-
-    Ok(all_repos)
-}
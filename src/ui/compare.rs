@@ -0,0 +1,235 @@
+use chrono::Utc;
+use eframe::egui::{self, RichText, Ui};
+
+use crate::git::compare::{RefComparison, compare_refs};
+use crate::git::log::CommitInfo;
+use crate::ui::{context::RepoContext, diff_view::file_title, theme::Theme};
+
+const COMPARE_COMMIT_LIMIT: usize = 200;
+
+pub struct ComparePanel {
+    theme: Theme,
+    last_repo: Option<String>,
+    base: String,
+    target: String,
+    comparison: Option<RefComparison>,
+    error: Option<String>,
+}
+
+impl ComparePanel {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            theme,
+            last_repo: None,
+            base: "HEAD".to_string(),
+            target: String::new(),
+            comparison: None,
+            error: None,
+        }
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    pub fn open(&mut self, repo: &RepoContext, base: &str, target: &str) {
+        self.last_repo = Some(repo.path.clone());
+        self.base = base.to_string();
+        self.target = target.to_string();
+        self.compare(repo);
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui, repo: &RepoContext) {
+        if self.last_repo.as_deref() != Some(&repo.path) {
+            self.last_repo = Some(repo.path.clone());
+            self.base = "HEAD".to_string();
+            self.target.clear();
+            self.comparison = None;
+            self.error = None;
+        }
+
+        ui.heading(RichText::new("Compare refs").color(self.theme.palette.text_primary));
+        ui.label(
+            RichText::new("Compare any two branches, tags or commits.")
+                .color(self.theme.palette.text_secondary),
+        );
+        ui.add_space(6.0);
+
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("Base").color(self.theme.palette.text_secondary));
+            ui.add(
+                egui::TextEdit::singleline(&mut self.base)
+                    .hint_text("HEAD")
+                    .desired_width(160.0),
+            );
+            if ui.button("⇄").on_hover_text("Swap sides").clicked() {
+                std::mem::swap(&mut self.base, &mut self.target);
+                if self.comparison.is_some() {
+                    self.compare(repo);
+                }
+            }
+            ui.label(RichText::new("Target").color(self.theme.palette.text_secondary));
+            ui.add(
+                egui::TextEdit::singleline(&mut self.target)
+                    .hint_text("branch, tag or commit")
+                    .desired_width(160.0),
+            );
+            if ui.button("Compare").clicked() {
+                self.compare(repo);
+            }
+        });
+
+        if let Some(error) = &self.error {
+            ui.colored_label(self.theme.palette.accent, error);
+            return;
+        }
+
+        let Some(comparison) = &self.comparison else {
+            ui.label(
+                RichText::new(
+                    "Pick two refs above or use \"Compare\" from the History or branch context menus.",
+                )
+                .color(self.theme.palette.text_secondary),
+            );
+            return;
+        };
+
+        ui.add_space(6.0);
+        match &comparison.merge_base {
+            Some(base) => ui.label(
+                RichText::new(format!(
+                    "Merge base: {} {} • {}",
+                    short_id(&base.id),
+                    base.summary,
+                    format_time(base.time.seconds())
+                ))
+                .color(self.theme.palette.text_secondary),
+            ),
+            None => ui.label(
+                RichText::new("No common ancestor between these refs.")
+                    .color(self.theme.palette.text_secondary),
+            ),
+        };
+        ui.label(
+            RichText::new(format!(
+                "{} files changed • +{} / -{}",
+                comparison.diff.files_changed, comparison.diff.additions, comparison.diff.deletions
+            ))
+            .color(self.theme.palette.text_secondary),
+        );
+
+        ui.add_space(6.0);
+        ui.columns(2, |columns| {
+            self.commit_column(
+                &mut columns[0],
+                "compare_only_base",
+                &format!("Only in {}", comparison.base),
+                &comparison.only_in_base,
+            );
+            self.commit_column(
+                &mut columns[1],
+                "compare_only_target",
+                &format!("Only in {}", comparison.target),
+                &comparison.only_in_target,
+            );
+        });
+
+        ui.add_space(8.0);
+        ui.label(
+            RichText::new("Files changed")
+                .color(self.theme.palette.text_primary)
+                .strong(),
+        );
+        if comparison.files.is_empty() {
+            ui.label(
+                RichText::new("The trees are identical.").color(self.theme.palette.text_secondary),
+            );
+            return;
+        }
+        egui::ScrollArea::vertical()
+            .id_source("compare_files")
+            .auto_shrink([false, true])
+            .max_height(420.0)
+            .show(ui, |ui| {
+                for (idx, diff) in comparison.files.iter().enumerate() {
+                    ui.push_id(("compare_file", idx), |ui| {
+                        ui.collapsing(
+                            RichText::new(file_title(diff)).color(self.theme.palette.text_primary),
+                            |ui| {
+                                ui.add(
+                                    egui::TextEdit::multiline(&mut diff.patch.clone())
+                                        .font(egui::TextStyle::Monospace)
+                                        .desired_width(f32::INFINITY)
+                                        .interactive(false),
+                                );
+                            },
+                        );
+                    });
+                }
+            });
+    }
+
+    fn commit_column(&self, ui: &mut Ui, id: &str, title: &str, commits: &[CommitInfo]) {
+        ui.label(
+            RichText::new(format!("{title} ({})", commits.len()))
+                .color(self.theme.palette.text_primary)
+                .strong(),
+        );
+        if commits.is_empty() {
+            ui.label(RichText::new("No unique commits.").color(self.theme.palette.text_secondary));
+            return;
+        }
+        egui::ScrollArea::vertical()
+            .id_source(id)
+            .auto_shrink([false, true])
+            .max_height(220.0)
+            .show(ui, |ui| {
+                for commit in commits {
+                    ui.label(
+                        RichText::new(&commit.summary)
+                            .color(self.theme.palette.text_primary)
+                            .strong(),
+                    );
+                    ui.label(
+                        RichText::new(format!(
+                            "{} • {} • {}",
+                            short_id(&commit.id),
+                            commit.author,
+                            format_time(commit.time.seconds())
+                        ))
+                        .color(self.theme.palette.text_secondary),
+                    );
+                    ui.separator();
+                }
+            });
+    }
+
+    fn compare(&mut self, repo: &RepoContext) {
+        self.error = None;
+        let base = self.base.trim().to_string();
+        let target = self.target.trim().to_string();
+        if base.is_empty() || target.is_empty() {
+            self.comparison = None;
+            self.error = Some("Enter both refs to compare.".to_string());
+            return;
+        }
+
+        match compare_refs(&repo.path, &base, &target, COMPARE_COMMIT_LIMIT) {
+            Ok(comparison) => self.comparison = Some(comparison),
+            Err(err) => {
+                self.comparison = None;
+                self.error = Some(format!("Failed to compare {base} with {target}: {err}"));
+            }
+        }
+    }
+}
+
+fn short_id(id: &str) -> String {
+    id.chars().take(7).collect()
+}
+
+fn format_time(seconds: i64) -> String {
+    chrono::DateTime::<Utc>::from_timestamp(seconds, 0)
+        .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|| "Unknown time".to_string())
+}
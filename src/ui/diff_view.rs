@@ -0,0 +1,816 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::ops::Range;
+
+use eframe::egui::{self, Color32, RichText, Ui, text::LayoutJob};
+
+use crate::git::diff::{DiffStatus, FileDiff};
+use crate::ui::{fonts, theme::Theme};
+
+const CONTEXT_LINES: usize = 3;
+const MIN_COLLAPSED_LINES: usize = 4;
+const MAX_WORD_DIFF_TOKENS: usize = 400;
+const GUTTER_WIDTH: f32 = 36.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffMode {
+    Unified,
+    SideBySide,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineKind {
+    Meta,
+    Hunk,
+    Context,
+    Added,
+    Removed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffLine {
+    pub kind: LineKind,
+    pub old_line: Option<u32>,
+    pub new_line: Option<u32>,
+    pub text: String,
+    pub changed: Vec<Range<usize>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyntaxKind {
+    Plain,
+    Keyword,
+    String,
+    Number,
+    Comment,
+}
+
+struct Language {
+    keywords: &'static [&'static str],
+    line_comment: &'static str,
+    quotes: &'static [char],
+}
+
+const RUST: Language = Language {
+    keywords: &[
+        "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+        "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move",
+        "mut", "pub", "ref", "return", "self", "Self", "static", "struct", "super", "trait",
+        "true", "type", "unsafe", "use", "where", "while",
+    ],
+    line_comment: "//",
+    quotes: &['"'],
+};
+
+const C_LIKE: Language = Language {
+    keywords: &[
+        "break",
+        "case",
+        "catch",
+        "class",
+        "const",
+        "continue",
+        "default",
+        "delete",
+        "do",
+        "else",
+        "enum",
+        "export",
+        "extends",
+        "false",
+        "final",
+        "finally",
+        "for",
+        "func",
+        "function",
+        "go",
+        "if",
+        "implements",
+        "import",
+        "interface",
+        "let",
+        "namespace",
+        "new",
+        "null",
+        "package",
+        "private",
+        "protected",
+        "public",
+        "return",
+        "static",
+        "struct",
+        "switch",
+        "this",
+        "throw",
+        "true",
+        "try",
+        "typedef",
+        "var",
+        "void",
+        "while",
+    ],
+    line_comment: "//",
+    quotes: &['"', '\'', '`'],
+};
+
+const SCRIPT: Language = Language {
+    keywords: &[
+        "and", "as", "class", "def", "do", "done", "elif", "else", "esac", "except", "export",
+        "False", "fi", "for", "from", "function", "if", "import", "in", "is", "lambda", "local",
+        "None", "not", "or", "pass", "raise", "return", "then", "True", "try", "while", "with",
+        "yield",
+    ],
+    line_comment: "#",
+    quotes: &['"', '\''],
+};
+
+const CONFIG: Language = Language {
+    keywords: &["true", "false", "null", "yes", "no"],
+    line_comment: "#",
+    quotes: &['"', '\''],
+};
+
+fn language_for(path: &str) -> Option<&'static Language> {
+    let extension = path.rsplit_once('.')?.1.to_ascii_lowercase();
+    match extension.as_str() {
+        "rs" => Some(&RUST),
+        "c" | "h" | "cc" | "cpp" | "hpp" | "cs" | "go" | "java" | "js" | "jsx" | "kt" | "swift"
+        | "ts" | "tsx" => Some(&C_LIKE),
+        "py" | "rb" | "sh" | "bash" | "zsh" => Some(&SCRIPT),
+        "toml" | "yaml" | "yml" | "ini" | "cfg" | "conf" => Some(&CONFIG),
+        _ => None,
+    }
+}
+
+// Accepts a `FileDiff` patch (origin-prefixed lines) or plain unified diff
+// text.
+pub fn parse_patch(patch: &str) -> Vec<DiffLine> {
+    let mut lines = Vec::new();
+    let mut old_line = 0;
+    let mut new_line = 0;
+    let mut in_hunk = false;
+
+    for raw in patch.lines() {
+        let hunk = raw.strip_prefix('H').unwrap_or(raw);
+        if hunk.starts_with("@@") {
+            in_hunk = true;
+            (old_line, new_line) = parse_hunk_header(hunk);
+            lines.push(line(LineKind::Hunk, None, None, hunk));
+            continue;
+        }
+        if raw.starts_with("Fdiff") || raw.starts_with("diff --git") {
+            in_hunk = false;
+        }
+        if !in_hunk {
+            lines.push(line(
+                LineKind::Meta,
+                None,
+                None,
+                raw.trim_start_matches('F'),
+            ));
+            continue;
+        }
+
+        let mut chars = raw.chars();
+        let kind = match chars.next() {
+            Some('+') => LineKind::Added,
+            Some('-') => LineKind::Removed,
+            Some(' ') | None => LineKind::Context,
+            _ => LineKind::Meta,
+        };
+        let text = chars.as_str();
+        match kind {
+            LineKind::Added => {
+                lines.push(line(kind, None, Some(new_line), text));
+                new_line += 1;
+            }
+            LineKind::Removed => {
+                lines.push(line(kind, Some(old_line), None, text));
+                old_line += 1;
+            }
+            LineKind::Context => {
+                lines.push(line(kind, Some(old_line), Some(new_line), text));
+                old_line += 1;
+                new_line += 1;
+            }
+            _ => lines.push(line(
+                LineKind::Meta,
+                None,
+                None,
+                raw.trim_start_matches(['<', '>', '=']),
+            )),
+        }
+    }
+
+    mark_word_changes(&mut lines);
+    lines
+}
+
+pub fn collapsible_regions(lines: &[DiffLine]) -> Vec<Range<usize>> {
+    let mut regions = Vec::new();
+    let mut idx = 0;
+    while idx < lines.len() {
+        if lines[idx].kind != LineKind::Context {
+            idx += 1;
+            continue;
+        }
+        let start = idx;
+        while idx < lines.len() && lines[idx].kind == LineKind::Context {
+            idx += 1;
+        }
+        let after_change = start > 0 && is_change(lines[start - 1].kind);
+        let before_change = idx < lines.len() && is_change(lines[idx].kind);
+        let hidden_start = if after_change {
+            start + CONTEXT_LINES
+        } else {
+            start
+        };
+        let hidden_end = if before_change {
+            idx.saturating_sub(CONTEXT_LINES)
+        } else {
+            idx
+        };
+        if hidden_end >= hidden_start + MIN_COLLAPSED_LINES {
+            regions.push(hidden_start..hidden_end);
+        }
+    }
+    regions
+}
+
+pub fn highlight(path: &str, text: &str) -> Vec<(Range<usize>, SyntaxKind)> {
+    let Some(language) = language_for(path) else {
+        return vec![(0..text.len(), SyntaxKind::Plain)];
+    };
+
+    let mut spans = Vec::new();
+    let mut chars = text.char_indices().peekable();
+    while let Some((start, ch)) = chars.next() {
+        let (end, kind) = if text[start..].starts_with(language.line_comment) {
+            (text.len(), SyntaxKind::Comment)
+        } else if language.quotes.contains(&ch) {
+            let mut end = text.len();
+            let mut escaped = false;
+            for (idx, next) in chars.by_ref() {
+                if next == ch && !escaped {
+                    end = idx + next.len_utf8();
+                    break;
+                }
+                escaped = next == '\\' && !escaped;
+            }
+            (end, SyntaxKind::String)
+        } else if ch.is_ascii_digit() {
+            let mut end = start + 1;
+            while let Some((idx, next)) = chars.peek().copied() {
+                if !(next.is_ascii_alphanumeric() || next == '_' || next == '.') {
+                    break;
+                }
+                end = idx + next.len_utf8();
+                chars.next();
+            }
+            (end, SyntaxKind::Number)
+        } else if is_word_char(ch) {
+            let mut end = start + ch.len_utf8();
+            while let Some((idx, next)) = chars.peek().copied() {
+                if !is_word_char(next) {
+                    break;
+                }
+                end = idx + next.len_utf8();
+                chars.next();
+            }
+            let kind = if language.keywords.contains(&&text[start..end]) {
+                SyntaxKind::Keyword
+            } else {
+                SyntaxKind::Plain
+            };
+            (end, kind)
+        } else {
+            (start + ch.len_utf8(), SyntaxKind::Plain)
+        };
+
+        if kind == SyntaxKind::Comment {
+            spans.push((start..end, kind));
+            break;
+        }
+        match spans.last_mut() {
+            Some((range, last)) if *last == kind && range.end == start => range.end = end,
+            _ => spans.push((start..end, kind)),
+        }
+    }
+    spans
+}
+
+// `old → new (+1, -2)` for renames and copies.
+pub fn file_title(diff: &FileDiff) -> String {
+    let path = match &diff.old_path {
+        Some(old_path) => format!("{old_path} → {}", diff.path),
+        None => diff.path.clone(),
+    };
+    if diff.binary {
+        format!("{path} (binary)")
+    } else {
+        format!("{path} (+{}, -{})", diff.additions, diff.deletions)
+    }
+}
+
+pub fn format_size(bytes: u64) -> String {
+    match bytes {
+        0..1024 => format!("{bytes} B"),
+        1024..1_048_576 => format!("{:.1} KiB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MiB", bytes as f64 / 1_048_576.0),
+    }
+}
+
+pub struct DiffView {
+    theme: Theme,
+    mode: DiffMode,
+    expanded: HashSet<(egui::Id, usize)>,
+    cache: HashMap<egui::Id, (u64, Vec<DiffLine>)>,
+}
+
+impl DiffView {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            theme,
+            mode: DiffMode::Unified,
+            expanded: HashSet::new(),
+            cache: HashMap::new(),
+        }
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    pub fn mode_switch(&mut self, ui: &mut Ui) {
+        ui.selectable_value(&mut self.mode, DiffMode::Unified, "Unified");
+        ui.selectable_value(&mut self.mode, DiffMode::SideBySide, "Side by side");
+    }
+
+    pub fn file_summary(&self, ui: &mut Ui, diff: &FileDiff) {
+        let palette = &self.theme.palette;
+        let mut chips: Vec<(String, Color32)> = Vec::new();
+        let status_color = match diff.status {
+            DiffStatus::Added => palette.diff_added,
+            DiffStatus::Deleted => palette.diff_removed,
+            _ => palette.accent,
+        };
+        chips.push((diff.status.label().to_string(), status_color));
+
+        if let Some(old_path) = &diff.old_path {
+            let similarity = diff
+                .similarity
+                .map(|score| format!(" ({score}% similar)"))
+                .unwrap_or_default();
+            chips.push((
+                format!("from {old_path}{similarity}"),
+                palette.text_secondary,
+            ));
+        }
+        if diff.mode_changed()
+            && let (Some(old), Some(new)) = (diff.old_mode, diff.new_mode)
+        {
+            chips.push((format!("mode {old:o} → {new:o}"), palette.accent_weak));
+        }
+        if diff.lfs {
+            let sizes = match (diff.old_size, diff.new_size) {
+                (Some(old), Some(new)) => format!("{} → {}", format_size(old), format_size(new)),
+                (None, Some(size)) | (Some(size), None) => format_size(size),
+                (None, None) => "size unknown".to_string(),
+            };
+            chips.push((format!("Git LFS • {sizes}"), palette.accent_weak));
+        } else if diff.binary {
+            let sizes = match (diff.old_size, diff.new_size) {
+                (Some(old), Some(new)) => format!("{} → {}", format_size(old), format_size(new)),
+                (None, Some(new)) => format_size(new),
+                (Some(old), None) => format!("{} removed", format_size(old)),
+                (None, None) => "size unknown".to_string(),
+            };
+            chips.push((format!("binary • {sizes}"), palette.text_secondary));
+        }
+
+        ui.horizontal_wrapped(|ui| {
+            for (label, color) in chips {
+                egui::Frame::none()
+                    .fill(palette.surface_highlight)
+                    .rounding(egui::Rounding::same(6.0))
+                    .inner_margin(egui::Margin::symmetric(6.0, 2.0))
+                    .show(ui, |ui| {
+                        ui.label(
+                            RichText::new(label)
+                                .color(color)
+                                .size(self.theme.typography.label),
+                        );
+                    });
+            }
+        });
+    }
+
+    pub fn show(&mut self, ui: &mut Ui, id_source: impl Hash, path: &str, patch: &str) {
+        let id = ui.make_persistent_id(id_source);
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        patch.hash(&mut hasher);
+        let hash = hasher.finish();
+        let lines = match self.cache.remove(&id) {
+            Some((cached, lines)) if cached == hash => lines,
+            _ => parse_patch(patch),
+        };
+
+        if lines.is_empty() {
+            ui.label(RichText::new("No textual changes.").color(self.theme.palette.text_secondary));
+        } else {
+            let regions = collapsible_regions(&lines);
+            ui.spacing_mut().item_spacing.y = 0.0;
+            match self.mode {
+                DiffMode::Unified => self.unified(ui, id, path, &lines, &regions),
+                DiffMode::SideBySide => self.side_by_side(ui, id, path, &lines, &regions),
+            }
+        }
+
+        self.cache.insert(id, (hash, lines));
+    }
+
+    fn unified(
+        &mut self,
+        ui: &mut Ui,
+        id: egui::Id,
+        path: &str,
+        lines: &[DiffLine],
+        regions: &[Range<usize>],
+    ) {
+        let mut idx = 0;
+        while idx < lines.len() {
+            if let Some(region) = self.folded_region(id, regions, idx) {
+                if self.fold_row(ui, id, &region) {
+                    self.expanded.insert((id, region.start));
+                }
+                idx = region.end;
+                continue;
+            }
+            let line = &lines[idx];
+            let width = ui.available_width();
+            self.row_frame(line.kind).show(ui, |ui| {
+                ui.set_width(width);
+                ui.horizontal(|ui| {
+                    self.gutter(ui, line.old_line);
+                    self.gutter(ui, line.new_line);
+                    let sign = match line.kind {
+                        LineKind::Added => "+",
+                        LineKind::Removed => "-",
+                        _ => " ",
+                    };
+                    ui.label(self.code_text(sign, self.theme.palette.text_secondary));
+                    ui.label(self.line_job(path, line, ui.available_width()));
+                });
+            });
+            idx += 1;
+        }
+    }
+
+    fn side_by_side(
+        &mut self,
+        ui: &mut Ui,
+        id: egui::Id,
+        path: &str,
+        lines: &[DiffLine],
+        regions: &[Range<usize>],
+    ) {
+        let mut idx = 0;
+        while idx < lines.len() {
+            if let Some(region) = self.folded_region(id, regions, idx) {
+                if self.fold_row(ui, id, &region) {
+                    self.expanded.insert((id, region.start));
+                }
+                idx = region.end;
+                continue;
+            }
+
+            let line = &lines[idx];
+            if !is_change(line.kind) {
+                let pair = (line.kind == LineKind::Context).then_some(line);
+                self.side_row(
+                    ui,
+                    path,
+                    pair.or(Some(line)),
+                    pair,
+                    line.kind != LineKind::Context,
+                );
+                idx += 1;
+                continue;
+            }
+
+            let removed_start = idx;
+            while idx < lines.len() && lines[idx].kind == LineKind::Removed {
+                idx += 1;
+            }
+            let added_start = idx;
+            while idx < lines.len() && lines[idx].kind == LineKind::Added {
+                idx += 1;
+            }
+            let removed = &lines[removed_start..added_start];
+            let added = &lines[added_start..idx];
+            for row in 0..removed.len().max(added.len()) {
+                self.side_row(ui, path, removed.get(row), added.get(row), false);
+            }
+        }
+    }
+
+    fn side_row(
+        &self,
+        ui: &mut Ui,
+        path: &str,
+        left: Option<&DiffLine>,
+        right: Option<&DiffLine>,
+        full_width: bool,
+    ) {
+        let total = ui.available_width();
+        if full_width {
+            if let Some(line) = left {
+                self.row_frame(line.kind).show(ui, |ui| {
+                    ui.set_width(total);
+                    ui.label(self.line_job(path, line, total));
+                });
+            }
+            return;
+        }
+
+        let half = (total - ui.spacing().item_spacing.x) / 2.0;
+        ui.horizontal(|ui| {
+            for (side, line) in [(0, left), (1, right)] {
+                let kind = line.map(|line| line.kind).unwrap_or(LineKind::Meta);
+                let frame = match line {
+                    Some(_) => self.row_frame(kind),
+                    None => egui::Frame::none().fill(self.theme.palette.surface_highlight),
+                };
+                frame.show(ui, |ui| {
+                    ui.set_width(half);
+                    ui.horizontal(|ui| {
+                        let number = line.and_then(|line| {
+                            if side == 0 {
+                                line.old_line
+                            } else {
+                                line.new_line
+                            }
+                        });
+                        self.gutter(ui, number);
+                        if let Some(line) = line {
+                            ui.label(self.line_job(path, line, ui.available_width()));
+                        }
+                    });
+                });
+            }
+        });
+    }
+
+    fn folded_region(
+        &self,
+        id: egui::Id,
+        regions: &[Range<usize>],
+        idx: usize,
+    ) -> Option<Range<usize>> {
+        regions
+            .iter()
+            .find(|region| region.start == idx && !self.expanded.contains(&(id, region.start)))
+            .cloned()
+    }
+
+    fn fold_row(&self, ui: &mut Ui, id: egui::Id, region: &Range<usize>) -> bool {
+        let label = format!("⋯ {} unchanged lines", region.len());
+        ui.push_id((id, region.start), |ui| {
+            ui.add(
+                egui::Button::new(self.code_text(&label, self.theme.palette.text_secondary))
+                    .fill(self.theme.palette.surface_highlight)
+                    .min_size(egui::vec2(ui.available_width(), 0.0)),
+            )
+            .on_hover_text("Show hidden lines")
+            .clicked()
+        })
+        .inner
+    }
+
+    fn row_frame(&self, kind: LineKind) -> egui::Frame {
+        let palette = &self.theme.palette;
+        let fill = match kind {
+            LineKind::Added => palette.diff_added.gamma_multiply(0.15),
+            LineKind::Removed => palette.diff_removed.gamma_multiply(0.15),
+            LineKind::Hunk | LineKind::Meta => palette.surface_highlight,
+            LineKind::Context => Color32::TRANSPARENT,
+        };
+        egui::Frame::none()
+            .fill(fill)
+            .inner_margin(egui::Margin::symmetric(4.0, 1.0))
+    }
+
+    fn gutter(&self, ui: &mut Ui, number: Option<u32>) {
+        let text = number.map(|number| number.to_string()).unwrap_or_default();
+        ui.add_sized(
+            [GUTTER_WIDTH, 0.0],
+            egui::Label::new(self.code_text(&text, self.theme.palette.text_secondary)),
+        );
+    }
+
+    fn code_text(&self, text: &str, color: Color32) -> RichText {
+        RichText::new(text)
+            .font(fonts::code_font(self.theme.typography.label))
+            .color(color)
+    }
+
+    fn line_job(&self, path: &str, line: &DiffLine, wrap_width: f32) -> LayoutJob {
+        let palette = &self.theme.palette;
+        let font = fonts::code_font(self.theme.typography.label);
+        let mut job = LayoutJob::default();
+        job.wrap.max_width = wrap_width;
+
+        if matches!(line.kind, LineKind::Hunk | LineKind::Meta) {
+            job.append(
+                &line.text,
+                0.0,
+                egui::TextFormat::simple(font, palette.text_secondary),
+            );
+            return job;
+        }
+
+        let word_fill = match line.kind {
+            LineKind::Added => palette.diff_added.gamma_multiply(0.4),
+            _ => palette.diff_removed.gamma_multiply(0.4),
+        };
+        let spans = highlight(path, &line.text);
+        let mut bounds: Vec<usize> = spans
+            .iter()
+            .flat_map(|(range, _)| [range.start, range.end])
+            .chain(
+                line.changed
+                    .iter()
+                    .flat_map(|range| [range.start, range.end]),
+            )
+            .collect();
+        bounds.push(0);
+        bounds.push(line.text.len());
+        bounds.sort_unstable();
+        bounds.dedup();
+
+        for window in bounds.windows(2) {
+            let (start, end) = (window[0], window[1]);
+            let kind = spans
+                .iter()
+                .find(|(range, _)| range.start <= start && start < range.end)
+                .map(|(_, kind)| *kind)
+                .unwrap_or(SyntaxKind::Plain);
+            let color = match kind {
+                SyntaxKind::Plain => palette.text_primary,
+                SyntaxKind::Keyword => palette.syntax_keyword,
+                SyntaxKind::String => palette.accent_weak,
+                SyntaxKind::Number => palette.accent,
+                SyntaxKind::Comment => palette.text_secondary,
+            };
+            let mut format = egui::TextFormat::simple(font.clone(), color);
+            format.italics = kind == SyntaxKind::Comment;
+            if line
+                .changed
+                .iter()
+                .any(|range| range.start <= start && start < range.end)
+            {
+                format.background = word_fill;
+            }
+            job.append(&line.text[start..end], 0.0, format);
+        }
+        job
+    }
+}
+
+fn line(kind: LineKind, old_line: Option<u32>, new_line: Option<u32>, text: &str) -> DiffLine {
+    DiffLine {
+        kind,
+        old_line,
+        new_line,
+        text: text.to_string(),
+        changed: Vec::new(),
+    }
+}
+
+fn parse_hunk_header(header: &str) -> (u32, u32) {
+    let mut parts = header.split_whitespace().skip(1);
+    let mut start = |prefix: char| {
+        parts
+            .next()
+            .and_then(|part| part.strip_prefix(prefix))
+            .and_then(|part| part.split(',').next())
+            .and_then(|start| start.parse().ok())
+            .unwrap_or(0)
+    };
+    let old = start('-');
+    let new = start('+');
+    (old, new)
+}
+
+fn is_change(kind: LineKind) -> bool {
+    matches!(kind, LineKind::Added | LineKind::Removed)
+}
+
+fn is_word_char(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '_'
+}
+
+fn mark_word_changes(lines: &mut [DiffLine]) {
+    let mut idx = 0;
+    while idx < lines.len() {
+        if lines[idx].kind != LineKind::Removed {
+            idx += 1;
+            continue;
+        }
+        let removed_start = idx;
+        while idx < lines.len() && lines[idx].kind == LineKind::Removed {
+            idx += 1;
+        }
+        let added_start = idx;
+        while idx < lines.len() && lines[idx].kind == LineKind::Added {
+            idx += 1;
+        }
+
+        let pairs = (added_start - removed_start).min(idx - added_start);
+        for offset in 0..pairs {
+            let (old, new) = word_diff(
+                &lines[removed_start + offset].text,
+                &lines[added_start + offset].text,
+            );
+            lines[removed_start + offset].changed = old;
+            lines[added_start + offset].changed = new;
+        }
+    }
+}
+
+// Lines that share too little are left unmarked so the whole row reads as
+// replaced.
+fn word_diff(old: &str, new: &str) -> (Vec<Range<usize>>, Vec<Range<usize>>) {
+    let old_tokens = tokenize(old);
+    let new_tokens = tokenize(new);
+    if old_tokens.len() > MAX_WORD_DIFF_TOKENS || new_tokens.len() > MAX_WORD_DIFF_TOKENS {
+        return (Vec::new(), Vec::new());
+    }
+
+    let (rows, cols) = (old_tokens.len(), new_tokens.len());
+    let mut table = vec![vec![0usize; cols + 1]; rows + 1];
+    for i in (0..rows).rev() {
+        for j in (0..cols).rev() {
+            table[i][j] = if old[old_tokens[i].clone()] == new[new_tokens[j].clone()] {
+                table[i + 1][j + 1] + 1
+            } else {
+                table[i + 1][j].max(table[i][j + 1])
+            };
+        }
+    }
+
+    let common = table[0][0];
+    if common * 3 < rows.max(cols) {
+        return (Vec::new(), Vec::new());
+    }
+
+    let mut old_changed = Vec::new();
+    let mut new_changed = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < rows || j < cols {
+        if i < rows && j < cols && old[old_tokens[i].clone()] == new[new_tokens[j].clone()] {
+            i += 1;
+            j += 1;
+        } else if j < cols && (i == rows || table[i][j + 1] >= table[i + 1][j]) {
+            push_range(&mut new_changed, new_tokens[j].clone());
+            j += 1;
+        } else {
+            push_range(&mut old_changed, old_tokens[i].clone());
+            i += 1;
+        }
+    }
+    (old_changed, new_changed)
+}
+
+fn push_range(ranges: &mut Vec<Range<usize>>, range: Range<usize>) {
+    match ranges.last_mut() {
+        Some(last) if last.end == range.start => last.end = range.end,
+        _ => ranges.push(range),
+    }
+}
+
+fn tokenize(text: &str) -> Vec<Range<usize>> {
+    let mut tokens = Vec::new();
+    let mut chars = text.char_indices().peekable();
+    while let Some((start, ch)) = chars.next() {
+        let mut end = start + ch.len_utf8();
+        if is_word_char(ch) || ch.is_whitespace() {
+            let same_class = |next: char| {
+                if ch.is_whitespace() {
+                    next.is_whitespace()
+                } else {
+                    is_word_char(next)
+                }
+            };
+            while let Some((idx, next)) = chars.peek().copied() {
+                if !same_class(next) {
+                    break;
+                }
+                end = idx + next.len_utf8();
+                chars.next();
+            }
+        }
+        tokens.push(start..end);
+    }
+    tokens
+}
@@ -164,3 +164,7 @@ pub fn install_fonts(ctx: &egui::Context) {
 
     ctx.set_fonts(definitions);
 }
+
+pub fn code_font(size: f32) -> egui::FontId {
+    egui::FontId::new(size, egui::FontFamily::Monospace)
+}
@@ -1,13 +1,43 @@
+use std::sync::mpsc::{self, Receiver, Sender};
+
 use chrono::{Datelike, NaiveDate, TimeZone, Utc};
 use eframe::egui::{self, Align, Layout, Pos2, RichText, Sense, Ui};
+use poll_promise::Promise;
+use rfd::FileDialog;
 
+use crate::config::app_data_dir;
 use crate::git::{
-    diff::{FileDiff, commit_diff},
-    log::{CommitFilter, CommitInfo, list_local_branches, read_commit_log},
+    bisect::{
+        BisectSession, BisectStep, BisectVerdict, bisect_status, load_bisect, mark_bisect,
+        reset_bisect, run_bisect, start_bisect,
+    },
+    commit::create_annotated_tag,
+    diff::{FileDiff, commit_diff, commit_path_diff},
+    log::{
+        CommitFilter, CommitInfo, LogEvent, find_commit_info, list_local_branches,
+        stream_commit_log,
+    },
+    patch::{format_patches, write_mbox, write_patch_series},
+    search::{
+        CommitIndex, SearchQuery, load_commit_index, search_commit_index, update_commit_index,
+    },
+    signing::{SignatureState, verify_commit_signature},
+};
+use crate::ui::{
+    binary_view::BinaryView,
+    context::RepoContext,
+    diff_view::{DiffView, file_title},
+    menu,
+    theme::Theme,
 };
-use crate::ui::{context::RepoContext, menu, theme::Theme};
 
-const MAX_COMMITS: usize = 200;
+const PAGE_SIZE: usize = 200;
+const SEARCH_LIMIT: usize = 500;
+const SEARCH_INDEX_DIR: &str = "search-index";
+
+type IndexSearch = Result<(CommitIndex, Vec<CommitInfo>, usize), String>;
+type IndexUpdate = Result<CommitIndex, String>;
+type SignatureCheck = (String, Option<SignatureState>);
 const ROW_HEIGHT: f32 = 88.0;
 
 #[derive(Default, Clone)]
@@ -17,6 +47,8 @@ pub struct HistoryFilters {
     pub search: String,
     pub since: String,
     pub until: String,
+    pub path: String,
+    pub follow: bool,
 }
 
 pub struct HistoryPanel {
@@ -24,31 +56,93 @@ pub struct HistoryPanel {
     filters: HistoryFilters,
     branches: Vec<String>,
     commits: Vec<CommitInfo>,
+    log_requests: Option<Sender<usize>>,
+    log_events: Option<Receiver<Result<LogEvent, String>>>,
+    log_loading: bool,
+    log_exhausted: bool,
+    log_scanned: usize,
+    index_query: String,
+    search_index: Option<CommitIndex>,
+    index_promise: Option<Promise<IndexSearch>>,
+    index_rerun: bool,
+    index_update: Option<Promise<IndexUpdate>>,
+    index_update_queued: bool,
+    index_status: Option<String>,
     selected_commit: Option<String>,
     diffs: Vec<FileDiff>,
     last_repo: Option<String>,
     error: Option<String>,
     diff_error: Option<String>,
     pending_refresh: bool,
+    compare_base: Option<String>,
+    compare_request: Option<(String, String)>,
+    signature: Option<SignatureState>,
+    signature_check: Option<Promise<SignatureCheck>>,
+    tag_name: String,
+    tag_message: String,
+    tag_status: Option<String>,
+    patch_series: Vec<CommitInfo>,
+    patch_status: Option<String>,
+    bisect: Option<BisectSession>,
+    bisect_step: Option<BisectStep>,
+    bisect_status: Option<String>,
+    bisect_command: String,
+    bisect_events: Option<Receiver<Result<BisectStep, String>>>,
+    bisect_result: Option<CommitInfo>,
+    diff_view: DiffView,
+    binary_view: BinaryView,
 }
 
 impl HistoryPanel {
     pub fn new(theme: Theme) -> Self {
+        let diff_view = DiffView::new(theme.clone());
+        let binary_view = BinaryView::new(theme.clone());
         Self {
             theme,
             filters: HistoryFilters::default(),
             branches: Vec::new(),
             commits: Vec::new(),
+            log_requests: None,
+            log_events: None,
+            log_loading: false,
+            log_exhausted: true,
+            log_scanned: 0,
+            index_query: String::new(),
+            search_index: None,
+            index_promise: None,
+            index_rerun: false,
+            index_update: None,
+            index_update_queued: false,
+            index_status: None,
             selected_commit: None,
             diffs: Vec::new(),
             last_repo: None,
             error: None,
             diff_error: None,
             pending_refresh: false,
+            compare_base: None,
+            compare_request: None,
+            signature: None,
+            signature_check: None,
+            tag_name: String::new(),
+            tag_message: String::new(),
+            tag_status: None,
+            patch_series: Vec::new(),
+            patch_status: None,
+            bisect: None,
+            bisect_step: None,
+            bisect_status: None,
+            bisect_command: String::new(),
+            bisect_events: None,
+            bisect_result: None,
+            diff_view,
+            binary_view,
         }
     }
 
     pub fn set_theme(&mut self, theme: Theme) {
+        self.diff_view.set_theme(theme.clone());
+        self.binary_view.set_theme(theme.clone());
         self.theme = theme;
     }
 
@@ -63,6 +157,22 @@ impl HistoryPanel {
         }
     }
 
+    pub fn show_file_history(&mut self, path: String, repo: Option<&RepoContext>) {
+        self.filters.path = path;
+        self.filters.follow = true;
+        self.selected_commit = None;
+        if let Some(repo) = repo {
+            self.refresh(repo);
+            self.pending_refresh = false;
+        } else {
+            self.pending_refresh = true;
+        }
+    }
+
+    pub fn take_compare_request(&mut self) -> Option<(String, String)> {
+        self.compare_request.take()
+    }
+
     pub fn ui(&mut self, ui: &mut Ui, repo: Option<&RepoContext>) {
         ui.add_space(8.0);
         ui.heading(RichText::new("Commit history").color(self.theme.palette.text_primary));
@@ -74,12 +184,34 @@ impl HistoryPanel {
 
         if let Some(repo) = repo {
             if self.last_repo.as_deref() != Some(&repo.path) {
+                self.search_index = None;
+                self.index_promise = None;
+                self.index_update = None;
+                self.index_update_queued = false;
+                self.index_query.clear();
+                self.index_status = None;
+                self.patch_series.clear();
+                self.patch_status = None;
                 self.refresh(repo);
+                self.load_bisect_session(&repo.path);
             }
             if self.pending_refresh {
                 self.refresh(repo);
                 self.pending_refresh = false;
             }
+            self.poll_log();
+            self.poll_index_search(repo);
+            self.poll_index_update(repo);
+            self.poll_bisect();
+            self.poll_signature_check();
+            if self.log_loading
+                || self.index_promise.is_some()
+                || self.index_update.is_some()
+                || self.bisect_events.is_some()
+                || self.signature_check.is_some()
+            {
+                ui.ctx().request_repaint();
+            }
 
             if let Some(error) = &self.error {
                 ui.colored_label(self.theme.palette.accent, error);
@@ -90,6 +222,8 @@ impl HistoryPanel {
             ui.add_space(8.0);
             ui.separator();
             ui.add_space(6.0);
+            self.patch_series_bar(ui);
+            self.bisect_bar(ui);
 
             let available_height = ui.available_height();
             ui.horizontal(|ui| {
@@ -198,6 +332,48 @@ impl HistoryPanel {
                             self.refresh(repo);
                         }
                     });
+
+                    ui.add_space(6.0);
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new("Path").color(self.theme.palette.text_secondary));
+                        ui.add(
+                            egui::TextEdit::singleline(&mut self.filters.path)
+                                .hint_text("file or directory"),
+                        );
+                        ui.checkbox(&mut self.filters.follow, "Follow renames");
+                        if !self.filters.path.is_empty() && ui.button("Clear path").clicked() {
+                            self.filters.path.clear();
+                            self.refresh(repo);
+                        }
+                    });
+
+                    ui.add_space(6.0);
+                    ui.horizontal(|ui| {
+                        ui.label(
+                            RichText::new("Index search").color(self.theme.palette.text_secondary),
+                        );
+                        let response = ui.add(
+                            egui::TextEdit::singleline(&mut self.index_query)
+                                .hint_text(
+                                    "author:ana path:src/ before:2024-01-01 re:fix(es)? -Stoken",
+                                )
+                                .desired_width(360.0),
+                        );
+                        if response.changed() {
+                            self.run_index_search(repo);
+                        }
+                        if self.index_promise.is_some() {
+                            ui.spinner();
+                        }
+                        if !self.index_query.is_empty() && ui.button("Clear search").clicked() {
+                            self.index_query.clear();
+                            self.index_status = None;
+                            self.refresh(repo);
+                        }
+                    });
+                    if let Some(status) = &self.index_status {
+                        ui.label(RichText::new(status).color(self.theme.palette.text_secondary));
+                    }
                 });
             });
     }
@@ -205,16 +381,26 @@ impl HistoryPanel {
     fn commit_list(&mut self, ui: &mut Ui) {
         let palette = self.theme.palette.clone();
         let mut newly_selected: Option<String> = None;
+        let mut compare_base = self.compare_base.clone();
+        let mut compare_request = None;
+        let mut patch_export = None;
+        let mut series_toggle = None;
+        let mut bisect_request = None;
         if self.commits.is_empty() {
-            ui.label(
-                RichText::new("No commits match the current filters.")
-                    .color(palette.text_secondary),
-            );
+            if self.log_loading {
+                self.log_progress(ui);
+            } else {
+                ui.label(
+                    RichText::new("No commits match the current filters.")
+                        .color(palette.text_secondary),
+                );
+            }
             return;
         }
 
+        let mut load_more = false;
         let available_height = ui.available_height();
-        egui::ScrollArea::vertical()
+        let output = egui::ScrollArea::vertical()
             .id_source("history_commit_list")
             .auto_shrink([false, false])
             .max_height(available_height)
@@ -271,6 +457,30 @@ impl HistoryPanel {
                                         )
                                         .wrap(true),
                                     );
+                                    if let Some(mark) = self.bisect_mark(&commit.id) {
+                                        ui.label(RichText::new(mark).color(palette.accent));
+                                    }
+                                    if let Some(position) =
+                                        self.patch_series.iter().position(|c| c.id == commit.id)
+                                    {
+                                        ui.label(
+                                            RichText::new(format!(
+                                                "Patch {}/{}",
+                                                position + 1,
+                                                self.patch_series.len()
+                                            ))
+                                            .color(palette.accent),
+                                        );
+                                    }
+                                    if let Some(path) = &commit.path
+                                        && path.as_str()
+                                            != self.filters.path.trim().trim_matches('/')
+                                    {
+                                        ui.label(
+                                            RichText::new(format!("as {path}"))
+                                                .color(palette.accent),
+                                        );
+                                    }
                                     let date = chrono::DateTime::<Utc>::from_timestamp(
                                         commit.time.seconds(),
                                         0,
@@ -294,9 +504,138 @@ impl HistoryPanel {
                     if response.clicked() {
                         newly_selected = Some(commit.id.clone());
                     }
+
+                    response.context_menu(|ui| {
+                        menu::with_menu_popup_motion(
+                            ui,
+                            ("history-commit-menu", &commit.id),
+                            |ui| {
+                                if menu::menu_item(
+                                    ui,
+                                    &self.theme,
+                                    ("history-compare-head", &commit.id),
+                                    "Compare with HEAD",
+                                    false,
+                                )
+                                .clicked()
+                                {
+                                    compare_request = Some((commit.id.clone(), "HEAD".to_string()));
+                                    ui.close_menu();
+                                }
+                                if menu::menu_item(
+                                    ui,
+                                    &self.theme,
+                                    ("history-compare-base", &commit.id),
+                                    "Mark as compare base",
+                                    compare_base.as_deref() == Some(commit.id.as_str()),
+                                )
+                                .clicked()
+                                {
+                                    compare_base = Some(commit.id.clone());
+                                    ui.close_menu();
+                                }
+                                if let Some(base) = compare_base.clone()
+                                    && base != commit.id
+                                    && menu::menu_item(
+                                        ui,
+                                        &self.theme,
+                                        ("history-compare-with-base", &commit.id),
+                                        format!("Compare with {}", &base[..base.len().min(8)]),
+                                        false,
+                                    )
+                                    .clicked()
+                                {
+                                    compare_request = Some((base, commit.id.clone()));
+                                    ui.close_menu();
+                                }
+                                if menu::menu_item(
+                                    ui,
+                                    &self.theme,
+                                    ("history-export-patch", &commit.id),
+                                    "Export as patch…",
+                                    false,
+                                )
+                                .clicked()
+                                {
+                                    patch_export = Some(commit.id.clone());
+                                    ui.close_menu();
+                                }
+                                let in_series = self.patch_series.iter().any(|c| c.id == commit.id);
+                                if menu::menu_item(
+                                    ui,
+                                    &self.theme,
+                                    ("history-patch-series", &commit.id),
+                                    "Include in patch series",
+                                    in_series,
+                                )
+                                .clicked()
+                                {
+                                    series_toggle = Some(commit.clone());
+                                    ui.close_menu();
+                                }
+                                let verdicts: &[(BisectVerdict, &str)] = if self.bisect.is_some() {
+                                    &[
+                                        (BisectVerdict::Good, "Bisect: mark good"),
+                                        (BisectVerdict::Bad, "Bisect: mark bad"),
+                                        (BisectVerdict::Skip, "Bisect: skip"),
+                                    ]
+                                } else {
+                                    &[(BisectVerdict::Bad, "Start bisect (mark bad)")]
+                                };
+                                for (verdict, label) in verdicts {
+                                    if menu::menu_item(
+                                        ui,
+                                        &self.theme,
+                                        ("history-bisect", &commit.id, *label),
+                                        *label,
+                                        false,
+                                    )
+                                    .clicked()
+                                    {
+                                        bisect_request = Some((commit.id.clone(), *verdict));
+                                        ui.close_menu();
+                                    }
+                                }
+                            },
+                        );
+                    });
+                }
+
+                if self.log_loading {
+                    self.log_progress(ui);
+                } else if !self.log_exhausted {
+                    load_more = ui.button("Load more commits").clicked();
                 }
             });
 
+        let remaining =
+            output.content_size.y - (output.state.offset.y + output.inner_rect.height());
+        if !self.log_exhausted && remaining < ROW_HEIGHT * 3.0 {
+            load_more = true;
+        }
+        if load_more {
+            self.request_page();
+        }
+
+        self.compare_base = compare_base;
+        if compare_request.is_some() {
+            self.compare_request = compare_request;
+        }
+        if let Some(commit_id) = patch_export {
+            self.export_patch(&commit_id);
+        }
+        if let Some((commit_id, verdict)) = bisect_request {
+            self.mark_bisect_commit(&commit_id, verdict);
+        }
+        if let Some(commit) = series_toggle {
+            let before = self.patch_series.len();
+            self.patch_series.retain(|c| c.id != commit.id);
+            if self.patch_series.len() == before {
+                self.patch_series.push(commit);
+                self.patch_series.sort_by_key(|c| c.time.seconds());
+            }
+        }
+
         if let Some(selected) = newly_selected {
             self.selected_commit = Some(selected);
             self.load_diff();
@@ -333,13 +672,25 @@ impl HistoryPanel {
     fn details_pane(&mut self, ui: &mut Ui) {
         ui.heading(RichText::new("Details").color(self.theme.palette.text_primary));
         ui.add_space(6.0);
+        let mut tag_request = None;
+        let mut history_request = None;
         if let Some(id) = &self.selected_commit {
-            if let Some(commit) = self.commits.iter().find(|c| &c.id == id) {
-                ui.label(
-                    RichText::new(&commit.summary)
-                        .color(self.theme.palette.text_primary)
-                        .strong(),
-                );
+            if let Some(commit) = self
+                .commits
+                .iter()
+                .chain(self.bisect_result.as_ref())
+                .find(|c| &c.id == id)
+            {
+                ui.horizontal_wrapped(|ui| {
+                    ui.label(
+                        RichText::new(&commit.summary)
+                            .color(self.theme.palette.text_primary)
+                            .strong(),
+                    );
+                    if let Some(signature) = &self.signature {
+                        self.signature_badge(ui, signature);
+                    }
+                });
                 let full_message = commit.message.trim();
                 let summary_trimmed = commit.summary.trim();
                 let message_body = if full_message.starts_with(summary_trimmed) {
@@ -365,10 +716,36 @@ impl HistoryPanel {
                         .color(self.theme.palette.text_secondary),
                     );
                 }
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.tag_name)
+                            .hint_text("v1.2.0")
+                            .desired_width(100.0),
+                    );
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.tag_message)
+                            .hint_text("Tag message")
+                            .desired_width(180.0),
+                    );
+                    if ui.button("Create annotated tag").clicked() {
+                        tag_request = Some(commit.id.clone());
+                    }
+                });
+                if let Some(status) = &self.tag_status {
+                    ui.label(RichText::new(status).color(self.theme.palette.text_secondary));
+                }
                 ui.add_space(8.0);
                 ui.separator();
                 ui.add_space(8.0);
-                ui.heading(RichText::new("Files changed").color(self.theme.palette.text_primary));
+                ui.horizontal(|ui| {
+                    ui.heading(
+                        RichText::new("Files changed").color(self.theme.palette.text_primary),
+                    );
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        self.diff_view.mode_switch(ui);
+                    });
+                });
                 if let Some(error) = &self.diff_error {
                     ui.colored_label(self.theme.palette.accent, error);
                 }
@@ -390,18 +767,24 @@ impl HistoryPanel {
                         for (idx, diff) in self.diffs.iter().enumerate() {
                             ui.push_id(idx, |ui| {
                                 ui.collapsing(
-                                    RichText::new(format!(
-                                        "{} (+{}, -{})",
-                                        diff.path, diff.additions, diff.deletions
-                                    ))
-                                    .color(self.theme.palette.text_primary),
+                                    RichText::new(file_title(diff))
+                                        .color(self.theme.palette.text_primary),
                                     |ui| {
-                                        ui.add(
-                                            egui::TextEdit::multiline(&mut diff.patch.clone())
-                                                .font(egui::TextStyle::Monospace)
-                                                .desired_width(f32::INFINITY)
-                                                .interactive(false),
-                                        );
+                                        self.diff_view.file_summary(ui, diff);
+                                        if ui.small_button("History of this file").clicked() {
+                                            history_request = Some(diff.path.clone());
+                                        }
+                                        match &self.last_repo {
+                                            Some(repo_path) if diff.binary => {
+                                                self.binary_view.show(ui, repo_path, diff);
+                                            }
+                                            _ => self.diff_view.show(
+                                                ui,
+                                                ("history-diff", &commit.id, idx),
+                                                &diff.path,
+                                                &diff.patch,
+                                            ),
+                                        }
                                     },
                                 );
                                 ui.add_space(6.0);
@@ -419,6 +802,401 @@ impl HistoryPanel {
                     .color(self.theme.palette.text_secondary),
             );
         }
+
+        if let Some(commit_id) = tag_request {
+            self.create_tag(&commit_id);
+        }
+        if let Some(path) = history_request {
+            self.show_file_history(path, None);
+        }
+    }
+
+    fn signature_badge(&self, ui: &mut Ui, signature: &SignatureState) {
+        let palette = &self.theme.palette;
+        let (label, fill, text, detail) = match signature {
+            SignatureState::Verified(detail) => (
+                "Verified",
+                palette.accent_weak,
+                palette.background,
+                detail.as_str(),
+            ),
+            SignatureState::Untrusted(detail) => (
+                "Untrusted key",
+                palette.surface_highlight,
+                palette.text_primary,
+                detail.as_str(),
+            ),
+            SignatureState::Unverified(detail) => (
+                "Unverified",
+                palette.accent,
+                palette.background,
+                detail.as_str(),
+            ),
+            SignatureState::Unknown(detail) => (
+                "Unknown signature",
+                palette.surface_highlight,
+                palette.text_primary,
+                detail.as_str(),
+            ),
+            SignatureState::Unsigned => (
+                "Unsigned",
+                palette.surface_highlight,
+                palette.text_secondary,
+                "This commit has no signature.",
+            ),
+        };
+        egui::Frame::none()
+            .fill(fill)
+            .rounding(egui::Rounding::same(6.0))
+            .inner_margin(egui::Margin::symmetric(6.0, 2.0))
+            .show(ui, |ui| {
+                ui.label(
+                    RichText::new(label)
+                        .color(text)
+                        .size(self.theme.typography.label),
+                );
+            })
+            .response
+            .on_hover_text(detail);
+    }
+
+    fn patch_series_bar(&mut self, ui: &mut Ui) {
+        if self.patch_series.is_empty() && self.patch_status.is_none() {
+            return;
+        }
+        ui.horizontal_wrapped(|ui| {
+            if !self.patch_series.is_empty() {
+                ui.label(
+                    RichText::new(format!(
+                        "{} commit(s) in patch series",
+                        self.patch_series.len()
+                    ))
+                    .color(self.theme.palette.text_primary),
+                );
+                if ui.button("Export patches…").clicked() {
+                    self.export_series(false);
+                }
+                if ui.button("Export as mbox…").clicked() {
+                    self.export_series(true);
+                }
+                if ui.button("Clear").clicked() {
+                    self.patch_series.clear();
+                }
+            }
+            if let Some(status) = &self.patch_status {
+                ui.label(RichText::new(status).color(self.theme.palette.text_secondary));
+            }
+        });
+        ui.add_space(6.0);
+    }
+
+    fn bisect_bar(&mut self, ui: &mut Ui) {
+        if self.bisect.is_none() {
+            if let Some(status) = &self.bisect_status {
+                ui.label(RichText::new(status).color(self.theme.palette.text_secondary));
+            }
+            return;
+        }
+        let palette = self.theme.palette.clone();
+        let running = self.bisect_events.is_some();
+        let mut verdict = None;
+        egui::Frame::none()
+            .fill(palette.surface)
+            .stroke(egui::Stroke::new(1.0, palette.surface_highlight))
+            .inner_margin(egui::Margin::same(8.0))
+            .rounding(6.0)
+            .show(ui, |ui| {
+                let summary = match &self.bisect_step {
+                    Some(BisectStep::Testing {
+                        commit,
+                        remaining,
+                        steps,
+                    }) => format!(
+                        "Bisecting: testing {} — {remaining} revision(s) left, about {steps} step(s)",
+                        short_id(commit)
+                    ),
+                    Some(BisectStep::Found(commit)) => {
+                        let summary = self
+                            .bisect_result
+                            .as_ref()
+                            .map(|info| info.summary.as_str())
+                            .unwrap_or_default();
+                        format!("First bad commit: {} {summary}", short_id(commit))
+                    }
+                    Some(BisectStep::Inconclusive(candidates)) => format!(
+                        "Only skipped commits remain; the first bad commit is one of: {}",
+                        candidates
+                            .iter()
+                            .map(|id| short_id(id))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                    Some(BisectStep::NeedsMarks) | None => {
+                        "Bisecting: mark a good and a bad commit from the list's context menu."
+                            .to_string()
+                    }
+                };
+                ui.label(RichText::new(summary).color(palette.text_primary));
+                ui.horizontal_wrapped(|ui| {
+                    if matches!(self.bisect_step, Some(BisectStep::Testing { .. })) {
+                        ui.add_enabled_ui(!running, |ui| {
+                            if ui.button("Good").clicked() {
+                                verdict = Some(BisectVerdict::Good);
+                            }
+                            if ui.button("Bad").clicked() {
+                                verdict = Some(BisectVerdict::Bad);
+                            }
+                            if ui.button("Skip").clicked() {
+                                verdict = Some(BisectVerdict::Skip);
+                            }
+                        });
+                        ui.add(
+                            egui::TextEdit::singleline(&mut self.bisect_command)
+                                .hint_text("Test command, e.g. cargo test")
+                                .desired_width(200.0),
+                        );
+                        if running {
+                            ui.spinner();
+                        } else if ui.button("Run automatically").clicked() {
+                            self.start_bisect_run();
+                        }
+                    }
+                    if ui
+                        .add_enabled(!running, egui::Button::new("Exit bisect"))
+                        .clicked()
+                    {
+                        self.exit_bisect();
+                    }
+                });
+                if let Some(status) = &self.bisect_status {
+                    ui.label(RichText::new(status).color(palette.text_secondary));
+                }
+            });
+        ui.add_space(6.0);
+
+        if let (Some(verdict), Some(BisectStep::Testing { commit, .. })) =
+            (verdict, self.bisect_step.clone())
+        {
+            self.mark_bisect_commit(&commit, verdict);
+        }
+    }
+
+    fn bisect_mark(&self, commit_id: &str) -> Option<&'static str> {
+        let session = self.bisect.as_ref()?;
+        let commit_id = commit_id.to_string();
+        if let Some(BisectStep::Found(found)) = &self.bisect_step
+            && found == &commit_id
+        {
+            Some("First bad commit")
+        } else if session.current.as_ref() == Some(&commit_id) {
+            Some("Bisect: testing")
+        } else if session.bad.as_ref() == Some(&commit_id) {
+            Some("Bisect: bad")
+        } else if session.good.contains(&commit_id) {
+            Some("Bisect: good")
+        } else if session.skipped.contains(&commit_id) {
+            Some("Bisect: skipped")
+        } else {
+            None
+        }
+    }
+
+    fn load_bisect_session(&mut self, repo_path: &str) {
+        self.bisect_events = None;
+        self.bisect_result = None;
+        self.bisect_status = None;
+        self.bisect = load_bisect(repo_path).unwrap_or_default();
+        self.bisect_step = None;
+        if self.bisect.is_some() {
+            match bisect_status(repo_path) {
+                Ok(step) => self.set_bisect_step(repo_path, step),
+                Err(err) => self.bisect_status = Some(format!("Bisect failed: {err}")),
+            }
+        }
+    }
+
+    fn mark_bisect_commit(&mut self, commit_id: &str, verdict: BisectVerdict) {
+        let Some(repo) = self.last_repo.clone() else {
+            return;
+        };
+        if self.bisect.is_none() {
+            match start_bisect(&repo) {
+                Ok(session) => self.bisect = Some(session),
+                Err(err) => {
+                    self.bisect_status = Some(format!("Failed to start bisect: {err}"));
+                    return;
+                }
+            }
+        }
+        match mark_bisect(&repo, commit_id, verdict) {
+            Ok((session, step)) => {
+                self.bisect = Some(session);
+                self.bisect_status = None;
+                self.set_bisect_step(&repo, step);
+            }
+            Err(err) => self.bisect_status = Some(format!("Bisect failed: {err}")),
+        }
+    }
+
+    fn set_bisect_step(&mut self, repo_path: &str, step: BisectStep) {
+        if let BisectStep::Found(commit_id) = &step {
+            self.bisect_result = find_commit_info(repo_path, commit_id).ok();
+            self.selected_commit = Some(commit_id.clone());
+            self.load_diff();
+        }
+        self.bisect_step = Some(step);
+    }
+
+    fn start_bisect_run(&mut self) {
+        let Some(repo) = self.last_repo.clone() else {
+            return;
+        };
+        let command = self.bisect_command.trim().to_string();
+        if command.is_empty() {
+            self.bisect_status = Some("Enter a test command first.".to_string());
+            return;
+        }
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let result = run_bisect(&repo, &command, |step| {
+                !matches!(step, BisectStep::Testing { .. }) || sender.send(Ok(step.clone())).is_ok()
+            });
+            let _ = sender.send(result.map_err(|err| err.to_string()));
+        });
+        self.bisect_status = Some(format!(
+            "Running `{}` on each commit…",
+            self.bisect_command.trim()
+        ));
+        self.bisect_events = Some(receiver);
+    }
+
+    fn poll_bisect(&mut self) {
+        let Some(repo) = self.last_repo.clone() else {
+            return;
+        };
+        let Some(events) = &self.bisect_events else {
+            return;
+        };
+        let events: Vec<_> = events.try_iter().collect();
+        if events.is_empty() {
+            return;
+        }
+        for event in events {
+            match event {
+                Ok(step @ BisectStep::Testing { .. }) => self.set_bisect_step(&repo, step),
+                Ok(step) => {
+                    self.bisect_events = None;
+                    self.bisect_status = None;
+                    self.set_bisect_step(&repo, step);
+                }
+                Err(err) => {
+                    self.bisect_events = None;
+                    self.bisect_status = Some(format!("Automatic bisect stopped: {err}"));
+                }
+            }
+        }
+        self.bisect = load_bisect(&repo).unwrap_or(self.bisect.take());
+    }
+
+    fn exit_bisect(&mut self) {
+        let Some(repo) = self.last_repo.clone() else {
+            return;
+        };
+        match reset_bisect(&repo) {
+            Ok(()) => {
+                self.bisect = None;
+                self.bisect_step = None;
+                self.bisect_result = None;
+                self.bisect_status = Some("Bisect finished; original HEAD restored.".to_string());
+                self.pending_refresh = true;
+            }
+            Err(err) => self.bisect_status = Some(format!("Failed to exit bisect: {err}")),
+        }
+    }
+
+    fn export_patch(&mut self, commit_id: &str) {
+        let Some(repo) = self.last_repo.clone() else {
+            return;
+        };
+        let ids = vec![commit_id.to_string()];
+        let file_name = match format_patches(&repo, &ids) {
+            Ok(patches) => patches
+                .into_iter()
+                .next()
+                .map(|patch| patch.file_name)
+                .unwrap_or_default(),
+            Err(err) => {
+                self.patch_status = Some(format!("Failed to export patch: {err}"));
+                return;
+            }
+        };
+        let Some(path) = FileDialog::new()
+            .add_filter("Patch", &["patch", "mbox"])
+            .set_file_name(&file_name)
+            .save_file()
+        else {
+            return;
+        };
+        self.patch_status = Some(match write_mbox(&repo, &ids, &path) {
+            Ok(()) => format!("Exported patch to {}", path.display()),
+            Err(err) => format!("Failed to export patch: {err}"),
+        });
+    }
+
+    fn export_series(&mut self, as_mbox: bool) {
+        let Some(repo) = self.last_repo.clone() else {
+            return;
+        };
+        let ids: Vec<String> = self.patch_series.iter().map(|c| c.id.clone()).collect();
+        let result = if as_mbox {
+            let Some(path) = FileDialog::new()
+                .add_filter("Mailbox", &["mbox", "patch"])
+                .set_file_name("series.mbox")
+                .save_file()
+            else {
+                return;
+            };
+            write_mbox(&repo, &ids, &path)
+                .map(|()| format!("Exported {} patch(es) to {}", ids.len(), path.display()))
+        } else {
+            let Some(dir) = FileDialog::new().pick_folder() else {
+                return;
+            };
+            write_patch_series(&repo, &ids, &dir)
+                .map(|paths| format!("Wrote {} patch file(s) to {}", paths.len(), dir.display()))
+        };
+        self.patch_status = Some(match result {
+            Ok(status) => {
+                self.patch_series.clear();
+                status
+            }
+            Err(err) => format!("Failed to export patches: {err}"),
+        });
+    }
+
+    fn create_tag(&mut self, commit_id: &str) {
+        let Some(repo) = self.last_repo.clone() else {
+            return;
+        };
+        let name = self.tag_name.trim().to_string();
+        if name.is_empty() {
+            self.tag_status = Some("Enter a tag name first.".to_string());
+            return;
+        }
+        let message = if self.tag_message.trim().is_empty() {
+            name.clone()
+        } else {
+            self.tag_message.clone()
+        };
+        self.tag_status = Some(
+            match create_annotated_tag(&repo, &name, commit_id, &message) {
+                Ok(tag) if tag.signed => format!("Created signed tag {name}"),
+                Ok(_) => format!("Created tag {name}"),
+                Err(err) => format!("Failed to create tag: {err}"),
+            },
+        );
+        self.tag_name.clear();
+        self.tag_message.clear();
     }
 
     fn refresh(&mut self, repo: &RepoContext) {
@@ -446,18 +1224,242 @@ impl HistoryPanel {
             },
             since: parse_date(&self.filters.since),
             until: parse_date(&self.filters.until),
+            path: if self.filters.path.trim().is_empty() {
+                None
+            } else {
+                Some(self.filters.path.trim().to_string())
+            },
+            follow: self.filters.follow,
         };
 
-        match read_commit_log(&repo.path, &filter, MAX_COMMITS, false) {
-            Ok(commits) => self.commits = commits,
-            Err(err) => self.error = Some(format!("Failed to read commits: {err}")),
+        self.commits.clear();
+        self.log_scanned = 0;
+        self.log_exhausted = false;
+        self.log_loading = false;
+
+        // Replacing the channels stops the previous walker on its next send.
+        let (request_tx, request_rx) = mpsc::channel();
+        let (event_tx, event_rx) = mpsc::channel();
+        self.log_requests = Some(request_tx);
+        self.log_events = Some(event_rx);
+        let repo_path = repo.path.clone();
+        std::thread::spawn(move || {
+            let result = stream_commit_log(&repo_path, filter, request_rx, |event| {
+                event_tx.send(Ok(event)).is_ok()
+            });
+            if let Err(err) = result {
+                let _ = event_tx.send(Err(err.to_string()));
+            }
+        });
+        self.request_page();
+    }
+
+    pub fn update_search_index(&mut self, repo_path: &str) {
+        if self.index_promise.is_some() || self.index_update.is_some() {
+            self.index_update_queued = true;
+            return;
+        }
+
+        let repo_path = repo_path.to_string();
+        let index = self
+            .search_index
+            .take()
+            .filter(|index| index.repo_path == repo_path);
+        self.index_update = Some(Promise::spawn_thread("commit_index_update", move || {
+            let index_dir = app_data_dir().join(SEARCH_INDEX_DIR);
+            let mut index = index.unwrap_or_else(|| load_commit_index(&index_dir, &repo_path));
+            update_commit_index(&index_dir, &repo_path, &mut index)
+                .map_err(|err| format!("Failed to update the commit index: {err}"))?;
+            Ok(index)
+        }));
+    }
+
+    fn poll_index_update(&mut self, repo: &RepoContext) {
+        let Some(promise) = self.index_update.take() else {
+            return;
+        };
+        match promise.try_take() {
+            Ok(Ok(index)) if index.repo_path == repo.path => self.search_index = Some(index),
+            Ok(Ok(_)) => {}
+            Ok(Err(err)) => self.index_status = Some(err),
+            Err(promise) => {
+                self.index_update = Some(promise);
+                return;
+            }
+        }
+
+        if std::mem::take(&mut self.index_update_queued) {
+            self.update_search_index(&repo.path);
+        }
+        if std::mem::take(&mut self.index_rerun) {
+            self.run_index_search(repo);
+        }
+    }
+
+    // Verification shells out to gpg or ssh-keygen, which may wait on a
+    // keyserver or agent, so it runs off the UI thread.
+    fn check_signature(&mut self, repo_path: String, commit: String) {
+        self.signature = Some(SignatureState::Unknown(
+            "Checking the signature...".to_string(),
+        ));
+        self.signature_check = Some(Promise::spawn_thread("verify_signature", move || {
+            let state = verify_commit_signature(&repo_path, &commit).ok();
+            (commit, state)
+        }));
+    }
+
+    fn poll_signature_check(&mut self) {
+        let Some(promise) = self.signature_check.take() else {
+            return;
+        };
+        match promise.try_take() {
+            Ok((commit, state)) => {
+                if self.selected_commit.as_deref() == Some(commit.as_str()) {
+                    self.signature = state;
+                }
+            }
+            Err(promise) => self.signature_check = Some(promise),
+        }
+    }
+
+    fn run_index_search(&mut self, repo: &RepoContext) {
+        if self.index_promise.is_some() || self.index_update.is_some() {
+            self.index_rerun = true;
+            return;
+        }
+        if self.index_query.trim().is_empty() {
+            self.index_status = None;
+            self.refresh(repo);
+            return;
+        }
+        let query = match SearchQuery::parse(&self.index_query) {
+            Ok(query) => query,
+            Err(err) => {
+                self.index_status = Some(err.message().to_string());
+                return;
+            }
+        };
+
+        let repo_path = repo.path.clone();
+        let index = self.search_index.take();
+        self.index_promise = Some(Promise::spawn_thread("commit_index_search", move || {
+            let index_dir = app_data_dir().join(SEARCH_INDEX_DIR);
+            let mut index = index.unwrap_or_else(|| load_commit_index(&index_dir, &repo_path));
+            let added = update_commit_index(&index_dir, &repo_path, &mut index)
+                .map_err(|err| format!("Failed to update the commit index: {err}"))?;
+            let results = search_commit_index(&repo_path, &index, &query, SEARCH_LIMIT)
+                .map_err(|err| format!("Search failed: {err}"))?;
+            Ok((index, results, added))
+        }));
+    }
+
+    fn poll_index_search(&mut self, repo: &RepoContext) {
+        let Some(promise) = self.index_promise.take() else {
+            return;
+        };
+        let result = match promise.try_take() {
+            Ok(result) => result,
+            Err(promise) => {
+                self.index_promise = Some(promise);
+                return;
+            }
+        };
+
+        match result {
+            Ok((index, results, added)) => {
+                let mut status = format!("{} matching commits", results.len());
+                if added > 0 {
+                    status.push_str(&format!(" • indexed {added} new commits"));
+                }
+                self.search_index = Some(index);
+                self.index_status = Some(status);
+                self.commits = results;
+                self.selected_commit = None;
+                self.diffs.clear();
+                self.log_requests = None;
+                self.log_events = None;
+                self.log_loading = false;
+                self.log_exhausted = true;
+            }
+            Err(err) => self.index_status = Some(err),
+        }
+
+        if std::mem::take(&mut self.index_update_queued) {
+            self.update_search_index(&repo.path);
+        }
+        if std::mem::take(&mut self.index_rerun) {
+            self.run_index_search(repo);
         }
     }
 
+    fn request_page(&mut self) {
+        if self.log_loading || self.log_exhausted {
+            return;
+        }
+        if let Some(requests) = &self.log_requests
+            && requests.send(PAGE_SIZE).is_ok()
+        {
+            self.log_loading = true;
+        }
+    }
+
+    fn poll_log(&mut self) {
+        let Some(events) = &self.log_events else {
+            return;
+        };
+        for event in events.try_iter() {
+            match event {
+                Ok(LogEvent::Progress { scanned }) => self.log_scanned = scanned,
+                Ok(LogEvent::Page {
+                    commits,
+                    scanned,
+                    exhausted,
+                }) => {
+                    self.commits.extend(commits);
+                    self.log_scanned = scanned;
+                    self.log_exhausted = exhausted;
+                    self.log_loading = false;
+                }
+                Err(err) => {
+                    self.error = Some(format!("Failed to read commits: {err}"));
+                    self.log_loading = false;
+                    self.log_exhausted = true;
+                }
+            }
+        }
+    }
+
+    fn log_progress(&self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.spinner();
+            let text = if self.log_scanned == 0 {
+                "Loading commits...".to_string()
+            } else {
+                format!(
+                    "Scanned {} commits, {} matching so far...",
+                    self.log_scanned,
+                    self.commits.len()
+                )
+            };
+            ui.label(RichText::new(text).color(self.theme.palette.text_secondary));
+        });
+    }
+
     fn load_diff(&mut self) {
         if let Some(repo) = self.last_repo.clone() {
-            if let Some(commit) = &self.selected_commit {
-                match commit_diff(&repo, commit) {
+            if let Some(commit) = self.selected_commit.clone() {
+                self.check_signature(repo.clone(), commit.clone());
+                self.tag_status = None;
+                let path = self
+                    .commits
+                    .iter()
+                    .find(|info| info.id == commit)
+                    .and_then(|info| info.path.clone());
+                let diffs = match &path {
+                    Some(path) => commit_path_diff(&repo, &commit, path),
+                    None => commit_diff(&repo, &commit),
+                };
+                match diffs {
                     Ok(diffs) => {
                         self.diffs = diffs;
                         self.diff_error = None;
@@ -472,6 +1474,10 @@ impl HistoryPanel {
     }
 }
 
+fn short_id(id: &str) -> &str {
+    &id[..id.len().min(8)]
+}
+
 fn parse_date(input: &str) -> Option<i64> {
     if input.trim().is_empty() {
         return None;
@@ -21,6 +21,12 @@ pub enum MainTab {
     DevGallery,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HeaderAction {
+    SwitchProfile(String),
+    ManageProfiles,
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum NavigationTrigger {
     Click,
@@ -89,7 +95,13 @@ impl<'a> ShellLayout<'a> {
         Self { theme }
     }
 
-    pub fn header(&self, ctx: &egui::Context) {
+    pub fn header(
+        &self,
+        ctx: &egui::Context,
+        profiles: &[String],
+        active_profile: Option<&str>,
+    ) -> Option<HeaderAction> {
+        let mut action = None;
         egui::TopBottomPanel::top("header")
             .exact_height(48.0)
             .frame(egui::Frame::none().fill(self.theme.palette.surface))
@@ -102,8 +114,58 @@ impl<'a> ShellLayout<'a> {
                             .strong(),
                     );
                     ui.colored_label(self.theme.palette.accent, RichText::new("Workspace shell"));
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        ui.add_space(8.0);
+                        action = self.profile_switcher(ui, profiles, active_profile);
+                    });
+                });
+            });
+        action
+    }
+
+    fn profile_switcher(
+        &self,
+        ui: &mut Ui,
+        profiles: &[String],
+        active_profile: Option<&str>,
+    ) -> Option<HeaderAction> {
+        let mut action = None;
+        let icon_id = ui.make_persistent_id("header-profile-icon");
+        egui::ComboBox::from_id_source("header-profile")
+            .selected_text(active_profile.unwrap_or("No profile"))
+            .icon(menu::combo_icon(self.theme.clone(), icon_id))
+            .show_ui(ui, |ui| {
+                menu::with_menu_popup_motion(ui, "header-profile-menu", |ui| {
+                    for name in profiles {
+                        if menu::menu_item(
+                            ui,
+                            self.theme,
+                            ("header-profile-item", name),
+                            name.as_str(),
+                            active_profile == Some(name.as_str()),
+                        )
+                        .clicked()
+                        {
+                            action = Some(HeaderAction::SwitchProfile(name.clone()));
+                        }
+                    }
+                    if !profiles.is_empty() {
+                        ui.separator();
+                    }
+                    if menu::menu_item(
+                        ui,
+                        self.theme,
+                        "header-profile-manage",
+                        "Manage profiles...",
+                        false,
+                    )
+                    .clicked()
+                    {
+                        action = Some(HeaderAction::ManageProfiles);
+                    }
                 });
             });
+        action
     }
 
     pub fn sidebar(&self, ctx: &egui::Context, active_tab: MainTab) -> Option<NavigationSelection> {
@@ -411,7 +473,7 @@ impl<'a> ShellLayout<'a> {
                 None
             }
             MainTab::Settings => {
-                settings_panel.ui(ui, notifications);
+                settings_panel.ui(ui, auth_manager, notifications);
                 None
             }
             MainTab::DevGallery => {
@@ -0,0 +1,304 @@
+use eframe::egui::{self, Margin, RichText, Ui};
+use poll_promise::Promise;
+
+use crate::auth::AuthManager;
+use crate::config::NetworkOptions;
+use crate::error::AppError;
+use crate::git::lfs::{
+    LfsFetchSummary, LfsFile, fetch_lfs_objects, lfs_tracked_patterns, list_lfs_files,
+    pull_lfs_objects, track_lfs_pattern, untrack_lfs_pattern,
+};
+use crate::git::remote::list_remotes;
+use crate::ui::{context::RepoContext, diff_view::format_size, theme::Theme};
+
+enum LfsAction {
+    Track(String),
+    Untrack(String),
+    Fetch,
+    Pull,
+}
+
+pub struct LfsPanel {
+    theme: Theme,
+    last_repo: Option<String>,
+    patterns: Vec<String>,
+    files: Vec<LfsFile>,
+    new_pattern: String,
+    needs_refresh: bool,
+    error: Option<String>,
+    status: Option<String>,
+    transfer_promise: Option<Promise<Result<LfsFetchSummary, AppError>>>,
+}
+
+impl LfsPanel {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            theme,
+            last_repo: None,
+            patterns: Vec::new(),
+            files: Vec::new(),
+            new_pattern: String::new(),
+            needs_refresh: true,
+            error: None,
+            status: None,
+            transfer_promise: None,
+        }
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    pub fn mark_stale(&mut self) {
+        self.needs_refresh = true;
+    }
+
+    pub fn ui(
+        &mut self,
+        ui: &mut Ui,
+        repo: &RepoContext,
+        auth: &AuthManager,
+        network: &NetworkOptions,
+    ) {
+        self.poll_transfer();
+        self.refresh_if_needed(repo);
+
+        ui.heading(RichText::new("Git LFS").color(self.theme.palette.text_primary));
+        ui.add_space(4.0);
+
+        if let Some(error) = &self.error {
+            ui.colored_label(self.theme.palette.accent, error);
+        }
+        if let Some(status) = &self.status {
+            ui.label(RichText::new(status).color(self.theme.palette.text_secondary));
+        }
+
+        let mut action = self.pattern_editor(ui);
+        ui.add_space(6.0);
+        if let Some(files_action) = self.file_summary(ui) {
+            action = Some(files_action);
+        }
+
+        if let Some(action) = action {
+            self.run_action(repo, auth, network, action);
+        }
+    }
+
+    fn refresh_if_needed(&mut self, repo: &RepoContext) {
+        if self.last_repo.as_deref() != Some(&repo.path) {
+            self.last_repo = Some(repo.path.clone());
+            self.status = None;
+            self.needs_refresh = true;
+        }
+
+        if !self.needs_refresh {
+            return;
+        }
+        self.needs_refresh = false;
+        self.error = None;
+
+        let loaded = lfs_tracked_patterns(&repo.path)
+            .and_then(|patterns| Ok((patterns, list_lfs_files(&repo.path)?)));
+        match loaded {
+            Ok((patterns, files)) => {
+                self.patterns = patterns;
+                self.files = files;
+            }
+            Err(err) => {
+                self.patterns.clear();
+                self.files.clear();
+                self.error = Some(format!("Failed to read LFS state: {err}"));
+            }
+        }
+    }
+
+    fn pattern_editor(&mut self, ui: &mut Ui) -> Option<LfsAction> {
+        let mut action = None;
+        if self.patterns.is_empty() {
+            ui.label(
+                RichText::new("No patterns are tracked with Git LFS.")
+                    .color(self.theme.palette.text_secondary),
+            );
+        }
+        ui.horizontal_wrapped(|ui| {
+            for pattern in &self.patterns {
+                egui::Frame::none()
+                    .fill(self.theme.palette.surface)
+                    .stroke(egui::Stroke::new(1.0, self.theme.palette.surface_highlight))
+                    .rounding(6.0)
+                    .inner_margin(Margin::symmetric(6.0, 2.0))
+                    .show(ui, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.label(RichText::new(pattern).color(self.theme.palette.text_primary));
+                            if ui.small_button("✕").on_hover_text("Untrack").clicked() {
+                                action = Some(LfsAction::Untrack(pattern.clone()));
+                            }
+                        });
+                    });
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.new_pattern)
+                    .hint_text("*.psd")
+                    .desired_width(160.0),
+            );
+            if ui.button("Track pattern").clicked() {
+                action = Some(LfsAction::Track(self.new_pattern.trim().to_string()));
+            }
+        });
+        action
+    }
+
+    fn file_summary(&self, ui: &mut Ui) -> Option<LfsAction> {
+        let mut action = None;
+        let busy = self.transfer_promise.is_some();
+        let downloaded = self.files.iter().filter(|file| file.downloaded).count();
+        let total: u64 = self.files.iter().map(|file| file.pointer.size).sum();
+
+        ui.label(
+            RichText::new(format!(
+                "{} LFS file(s) in HEAD • {downloaded} downloaded • {}",
+                self.files.len(),
+                format_size(total)
+            ))
+            .color(self.theme.palette.text_secondary),
+        );
+        ui.horizontal(|ui| {
+            ui.add_enabled_ui(!busy && !self.files.is_empty(), |ui| {
+                if ui.button("Fetch LFS objects").clicked() {
+                    action = Some(LfsAction::Fetch);
+                }
+                if ui.button("Pull LFS objects").clicked() {
+                    action = Some(LfsAction::Pull);
+                }
+            });
+            if busy {
+                ui.spinner();
+                ui.ctx().request_repaint();
+            }
+        });
+
+        if !self.files.is_empty() {
+            ui.collapsing(
+                RichText::new("LFS files").color(self.theme.palette.text_primary),
+                |ui| {
+                    for file in &self.files {
+                        ui.horizontal(|ui| {
+                            ui.label(
+                                RichText::new(&file.path).color(self.theme.palette.text_primary),
+                            );
+                            ui.label(
+                                RichText::new(format_size(file.pointer.size))
+                                    .color(self.theme.palette.text_secondary),
+                            );
+                            if !file.downloaded {
+                                ui.label(
+                                    RichText::new("not downloaded")
+                                        .color(self.theme.palette.accent),
+                                );
+                            }
+                        });
+                    }
+                },
+            );
+        }
+        action
+    }
+
+    fn run_action(
+        &mut self,
+        repo: &RepoContext,
+        auth: &AuthManager,
+        network: &NetworkOptions,
+        action: LfsAction,
+    ) {
+        self.status = None;
+        self.error = None;
+        let result = match action {
+            LfsAction::Track(pattern) => track_lfs_pattern(&repo.path, &pattern).map(|added| {
+                self.new_pattern.clear();
+                if added {
+                    format!("Tracking {pattern} with Git LFS")
+                } else {
+                    format!("{pattern} is already tracked")
+                }
+            }),
+            LfsAction::Untrack(pattern) => untrack_lfs_pattern(&repo.path, &pattern)
+                .map(|_| format!("Stopped tracking {pattern} with Git LFS")),
+            LfsAction::Fetch | LfsAction::Pull => {
+                self.begin_transfer(repo, auth, network, matches!(action, LfsAction::Pull));
+                return;
+            }
+        };
+
+        self.needs_refresh = true;
+        match result {
+            Ok(message) => self.status = Some(message),
+            Err(err) => self.error = Some(err.to_string()),
+        }
+    }
+
+    fn begin_transfer(
+        &mut self,
+        repo: &RepoContext,
+        auth: &AuthManager,
+        network: &NetworkOptions,
+        checkout: bool,
+    ) {
+        let remote = match list_remotes(&repo.path) {
+            Ok(remotes) => remotes
+                .iter()
+                .find(|remote| remote.name == "origin")
+                .or(remotes.first())
+                .cloned(),
+            Err(err) => {
+                self.error = Some(format!("Failed to read remotes: {err}"));
+                return;
+            }
+        };
+        let Some(remote) = remote else {
+            self.error = Some("No remotes configured for this repository.".to_string());
+            return;
+        };
+        let token = auth.resolve_for_repo(&repo.path, &remote.url);
+        let repo_path = repo.path.clone();
+        let network = network.clone();
+
+        self.transfer_promise = Some(Promise::spawn_thread("lfs_transfer", move || {
+            if checkout {
+                pull_lfs_objects(&repo_path, &remote.name, &network, token)
+            } else {
+                fetch_lfs_objects(&repo_path, &remote.name, &network, token)
+            }
+        }));
+    }
+
+    fn poll_transfer(&mut self) {
+        let Some(result) = self.transfer_promise.as_ref().and_then(|p| p.ready()) else {
+            return;
+        };
+        match result {
+            Ok(summary) => {
+                let mut message = format!(
+                    "Downloaded {} object(s), {} already present",
+                    summary.downloaded, summary.already_present
+                );
+                if summary.checked_out > 0 {
+                    message.push_str(&format!(", checked out {} file(s)", summary.checked_out));
+                }
+                self.status = Some(message);
+                if let Some((oid, reason)) = summary.failed.first() {
+                    self.error = Some(format!(
+                        "{} object(s) failed; {}: {reason}",
+                        summary.failed.len(),
+                        &oid[..oid.len().min(12)]
+                    ));
+                }
+            }
+            Err(err) => self.error = Some(err.to_string()),
+        }
+        self.transfer_promise = None;
+        self.needs_refresh = true;
+    }
+}
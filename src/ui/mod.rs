@@ -1,21 +1,29 @@
 pub mod animation;
 pub mod app;
 pub mod auth;
+pub mod binary_view;
 pub mod branches;
 pub mod clone;
+pub mod compare;
 pub mod context;
 pub mod dev_gallery;
+pub mod diff_view;
 pub mod fonts;
 pub mod history;
 pub mod layout;
+pub mod lfs;
 pub mod menu;
 pub mod notifications;
 pub mod perf;
 pub mod recent;
+pub mod reflog;
 pub mod repo_overview;
 pub mod settings;
 pub mod stage;
+pub mod submodules;
 pub mod theme;
+pub mod unlock;
+pub mod worktrees;
 
 #[cfg(test)]
 mod tests;
@@ -14,6 +14,8 @@ pub enum NotificationAction {
     RetryClone,
     CopyLogPath(PathBuf),
     OpenRelease(String),
+    InstallUpdate,
+    RestartToUpdate,
     EnableTelemetry,
     DeclineTelemetry,
 }
@@ -153,6 +155,17 @@ impl NotificationCenter {
                                                     ));
                                                 }
                                             }
+                                            NotificationAction::InstallUpdate => {
+                                                if ui.button("Install update").clicked() {
+                                                    actions.push(NotificationAction::InstallUpdate);
+                                                }
+                                            }
+                                            NotificationAction::RestartToUpdate => {
+                                                if ui.button("Restart to update").clicked() {
+                                                    actions
+                                                        .push(NotificationAction::RestartToUpdate);
+                                                }
+                                            }
                                             NotificationAction::EnableTelemetry => {
                                                 if ui.button("Enable analytics").clicked() {
                                                     actions
@@ -0,0 +1,445 @@
+use chrono::Utc;
+use eframe::egui::{self, ComboBox, RichText, Sense, Ui, Window};
+
+use crate::git::branch::{checkout_branch, create_branch};
+use crate::git::reflog::{
+    ArchivedBranch, ReflogEntry, ResetMode, list_archived_branches, list_reflog_refs, read_reflog,
+    reset_head_to,
+};
+use crate::ui::{context::RepoContext, menu, theme::Theme};
+
+const RESET_MODES: [(ResetMode, &str); 3] = [
+    (ResetMode::Soft, "Soft"),
+    (ResetMode::Mixed, "Mixed"),
+    (ResetMode::Hard, "Hard"),
+];
+
+enum ReflogAction {
+    CreateBranch(String),
+    Checkout(String),
+    Reset(String, ResetMode),
+    RestoreArchive(ArchivedBranch),
+}
+
+pub struct ReflogPanel {
+    theme: Theme,
+    last_repo: Option<String>,
+    refs: Vec<String>,
+    selected_ref: String,
+    entries: Vec<ReflogEntry>,
+    archived: Vec<ArchivedBranch>,
+    branch_name: String,
+    reset_mode: ResetMode,
+    pending_hard_reset: Option<String>,
+    needs_refresh: bool,
+    error: Option<String>,
+    status: Option<String>,
+}
+
+impl ReflogPanel {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            theme,
+            last_repo: None,
+            refs: Vec::new(),
+            selected_ref: "HEAD".to_string(),
+            entries: Vec::new(),
+            archived: Vec::new(),
+            branch_name: String::new(),
+            reset_mode: ResetMode::Mixed,
+            pending_hard_reset: None,
+            needs_refresh: true,
+            error: None,
+            status: None,
+        }
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    pub fn mark_stale(&mut self) {
+        self.needs_refresh = true;
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui, repo: &RepoContext) -> bool {
+        self.refresh_if_needed(repo);
+
+        ui.heading(RichText::new("Reflog & recovery").color(self.theme.palette.text_primary));
+        ui.label(
+            RichText::new(
+                "Find lost commits after a reset or branch deletion and bring them back.",
+            )
+            .color(self.theme.palette.text_secondary),
+        );
+        ui.add_space(6.0);
+
+        if let Some(error) = &self.error {
+            ui.colored_label(self.theme.palette.accent, error);
+        }
+        if let Some(status) = &self.status {
+            ui.label(RichText::new(status).color(self.theme.palette.text_secondary));
+        }
+
+        self.controls(ui);
+        ui.add_space(6.0);
+
+        let mut action = self.entry_list(ui);
+        ui.add_space(8.0);
+        if let Some(archive_action) = self.archive_list(ui) {
+            action = Some(archive_action);
+        }
+
+        if let Some(ReflogAction::Reset(commit_id, ResetMode::Hard)) = &action {
+            self.pending_hard_reset = Some(commit_id.clone());
+            action = None;
+        }
+        if let Some(confirmed) = self.hard_reset_confirmation(ui) {
+            action = Some(confirmed);
+        }
+
+        match action {
+            Some(action) => self.run_action(repo, action),
+            None => false,
+        }
+    }
+
+    fn refresh_if_needed(&mut self, repo: &RepoContext) {
+        if self.last_repo.as_deref() != Some(&repo.path) {
+            self.last_repo = Some(repo.path.clone());
+            self.selected_ref = "HEAD".to_string();
+            self.status = None;
+            self.needs_refresh = true;
+        }
+
+        if !self.needs_refresh {
+            return;
+        }
+        self.needs_refresh = false;
+        self.error = None;
+
+        match list_reflog_refs(&repo.path) {
+            Ok(refs) => self.refs = refs,
+            Err(err) => self.error = Some(format!("Failed to list reflogs: {err}")),
+        }
+        if !self.refs.contains(&self.selected_ref) {
+            self.selected_ref = "HEAD".to_string();
+        }
+
+        match read_reflog(&repo.path, &self.selected_ref) {
+            Ok(entries) => self.entries = entries,
+            Err(err) => {
+                self.entries.clear();
+                self.error = Some(format!("Failed to read reflog: {err}"));
+            }
+        }
+
+        match list_archived_branches(&repo.path) {
+            Ok(archived) => self.archived = archived,
+            Err(err) => {
+                self.archived.clear();
+                self.error
+                    .get_or_insert_with(|| format!("Failed to read archive tags: {err}"));
+            }
+        }
+    }
+
+    fn controls(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("Reference").color(self.theme.palette.text_secondary));
+            let icon_id = ui.make_persistent_id("reflog-ref-icon");
+            ComboBox::from_id_source("reflog_ref")
+                .selected_text(display_ref(&self.selected_ref))
+                .icon(menu::combo_icon(self.theme.clone(), icon_id))
+                .show_ui(ui, |ui| {
+                    menu::with_menu_popup_motion(ui, "reflog-ref-menu", |ui| {
+                        for reference in &self.refs {
+                            if menu::menu_item(
+                                ui,
+                                &self.theme,
+                                ("reflog-ref-item", reference),
+                                display_ref(reference),
+                                &self.selected_ref == reference,
+                            )
+                            .clicked()
+                            {
+                                self.selected_ref = reference.clone();
+                                self.needs_refresh = true;
+                            }
+                        }
+                    });
+                });
+
+            ui.label(RichText::new("Reset mode").color(self.theme.palette.text_secondary));
+            let icon_id = ui.make_persistent_id("reflog-reset-icon");
+            let selected_label = RESET_MODES
+                .iter()
+                .find(|(mode, _)| *mode == self.reset_mode)
+                .map(|(_, label)| *label)
+                .unwrap_or("Mixed");
+            ComboBox::from_id_source("reflog_reset_mode")
+                .selected_text(selected_label)
+                .icon(menu::combo_icon(self.theme.clone(), icon_id))
+                .show_ui(ui, |ui| {
+                    menu::with_menu_popup_motion(ui, "reflog-reset-menu", |ui| {
+                        for (mode, label) in RESET_MODES {
+                            if menu::menu_item(
+                                ui,
+                                &self.theme,
+                                ("reflog-reset-item", label),
+                                label,
+                                self.reset_mode == mode,
+                            )
+                            .clicked()
+                            {
+                                self.reset_mode = mode;
+                            }
+                        }
+                    });
+                });
+
+            if ui.button("Reload").clicked() {
+                self.needs_refresh = true;
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("Recovery branch").color(self.theme.palette.text_secondary));
+            ui.add(
+                egui::TextEdit::singleline(&mut self.branch_name).hint_text("recovered/my-work"),
+            );
+        });
+    }
+
+    fn entry_list(&self, ui: &mut Ui) -> Option<ReflogAction> {
+        let mut action = None;
+
+        if self.entries.is_empty() {
+            ui.label(
+                RichText::new("No reflog entries recorded for this reference.")
+                    .color(self.theme.palette.text_secondary),
+            );
+            return None;
+        }
+
+        egui::ScrollArea::vertical()
+            .id_source("reflog_entries")
+            .auto_shrink([false, false])
+            .max_height(260.0)
+            .show(ui, |ui| {
+                for entry in &self.entries {
+                    let archived_as = self
+                        .archived
+                        .iter()
+                        .find(|archived| archived.commit_id == entry.new_id);
+                    let response = ui
+                        .vertical(|ui| {
+                            ui.horizontal(|ui| {
+                                ui.label(
+                                    RichText::new(format!(
+                                        "{}@{{{}}}",
+                                        display_ref(&entry.reference),
+                                        entry.index
+                                    ))
+                                    .color(self.theme.palette.accent)
+                                    .strong(),
+                                );
+                                ui.label(
+                                    RichText::new(&entry.message)
+                                        .color(self.theme.palette.text_primary),
+                                );
+                            });
+                            ui.label(
+                                RichText::new(format!(
+                                    "{} → {} • {} • {}",
+                                    short_id(&entry.old_id),
+                                    short_id(&entry.new_id),
+                                    entry.committer,
+                                    format_time(entry.time.seconds())
+                                ))
+                                .color(self.theme.palette.text_secondary),
+                            );
+                            if let Some(archived) = archived_as {
+                                ui.label(
+                                    RichText::new(format!("Archived as {}", archived.tag))
+                                        .color(self.theme.palette.text_secondary)
+                                        .italics(),
+                                );
+                            }
+                        })
+                        .response
+                        .interact(Sense::click());
+
+                    response.context_menu(|ui| {
+                        menu::with_menu_popup_motion(
+                            ui,
+                            ("reflog-entry-menu", &entry.reference, entry.index),
+                            |ui| {
+                                if menu::menu_item(
+                                    ui,
+                                    &self.theme,
+                                    ("reflog-branch", entry.index),
+                                    "Create branch here",
+                                    false,
+                                )
+                                .clicked()
+                                {
+                                    action = Some(ReflogAction::CreateBranch(entry.new_id.clone()));
+                                    ui.close_menu();
+                                }
+                                if menu::menu_item(
+                                    ui,
+                                    &self.theme,
+                                    ("reflog-checkout", entry.index),
+                                    "Checkout (detached)",
+                                    false,
+                                )
+                                .clicked()
+                                {
+                                    action = Some(ReflogAction::Checkout(entry.new_id.clone()));
+                                    ui.close_menu();
+                                }
+                                if menu::menu_item(
+                                    ui,
+                                    &self.theme,
+                                    ("reflog-reset", entry.index),
+                                    "Reset current branch here",
+                                    false,
+                                )
+                                .clicked()
+                                {
+                                    action = Some(ReflogAction::Reset(
+                                        entry.new_id.clone(),
+                                        self.reset_mode,
+                                    ));
+                                    ui.close_menu();
+                                }
+                            },
+                        );
+                    });
+                    ui.separator();
+                }
+            });
+
+        action
+    }
+
+    fn archive_list(&self, ui: &mut Ui) -> Option<ReflogAction> {
+        ui.label(
+            RichText::new("Archived branches")
+                .color(self.theme.palette.text_primary)
+                .strong(),
+        );
+
+        if self.archived.is_empty() {
+            ui.label(
+                RichText::new("No archive tags found. Archive a branch before deleting it.")
+                    .color(self.theme.palette.text_secondary),
+            );
+            return None;
+        }
+
+        let mut action = None;
+        for archived in &self.archived {
+            ui.horizontal(|ui| {
+                ui.label(
+                    RichText::new(&archived.tag)
+                        .color(self.theme.palette.text_primary)
+                        .strong(),
+                );
+                ui.label(
+                    RichText::new(short_id(&archived.commit_id))
+                        .color(self.theme.palette.text_secondary),
+                );
+                if ui.button("Restore branch").clicked() {
+                    action = Some(ReflogAction::RestoreArchive(archived.clone()));
+                }
+            });
+        }
+        action
+    }
+
+    fn hard_reset_confirmation(&mut self, ui: &mut Ui) -> Option<ReflogAction> {
+        let commit_id = self.pending_hard_reset.clone()?;
+        let mut open = true;
+        let mut confirmed = false;
+        let mut cancelled = false;
+
+        Window::new("Hard reset")
+            .open(&mut open)
+            .collapsible(false)
+            .show(ui.ctx(), |ui| {
+                ui.label(
+                    RichText::new(format!(
+                        "Reset the current branch to {} and discard all uncommitted changes?",
+                        short_id(&commit_id)
+                    ))
+                    .color(self.theme.palette.text_secondary),
+                );
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui.button("Discard changes and reset").clicked() {
+                        confirmed = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        cancelled = true;
+                    }
+                });
+            });
+
+        if confirmed || cancelled || !open {
+            self.pending_hard_reset = None;
+        }
+        confirmed.then_some(ReflogAction::Reset(commit_id, ResetMode::Hard))
+    }
+
+    fn run_action(&mut self, repo: &RepoContext, action: ReflogAction) -> bool {
+        self.status = None;
+        self.error = None;
+        let result = match action {
+            ReflogAction::CreateBranch(commit_id) => {
+                let name = self.branch_name.trim().to_string();
+                if name.is_empty() {
+                    self.error = Some("Enter a recovery branch name first.".to_string());
+                    return false;
+                }
+                create_branch(&repo.path, &name, Some(&commit_id))
+                    .map(|_| format!("Created {name} at {}", short_id(&commit_id)))
+            }
+            ReflogAction::Checkout(commit_id) => checkout_branch(&repo.path, &commit_id)
+                .map(|_| format!("Checked out {} (detached HEAD)", short_id(&commit_id))),
+            ReflogAction::Reset(commit_id, mode) => reset_head_to(&repo.path, &commit_id, mode)
+                .map(|_| format!("Reset current branch to {}", short_id(&commit_id))),
+            ReflogAction::RestoreArchive(archived) => {
+                create_branch(&repo.path, &archived.branch, Some(&archived.tag))
+                    .map(|_| format!("Restored {} from {}", archived.branch, archived.tag))
+            }
+        };
+
+        self.needs_refresh = true;
+        match result {
+            Ok(message) => {
+                self.status = Some(message);
+                true
+            }
+            Err(err) => {
+                self.error = Some(err.to_string());
+                false
+            }
+        }
+    }
+}
+
+fn display_ref(reference: &str) -> &str {
+    reference.strip_prefix("refs/heads/").unwrap_or(reference)
+}
+
+fn short_id(id: &str) -> String {
+    id.chars().take(7).collect()
+}
+
+fn format_time(seconds: i64) -> String {
+    chrono::DateTime::<Utc>::from_timestamp(seconds, 0)
+        .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|| "Unknown time".to_string())
+}
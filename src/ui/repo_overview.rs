@@ -3,14 +3,20 @@ use std::process::Command;
 use eframe::egui::{self, Align, Layout, Margin, RichText, Ui};
 
 use crate::auth::AuthManager;
+use crate::auth::credential_helper::helper_command;
 use crate::config::{MIN_BRANCH_BOX_HEIGHT, NetworkOptions};
 use crate::git::{
-    remote::{PullOutcome, RemoteInfo, fetch_remote, list_remotes, pull_branch, push_branch},
+    remote::{
+        PullOutcome, PullStrategy, RemoteInfo, fetch_remote, list_remotes, pull_branch, push_branch,
+    },
     status::{RepoStatus, read_repo_status},
 };
-use crate::ui::{animation::motion_settings, context::RepoContext, perf::PerfScope, theme::Theme};
+use crate::repo_settings::EffectiveRepoSettings;
+use crate::ui::{
+    animation::motion_settings, context::RepoContext, lfs::LfsPanel, perf::PerfScope,
+    submodules::SubmodulePanel, theme::Theme, worktrees::WorktreePanel,
+};
 
-#[derive(Debug, Clone)]
 pub struct RepoOverviewPanel {
     theme: Theme,
     status: Option<RepoStatus>,
@@ -23,6 +29,13 @@ pub struct RepoOverviewPanel {
     resize_delta_accumulator: f32,
     last_resize_update: Option<f64>,
     network: NetworkOptions,
+    pull_strategy: PullStrategy,
+    default_remote: String,
+    worktree_panel: WorktreePanel,
+    submodule_panel: SubmodulePanel,
+    lfs_panel: LfsPanel,
+    open_request: Option<String>,
+    history_changed: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -36,6 +49,9 @@ pub struct AutoFetchContext {
 impl RepoOverviewPanel {
     pub fn new(theme: Theme, branch_box_height: f32, network: NetworkOptions) -> Self {
         Self {
+            worktree_panel: WorktreePanel::new(theme.clone()),
+            submodule_panel: SubmodulePanel::new(theme.clone()),
+            lfs_panel: LfsPanel::new(theme.clone()),
             theme,
             status: None,
             remotes: Vec::new(),
@@ -46,11 +62,18 @@ impl RepoOverviewPanel {
             pending_branch_box_height: None,
             resize_delta_accumulator: 0.0,
             last_resize_update: None,
+            open_request: None,
+            history_changed: false,
             network,
+            pull_strategy: PullStrategy::default(),
+            default_remote: String::new(),
         }
     }
 
     pub fn set_theme(&mut self, theme: Theme) {
+        self.worktree_panel.set_theme(theme.clone());
+        self.submodule_panel.set_theme(theme.clone());
+        self.lfs_panel.set_theme(theme.clone());
         self.theme = theme;
     }
 
@@ -62,10 +85,23 @@ impl RepoOverviewPanel {
         self.network = network;
     }
 
+    pub fn set_repo_settings(&mut self, settings: &EffectiveRepoSettings) {
+        self.pull_strategy = settings.pull_strategy.value;
+        self.default_remote = settings.default_remote.value.clone();
+    }
+
     pub fn take_branch_box_height_change(&mut self) -> Option<f32> {
         self.pending_branch_box_height.take()
     }
 
+    pub fn take_open_request(&mut self) -> Option<String> {
+        self.open_request.take()
+    }
+
+    pub fn take_history_changed(&mut self) -> bool {
+        std::mem::take(&mut self.history_changed)
+    }
+
     pub fn set_action_status<S: Into<String>>(&mut self, status: Option<S>) {
         self.action_status = status.map(Into::into);
     }
@@ -94,6 +130,16 @@ impl RepoOverviewPanel {
             self.remotes_section(ui);
             ui.add_space(8.0);
             self.actions(ui, repo, auth);
+            ui.add_space(8.0);
+            if let Some(path) = self.worktree_panel.ui(ui, repo) {
+                self.open_request = Some(path);
+            }
+            ui.add_space(8.0);
+            if let Some(path) = self.submodule_panel.ui(ui, repo, auth, &self.network) {
+                self.open_request = Some(path);
+            }
+            ui.add_space(8.0);
+            self.lfs_panel.ui(ui, repo, auth, &self.network);
         } else {
             ui.label(
                 RichText::new("Select or clone a repository to see its Git status, remotes, and quick actions.")
@@ -116,6 +162,9 @@ impl RepoOverviewPanel {
         self.status = None;
         self.remotes.clear();
         self.error = None;
+        self.worktree_panel.mark_stale();
+        self.submodule_panel.mark_stale();
+        self.lfs_panel.mark_stale();
 
         match read_repo_status(&repo.path) {
             Ok(status) => self.status = Some(status),
@@ -339,10 +388,16 @@ impl RepoOverviewPanel {
                     };
 
                     if result.is_ok()
-                        && matches!(action, ActionKind::Fetch | ActionKind::Pull | ActionKind::Push)
+                        && matches!(
+                            action,
+                            ActionKind::Fetch | ActionKind::Pull | ActionKind::Push
+                        )
                     {
                         self.reload_repo_state(repo);
                     }
+                    if result.is_ok() && matches!(action, ActionKind::Fetch | ActionKind::Pull) {
+                        self.history_changed = true;
+                    }
 
                     self.action_status = Some(match result {
                         Ok(msg) => msg,
@@ -362,7 +417,7 @@ impl RepoOverviewPanel {
             self.reload_repo_state(repo);
         }
         let selection = self.resolve_remote_selection()?;
-        let token = self.resolve_remote_token(auth, &selection.remote_name);
+        let token = self.resolve_remote_token(repo, auth, &selection.remote_name);
         Ok(AutoFetchContext {
             repo_path: repo.path.clone(),
             remote_name: selection.remote_name,
@@ -373,7 +428,7 @@ impl RepoOverviewPanel {
 
     fn fetch(&self, repo: &RepoContext, auth: &AuthManager) -> Result<String, String> {
         let selection = self.resolve_remote_selection()?;
-        let token = self.resolve_remote_token(auth, &selection.remote_name);
+        let token = self.resolve_remote_token(repo, auth, &selection.remote_name);
         fetch_remote(&repo.path, &selection.remote_name, &self.network, token)
             .map_err(|err| err.to_string())?;
         Ok(format!("Fetched {}", selection.remote_name))
@@ -384,18 +439,32 @@ impl RepoOverviewPanel {
         let branch = selection
             .branch
             .ok_or_else(|| "No branch checked out for pull.".to_string())?;
-        let token = self.resolve_remote_token(auth, &selection.remote_name);
+        let token = self.resolve_remote_token(repo, auth, &selection.remote_name);
         let outcome = pull_branch(
             &repo.path,
             &selection.remote_name,
             &branch,
+            self.pull_strategy,
             &self.network,
             token,
+            helper_command().as_deref(),
         )
         .map_err(|err| err.to_string())?;
         let message = match outcome {
             PullOutcome::UpToDate => "Already up to date.".to_string(),
             PullOutcome::FastForward => format!("Pulled {} from {}", branch, selection.remote_name),
+            PullOutcome::Integrated { conflicts } if conflicts.is_empty() => format!(
+                "Pulled {} from {} ({})",
+                branch,
+                selection.remote_name,
+                self.pull_strategy.label().to_lowercase()
+            ),
+            PullOutcome::Integrated { conflicts } => format!(
+                "Pulled {} from {} with {} conflicted file(s); resolve them to finish.",
+                branch,
+                selection.remote_name,
+                conflicts.len()
+            ),
         };
         Ok(message)
     }
@@ -405,7 +474,7 @@ impl RepoOverviewPanel {
         let branch = selection
             .branch
             .ok_or_else(|| "No branch checked out for push.".to_string())?;
-        let token = self.resolve_remote_token(auth, &selection.remote_name);
+        let token = self.resolve_remote_token(repo, auth, &selection.remote_name);
         push_branch(
             &repo.path,
             &selection.remote_name,
@@ -428,17 +497,27 @@ impl RepoOverviewPanel {
         } else {
             let remote = self
                 .remotes
-                .first()
+                .iter()
+                .find(|remote| remote.name == self.default_remote)
+                .or_else(|| self.remotes.first())
                 .map(|remote| remote.name.clone())
                 .ok_or_else(|| "No remotes configured for this repository.".to_string())?;
             (remote, None)
         };
 
         let branch = upstream_branch.or(status.branch);
-        Ok(RemoteSelection { remote_name, branch })
+        Ok(RemoteSelection {
+            remote_name,
+            branch,
+        })
     }
 
-    fn resolve_remote_token(&self, auth: &AuthManager, remote_name: &str) -> Option<String> {
+    fn resolve_remote_token(
+        &self,
+        repo: &RepoContext,
+        auth: &AuthManager,
+        remote_name: &str,
+    ) -> Option<String> {
         let remote = self
             .remotes
             .iter()
@@ -446,8 +525,7 @@ impl RepoOverviewPanel {
         if remote.url == "(no url)" {
             return None;
         }
-        auth.resolve_for_url(&remote.url)
-            .or_else(|| auth.resolve_for_host(&remote.url))
+        auth.resolve_for_repo(&repo.path, &remote.url)
     }
 
     fn open_terminal(&self, repo: &RepoContext) -> Result<String, String> {
@@ -1,14 +1,25 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::PathBuf;
+
 use eframe::egui::{
-    ComboBox, RichText, Slider, TextEdit, Ui, collapsing_header::CollapsingState,
-    output::OpenUrl,
+    Button, ComboBox, DragValue, Grid, ProgressBar, RichText, Slider, TextEdit, Ui,
+    collapsing_header::CollapsingState, output::OpenUrl,
 };
 use rfd::FileDialog;
 
+use crate::auth::AuthManager;
+use crate::auth::bundle::{BundleEntry, ImportChoice, open_bundle, seal_bundle};
 use crate::config::{
     Keybinding, LoggingOptions, MotionIntensity, Preferences, ReleaseChannel, ThemeMode,
     MAX_LOG_RETENTION_FILES, MIN_LOG_RETENTION_FILES,
 };
 use crate::dotnet::{DialogOpenRequest, DialogOptions, DotnetClient};
+use crate::git::remote::PullStrategy;
+use crate::profiles::{Profile, ProfileRule};
+use crate::repo_settings::{
+    EffectiveRepoSettings, Layered, REPO_SETTINGS_FILE, RepoLayers, RepoSettings, SettingSource,
+};
 use crate::ui::menu;
 use crate::ui::notifications::{Notification, NotificationCenter};
 use crate::ui::theme::Theme;
@@ -25,12 +36,85 @@ pub struct SettingsPanel {
     native_dialog_status: Option<String>,
     update_request: bool,
     update_status: Option<String>,
+    update_install: UpdateInstallState,
+    pending_update_action: Option<UpdateAction>,
     telemetry_status: Option<String>,
     telemetry_purge_requested: bool,
+    credential_hosts: BTreeSet<String>,
+    export_passphrase: String,
+    export_confirm: String,
+    import_path: Option<PathBuf>,
+    import_passphrase: String,
+    pending_import: Vec<PendingImport>,
+    credential_status: Option<String>,
+    active_tab: SettingsTab,
+    repository: Option<RepoLayers>,
+    repo_effective: EffectiveRepoSettings,
+    repo_draft: RepoSettings,
+    pinned_input: String,
+    pending_repo_change: Option<RepoSettingsChange>,
+    profiles: Vec<Profile>,
+    active_profile: Option<String>,
+    auto_select_profiles: bool,
+    profile_name: String,
+    rule_profile: String,
+    rule_is_network: bool,
+    rule_value: String,
+    profile_status: Option<String>,
+    pending_profile_action: Option<ProfileAction>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SettingsTab {
+    Global,
+    Repository,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProfileAction {
+    SaveCurrent(String),
+    Switch(String),
+    Delete(String),
+    AddRule(String, ProfileRule),
+    RemoveRule(String, ProfileRule),
+    SetAutoSelect(bool),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum UpdateInstallState {
+    Unavailable,
+    Available(String),
+    Downloading {
+        version: String,
+        progress: Option<f32>,
+    },
+    Ready(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpdateAction {
+    Install,
+    Restart,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RepoSettingsChange {
+    // Keep these overrides on this machine only.
+    Save(RepoSettings),
+    // Write these overrides into `.gitspace.toml` and drop the local copy.
+    Share(RepoSettings),
+}
+
+// `conflict` marks entries whose account already holds a different token.
+struct PendingImport {
+    entry: BundleEntry,
+    conflict: bool,
+    choice: ImportChoice,
 }
 
 impl SettingsPanel {
     pub fn new(theme: Theme, preferences: Preferences, logging: LoggingOptions) -> Self {
+        let repo_effective = EffectiveRepoSettings::global(&preferences);
         Self {
             theme,
             preferences,
@@ -43,8 +127,32 @@ impl SettingsPanel {
             native_dialog_status: None,
             update_request: false,
             update_status: None,
+            update_install: UpdateInstallState::Unavailable,
+            pending_update_action: None,
             telemetry_status: None,
             telemetry_purge_requested: false,
+            credential_hosts: BTreeSet::new(),
+            export_passphrase: String::new(),
+            export_confirm: String::new(),
+            import_path: None,
+            import_passphrase: String::new(),
+            pending_import: Vec::new(),
+            credential_status: None,
+            active_tab: SettingsTab::Global,
+            repo_effective,
+            repository: None,
+            repo_draft: RepoSettings::default(),
+            pinned_input: String::new(),
+            pending_repo_change: None,
+            profiles: Vec::new(),
+            active_profile: None,
+            auto_select_profiles: false,
+            profile_name: String::new(),
+            rule_profile: String::new(),
+            rule_is_network: false,
+            rule_value: String::new(),
+            profile_status: None,
+            pending_profile_action: None,
         }
     }
 
@@ -56,6 +164,43 @@ impl SettingsPanel {
         self.preferences = preferences;
     }
 
+    pub fn set_repository(&mut self, layers: Option<RepoLayers>, effective: EffectiveRepoSettings) {
+        self.repo_draft = layers
+            .as_ref()
+            .map(|layers| layers.local.clone())
+            .unwrap_or_default();
+        self.pinned_input = pinned_text(&effective);
+        self.repository = layers;
+        self.repo_effective = effective;
+    }
+
+    pub fn set_profiles(
+        &mut self,
+        profiles: Vec<Profile>,
+        active: Option<String>,
+        auto_select: bool,
+    ) {
+        self.profiles = profiles;
+        self.active_profile = active;
+        self.auto_select_profiles = auto_select;
+    }
+
+    pub fn set_profile_status<S: Into<String>>(&mut self, status: S) {
+        self.profile_status = Some(status.into());
+    }
+
+    pub fn take_profile_action(&mut self) -> Option<ProfileAction> {
+        self.pending_profile_action.take()
+    }
+
+    pub fn show_global(&mut self) {
+        self.active_tab = SettingsTab::Global;
+    }
+
+    pub fn take_repo_change(&mut self) -> Option<RepoSettingsChange> {
+        self.pending_repo_change.take()
+    }
+
     pub fn take_changes(&mut self) -> Option<Preferences> {
         self.pending_preferences.take()
     }
@@ -81,6 +226,14 @@ impl SettingsPanel {
         self.update_status = Some(status.into());
     }
 
+    pub fn set_update_install(&mut self, state: UpdateInstallState) {
+        self.update_install = state;
+    }
+
+    pub fn take_update_action(&mut self) -> Option<UpdateAction> {
+        self.pending_update_action.take()
+    }
+
     pub fn set_telemetry_status<S: Into<String>>(&mut self, status: S) {
         self.telemetry_status = Some(status.into());
     }
@@ -94,7 +247,7 @@ impl SettingsPanel {
         false
     }
 
-    pub fn ui(&mut self, ui: &mut Ui, notifications: &mut NotificationCenter) {
+    pub fn ui(&mut self, ui: &mut Ui, auth: &AuthManager, notifications: &mut NotificationCenter) {
         ui.add_space(8.0);
         ui.heading(RichText::new("Settings").color(self.theme.palette.text_primary));
         ui.label(
@@ -103,12 +256,24 @@ impl SettingsPanel {
             )
             .color(self.theme.palette.text_secondary),
         );
+        ui.add_space(8.0);
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut self.active_tab, SettingsTab::Global, "Global");
+            ui.selectable_value(&mut self.active_tab, SettingsTab::Repository, "Repository");
+        });
         ui.add_space(12.0);
 
+        if self.active_tab == SettingsTab::Repository {
+            self.repository_tab(ui);
+            return;
+        }
+
+        self.profiles_section(ui);
         self.theme_section(ui);
         self.clone_section(ui, notifications);
         self.keybinding_section(ui);
         self.network_section(ui);
+        self.repository_defaults_section(ui);
         self.logging_section(ui);
         self.privacy_section(ui);
         self.update_section(ui);
@@ -116,7 +281,7 @@ impl SettingsPanel {
         ui.add_space(4.0);
         self.actions(ui);
         ui.add_space(10.0);
-        self.import_export(ui);
+        self.import_export(ui, auth);
     }
 
     fn theme_section(&mut self, ui: &mut Ui) {
@@ -434,6 +599,307 @@ impl SettingsPanel {
         );
     }
 
+    fn profiles_section(&mut self, ui: &mut Ui) {
+        self.collapsible_section(
+            ui,
+            "settings-profiles",
+            "Profiles",
+            "Named sets of preferences and network options. Switch between them from the header.",
+            |ui, panel| {
+                if panel.profiles.is_empty() {
+                    ui.label(
+                        RichText::new("No profiles yet. Save the current preferences to create one.")
+                            .color(panel.theme.palette.text_secondary),
+                    );
+                }
+                for profile in panel.profiles.clone() {
+                    let active = panel.active_profile.as_deref() == Some(profile.name.as_str());
+                    ui.horizontal(|ui| {
+                        let label = RichText::new(&profile.name).color(panel.theme.palette.text_primary);
+                        ui.label(if active { label.strong() } else { label });
+                        if active {
+                            ui.label(RichText::new("Active").color(panel.theme.palette.accent));
+                        } else if ui.button("Switch").clicked() {
+                            panel.pending_profile_action = Some(ProfileAction::Switch(profile.name.clone()));
+                        }
+                        if ui.button("Delete").clicked() {
+                            panel.pending_profile_action = Some(ProfileAction::Delete(profile.name.clone()));
+                        }
+                    });
+                    for rule in &profile.rules {
+                        ui.horizontal(|ui| {
+                            ui.add_space(16.0);
+                            ui.label(RichText::new(rule.label()).color(panel.theme.palette.text_secondary));
+                            if ui.small_button("Remove").clicked() {
+                                panel.pending_profile_action =
+                                    Some(ProfileAction::RemoveRule(profile.name.clone(), rule.clone()));
+                            }
+                        });
+                    }
+                }
+
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    ui.add(
+                        TextEdit::singleline(&mut panel.profile_name)
+                            .hint_text("Profile name")
+                            .desired_width(160.0),
+                    );
+                    if ui
+                        .add_enabled(
+                            !panel.profile_name.trim().is_empty(),
+                            Button::new("Save current preferences as profile"),
+                        )
+                        .clicked()
+                    {
+                        panel.pending_profile_action =
+                            Some(ProfileAction::SaveCurrent(panel.profile_name.trim().to_string()));
+                        panel.profile_name.clear();
+                    }
+                });
+
+                if !panel.profiles.is_empty() {
+                    ui.add_space(6.0);
+                    let mut auto_select = panel.auto_select_profiles;
+                    if ui
+                        .checkbox(&mut auto_select, "Switch profiles automatically")
+                        .on_hover_text("Picks the profile whose workspace contains the open repository, or whose network range contains this machine's address")
+                        .changed()
+                    {
+                        panel.pending_profile_action = Some(ProfileAction::SetAutoSelect(auto_select));
+                    }
+
+                    if !panel.profiles.iter().any(|profile| profile.name == panel.rule_profile) {
+                        panel.rule_profile = panel.profiles[0].name.clone();
+                    }
+                    ui.horizontal(|ui| {
+                        ComboBox::from_id_source("settings-profile-rule-target")
+                            .selected_text(panel.rule_profile.clone())
+                            .show_ui(ui, |ui| {
+                                for profile in &panel.profiles {
+                                    ui.selectable_value(
+                                        &mut panel.rule_profile,
+                                        profile.name.clone(),
+                                        profile.name.as_str(),
+                                    );
+                                }
+                            });
+                        ComboBox::from_id_source("settings-profile-rule-kind")
+                            .selected_text(if panel.rule_is_network { "Network" } else { "Workspace" })
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut panel.rule_is_network, false, "Workspace");
+                                ui.selectable_value(&mut panel.rule_is_network, true, "Network");
+                            });
+                        let hint = if panel.rule_is_network { "10.20.0.0/16" } else { "~/work" };
+                        ui.add(
+                            TextEdit::singleline(&mut panel.rule_value)
+                                .hint_text(hint)
+                                .desired_width(180.0),
+                        );
+                        if ui
+                            .add_enabled(!panel.rule_value.trim().is_empty(), Button::new("Add rule"))
+                            .clicked()
+                        {
+                            let value = panel.rule_value.trim().to_string();
+                            let rule = if panel.rule_is_network {
+                                ProfileRule::Network(value)
+                            } else {
+                                ProfileRule::Workspace(value)
+                            };
+                            panel.pending_profile_action =
+                                Some(ProfileAction::AddRule(panel.rule_profile.clone(), rule));
+                            panel.rule_value.clear();
+                        }
+                    });
+                }
+
+                if let Some(status) = &panel.profile_status {
+                    ui.add_space(6.0);
+                    ui.label(RichText::new(status).color(panel.theme.palette.text_secondary));
+                }
+            },
+        );
+    }
+
+    fn repository_defaults_section(&mut self, ui: &mut Ui) {
+        self.collapsible_section(
+            ui,
+            "settings-repository-defaults",
+            "Repository defaults",
+            "Used by every repository unless it overrides them in the Repository tab or a committed .gitspace.toml.",
+            |ui, panel| {
+                let mut strategy = panel.preferences.pull_strategy();
+                pull_strategy_combo(ui, &panel.theme, "settings-global-pull", &mut strategy);
+                panel.preferences.set_pull_strategy(strategy);
+
+                ui.add_space(4.0);
+                let mut remote = panel.preferences.default_remote().to_string();
+                ui.horizontal(|ui| {
+                    ui.label(
+                        RichText::new("Default remote").color(panel.theme.palette.text_secondary),
+                    );
+                    ui.add(TextEdit::singleline(&mut remote).desired_width(160.0));
+                });
+                panel.preferences.set_default_remote(remote);
+
+                ui.add_space(4.0);
+                let mut template = panel.preferences.commit_template().to_string();
+                ui.label(
+                    RichText::new("Commit template").color(panel.theme.palette.text_secondary),
+                );
+                ui.add(
+                    TextEdit::multiline(&mut template)
+                        .desired_rows(3)
+                        .hint_text("Prefilled in the commit editor"),
+                );
+                panel.preferences.set_commit_template(template);
+            },
+        );
+    }
+
+    fn repository_tab(&mut self, ui: &mut Ui) {
+        let Some(layers) = self.repository.clone() else {
+            ui.label(
+                RichText::new("Open a repository to configure its settings.")
+                    .color(self.theme.palette.text_secondary),
+            );
+            return;
+        };
+
+        ui.label(RichText::new(&layers.path).color(self.theme.palette.text_primary));
+        let file_note = match (&layers.file_error, &layers.file) {
+            (Some(err), _) => format!("{err} The file is ignored until it is fixed."),
+            (None, Some(_)) => format!("Shared settings are read from {REPO_SETTINGS_FILE}."),
+            (None, None) => format!(
+                "No {REPO_SETTINGS_FILE} in this repository. Overrides below apply only on this machine."
+            ),
+        };
+        ui.label(RichText::new(file_note).color(self.theme.palette.text_secondary));
+        ui.add_space(8.0);
+
+        let effective = self.repo_effective.clone();
+        let theme = self.theme.clone();
+        Grid::new("settings-repository-grid")
+            .num_columns(3)
+            .spacing([12.0, 8.0])
+            .show(ui, |ui| {
+                ui.label(RichText::new("Setting").strong());
+                ui.label(RichText::new("Value").strong());
+                ui.label(RichText::new("From").strong());
+                ui.end_row();
+
+                override_row(
+                    ui,
+                    &theme,
+                    "Pull strategy",
+                    &mut self.repo_draft.pull_strategy,
+                    &effective.pull_strategy,
+                    |value| value.label().to_string(),
+                    |ui, value| pull_strategy_combo(ui, &theme, "settings-repo-pull", value),
+                );
+                override_row(
+                    ui,
+                    &theme,
+                    "Auto-fetch interval",
+                    &mut self.repo_draft.auto_fetch_interval_minutes,
+                    &effective.auto_fetch_interval_minutes,
+                    |minutes| auto_fetch_interval_label(*minutes),
+                    |ui, minutes| {
+                        ui.add(DragValue::new(minutes).clamp_range(1..=1440).suffix(" min"));
+                    },
+                );
+                let pinned_input = &mut self.pinned_input;
+                override_row(
+                    ui,
+                    &theme,
+                    "Pinned branches",
+                    &mut self.repo_draft.pinned_branches,
+                    &effective.pinned_branches,
+                    |branches| {
+                        if branches.is_empty() {
+                            "None".to_string()
+                        } else {
+                            branches.join(", ")
+                        }
+                    },
+                    |ui, branches| {
+                        if ui
+                            .add(
+                                TextEdit::singleline(pinned_input)
+                                    .hint_text("main, release")
+                                    .desired_width(220.0),
+                            )
+                            .changed()
+                        {
+                            *branches = pinned_input
+                                .split(',')
+                                .map(str::trim)
+                                .filter(|name| !name.is_empty())
+                                .map(str::to_string)
+                                .collect();
+                        }
+                    },
+                );
+                override_row(
+                    ui,
+                    &theme,
+                    "Default remote",
+                    &mut self.repo_draft.default_remote,
+                    &effective.default_remote,
+                    |remote| remote.clone(),
+                    |ui, remote| {
+                        ui.add(TextEdit::singleline(remote).desired_width(160.0));
+                    },
+                );
+                override_row(
+                    ui,
+                    &theme,
+                    "Commit template",
+                    &mut self.repo_draft.commit_template,
+                    &effective.commit_template,
+                    |template| {
+                        let first_line = template.lines().next().unwrap_or_default();
+                        if first_line.is_empty() {
+                            "None".to_string()
+                        } else {
+                            first_line.to_string()
+                        }
+                    },
+                    |ui, template| {
+                        ui.add(TextEdit::multiline(template).desired_rows(3));
+                    },
+                );
+            });
+
+        ui.add_space(10.0);
+        ui.horizontal(|ui| {
+            let changed = self.repo_draft != layers.local;
+            if ui
+                .add_enabled(changed, Button::new("Save for this repository"))
+                .clicked()
+            {
+                self.pending_repo_change = Some(RepoSettingsChange::Save(self.repo_draft.clone()));
+            }
+            if ui
+                .add_enabled(
+                    !self.repo_draft.is_empty() && layers.file_error.is_none(),
+                    Button::new(format!("Move to {REPO_SETTINGS_FILE}")),
+                )
+                .on_hover_text("Writes these overrides to the shared file so they can be committed")
+                .clicked()
+            {
+                self.pending_repo_change = Some(RepoSettingsChange::Share(self.repo_draft.clone()));
+            }
+            if ui
+                .add_enabled(changed, Button::new("Discard changes"))
+                .clicked()
+            {
+                self.repo_draft = layers.local.clone();
+                self.pinned_input = pinned_text(&self.repo_effective);
+            }
+        });
+    }
+
     fn privacy_section(&mut self, ui: &mut Ui) {
         self.collapsible_section(
             ui,
@@ -461,6 +927,22 @@ impl SettingsPanel {
                     .preferences
                     .set_allow_encrypted_tokens(encrypted_tokens);
 
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    let mut minutes = panel.preferences.token_auto_lock_minutes();
+                    ui.label("Lock the master-password token store after");
+                    ui.add(
+                        DragValue::new(&mut minutes)
+                            .clamp_range(0..=240)
+                            .suffix(" min"),
+                    );
+                    panel.preferences.set_token_auto_lock_minutes(minutes);
+                })
+                .response
+                .on_hover_text(
+                    "Only applies when a master password is set in the Auth tab. Use 0 to stay unlocked until GitSpace closes.",
+                );
+
                 ui.add_space(4.0);
                 ui.label(
                     RichText::new(
@@ -609,6 +1091,36 @@ impl SettingsPanel {
                         ui.label(RichText::new(status).color(panel.theme.palette.text_secondary));
                     }
                 });
+
+                match &panel.update_install {
+                    UpdateInstallState::Unavailable => {}
+                    UpdateInstallState::Available(version) => {
+                        if ui
+                            .button(format!("Download and install {version}"))
+                            .clicked()
+                        {
+                            panel.pending_update_action = Some(UpdateAction::Install);
+                        }
+                    }
+                    UpdateInstallState::Downloading { version, progress } => {
+                        let bar = match progress {
+                            Some(progress) => ProgressBar::new(*progress).show_percentage(),
+                            None => ProgressBar::new(0.0).animate(true),
+                        };
+                        ui.add(bar.text(format!("Downloading {version}...")));
+                    }
+                    UpdateInstallState::Ready(version) => {
+                        ui.horizontal(|ui| {
+                            if ui.button("Restart to update").clicked() {
+                                panel.pending_update_action = Some(UpdateAction::Restart);
+                            }
+                            ui.label(
+                                RichText::new(format!("{version} is ready to install."))
+                                    .color(panel.theme.palette.text_secondary),
+                            );
+                        });
+                    }
+                }
             },
         );
     }
@@ -689,12 +1201,12 @@ impl SettingsPanel {
         }
     }
 
-    fn import_export(&mut self, ui: &mut Ui) {
+    fn import_export(&mut self, ui: &mut Ui, auth: &AuthManager) {
         self.collapsible_section(
             ui,
             "settings-import-export",
             "Import / Export",
-            "Move your GitSpace preferences between machines as JSON.",
+            "Move your GitSpace preferences and saved credentials between machines.",
             |ui, panel| {
                 ui.horizontal(|ui| {
                     if ui.button("Import settings").clicked()
@@ -734,10 +1246,248 @@ impl SettingsPanel {
                     ui.add_space(6.0);
                     ui.label(RichText::new(status).color(panel.theme.palette.text_secondary));
                 }
+
+                panel.credential_transfer(ui, auth);
             },
         );
     }
 
+    fn credential_transfer(&mut self, ui: &mut Ui, auth: &AuthManager) {
+        ui.add_space(10.0);
+        ui.label(
+            RichText::new("Credentials")
+                .strong()
+                .color(self.theme.palette.text_primary),
+        );
+        ui.label(
+            RichText::new(
+                "Saved accounts and tokens are exported as a bundle encrypted with a passphrase you choose.",
+            )
+            .color(self.theme.palette.text_secondary),
+        );
+        ui.add_space(6.0);
+
+        let hosts: BTreeSet<String> = auth
+            .accounts()
+            .accounts
+            .into_iter()
+            .map(|account| account.host)
+            .collect();
+        if hosts.is_empty() {
+            ui.label(
+                RichText::new("No saved accounts to export.")
+                    .color(self.theme.palette.text_secondary),
+            );
+        }
+        for host in &hosts {
+            let mut selected = self.credential_hosts.contains(host);
+            if ui.checkbox(&mut selected, host).changed() {
+                if selected {
+                    self.credential_hosts.insert(host.clone());
+                } else {
+                    self.credential_hosts.remove(host);
+                }
+            }
+        }
+        self.credential_hosts.retain(|host| hosts.contains(host));
+
+        ui.horizontal(|ui| {
+            ui.add(
+                TextEdit::singleline(&mut self.export_passphrase)
+                    .hint_text("Passphrase")
+                    .password(true)
+                    .desired_width(180.0),
+            );
+            ui.add(
+                TextEdit::singleline(&mut self.export_confirm)
+                    .hint_text("Confirm passphrase")
+                    .password(true)
+                    .desired_width(180.0),
+            );
+            let ready = !self.credential_hosts.is_empty() && !self.export_passphrase.is_empty();
+            if ui
+                .add_enabled(ready, Button::new("Export credentials"))
+                .clicked()
+            {
+                self.credential_status = Some(self.export_credentials(auth));
+            }
+        });
+
+        ui.add_space(6.0);
+        if ui.button("Open credential bundle").clicked()
+            && let Some(path) = FileDialog::new().add_filter("JSON", &["json"]).pick_file()
+        {
+            self.credential_status = Some(format!("Enter the passphrase for {}", path.display()));
+            self.import_path = Some(path);
+            self.pending_import.clear();
+        }
+
+        if self.import_path.is_some() && self.pending_import.is_empty() {
+            ui.horizontal(|ui| {
+                ui.add(
+                    TextEdit::singleline(&mut self.import_passphrase)
+                        .hint_text("Bundle passphrase")
+                        .password(true)
+                        .desired_width(180.0),
+                );
+                if ui
+                    .add_enabled(
+                        !self.import_passphrase.is_empty(),
+                        Button::new("Unlock bundle"),
+                    )
+                    .clicked()
+                {
+                    self.open_credential_bundle(auth);
+                }
+                if ui.button("Cancel").clicked() {
+                    self.import_path = None;
+                    self.import_passphrase.clear();
+                    self.credential_status = None;
+                }
+            });
+        }
+
+        if !self.pending_import.is_empty() {
+            self.pending_import_ui(ui, auth);
+        }
+
+        if let Some(status) = &self.credential_status {
+            ui.add_space(6.0);
+            ui.label(RichText::new(status).color(self.theme.palette.text_secondary));
+        }
+    }
+
+    fn export_credentials(&mut self, auth: &AuthManager) -> String {
+        if self.export_passphrase != self.export_confirm {
+            return "Passphrases do not match".to_string();
+        }
+        let hosts: Vec<String> = self.credential_hosts.iter().cloned().collect();
+        let sealed = auth.bundle_entries(&hosts).and_then(|entries| {
+            seal_bundle(&entries, &self.export_passphrase).map(|data| (entries.len(), data))
+        });
+        let (count, data) = match sealed {
+            Ok(sealed) => sealed,
+            Err(err) => return err,
+        };
+        let Some(path) = FileDialog::new()
+            .add_filter("JSON", &["json"])
+            .set_file_name("gitspace-credentials.json")
+            .save_file()
+        else {
+            return "Export cancelled".to_string();
+        };
+        match fs::write(&path, data) {
+            Ok(()) => {
+                self.export_passphrase.clear();
+                self.export_confirm.clear();
+                format!("Exported {count} account(s) to {}", path.display())
+            }
+            Err(err) => format!("Failed to write {}: {err}", path.display()),
+        }
+    }
+
+    fn open_credential_bundle(&mut self, auth: &AuthManager) {
+        let Some(path) = &self.import_path else {
+            return;
+        };
+        let opened = fs::read_to_string(path)
+            .map_err(|err| format!("Failed to read {}: {err}", path.display()))
+            .and_then(|data| open_bundle(&data, &self.import_passphrase));
+        self.import_passphrase.clear();
+        match opened {
+            Ok(entries) if entries.is_empty() => {
+                self.credential_status = Some("The bundle contains no accounts".to_string());
+            }
+            Ok(entries) => {
+                self.pending_import = entries
+                    .into_iter()
+                    .map(|entry| {
+                        let conflict = auth.import_conflict(&entry);
+                        PendingImport {
+                            choice: if conflict {
+                                ImportChoice::Skip
+                            } else {
+                                ImportChoice::Import
+                            },
+                            entry,
+                            conflict,
+                        }
+                    })
+                    .collect();
+                self.credential_status = None;
+            }
+            Err(err) => self.credential_status = Some(err),
+        }
+    }
+
+    fn pending_import_ui(&mut self, ui: &mut Ui, auth: &AuthManager) {
+        ui.add_space(6.0);
+        for (index, pending) in self.pending_import.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                if pending.conflict {
+                    ui.label(
+                        RichText::new(pending.entry.account.label())
+                            .color(self.theme.palette.text_primary),
+                    );
+                    ComboBox::from_id_source(("settings-credential-conflict", index))
+                        .selected_text(import_choice_label(pending.choice))
+                        .show_ui(ui, |ui| {
+                            for choice in [
+                                ImportChoice::Import,
+                                ImportChoice::Skip,
+                                ImportChoice::KeepBoth,
+                            ] {
+                                ui.selectable_value(
+                                    &mut pending.choice,
+                                    choice,
+                                    import_choice_label(choice),
+                                );
+                            }
+                        });
+                } else {
+                    let mut include = pending.choice == ImportChoice::Import;
+                    if ui
+                        .checkbox(&mut include, pending.entry.account.label())
+                        .changed()
+                    {
+                        pending.choice = if include {
+                            ImportChoice::Import
+                        } else {
+                            ImportChoice::Skip
+                        };
+                    }
+                }
+            });
+            if pending.conflict {
+                ui.label(
+                    RichText::new("A different token is already saved for this account.")
+                        .color(self.theme.palette.text_secondary),
+                );
+            }
+        }
+
+        ui.add_space(6.0);
+        ui.horizontal(|ui| {
+            if ui.button("Import selected").clicked() {
+                let choices: Vec<_> = self
+                    .pending_import
+                    .drain(..)
+                    .map(|pending| (pending.entry, pending.choice))
+                    .collect();
+                self.credential_status = Some(match auth.import_credentials(&choices) {
+                    Ok(count) => format!("Imported {count} account(s)"),
+                    Err(err) => err,
+                });
+                self.import_path = None;
+            }
+            if ui.button("Cancel").clicked() {
+                self.pending_import.clear();
+                self.import_path = None;
+                self.credential_status = None;
+            }
+        });
+    }
+
     fn collapsible_section(
         &mut self,
         ui: &mut Ui,
@@ -770,6 +1520,78 @@ fn mode_label(mode: ThemeMode) -> &'static str {
     }
 }
 
+fn import_choice_label(choice: ImportChoice) -> &'static str {
+    match choice {
+        ImportChoice::Import => "Replace existing",
+        ImportChoice::Skip => "Keep existing",
+        ImportChoice::KeepBoth => "Keep both",
+    }
+}
+
+fn pull_strategy_combo(ui: &mut Ui, theme: &Theme, id: &str, strategy: &mut PullStrategy) {
+    let icon_id = ui.make_persistent_id((id, "icon"));
+    ComboBox::from_id_source(id)
+        .selected_text(strategy.label())
+        .icon(menu::combo_icon(theme.clone(), icon_id))
+        .show_ui(ui, |ui| {
+            menu::with_menu_popup_motion(ui, (id, "menu"), |ui| {
+                for option in PullStrategy::ALL {
+                    if menu::menu_item(
+                        ui,
+                        theme,
+                        (id, option.label()),
+                        option.label(),
+                        *strategy == option,
+                    )
+                    .clicked()
+                    {
+                        *strategy = option;
+                    }
+                }
+            });
+        });
+}
+
+fn override_row<T: Clone>(
+    ui: &mut Ui,
+    theme: &Theme,
+    label: &str,
+    draft: &mut Option<T>,
+    effective: &Layered<T>,
+    describe: impl Fn(&T) -> String,
+    edit: impl FnOnce(&mut Ui, &mut T),
+) {
+    let mut overridden = draft.is_some();
+    ui.checkbox(&mut overridden, label)
+        .on_hover_text("Override for this repository");
+    match (overridden, draft.as_mut()) {
+        (true, Some(value)) => {
+            ui.horizontal(|ui| edit(ui, value));
+            ui.label(RichText::new(SettingSource::Repo.label()).color(theme.palette.accent));
+        }
+        (true, None) => {
+            *draft = Some(effective.value.clone());
+            ui.label(describe(&effective.value));
+            ui.label(RichText::new(SettingSource::Repo.label()).color(theme.palette.accent));
+        }
+        (false, _) => {
+            *draft = None;
+            ui.label(RichText::new(describe(&effective.value)).color(theme.palette.text_secondary));
+            let source = if effective.source == SettingSource::Repo {
+                "Inherited after saving"
+            } else {
+                effective.source.label()
+            };
+            ui.label(RichText::new(source).color(theme.palette.text_secondary));
+        }
+    }
+    ui.end_row();
+}
+
+fn pinned_text(settings: &EffectiveRepoSettings) -> String {
+    settings.pinned_branches.value.join(", ")
+}
+
 fn channel_label(channel: ReleaseChannel) -> &'static str {
     match channel {
         ReleaseChannel::Stable => "Stable",
@@ -1,21 +1,36 @@
 use std::collections::{BTreeSet, HashMap};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
+use chrono::Utc;
 use eframe::egui::{self, Align, ComboBox, Layout, RichText, ScrollArea, Ui, Window};
 use git2::{Repository, Signature, Status, StatusOptions, StatusShow};
+use rfd::FileDialog;
 
+use crate::auth::credential_helper::helper_command;
 use crate::git::branch::restore_file_from_branch;
-use crate::git::diff::{diff_file, staged_diff, working_tree_diff};
-use crate::git::stash::{StashEntry, apply_stash, create_stash, drop_stash, list_stashes};
+use crate::git::commit::create_commit;
+use crate::git::diff::{FileDiff, diff_file, staged_diff, working_tree_diff};
+use crate::git::lfs::{is_lfs_tracked, is_materialized_lfs_file};
+use crate::git::patch::{
+    ApplyReport, ApplyTarget, FileApplyResult, FileApplyStatus, apply_patch_file,
+};
+use crate::git::signing::{SigningConfig, read_signing_config};
+use crate::git::stash::{
+    StashEntry, StashOptions, apply_stash, branch_from_stash, create_stash_with_options,
+    drop_stash, list_stashes, pop_stash, stash_diff,
+};
 use crate::git::status::read_repo_status;
-use crate::ui::{context::RepoContext, menu, theme::Theme};
+use crate::ui::{
+    binary_view::BinaryView, context::RepoContext, diff_view::DiffView, menu, theme::Theme,
+};
 
 #[derive(Debug, Clone)]
 struct FileEntry {
     path: String,
     status_label: String,
-    diff: String,
+    diff: FileDiff,
     checked: bool,
+    lfs: bool,
 }
 
 pub struct StagePanel {
@@ -27,15 +42,29 @@ pub struct StagePanel {
     status: Option<String>,
     error: Option<String>,
     commit_message: String,
+    commit_template: String,
     include_signoff: bool,
     selected_template: usize,
     signoff_line: String,
+    signing: Option<SigningConfig>,
     stash_message: String,
     stashes: Vec<StashEntry>,
     include_untracked_in_stash: bool,
+    keep_index_in_stash: bool,
+    stash_staged_only: bool,
+    stash_paths: String,
+    stash_branch_name: String,
+    stash_preview: Option<(usize, Vec<FileDiff>)>,
     needs_refresh: bool,
     restore_dialog_open: bool,
     restore_selection: Option<String>,
+    history_request: Option<String>,
+    committed: bool,
+    patch_path: Option<PathBuf>,
+    patch_target: ApplyTarget,
+    patch_report: Option<ApplyReport>,
+    diff_view: DiffView,
+    binary_view: BinaryView,
 }
 
 const COMMIT_TEMPLATES: &[(&str, &str)] = &[
@@ -53,6 +82,8 @@ const COMMIT_TEMPLATES: &[(&str, &str)] = &[
 impl StagePanel {
     pub fn new(theme: Theme) -> Self {
         let signoff_line = default_signoff_line();
+        let diff_view = DiffView::new(theme.clone());
+        let binary_view = BinaryView::new(theme.clone());
         Self {
             theme,
             staged: Vec::new(),
@@ -62,22 +93,46 @@ impl StagePanel {
             status: None,
             error: None,
             commit_message: String::new(),
+            commit_template: String::new(),
             include_signoff: false,
             selected_template: 0,
             signoff_line,
+            signing: None,
             stash_message: String::from("WIP changes"),
             stashes: Vec::new(),
             include_untracked_in_stash: true,
+            keep_index_in_stash: false,
+            stash_staged_only: false,
+            stash_paths: String::new(),
+            stash_branch_name: String::new(),
+            stash_preview: None,
             needs_refresh: true,
             restore_dialog_open: false,
             restore_selection: None,
+            history_request: None,
+            committed: false,
+            patch_path: None,
+            patch_target: ApplyTarget::WorkingTree,
+            patch_report: None,
+            diff_view,
+            binary_view,
         }
     }
 
     pub fn set_theme(&mut self, theme: Theme) {
+        self.diff_view.set_theme(theme.clone());
+        self.binary_view.set_theme(theme.clone());
         self.theme = theme;
     }
 
+    pub fn take_history_request(&mut self) -> Option<String> {
+        self.history_request.take()
+    }
+
+    pub fn take_committed(&mut self) -> bool {
+        std::mem::take(&mut self.committed)
+    }
+
     pub fn ui(&mut self, ui: &mut Ui, repo: Option<&RepoContext>) {
         ui.heading(RichText::new("Staging & commits").color(self.theme.palette.text_primary));
         ui.label(
@@ -123,7 +178,7 @@ impl StagePanel {
 
                 ui.vertical(|ui| {
                     ui.set_width(commit_width);
-                    self.render_commit_editor(ui);
+                    self.render_commit_editor(ui, repo);
                 });
 
                 ui.add_space(6.0);
@@ -133,6 +188,8 @@ impl StagePanel {
                     self.render_stash_controls(ui, repo);
                 });
             });
+            ui.add_space(10.0);
+            self.render_patch_controls(ui, repo);
             self.render_restore_dialog(ui, repo);
         } else {
             ui.add_space(8.0);
@@ -143,11 +200,20 @@ impl StagePanel {
         }
     }
 
+    // A message still holding the previous template is replaced.
+    pub fn set_commit_template(&mut self, template: String) {
+        if self.commit_message.is_empty() || self.commit_message == self.commit_template {
+            self.commit_message = template.clone();
+        }
+        self.commit_template = template;
+    }
+
     fn refresh_if_needed(&mut self, repo: &RepoContext) {
         if self.last_repo.as_deref() != Some(&repo.path) {
             self.last_repo = Some(repo.path.clone());
             self.selected_diff = None;
-            self.commit_message.clear();
+            self.commit_message = self.commit_template.clone();
+            self.stash_preview = None;
             self.status = None;
             self.error = None;
             self.needs_refresh = true;
@@ -169,6 +235,7 @@ impl StagePanel {
                 Ok(entries) => self.stashes = entries,
                 Err(err) => self.error = Some(format!("Failed to read stashes: {err}")),
             }
+            self.signing = read_signing_config(&repo.path).ok();
 
             self.needs_refresh = false;
         }
@@ -195,6 +262,7 @@ impl StagePanel {
         let mut pending_action: Option<(bool, String)> = None;
         let mut pending_diff: Option<(bool, String)> = None;
         let mut pending_restore: Option<String> = None;
+        let mut pending_history: Option<String> = None;
 
         ScrollArea::vertical()
             .auto_shrink([false, false])
@@ -202,13 +270,26 @@ impl StagePanel {
                 for entry in list.iter_mut() {
                     ui.horizontal(|ui| {
                         let mut toggle = entry.checked;
-                        let label = format!("{} ({})", entry.path, entry.status_label);
+                        let label = match &entry.diff.old_path {
+                            Some(old_path) => {
+                                format!("{} ← {old_path} ({})", entry.path, entry.status_label)
+                            }
+                            None => format!("{} ({})", entry.path, entry.status_label),
+                        };
                         let response = ui.checkbox(&mut toggle, label);
                         if response.changed() {
                             entry.checked = toggle;
                             pending_action = Some((staged, entry.path.clone()));
                         }
 
+                        if entry.lfs {
+                            ui.label(
+                                RichText::new("LFS")
+                                    .color(self.theme.palette.accent)
+                                    .size(self.theme.typography.label),
+                            );
+                        }
+
                         if ui.button("Diff").clicked() {
                             pending_diff = Some((staged, entry.path.clone()));
                         }
@@ -230,6 +311,18 @@ impl StagePanel {
                                         pending_restore = Some(entry.path.clone());
                                         ui.close_menu();
                                     }
+                                    if menu::menu_item(
+                                        ui,
+                                        &self.theme,
+                                        ("stage-history", &entry.path),
+                                        "History of this file",
+                                        false,
+                                    )
+                                    .clicked()
+                                    {
+                                        pending_history = Some(entry.path.clone());
+                                        ui.close_menu();
+                                    }
                                 },
                             );
                         });
@@ -249,6 +342,10 @@ impl StagePanel {
             self.selected_diff = Some(diff);
         }
 
+        if pending_history.is_some() {
+            self.history_request = pending_history;
+        }
+
         if let Some(path) = pending_restore {
             self.restore_dialog_open = true;
             self.restore_selection = Some(path);
@@ -276,6 +373,9 @@ impl StagePanel {
                             .color(self.theme.palette.text_secondary),
                         );
                     }
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        self.diff_view.mode_switch(ui);
+                    });
                 });
                 ui.separator();
 
@@ -286,11 +386,22 @@ impl StagePanel {
                         &self.unstaged
                     };
                     if let Some(entry) = list.iter().find(|f| &f.path == path) {
-                        ScrollArea::vertical()
-                            .auto_shrink([false, false])
-                            .show(ui, |ui| {
-                                ui.code(&entry.diff);
-                            });
+                        self.diff_view.file_summary(ui, &entry.diff);
+                        ui.add_space(4.0);
+                        ScrollArea::vertical().auto_shrink([false, false]).show(
+                            ui,
+                            |ui| match &self.last_repo {
+                                Some(repo_path) if entry.diff.binary => {
+                                    self.binary_view.show(ui, repo_path, &entry.diff);
+                                }
+                                _ => self.diff_view.show(
+                                    ui,
+                                    ("stage-diff", *staged, path),
+                                    path,
+                                    &entry.diff.patch,
+                                ),
+                            },
+                        );
                         return;
                     }
                 }
@@ -302,7 +413,7 @@ impl StagePanel {
             });
     }
 
-    fn render_commit_editor(&mut self, ui: &mut Ui) {
+    fn render_commit_editor(&mut self, ui: &mut Ui, repo: &RepoContext) {
         egui::Frame::none()
             .fill(self.theme.palette.surface)
             .stroke(egui::Stroke::new(1.0, self.theme.palette.surface_highlight))
@@ -349,9 +460,47 @@ impl StagePanel {
 
                 ui.checkbox(&mut self.include_signoff, "Add Signed-off-by");
                 self.apply_signoff();
+
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    let can_commit =
+                        !self.staged.is_empty() && !self.commit_message.trim().is_empty();
+                    if ui
+                        .add_enabled(can_commit, egui::Button::new("Commit"))
+                        .clicked()
+                    {
+                        self.commit(repo);
+                    }
+                    let signing_label = match &self.signing {
+                        Some(signing) if signing.sign_commits => {
+                            format!("Signed with {} (commit.gpgsign)", signing.format_label())
+                        }
+                        _ => "Unsigned".to_string(),
+                    };
+                    ui.label(RichText::new(signing_label).color(self.theme.palette.text_secondary));
+                });
             });
     }
 
+    fn commit(&mut self, repo: &RepoContext) {
+        self.status = None;
+        match create_commit(&repo.path, &self.commit_message) {
+            Ok(commit) => {
+                let short_id: String = commit.id.chars().take(7).collect();
+                self.status = Some(if commit.signed {
+                    format!("Committed {short_id} (signed)")
+                } else {
+                    format!("Committed {short_id}")
+                });
+                self.commit_message = self.commit_template.clone();
+                self.selected_diff = None;
+                self.needs_refresh = true;
+                self.committed = true;
+            }
+            Err(err) => self.error = Some(format!("Commit failed: {err}")),
+        }
+    }
+
     fn render_stash_controls(&mut self, ui: &mut Ui, repo: &RepoContext) {
         egui::Frame::none()
             .fill(self.theme.palette.surface)
@@ -368,28 +517,33 @@ impl StagePanel {
                         egui::TextEdit::singleline(&mut self.stash_message)
                             .hint_text("Describe the stash..."),
                     );
-                    ui.checkbox(&mut self.include_untracked_in_stash, "Include untracked");
                     if ui.button("Create stash").clicked() {
-                        self.status = None;
-                        match create_stash(
-                            &repo.path,
-                            self.stash_message.trim(),
-                            self.include_untracked_in_stash,
-                        ) {
-                            Ok(_) => {
-                                self.status = Some("Stashed working tree".to_string());
-                                self.needs_refresh = true;
-                            }
-                            Err(err) => self.error = Some(format!("Failed to stash: {err}")),
-                        }
+                        self.create_stash(repo);
                     }
                 });
+                ui.horizontal_wrapped(|ui| {
+                    ui.checkbox(&mut self.include_untracked_in_stash, "Include untracked");
+                    ui.checkbox(&mut self.keep_index_in_stash, "Keep index");
+                    ui.checkbox(&mut self.stash_staged_only, "Staged only");
+                });
+                ui.add_enabled(
+                    !self.stash_staged_only,
+                    egui::TextEdit::singleline(&mut self.stash_paths)
+                        .hint_text("Limit to paths (comma separated)"),
+                );
 
                 ui.add_space(8.0);
                 ui.label(
-                    RichText::new("Apply or drop an existing stash.")
+                    RichText::new("Apply, pop, preview, or branch from an existing stash.")
                         .color(self.theme.palette.text_secondary),
                 );
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new("Branch name").color(self.theme.palette.text_secondary));
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.stash_branch_name)
+                            .hint_text("feature/from-stash"),
+                    );
+                });
                 ui.add_space(4.0);
 
                 if self.stashes.is_empty() {
@@ -401,44 +555,317 @@ impl StagePanel {
                 }
 
                 for stash in self.stashes.clone() {
+                    ui.label(
+                        RichText::new(format!("#{} — {}", stash.index, stash.message))
+                            .color(self.theme.palette.text_primary),
+                    );
+                    let date = chrono::DateTime::<Utc>::from_timestamp(stash.time.seconds(), 0)
+                        .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+                        .unwrap_or_else(|| "Unknown time".to_string());
+                    ui.label(
+                        RichText::new(format!(
+                            "{} • {} • {}",
+                            stash.branch.as_deref().unwrap_or("(no branch)"),
+                            date,
+                            stash.id.chars().take(7).collect::<String>()
+                        ))
+                        .color(self.theme.palette.text_secondary),
+                    );
                     ui.horizontal(|ui| {
-                        ui.label(
-                            RichText::new(format!("#{} — {}", stash.index, stash.message))
-                                .color(self.theme.palette.text_primary),
-                        );
                         if ui.button("Apply").clicked() {
-                            match apply_stash(&repo.path, stash.index) {
-                                Ok(_) => {
-                                    self.status = Some(format!("Applied stash #{}", stash.index));
-                                    self.needs_refresh = true;
-                                }
-                                Err(err) => {
-                                    self.error = Some(format!(
-                                        "Failed to apply stash #{}: {err}",
-                                        stash.index
-                                    ))
-                                }
-                            }
+                            self.run_stash_action(
+                                apply_stash(&repo.path, stash.index),
+                                format!("Applied stash #{}", stash.index),
+                            );
+                        }
+                        if ui.button("Pop").clicked() {
+                            self.run_stash_action(
+                                pop_stash(&repo.path, stash.index),
+                                format!("Popped stash #{}", stash.index),
+                            );
                         }
                         if ui.button("Drop").clicked() {
-                            match drop_stash(&repo.path, stash.index) {
-                                Ok(_) => {
-                                    self.status = Some(format!("Dropped stash #{}", stash.index));
-                                    self.needs_refresh = true;
-                                }
+                            self.run_stash_action(
+                                drop_stash(&repo.path, stash.index),
+                                format!("Dropped stash #{}", stash.index),
+                            );
+                        }
+                        if ui.button("Preview").clicked() {
+                            match stash_diff(&repo.path, stash.index) {
+                                Ok(diffs) => self.stash_preview = Some((stash.index, diffs)),
                                 Err(err) => {
                                     self.error = Some(format!(
-                                        "Failed to drop stash #{}: {err}",
+                                        "Failed to preview stash #{}: {err}",
                                         stash.index
                                     ))
                                 }
                             }
                         }
+                        let branch_name = self.stash_branch_name.trim().to_string();
+                        if ui
+                            .add_enabled(!branch_name.is_empty(), egui::Button::new("Branch"))
+                            .clicked()
+                        {
+                            self.run_stash_action(
+                                branch_from_stash(&repo.path, stash.index, &branch_name),
+                                format!("Created {branch_name} from stash #{}", stash.index),
+                            );
+                            self.stash_branch_name.clear();
+                        }
                     });
+                    ui.add_space(4.0);
                 }
+
+                self.render_stash_preview(ui);
             });
     }
 
+    fn render_stash_preview(&mut self, ui: &mut Ui) {
+        let Some(index) = self.stash_preview.as_ref().map(|(index, _)| *index) else {
+            return;
+        };
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label(
+                RichText::new(format!("Stash #{index} changes"))
+                    .color(self.theme.palette.text_primary)
+                    .strong(),
+            );
+            if ui.button("Close").clicked() {
+                self.stash_preview = None;
+            }
+        });
+        let Some((_, diffs)) = &self.stash_preview else {
+            return;
+        };
+        if diffs.is_empty() {
+            ui.label(
+                RichText::new("This stash has no tracked changes.")
+                    .color(self.theme.palette.text_secondary),
+            );
+            return;
+        }
+
+        ScrollArea::vertical()
+            .id_source("stash_preview")
+            .max_height(220.0)
+            .auto_shrink([false, true])
+            .show(ui, |ui| {
+                for diff in diffs {
+                    ui.collapsing(
+                        RichText::new(format!(
+                            "{} (+{}, -{})",
+                            diff.path, diff.additions, diff.deletions
+                        ))
+                        .color(self.theme.palette.text_primary),
+                        |ui| {
+                            ui.code(&diff.patch);
+                        },
+                    );
+                }
+            });
+    }
+
+    fn create_stash(&mut self, repo: &RepoContext) {
+        let options = StashOptions {
+            include_untracked: self.include_untracked_in_stash,
+            keep_index: self.keep_index_in_stash,
+            staged_only: self.stash_staged_only,
+            paths: self
+                .stash_paths
+                .split(',')
+                .map(|path| path.trim().to_string())
+                .filter(|path| !path.is_empty())
+                .collect(),
+        };
+        let label = if options.staged_only {
+            "Stashed staged changes"
+        } else if !options.paths.is_empty() {
+            "Stashed selected paths"
+        } else {
+            "Stashed working tree"
+        };
+        self.run_stash_action(
+            create_stash_with_options(&repo.path, self.stash_message.trim(), &options),
+            label.to_string(),
+        );
+    }
+
+    fn run_stash_action(&mut self, result: Result<(), git2::Error>, success: String) {
+        self.status = None;
+        match result {
+            Ok(()) => {
+                self.status = Some(success);
+                self.stash_preview = None;
+                self.needs_refresh = true;
+            }
+            Err(err) => self.error = Some(format!("Stash operation failed: {err}")),
+        }
+    }
+
+    fn render_patch_controls(&mut self, ui: &mut Ui, repo: &RepoContext) {
+        egui::Frame::none()
+            .fill(self.theme.palette.surface)
+            .stroke(egui::Stroke::new(1.0, self.theme.palette.surface_highlight))
+            .inner_margin(egui::Margin::same(10.0))
+            .rounding(6.0)
+            .show(ui, |ui| {
+                ui.heading(RichText::new("Apply patch").color(self.theme.palette.text_primary));
+                ui.label(
+                    RichText::new(
+                        "Apply a .patch, .diff or mbox series; check first to see conflicts per file.",
+                    )
+                    .color(self.theme.palette.text_secondary),
+                );
+                ui.add_space(6.0);
+                ui.horizontal_wrapped(|ui| {
+                    if ui.button("Choose patch…").clicked()
+                        && let Some(path) = FileDialog::new()
+                            .add_filter("Patch", &["patch", "diff", "mbox", "eml"])
+                            .pick_file()
+                    {
+                        self.patch_path = Some(path);
+                        self.patch_report = None;
+                    }
+                    let chosen = self
+                        .patch_path
+                        .as_ref()
+                        .map(|path| path.display().to_string())
+                        .unwrap_or_else(|| "No patch selected".to_string());
+                    ui.label(RichText::new(chosen).color(self.theme.palette.text_secondary));
+                });
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new("Apply to").color(self.theme.palette.text_secondary));
+                    let icon_id = ui.make_persistent_id("patch-target-icon");
+                    ComboBox::from_id_source("patch_target")
+                        .selected_text(self.patch_target.label())
+                        .icon(menu::combo_icon(self.theme.clone(), icon_id))
+                        .show_ui(ui, |ui| {
+                            menu::with_menu_popup_motion(ui, "patch-target-menu", |ui| {
+                                for target in [
+                                    ApplyTarget::WorkingTree,
+                                    ApplyTarget::Index,
+                                    ApplyTarget::Both,
+                                ] {
+                                    if menu::menu_item(
+                                        ui,
+                                        &self.theme,
+                                        ("patch-target-item", target.label()),
+                                        target.label(),
+                                        self.patch_target == target,
+                                    )
+                                    .clicked()
+                                    {
+                                        self.patch_target = target;
+                                        self.patch_report = None;
+                                        ui.close_menu();
+                                    }
+                                }
+                            });
+                        });
+                    let ready = self.patch_path.is_some();
+                    if ui
+                        .add_enabled(ready, egui::Button::new("Check (dry run)"))
+                        .clicked()
+                    {
+                        self.apply_patch(repo, true);
+                    }
+                    if ui.add_enabled(ready, egui::Button::new("Apply")).clicked() {
+                        self.apply_patch(repo, false);
+                    }
+                });
+                if let Some(report) = &self.patch_report {
+                    self.render_patch_report(ui, report);
+                }
+            });
+    }
+
+    fn render_patch_report(&self, ui: &mut Ui, report: &ApplyReport) {
+        let palette = &self.theme.palette;
+        let summary = if report.dry_run && !report.has_conflicts() {
+            format!("All {} file(s) apply cleanly.", report.files.len())
+        } else if report.dry_run {
+            format!(
+                "{} of {} file(s) would conflict.",
+                report.conflicts().count(),
+                report.files.len()
+            )
+        } else {
+            format!(
+                "Applied {} of {} patch(es).",
+                report.applied, report.patches
+            )
+        };
+        let prefix = |file: &FileApplyResult| {
+            if report.patches > 1 {
+                format!("[{}/{}] ", file.patch, report.patches)
+            } else {
+                String::new()
+            }
+        };
+        ui.add_space(6.0);
+        ui.label(RichText::new(summary).color(palette.text_primary));
+
+        if report.has_conflicts() {
+            egui::Frame::none()
+                .stroke(egui::Stroke::new(1.0, palette.accent))
+                .inner_margin(egui::Margin::same(8.0))
+                .rounding(6.0)
+                .show(ui, |ui| {
+                    ui.label(RichText::new("Conflicts").color(palette.accent).strong());
+                    for file in report.conflicts() {
+                        let FileApplyStatus::Conflict(reason) = &file.status else {
+                            continue;
+                        };
+                        ui.label(
+                            RichText::new(format!("{}{} — {reason}", prefix(file), file.path))
+                                .color(palette.accent),
+                        )
+                        .on_hover_text(file.subject.as_deref().unwrap_or_default());
+                    }
+                    ui.label(
+                        RichText::new(
+                            "Update these files so the patch context matches, then apply again.",
+                        )
+                        .color(palette.text_secondary),
+                    );
+                });
+        }
+
+        for file in &report.files {
+            let label = match &file.status {
+                FileApplyStatus::Clean => "clean",
+                FileApplyStatus::Conflict(_) => continue,
+                FileApplyStatus::Unchecked => "depends on an earlier patch",
+            };
+            ui.label(
+                RichText::new(format!("{}{} — {label}", prefix(file), file.path))
+                    .color(palette.text_secondary),
+            )
+            .on_hover_text(file.subject.as_deref().unwrap_or_default());
+        }
+    }
+
+    fn apply_patch(&mut self, repo: &RepoContext, dry_run: bool) {
+        let Some(path) = self.patch_path.clone() else {
+            return;
+        };
+        self.status = None;
+        match apply_patch_file(&repo.path, &path, self.patch_target, dry_run) {
+            Ok(report) => {
+                if !dry_run && report.applied > 0 {
+                    self.needs_refresh = true;
+                }
+                self.patch_report = Some(report);
+            }
+            Err(err) => {
+                self.patch_report = None;
+                self.error = Some(format!("Failed to apply patch: {err}"));
+            }
+        }
+    }
+
     fn render_restore_dialog(&mut self, ui: &mut Ui, repo: &RepoContext) {
         if !self.restore_dialog_open {
             return;
@@ -507,7 +934,12 @@ impl StagePanel {
 
         if let Some(path) = request_restore {
             self.status = None;
-            match restore_file_from_branch(&repo.path, &current_branch, &path) {
+            match restore_file_from_branch(
+                &repo.path,
+                &current_branch,
+                &path,
+                helper_command().as_deref(),
+            ) {
                 Ok(()) => {
                     self.status = Some(format!("Restored {path} from {current_branch}"));
                     self.needs_refresh = true;
@@ -620,6 +1052,7 @@ fn read_statuses(repo_path: &str) -> Result<(Vec<FileEntry>, Vec<FileEntry>), gi
     for entry in statuses.iter() {
         let path = entry.path().unwrap_or("(unknown)").to_string();
         let status = entry.status();
+        let lfs = is_lfs_tracked(&repo, &path);
 
         if status.is_index_new()
             || status.is_index_modified()
@@ -632,20 +1065,25 @@ fn read_statuses(repo_path: &str) -> Result<(Vec<FileEntry>, Vec<FileEntry>), gi
                 diff: lookup_or_refresh_diff(&staged_map, repo_path, &path, true)?,
                 path: path.clone(),
                 checked: true,
+                lfs,
             });
         }
 
-        if status.is_wt_new()
-            || status.is_wt_modified()
-            || status.is_wt_deleted()
-            || status.is_wt_renamed()
-            || status.is_wt_typechange()
+        let materialized_lfs =
+            lfs && status.is_wt_modified() && is_materialized_lfs_file(&repo, &path);
+        if !materialized_lfs
+            && (status.is_wt_new()
+                || status.is_wt_modified()
+                || status.is_wt_deleted()
+                || status.is_wt_renamed()
+                || status.is_wt_typechange())
         {
             unstaged.push(FileEntry {
                 status_label: format_status_label(status),
                 diff: lookup_or_refresh_diff(&unstaged_map, repo_path, &path, false)?,
                 path,
                 checked: false,
+                lfs,
             });
         }
     }
@@ -653,27 +1091,29 @@ fn read_statuses(repo_path: &str) -> Result<(Vec<FileEntry>, Vec<FileEntry>), gi
     Ok((staged, unstaged))
 }
 
-fn build_diff_map(diffs: Vec<crate::git::diff::FileDiff>) -> HashMap<String, String> {
+fn build_diff_map(diffs: Vec<FileDiff>) -> HashMap<String, FileDiff> {
     diffs
         .into_iter()
-        .map(|diff| (diff.path, diff.patch))
+        .map(|diff| (diff.path.clone(), diff))
         .collect()
 }
 
 fn lookup_or_refresh_diff(
-    diffs: &HashMap<String, String>,
+    diffs: &HashMap<String, FileDiff>,
     repo_path: &str,
     path: &str,
     staged: bool,
-) -> Result<String, git2::Error> {
-    if let Some(patch) = diffs.get(path) {
-        return Ok(patch.clone());
+) -> Result<FileDiff, git2::Error> {
+    if let Some(diff) = diffs.get(path) {
+        return Ok(diff.clone());
     }
 
-    let patch = diff_file(repo_path, path, staged)?
-        .map(|entry| entry.patch)
-        .unwrap_or_else(|| "(no textual diff available)\n".to_string());
-    Ok(patch)
+    let diff = diff_file(repo_path, path, staged)?.unwrap_or_else(|| FileDiff {
+        path: path.to_string(),
+        patch: "(no textual diff available)\n".to_string(),
+        ..Default::default()
+    });
+    Ok(diff)
 }
 
 fn stage_path(repo_path: &str, path: &str) -> Result<(), git2::Error> {
@@ -0,0 +1,359 @@
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver};
+
+use eframe::egui::{self, Margin, RichText, Ui};
+use poll_promise::Promise;
+
+use crate::auth::AuthManager;
+use crate::config::NetworkOptions;
+use crate::error::AppError;
+use crate::git::clone::CloneProgress;
+use crate::git::submodule::{
+    SubmoduleInfo, init_submodule, read_submodule_status, sync_submodule, update_submodule,
+};
+use crate::ui::{context::RepoContext, theme::Theme};
+
+enum SubmoduleAction {
+    Init(Vec<String>),
+    Sync(Vec<String>),
+    Update(Vec<String>),
+    Open(String),
+}
+
+enum UpdateEvent {
+    Started(String),
+    Progress(CloneProgress),
+}
+
+pub struct SubmodulePanel {
+    theme: Theme,
+    last_repo: Option<String>,
+    submodules: Vec<SubmoduleInfo>,
+    needs_refresh: bool,
+    error: Option<String>,
+    status: Option<String>,
+    update_promise: Option<Promise<Result<usize, AppError>>>,
+    progress_rx: Option<Receiver<UpdateEvent>>,
+    progress: Option<(String, CloneProgress)>,
+}
+
+impl SubmodulePanel {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            theme,
+            last_repo: None,
+            submodules: Vec::new(),
+            needs_refresh: true,
+            error: None,
+            status: None,
+            update_promise: None,
+            progress_rx: None,
+            progress: None,
+        }
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    pub fn mark_stale(&mut self) {
+        self.needs_refresh = true;
+    }
+
+    pub fn ui(
+        &mut self,
+        ui: &mut Ui,
+        repo: &RepoContext,
+        auth: &AuthManager,
+        network: &NetworkOptions,
+    ) -> Option<String> {
+        self.poll_update();
+        self.refresh_if_needed(repo);
+
+        ui.heading(RichText::new("Submodules").color(self.theme.palette.text_primary));
+        ui.add_space(4.0);
+
+        if let Some(error) = &self.error {
+            ui.colored_label(self.theme.palette.accent, error);
+        }
+        if let Some(status) = &self.status {
+            ui.label(RichText::new(status).color(self.theme.palette.text_secondary));
+        }
+
+        if self.submodules.is_empty() {
+            ui.label(
+                RichText::new("This repository has no submodules.")
+                    .color(self.theme.palette.text_secondary),
+            );
+            return None;
+        }
+
+        self.progress_section(ui);
+        let action = self.submodule_list(ui);
+
+        match action {
+            Some(SubmoduleAction::Open(path)) => Some(
+                Path::new(&repo.path)
+                    .join(path)
+                    .to_string_lossy()
+                    .to_string(),
+            ),
+            Some(action) => {
+                self.run_action(repo, auth, network, action);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn refresh_if_needed(&mut self, repo: &RepoContext) {
+        if self.last_repo.as_deref() != Some(&repo.path) {
+            self.last_repo = Some(repo.path.clone());
+            self.status = None;
+            self.needs_refresh = true;
+        }
+
+        if !self.needs_refresh {
+            return;
+        }
+        self.needs_refresh = false;
+        self.error = None;
+
+        match read_submodule_status(&repo.path) {
+            Ok(submodules) => self.submodules = submodules,
+            Err(err) => {
+                self.submodules.clear();
+                self.error = Some(format!("Failed to read submodules: {err}"));
+            }
+        }
+    }
+
+    fn progress_section(&self, ui: &mut Ui) {
+        if self.update_promise.is_none() {
+            return;
+        }
+        ui.ctx().request_repaint();
+
+        match &self.progress {
+            Some((name, progress)) => {
+                let ratio = if progress.total_objects == 0 {
+                    0.0
+                } else {
+                    progress.received_objects as f32 / progress.total_objects as f32
+                };
+                ui.label(
+                    RichText::new(format!(
+                        "Updating {name}: {}/{} objects • {:.1} KiB",
+                        progress.received_objects,
+                        progress.total_objects,
+                        progress.received_bytes as f32 / 1024.0
+                    ))
+                    .color(self.theme.palette.text_secondary),
+                );
+                ui.add(egui::ProgressBar::new(ratio).show_percentage());
+            }
+            None => {
+                ui.label(
+                    RichText::new("Updating submodules...")
+                        .color(self.theme.palette.text_secondary),
+                );
+            }
+        }
+        ui.add_space(4.0);
+    }
+
+    fn submodule_list(&self, ui: &mut Ui) -> Option<SubmoduleAction> {
+        let mut action = None;
+        let busy = self.update_promise.is_some();
+        let all_names: Vec<String> = self.submodules.iter().map(|s| s.name.clone()).collect();
+
+        ui.horizontal(|ui| {
+            ui.add_enabled_ui(!busy, |ui| {
+                if ui.button("Init all").clicked() {
+                    action = Some(SubmoduleAction::Init(all_names.clone()));
+                }
+                if ui.button("Update all").clicked() {
+                    action = Some(SubmoduleAction::Update(all_names.clone()));
+                }
+                if ui.button("Sync all").clicked() {
+                    action = Some(SubmoduleAction::Sync(all_names.clone()));
+                }
+            });
+        });
+        ui.add_space(4.0);
+
+        for submodule in &self.submodules {
+            let frame = egui::Frame::none()
+                .fill(self.theme.palette.surface)
+                .stroke(egui::Stroke::new(1.0, self.theme.palette.surface_highlight))
+                .rounding(6.0)
+                .inner_margin(Margin::same(10.0));
+
+            frame.show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(
+                        RichText::new(&submodule.name)
+                            .color(self.theme.palette.text_primary)
+                            .strong(),
+                    );
+                    for (visible, label) in [
+                        (!submodule.initialized, "not initialized"),
+                        (
+                            submodule.initialized && !submodule.checked_out,
+                            "not checked out",
+                        ),
+                        (submodule.out_of_date, "out of date"),
+                        (submodule.dirty, "dirty"),
+                    ] {
+                        if visible {
+                            ui.label(RichText::new(label).color(self.theme.palette.accent));
+                        }
+                    }
+                });
+                ui.label(
+                    RichText::new(format!(
+                        "{} • {}",
+                        submodule.path,
+                        submodule.url.as_deref().unwrap_or("(no url)")
+                    ))
+                    .color(self.theme.palette.text_secondary),
+                );
+                ui.label(
+                    RichText::new(format!(
+                        "Recorded {} • Checked out {}",
+                        short_id(submodule.recorded_id.as_deref()),
+                        short_id(submodule.checked_out_id.as_deref())
+                    ))
+                    .color(self.theme.palette.text_secondary),
+                );
+
+                ui.horizontal(|ui| {
+                    ui.add_enabled_ui(!busy, |ui| {
+                        if !submodule.initialized && ui.button("Init").clicked() {
+                            action = Some(SubmoduleAction::Init(vec![submodule.name.clone()]));
+                        }
+                        if (!submodule.checked_out || submodule.out_of_date)
+                            && ui.button("Update").clicked()
+                        {
+                            action = Some(SubmoduleAction::Update(vec![submodule.name.clone()]));
+                        }
+                        if submodule.initialized && ui.button("Sync").clicked() {
+                            action = Some(SubmoduleAction::Sync(vec![submodule.name.clone()]));
+                        }
+                    });
+                    if submodule.checked_out && ui.button("Open").clicked() {
+                        action = Some(SubmoduleAction::Open(submodule.path.clone()));
+                    }
+                });
+            });
+            ui.add_space(6.0);
+        }
+
+        action
+    }
+
+    fn run_action(
+        &mut self,
+        repo: &RepoContext,
+        auth: &AuthManager,
+        network: &NetworkOptions,
+        action: SubmoduleAction,
+    ) {
+        self.status = None;
+        self.error = None;
+        let result = match action {
+            SubmoduleAction::Init(names) => names
+                .iter()
+                .try_for_each(|name| init_submodule(&repo.path, name))
+                .map(|_| format!("Initialized {} submodule(s)", names.len())),
+            SubmoduleAction::Sync(names) => names
+                .iter()
+                .try_for_each(|name| sync_submodule(&repo.path, name))
+                .map(|_| format!("Synchronized {} submodule URL(s)", names.len())),
+            SubmoduleAction::Update(names) => {
+                self.begin_update(repo, auth, network, names);
+                return;
+            }
+            SubmoduleAction::Open(_) => return,
+        };
+
+        self.needs_refresh = true;
+        match result {
+            Ok(message) => self.status = Some(message),
+            Err(err) => self.error = Some(err.to_string()),
+        }
+    }
+
+    fn begin_update(
+        &mut self,
+        repo: &RepoContext,
+        auth: &AuthManager,
+        network: &NetworkOptions,
+        names: Vec<String>,
+    ) {
+        let targets: Vec<(String, Option<String>)> = names
+            .into_iter()
+            .map(|name| {
+                let token = self
+                    .submodules
+                    .iter()
+                    .find(|submodule| submodule.name == name)
+                    .and_then(|submodule| submodule.url.as_deref())
+                    .and_then(|url| auth.resolve_for_repo(&repo.path, url));
+                (name, token)
+            })
+            .collect();
+
+        let (tx, rx) = mpsc::channel();
+        self.progress_rx = Some(rx);
+        self.progress = None;
+        let repo_path = repo.path.clone();
+        let network = network.clone();
+
+        self.update_promise = Some(Promise::spawn_thread("update_submodules", move || {
+            let count = targets.len();
+            for (name, token) in targets {
+                let _ = tx.send(UpdateEvent::Started(name.clone()));
+                let sender = tx.clone();
+                update_submodule(&repo_path, &name, &network, token, move |progress| {
+                    let _ = sender.send(UpdateEvent::Progress(progress));
+                })?;
+            }
+            Ok(count)
+        }));
+    }
+
+    fn poll_update(&mut self) {
+        if let Some(rx) = &self.progress_rx {
+            for event in rx.try_iter() {
+                match event {
+                    UpdateEvent::Started(name) => {
+                        self.progress = Some((name, CloneProgress::default()));
+                    }
+                    UpdateEvent::Progress(progress) => {
+                        if let Some((_, current)) = &mut self.progress {
+                            *current = progress;
+                        }
+                    }
+                }
+            }
+        }
+
+        let Some(result) = self.update_promise.as_ref().and_then(|p| p.ready()) else {
+            return;
+        };
+        match result {
+            Ok(count) => self.status = Some(format!("Updated {count} submodule(s)")),
+            Err(err) => self.error = Some(err.user_message()),
+        }
+        self.update_promise = None;
+        self.progress_rx = None;
+        self.progress = None;
+        self.needs_refresh = true;
+    }
+}
+
+fn short_id(id: Option<&str>) -> String {
+    id.map(|id| id.chars().take(7).collect())
+        .unwrap_or_else(|| "—".to_string())
+}
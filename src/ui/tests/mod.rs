@@ -44,8 +44,12 @@ fn build_layout_components() -> (
         StagePanel::new(theme.clone()),
         HistoryPanel::new(theme.clone()),
         BranchPanel::new(theme.clone(), preferences.pinned_branches().to_vec()),
-        AuthPanel::new(theme.clone(), auth_manager.clone()),
-        SettingsPanel::new(theme.clone(), preferences),
+        AuthPanel::new(
+            theme.clone(),
+            auth_manager.clone(),
+            preferences.network().clone(),
+        ),
+        SettingsPanel::new(theme.clone(), preferences, Default::default()),
         NotificationCenter::default(),
         auth_manager,
     )
@@ -74,7 +78,7 @@ fn layout_panels_render_without_panic() {
 
     let output = egui::Context::default().run(Default::default(), |ctx| {
         theme.apply(ctx);
-        layout.header(ctx);
+        layout.header(ctx, &[], None);
         layout.sidebar(ctx, active_tab);
         layout.right_panel(ctx, None);
 
@@ -151,3 +155,51 @@ fn layout_switches_tabs_in_run_loop() {
 
     assert!(output.textures_delta.free.is_empty());
 }
+
+#[test]
+fn diff_view_parses_patches_with_word_changes() {
+    use crate::ui::diff_view::{LineKind, SyntaxKind, collapsible_regions, highlight, parse_patch};
+
+    let patch = "Fdiff --git a/src/lib.rs b/src/lib.rs\n--- a/src/lib.rs\n+++ b/src/lib.rs\n\
+                 H@@ -10,9 +10,9 @@\n a\n b\n c\n d\n e\n f\n g\n-let total = count + 1;\n+let total = count + 2;\n";
+    let lines = parse_patch(patch);
+
+    assert_eq!(lines[0].kind, LineKind::Meta);
+    assert_eq!(lines[2].text, "+++ b/src/lib.rs");
+    assert_eq!(lines[3].kind, LineKind::Hunk);
+    assert_eq!((lines[4].old_line, lines[4].new_line), (Some(10), Some(10)));
+
+    let removed = &lines[11];
+    let added = &lines[12];
+    assert_eq!(
+        (removed.kind, removed.old_line),
+        (LineKind::Removed, Some(17))
+    );
+    assert_eq!((added.kind, added.new_line), (LineKind::Added, Some(17)));
+    assert_eq!(&removed.text[removed.changed[0].clone()], "1");
+    assert_eq!(&added.text[added.changed[0].clone()], "2");
+
+    assert_eq!(collapsible_regions(&lines), vec![4..8]);
+
+    let spans = highlight("src/lib.rs", "let name = \"x\"; // note");
+    assert_eq!(spans[0], (0..3, SyntaxKind::Keyword));
+    assert!(spans.contains(&(11..14, SyntaxKind::String)));
+    assert_eq!(spans.last().unwrap().1, SyntaxKind::Comment);
+}
+
+#[test]
+fn binary_view_dumps_hex_and_recognises_images() {
+    use crate::ui::binary_view::{hex_dump, is_image_path};
+
+    let dump = hex_dump(b"GIF89a\0\x01 binary payload!", 16);
+    assert_eq!(
+        dump,
+        "00000000  47 49 46 38 39 61 00 01 20 62 69 6e 61 72 79 20  GIF89a.. binary \n\
+         … 8 more bytes\n"
+    );
+
+    assert!(is_image_path("assets/Logo.PNG"));
+    assert!(is_image_path("icon.ico"));
+    assert!(!is_image_path("archive.zip"));
+    assert!(!is_image_path("png"));
+}
@@ -11,6 +11,9 @@ pub struct Palette {
     pub text_secondary: Color32,
     pub accent: Color32,
     pub accent_weak: Color32,
+    pub diff_added: Color32,
+    pub diff_removed: Color32,
+    pub syntax_keyword: Color32,
 }
 
 impl Palette {
@@ -23,6 +26,9 @@ impl Palette {
             text_secondary: Color32::from_rgb(0x5c, 0x5f, 0x77),
             accent: Color32::from_rgb(0x1e, 0x66, 0xf5),
             accent_weak: Color32::from_rgb(0x20, 0x9f, 0xb5),
+            diff_added: Color32::from_rgb(0x40, 0xa0, 0x2b),
+            diff_removed: Color32::from_rgb(0xd2, 0x0f, 0x39),
+            syntax_keyword: Color32::from_rgb(0x88, 0x39, 0xef),
         }
     }
 
@@ -35,6 +41,9 @@ impl Palette {
             text_secondary: Color32::from_rgb(0xb5, 0xbf, 0xe2),
             accent: Color32::from_rgb(0x8c, 0xaa, 0xee),
             accent_weak: Color32::from_rgb(0x85, 0xc1, 0xdc),
+            diff_added: Color32::from_rgb(0xa6, 0xd1, 0x89),
+            diff_removed: Color32::from_rgb(0xe7, 0x82, 0x84),
+            syntax_keyword: Color32::from_rgb(0xca, 0x9e, 0xe6),
         }
     }
 
@@ -47,6 +56,9 @@ impl Palette {
             text_secondary: Color32::from_rgb(0xb8, 0xc0, 0xe0),
             accent: Color32::from_rgb(0x8a, 0xad, 0xf4),
             accent_weak: Color32::from_rgb(0x7d, 0xc4, 0xe4),
+            diff_added: Color32::from_rgb(0xa6, 0xda, 0x95),
+            diff_removed: Color32::from_rgb(0xed, 0x87, 0x96),
+            syntax_keyword: Color32::from_rgb(0xc6, 0xa0, 0xf6),
         }
     }
 
@@ -59,6 +71,9 @@ impl Palette {
             text_secondary: Color32::from_rgb(0xba, 0xc2, 0xde),
             accent: Color32::from_rgb(0x89, 0xb4, 0xfa),
             accent_weak: Color32::from_rgb(0x74, 0xc7, 0xec),
+            diff_added: Color32::from_rgb(0xa6, 0xe3, 0xa1),
+            diff_removed: Color32::from_rgb(0xf3, 0x8b, 0xa8),
+            syntax_keyword: Color32::from_rgb(0xcb, 0xa6, 0xf7),
         }
     }
 }
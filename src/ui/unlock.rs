@@ -0,0 +1,94 @@
+use eframe::egui::{self, Key, RichText, TextEdit, Window};
+
+use crate::auth::AuthManager;
+use crate::ui::theme::Theme;
+
+// Dismissing the prompt leaves remotes without saved tokens until the user
+// unlocks from the Auth tab.
+pub struct UnlockPrompt {
+    theme: Theme,
+    password: String,
+    error: Option<String>,
+    dismissed: bool,
+    was_locked: bool,
+}
+
+impl UnlockPrompt {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            theme,
+            password: String::new(),
+            error: None,
+            dismissed: false,
+            was_locked: false,
+        }
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, auth: &AuthManager) {
+        let locked = auth.is_locked();
+        if locked && !self.was_locked {
+            self.dismissed = false;
+        }
+        self.was_locked = locked;
+        if !locked || self.dismissed {
+            return;
+        }
+
+        let mut unlock = false;
+        Window::new("Unlock saved tokens")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                ui.label(
+                    RichText::new(
+                        "Your access tokens are protected by a master password. Enter it to let GitSpace fetch, push and clone with them.",
+                    )
+                    .color(self.theme.palette.text_secondary),
+                );
+                ui.add_space(8.0);
+                let response = ui.add(
+                    TextEdit::singleline(&mut self.password)
+                        .hint_text("Master password")
+                        .password(true)
+                        .desired_width(280.0),
+                );
+                if response.lost_focus() && ui.input(|input| input.key_pressed(Key::Enter)) {
+                    unlock = true;
+                }
+                if let Some(error) = &self.error {
+                    ui.add_space(4.0);
+                    ui.colored_label(self.theme.palette.diff_removed, error);
+                }
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(!self.password.is_empty(), egui::Button::new("Unlock"))
+                        .clicked()
+                    {
+                        unlock = true;
+                    }
+                    if ui.button("Not now").clicked() {
+                        self.dismissed = true;
+                        self.password.clear();
+                        self.error = None;
+                    }
+                });
+            });
+
+        if unlock && !self.password.is_empty() {
+            match auth.unlock(&self.password) {
+                Ok(()) => {
+                    self.error = None;
+                    self.was_locked = false;
+                }
+                Err(err) => self.error = Some(err),
+            }
+            self.password.clear();
+        }
+    }
+}
@@ -0,0 +1,284 @@
+use std::path::PathBuf;
+
+use eframe::egui::{self, Margin, RichText, Ui};
+
+use crate::git::worktree::{
+    WorktreeEntry, WorktreeTarget, add_worktree, list_worktrees, lock_worktree, prune_worktrees,
+    unlock_worktree,
+};
+use crate::ui::{context::RepoContext, theme::Theme};
+
+#[derive(Debug, Clone)]
+enum WorktreeAction {
+    Create,
+    Lock(String),
+    Unlock(String),
+    Prune,
+    Open(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct WorktreePanel {
+    theme: Theme,
+    last_repo: Option<String>,
+    worktrees: Vec<WorktreeEntry>,
+    folder: String,
+    branch: String,
+    create_branch: bool,
+    start_point: String,
+    lock_reason: String,
+    needs_refresh: bool,
+    error: Option<String>,
+    status: Option<String>,
+}
+
+impl WorktreePanel {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            theme,
+            last_repo: None,
+            worktrees: Vec::new(),
+            folder: String::new(),
+            branch: String::new(),
+            create_branch: false,
+            start_point: String::new(),
+            lock_reason: String::new(),
+            needs_refresh: true,
+            error: None,
+            status: None,
+        }
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    pub fn mark_stale(&mut self) {
+        self.needs_refresh = true;
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui, repo: &RepoContext) -> Option<String> {
+        self.refresh_if_needed(repo);
+
+        ui.heading(RichText::new("Worktrees").color(self.theme.palette.text_primary));
+        ui.add_space(4.0);
+
+        if let Some(error) = &self.error {
+            ui.colored_label(self.theme.palette.accent, error);
+        }
+        if let Some(status) = &self.status {
+            ui.label(RichText::new(status).color(self.theme.palette.text_secondary));
+        }
+
+        let mut action = self.worktree_list(ui);
+        ui.add_space(6.0);
+        if let Some(create_action) = self.create_controls(ui) {
+            action = Some(create_action);
+        }
+
+        match action {
+            Some(WorktreeAction::Open(path)) => Some(path),
+            Some(action) => {
+                self.run_action(repo, action);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn refresh_if_needed(&mut self, repo: &RepoContext) {
+        if self.last_repo.as_deref() != Some(&repo.path) {
+            self.last_repo = Some(repo.path.clone());
+            self.status = None;
+            self.needs_refresh = true;
+        }
+
+        if !self.needs_refresh {
+            return;
+        }
+        self.needs_refresh = false;
+        self.error = None;
+
+        match list_worktrees(&repo.path) {
+            Ok(worktrees) => self.worktrees = worktrees,
+            Err(err) => {
+                self.worktrees.clear();
+                self.error = Some(format!("Failed to list worktrees: {err}"));
+            }
+        }
+    }
+
+    fn worktree_list(&self, ui: &mut Ui) -> Option<WorktreeAction> {
+        let mut action = None;
+
+        for worktree in &self.worktrees {
+            let frame = egui::Frame::none()
+                .fill(self.theme.palette.surface)
+                .stroke(egui::Stroke::new(1.0, self.theme.palette.surface_highlight))
+                .rounding(6.0)
+                .inner_margin(Margin::same(10.0));
+
+            frame.show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(
+                        RichText::new(&worktree.name)
+                            .color(self.theme.palette.text_primary)
+                            .strong(),
+                    );
+                    if worktree.is_main {
+                        ui.label(RichText::new("main").color(self.theme.palette.accent));
+                    }
+                    if worktree.locked {
+                        ui.label(RichText::new("locked").color(self.theme.palette.accent));
+                    }
+                    if worktree.prunable {
+                        ui.label(RichText::new("stale").color(self.theme.palette.accent));
+                    }
+                });
+                ui.label(RichText::new(&worktree.path).color(self.theme.palette.text_secondary));
+                ui.label(
+                    RichText::new(format!(
+                        "{} • {}",
+                        worktree.branch.as_deref().unwrap_or("(detached)"),
+                        worktree
+                            .head
+                            .as_deref()
+                            .map(short_id)
+                            .unwrap_or_else(|| "no HEAD".to_string())
+                    ))
+                    .color(self.theme.palette.text_secondary),
+                );
+                if let Some(reason) = &worktree.lock_reason {
+                    ui.label(
+                        RichText::new(format!("Lock reason: {reason}"))
+                            .color(self.theme.palette.text_secondary)
+                            .italics(),
+                    );
+                }
+
+                ui.horizontal(|ui| {
+                    if !worktree.prunable && ui.button("Open").clicked() {
+                        action = Some(WorktreeAction::Open(worktree.path.clone()));
+                    }
+                    if !worktree.is_main {
+                        if worktree.locked {
+                            if ui.button("Unlock").clicked() {
+                                action = Some(WorktreeAction::Unlock(worktree.name.clone()));
+                            }
+                        } else if ui.button("Lock").clicked() {
+                            action = Some(WorktreeAction::Lock(worktree.name.clone()));
+                        }
+                    }
+                });
+            });
+            ui.add_space(6.0);
+        }
+
+        if self.worktrees.iter().any(|worktree| worktree.prunable)
+            && ui.button("Prune stale worktrees").clicked()
+        {
+            action = Some(WorktreeAction::Prune);
+        }
+
+        action
+    }
+
+    fn create_controls(&mut self, ui: &mut Ui) -> Option<WorktreeAction> {
+        let mut action = None;
+
+        ui.label(
+            RichText::new("Add worktree")
+                .color(self.theme.palette.text_primary)
+                .strong(),
+        );
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("Folder").color(self.theme.palette.text_secondary));
+            ui.add(egui::TextEdit::singleline(&mut self.folder).hint_text("/path/to/worktree"));
+            if ui.button("Browse").clicked()
+                && let Some(folder) = rfd::FileDialog::new().pick_folder()
+            {
+                self.folder = folder.to_string_lossy().to_string();
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("Branch").color(self.theme.palette.text_secondary));
+            ui.add(egui::TextEdit::singleline(&mut self.branch).hint_text("feature/x"));
+            ui.checkbox(&mut self.create_branch, "Create new branch");
+        });
+        if self.create_branch {
+            ui.horizontal(|ui| {
+                ui.label(RichText::new("Start point").color(self.theme.palette.text_secondary));
+                ui.add(egui::TextEdit::singleline(&mut self.start_point).hint_text("HEAD"));
+            });
+        }
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("Lock reason").color(self.theme.palette.text_secondary));
+            ui.add(
+                egui::TextEdit::singleline(&mut self.lock_reason)
+                    .hint_text("Used when locking a worktree"),
+            );
+        });
+        if ui.button("Add worktree").clicked() {
+            action = Some(WorktreeAction::Create);
+        }
+
+        action
+    }
+
+    fn run_action(&mut self, repo: &RepoContext, action: WorktreeAction) {
+        self.status = None;
+        self.error = None;
+        let result = match action {
+            WorktreeAction::Create => self.create(repo),
+            WorktreeAction::Lock(name) => {
+                let reason = self.lock_reason.trim();
+                let reason = (!reason.is_empty()).then_some(reason);
+                lock_worktree(&repo.path, &name, reason)
+                    .map(|_| format!("Locked {name}"))
+                    .map_err(|err| err.to_string())
+            }
+            WorktreeAction::Unlock(name) => unlock_worktree(&repo.path, &name)
+                .map(|_| format!("Unlocked {name}"))
+                .map_err(|err| err.to_string()),
+            WorktreeAction::Prune => prune_worktrees(&repo.path)
+                .map(|pruned| format!("Pruned {} stale worktree(s)", pruned.len()))
+                .map_err(|err| err.to_string()),
+            WorktreeAction::Open(_) => return,
+        };
+
+        self.needs_refresh = true;
+        match result {
+            Ok(message) => self.status = Some(message),
+            Err(err) => self.error = Some(err),
+        }
+    }
+
+    fn create(&mut self, repo: &RepoContext) -> Result<String, String> {
+        let folder = self.folder.trim();
+        let branch = self.branch.trim();
+        if folder.is_empty() || branch.is_empty() {
+            return Err("Choose a folder and branch for the new worktree.".to_string());
+        }
+
+        let target = if self.create_branch {
+            let start_point = self.start_point.trim();
+            WorktreeTarget::NewBranch {
+                name: branch.to_string(),
+                start_point: (!start_point.is_empty()).then(|| start_point.to_string()),
+            }
+        } else {
+            WorktreeTarget::ExistingBranch(branch.to_string())
+        };
+
+        let entry = add_worktree(&repo.path, &PathBuf::from(folder), &target)
+            .map_err(|err| err.to_string())?;
+        self.folder.clear();
+        self.branch.clear();
+        self.start_point.clear();
+        Ok(format!("Created worktree {} at {}", entry.name, entry.path))
+    }
+}
+
+fn short_id(id: &str) -> String {
+    id.chars().take(7).collect()
+}
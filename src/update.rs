@@ -1,6 +1,8 @@
 use std::collections::HashMap;
 use std::fs;
+use std::io::Read;
 use std::path::{Path, PathBuf};
+use std::process::Command;
 use std::time::Duration;
 
 use base64::Engine;
@@ -25,6 +27,11 @@ const SIGNING_KEY_FILE: &str = "update-signing.pem";
 const EMBEDDED_SIGNING_KEY: &str = "-----BEGIN PUBLIC KEY-----
 MCowBQYDK2VwAyEAtZR3haYs4DLQXGepshiHit+bttO4OsGZxiiByTmmOJ4=
 -----END PUBLIC KEY-----";
+// These formats need an installer or unpacking, so they are never picked
+// for in-place installs.
+const PACKAGED_EXTENSIONS: &[&str] = &[
+    ".zip", ".tar", ".gz", ".tgz", ".xz", ".dmg", ".pkg", ".msi", ".deb", ".rpm",
+];
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum SignatureAlgorithm {
@@ -114,6 +121,20 @@ struct GitHubRelease {
 
 pub type UpdateResult = Result<Option<ReleaseInfo>, UpdateError>;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DownloadProgress {
+    pub downloaded: u64,
+    pub total: Option<u64>,
+}
+
+impl DownloadProgress {
+    pub fn fraction(&self) -> Option<f32> {
+        self.total
+            .filter(|total| *total > 0)
+            .map(|total| (self.downloaded as f64 / total as f64).min(1.0) as f32)
+    }
+}
+
 pub fn check_for_updates(
     channel: ReleaseChannel,
     feed_override: Option<&str>,
@@ -162,26 +183,101 @@ pub fn check_for_updates(
     }))
 }
 
-#[allow(dead_code)]
+pub fn select_platform_asset(assets: &[ReleaseAsset]) -> Option<&ReleaseAsset> {
+    select_asset(assets, std::env::consts::OS, std::env::consts::ARCH)
+}
+
+// Asset names carry both `os` and `arch`, e.g. `GitSpace-linux-x86_64` or
+// `GitSpace-windows-amd64.exe`.
+fn select_asset<'a>(assets: &'a [ReleaseAsset], os: &str, arch: &str) -> Option<&'a ReleaseAsset> {
+    let os_names: &[&str] = match os {
+        "macos" => &["macos", "darwin", "osx", "apple"],
+        "windows" => &["windows", "win64", "win"],
+        other => &[other],
+    };
+    let arch_names: &[&str] = match arch {
+        "x86_64" => &["x86_64", "amd64", "x64"],
+        "aarch64" => &["aarch64", "arm64"],
+        "x86" => &["x86", "i686", "i386"],
+        other => &[other],
+    };
+
+    assets.iter().find(|asset| {
+        let name = asset.name.to_ascii_lowercase();
+        if PACKAGED_EXTENSIONS
+            .iter()
+            .any(|extension| name.ends_with(extension))
+            || (os == "windows") != name.ends_with(".exe")
+        {
+            return false;
+        }
+        let parts: Vec<&str> = name
+            .trim_end_matches(".exe")
+            .split(['-', '.', ' '])
+            .collect();
+        parts.iter().any(|part| os_names.contains(part))
+            && parts.iter().any(|part| arch_names.contains(part))
+    })
+}
+
+// Staging next to `target` keeps the final rename on one filesystem.
+pub fn staged_path(target: &Path) -> PathBuf {
+    sibling_path(target, "new")
+}
+
+pub fn download_update(
+    network: &NetworkOptions,
+    asset: &ReleaseAsset,
+    on_progress: impl FnMut(DownloadProgress),
+) -> Result<PathBuf, UpdateError> {
+    let target = std::env::current_exe()?;
+    let staged = staged_path(&target);
+    download_verified_asset(network, asset, &staged, on_progress)?;
+    Ok(staged)
+}
+
 pub fn download_verified_asset(
     network: &NetworkOptions,
     asset: &ReleaseAsset,
     destination: &Path,
+    mut on_progress: impl FnMut(DownloadProgress),
 ) -> Result<(), UpdateError> {
     ensure_https_policy(&asset.download_url, network)?;
     let client = build_client(network)?;
 
     let backup = backup_existing(destination)?;
 
-    let bytes = client
+    let mut response = client
         .get(&asset.download_url)
         .send()
         .map_err(UpdateError::from)?
         .error_for_status()
-        .map_err(UpdateError::from)?
-        .bytes()
         .map_err(UpdateError::from)?;
 
+    let mut progress = DownloadProgress {
+        downloaded: 0,
+        total: response.content_length(),
+    };
+    on_progress(progress);
+    let mut bytes = Vec::with_capacity(progress.total.unwrap_or_default() as usize);
+    let mut chunk = [0; 64 * 1024];
+    loop {
+        let read = match response.read(&mut chunk) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(err) => {
+                rollback_from_backup(destination, backup);
+                return Err(UpdateError::Network(format!(
+                    "Download of {} failed: {err}",
+                    asset.name
+                )));
+            }
+        };
+        bytes.extend_from_slice(&chunk[..read]);
+        progress.downloaded += read as u64;
+        on_progress(progress);
+    }
+
     if let Err(err) = ensure_asset_verification(&bytes, asset, &client, network) {
         rollback_from_backup(destination, backup);
         return Err(err);
@@ -199,7 +295,6 @@ pub fn download_verified_asset(
     Ok(())
 }
 
-#[allow(dead_code)]
 fn ensure_asset_verification(
     bytes: &[u8],
     asset: &ReleaseAsset,
@@ -309,7 +404,6 @@ fn build_rsa_key(metadata: &SignatureMetadata) -> Result<RsaPublicKey, UpdateErr
         .map_err(|err| UpdateError::Verification(format!("Invalid RSA public key: {err}")))
 }
 
-#[allow(dead_code)]
 fn compute_sha256(bytes: &[u8]) -> String {
     let mut hasher = Sha256::new();
     hasher.update(bytes);
@@ -520,15 +614,79 @@ fn ensure_https_policy(url: &str, network: &NetworkOptions) -> Result<(), Update
     Ok(())
 }
 
-#[allow(dead_code)]
+// When either step fails the previous binary is put back.
+pub fn apply_staged_update(
+    staged: &Path,
+    target: &Path,
+    launch: impl FnOnce(&Path) -> std::io::Result<()>,
+) -> Result<(), UpdateError> {
+    if !staged.exists() {
+        return Err(UpdateError::Io(format!(
+            "No staged update found at {}",
+            staged.display()
+        )));
+    }
+
+    let backup = backup_existing(target)?;
+    if let Err(err) = replace_binary(staged, target) {
+        rollback_from_backup(target, backup);
+        return Err(UpdateError::Io(format!(
+            "Failed to install the update: {err}"
+        )));
+    }
+
+    if let Err(err) = launch(target) {
+        rollback_from_backup(target, backup);
+        return Err(UpdateError::Io(format!(
+            "Failed to start the updated GitSpace: {err}"
+        )));
+    }
+
+    if let Some(backup_path) = backup {
+        let _ = fs::remove_file(backup_path);
+    }
+
+    Ok(())
+}
+
+pub fn relaunch(target: &Path) -> std::io::Result<()> {
+    Command::new(target)
+        .args(std::env::args_os().skip(1))
+        .spawn()
+        .map(|_| ())
+}
+
+// Windows cannot delete a running executable, so the binary an update
+// moved aside is removed on the next launch.
+pub fn remove_replaced_binary() {
+    if let Ok(target) = std::env::current_exe() {
+        let _ = fs::remove_file(sibling_path(&target, "old"));
+    }
+}
+
+fn replace_binary(staged: &Path, target: &Path) -> std::io::Result<()> {
+    if let Ok(metadata) = fs::metadata(target) {
+        fs::set_permissions(staged, metadata.permissions())?;
+    }
+    if cfg!(windows) && target.exists() {
+        // A running executable can be renamed but not overwritten.
+        fs::rename(target, sibling_path(target, "old"))?;
+    }
+    fs::rename(staged, target)
+}
+
+fn sibling_path(path: &Path, extension: &str) -> PathBuf {
+    let fallback_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .map(|name| name.to_string())
+        .unwrap_or_else(|| "update".to_string());
+    path.with_file_name(format!("{}.{}", fallback_name, extension))
+}
+
 fn backup_existing(path: &Path) -> Result<Option<PathBuf>, UpdateError> {
     if path.exists() {
-        let fallback_name = path
-            .file_name()
-            .map(|name| name.to_string_lossy())
-            .map(|name| name.to_string())
-            .unwrap_or_else(|| "update".to_string());
-        let backup = path.with_file_name(format!("{}.bak", fallback_name));
+        let backup = sibling_path(path, "bak");
         fs::copy(path, &backup)?;
         return Ok(Some(backup));
     }
@@ -536,7 +694,6 @@ fn backup_existing(path: &Path) -> Result<Option<PathBuf>, UpdateError> {
     Ok(None)
 }
 
-#[allow(dead_code)]
 fn rollback_from_backup(path: &Path, backup: Option<PathBuf>) {
     if path.exists() {
         let _ = fs::remove_file(path);
@@ -653,4 +810,181 @@ hwIDAQAB
 
         verify_signature(payload, &signature.to_bytes(), &public_key).expect("rsa valid");
     }
+
+    // Serves a GitHub-style release feed with one checksummed asset per
+    // `(name, payload, published checksum)` entry and returns its URL.
+    fn start_release_feed(assets: Vec<(&'static str, &'static [u8], String)>) -> String {
+        use std::io::{BufRead, BufReader, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("bind feed stand-in");
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let mut routes: HashMap<String, Vec<u8>> = HashMap::new();
+        let mut listed = Vec::new();
+        for (name, payload, checksum) in assets {
+            let url = format!("{base}/download/{name}");
+            routes.insert(format!("/download/{name}"), payload.to_vec());
+            routes.insert(
+                format!("/download/{name}.sha256"),
+                format!("{checksum}  {name}\n").into_bytes(),
+            );
+            listed.push(serde_json::json!({ "name": name, "browser_download_url": url }));
+            listed.push(serde_json::json!({
+                "name": format!("{name}.sha256"),
+                "browser_download_url": format!("{url}.sha256"),
+            }));
+        }
+        let feed = serde_json::json!([{
+            "tag_name": "v99.0.0",
+            "html_url": format!("{base}/releases/v99.0.0"),
+            "prerelease": false,
+            "body": "Test release",
+            "assets": listed,
+        }]);
+        routes.insert("/releases".to_string(), feed.to_string().into_bytes());
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { break };
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                if reader.read_line(&mut request_line).is_err() {
+                    continue;
+                }
+                loop {
+                    let mut header = String::new();
+                    if reader.read_line(&mut header).is_err() || header.trim().is_empty() {
+                        break;
+                    }
+                }
+                let path = request_line.split_whitespace().nth(1).unwrap_or_default();
+                let (status, body) = match routes.get(path) {
+                    Some(body) => ("200 OK", body.as_slice()),
+                    None => ("404 Not Found", &[][..]),
+                };
+                let header = format!(
+                    "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                );
+                let _ = stream.write_all(header.as_bytes());
+                let _ = stream.write_all(body);
+            }
+        });
+        format!("{base}/releases")
+    }
+
+    fn plain_http() -> NetworkOptions {
+        NetworkOptions {
+            use_https: false,
+            ..NetworkOptions::default()
+        }
+    }
+
+    fn asset(name: &str) -> ReleaseAsset {
+        ReleaseAsset {
+            name: name.to_string(),
+            download_url: format!("https://example.com/{name}"),
+            checksum: Some(String::new()),
+            signature_url: None,
+            signature: None,
+        }
+    }
+
+    #[test]
+    fn selects_the_standalone_binary_for_the_platform() {
+        let assets = vec![
+            asset("GitSpace-linux-x86_64.tar.gz"),
+            asset("GitSpace-linux-x86_64"),
+            asset("GitSpace-linux-aarch64"),
+            asset("GitSpace-windows-amd64.exe"),
+            asset("GitSpace-darwin-arm64"),
+        ];
+        let pick = |os, arch| select_asset(&assets, os, arch).map(|asset| asset.name.as_str());
+
+        assert_eq!(pick("linux", "x86_64"), Some("GitSpace-linux-x86_64"));
+        assert_eq!(pick("linux", "aarch64"), Some("GitSpace-linux-aarch64"));
+        assert_eq!(
+            pick("windows", "x86_64"),
+            Some("GitSpace-windows-amd64.exe")
+        );
+        assert_eq!(pick("macos", "aarch64"), Some("GitSpace-darwin-arm64"));
+        assert_eq!(pick("macos", "x86_64"), None);
+        assert_eq!(pick("linux", "riscv64"), None);
+    }
+
+    #[test]
+    fn installs_an_update_from_the_release_feed() {
+        const PAYLOAD: &[u8] = b"new GitSpace binary";
+        let feed = start_release_feed(vec![
+            ("GitSpace-linux-x86_64", PAYLOAD, compute_sha256(PAYLOAD)),
+            (
+                "GitSpace-windows-x86_64.exe",
+                PAYLOAD,
+                compute_sha256(PAYLOAD),
+            ),
+        ]);
+        let network = plain_http();
+
+        let release = check_for_updates(ReleaseChannel::Stable, Some(&feed), &network)
+            .expect("feed reachable")
+            .expect("newer release");
+        assert_eq!(release.version, "99.0.0");
+        let asset = select_asset(&release.assets, "linux", "x86_64").expect("linux asset");
+
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("GitSpace");
+        fs::write(&target, b"old GitSpace binary").unwrap();
+        let staged = staged_path(&target);
+        let mut reports = Vec::new();
+        download_verified_asset(&network, asset, &staged, |progress| reports.push(progress))
+            .expect("download verified");
+
+        let last = reports.last().expect("progress reported");
+        assert_eq!(last.downloaded, PAYLOAD.len() as u64);
+        assert_eq!(last.fraction(), Some(1.0));
+        assert_eq!(fs::read(&staged).unwrap(), PAYLOAD);
+
+        let mut launched = None;
+        apply_staged_update(&staged, &target, |path| {
+            launched = Some(path.to_path_buf());
+            Ok(())
+        })
+        .expect("update applied");
+        assert_eq!(launched.as_deref(), Some(target.as_path()));
+        assert_eq!(fs::read(&target).unwrap(), PAYLOAD);
+        assert!(!staged.exists());
+        assert!(!sibling_path(&target, "bak").exists());
+    }
+
+    #[test]
+    fn keeps_the_current_binary_when_an_update_fails() {
+        const PAYLOAD: &[u8] = b"tampered binary";
+        let feed = start_release_feed(vec![(
+            "GitSpace-linux-x86_64",
+            PAYLOAD,
+            compute_sha256(b"published binary"),
+        )]);
+        let network = plain_http();
+        let release = check_for_updates(ReleaseChannel::Stable, Some(&feed), &network)
+            .unwrap()
+            .unwrap();
+        let asset = select_asset(&release.assets, "linux", "x86_64").unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("GitSpace");
+        fs::write(&target, b"old GitSpace binary").unwrap();
+        let staged = staged_path(&target);
+        let err = download_verified_asset(&network, asset, &staged, |_| {})
+            .expect_err("checksum mismatch");
+        assert!(matches!(err, UpdateError::Verification(_)));
+        assert!(!staged.exists());
+
+        fs::write(&staged, b"new GitSpace binary").unwrap();
+        let err = apply_staged_update(&staged, &target, |_| {
+            Err(std::io::Error::other("launch failed"))
+        })
+        .expect_err("launch failure");
+        assert!(matches!(err, UpdateError::Io(_)));
+        assert_eq!(fs::read(&target).unwrap(), b"old GitSpace binary");
+        assert!(!sibling_path(&target, "bak").exists());
+    }
 }